@@ -13,6 +13,15 @@ contract Counter is ICounter {
     /// @notice Image ID of the only zkVM binary to accept verification from.
     bytes32 public constant imageID = ImageID.DA_CHALLENGE_GUEST_ID;
 
+    /// @notice Image ID of the zkVM binary aggregating DA challenge proofs.
+    bytes32 public constant aggregatorImageID = ImageID.DA_CHALLENGE_AGGREGATOR_ID;
+
+    /// @notice Version of the journal layout accepted by this contract.
+    uint16 public constant JOURNAL_VERSION = 7;
+
+    /// @notice Version of the aggregated journal layout accepted by this contract.
+    uint16 public constant AGGREGATED_JOURNAL_VERSION = 5;
+
     /// @notice RISC Zero verifier contract address.
     IRiscZeroVerifier public immutable verifier;
 
@@ -22,10 +31,77 @@ contract Counter is ICounter {
     /// @notice Counter to track the number of successful verifications.
     uint256 public counter;
 
+    /// @notice Celestia blob, by its position in the original data square.
+    struct SpanSequence {
+        uint64 height;
+        uint32 start;
+        uint32 size;
+    }
+
+    /// @notice Rollup batch an index was posted for, see `toolkit::BatchMetadata`. All zero if the index was not read
+    /// or carries no metadata.
+    struct BatchMetadata {
+        bytes32 rollupId;
+        uint64 batchNumber;
+        /// @dev Seconds since the Unix epoch.
+        uint64 timestamp;
+    }
+
     /// @notice Journal that is committed to by the guest.
     struct Journal {
+        /// @dev Layout version of the journal, see `toolkit::journal::JOURNAL_VERSION`.
+        uint16 version;
+        Steel.Commitment commitment;
+        address blobstreamContract;
+        /// @dev 0: SP1Blobstream, 1: Blobstream0, 2: generic IDAOracle. Proofs against a generic IDAOracle cannot show
+        /// that a block height is too high (`BlockHeightTooHigh`), the oracle does not expose its latest height, and
+        /// their attestations are only as sound as the `verifyAttestation` of the oracle.
+        uint8 blobstreamImpl;
+        /// @dev 0: DA fraud proven, 1: the challenged blob is available.
+        uint8 outcome;
+        SpanSequence indexBlob;
+        SpanSequence challengedBlob;
+        /// @dev Kind of the proven DA fraud, see `toolkit::errors::DaFraudKind`. 0 if no fraud is proven.
+        uint8 fraudType;
+        /// @dev Celestia namespace the index blob was required to be posted to, empty if not checked.
+        bytes namespace;
+        /// @dev Other blobs of the index challenged in the same execution.
+        SpanSequence[] extraChallengedBlobs;
+        /// @dev Kind of the DA fraud proven for each extra challenged blob, 0 if none.
+        uint8[] extraFraudTypes;
+        /// @dev Chain ID of the Celestia network of the challenged blobs, ex: `celestia` or `mocha-4`.
+        string celestiaChainId;
+        /// @dev 0: the index is unavailable, 1: a blob of the index is unavailable, 2: the data of a blob of the index
+        /// does not match its data hash, see `toolkit::DaChallenge`. Extra challenged blobs are always 1.
+        uint8 challengeType;
+        /// @dev Keccak-256 hash of the canonical encoding of the index, see `toolkit::BlobIndex::canonical_hash`. Zero if
+        /// the index was not read: the index blob is the challenged blob, or the index or a sub-index is DA fraud.
+        bytes32 indexHash;
+        /// @dev Batch the index was posted for, lets verifiers tie the proof to a specific batch.
+        BatchMetadata batchMetadata;
+    }
+
+    /// @notice Journal that is committed to by the aggregator guest: DA frauds proven for several blobs of one index.
+    struct AggregatedJournal {
+        /// @dev Layout version of the journal, see `toolkit::journal::AGGREGATED_JOURNAL_VERSION`.
+        uint16 version;
+        /// @dev Image ID of the DA challenge guest that proved each aggregated challenge.
+        bytes32 challengeImageId;
         Steel.Commitment commitment;
         address blobstreamContract;
+        uint8 blobstreamImpl;
+        SpanSequence indexBlob;
+        bytes namespace;
+        SpanSequence[] challengedBlobs;
+        /// @dev Kind of the DA fraud proven for each challenged blob.
+        uint8[] fraudTypes;
+        string celestiaChainId;
+        /// @dev Kind of challenge of each challenged blob, see `Journal.challengeType`.
+        uint8[] challengeTypes;
+        /// @dev `Journal.indexHash` of the aggregated journals, zero if none of them read the index.
+        bytes32 indexHash;
+        /// @dev `Journal.batchMetadata` of the aggregated journals that read the index.
+        BatchMetadata batchMetadata;
     }
 
     /// @notice Initialize the contract, binding it to a specified RISC Zero verifier and ERC-20 token address.
@@ -38,7 +114,9 @@ contract Counter is ICounter {
     function increment(bytes calldata journalData, bytes calldata seal) external {
         // Decode and validate the journal data
         Journal memory journal = abi.decode(journalData, (Journal));
+        require(journal.version == JOURNAL_VERSION, "Unsupported journal version");
         require(Steel.validateCommitment(journal.commitment), "Invalid commitment");
+        require(journal.outcome == 0, "DA fraud not proven");
 
         // Verify the proof
         bytes32 journalHash = sha256(journalData);
@@ -47,6 +125,32 @@ contract Counter is ICounter {
         counter += 1;
     }
 
+    /// @inheritdoc ICounter
+    function incrementAggregated(bytes calldata journalData, bytes calldata seal) external {
+        // Decode and validate the journal data. The aggregator only accepts journals proving DA fraud.
+        AggregatedJournal memory journal = abi.decode(journalData, (AggregatedJournal));
+        require(journal.version == AGGREGATED_JOURNAL_VERSION, "Unsupported journal version");
+        require(journal.challengeImageId == imageID, "Unexpected DA challenge image ID");
+        require(Steel.validateCommitment(journal.commitment), "Invalid commitment");
+
+        // Verify the proof
+        bytes32 journalHash = sha256(journalData);
+        verifier.verify(seal, aggregatorImageID, journalHash);
+
+        counter += journal.challengedBlobs.length;
+    }
+
+    /// @notice Decodes a journal of the DA challenge guest, for off-chain inspection. Exposes the `Journal` layout in
+    /// the ABI of the contract, which the Rust definition is checked against.
+    function decodeJournal(bytes calldata journalData) external pure returns (Journal memory) {
+        return abi.decode(journalData, (Journal));
+    }
+
+    /// @notice Decodes a journal of the aggregator guest, see `decodeJournal`.
+    function decodeAggregatedJournal(bytes calldata journalData) external pure returns (AggregatedJournal memory) {
+        return abi.decode(journalData, (AggregatedJournal));
+    }
+
     /// @inheritdoc ICounter
     function get() external view returns (uint256) {
         return counter;
@@ -21,9 +21,16 @@ interface ICounter {
     /// @dev The Steel proof must be generated off-chain using RISC0-zkVM and submitted here.
     function increment(bytes calldata journalData, bytes calldata seal) external;
 
+    /// @notice Increments the counter once per challenged blob of an aggregated proof of DA challenges.
+    /// @dev The aggregated proof must be generated off-chain by the aggregator guest program and submitted here.
+    function incrementAggregated(bytes calldata journalData, bytes calldata seal) external;
+
     /// @notice Returns the value of the counter.
     function get() external view returns (uint256);
 
     /// @notice Returns the image ID used for verification.
     function imageID() external view returns (bytes32);
+
+    /// @notice Returns the image ID of the aggregator used for verification.
+    function aggregatorImageID() external view returns (bytes32);
 }
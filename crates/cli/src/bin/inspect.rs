@@ -0,0 +1,174 @@
+use alloy_primitives::{Address, Bytes, B256};
+use anyhow::{Context, Result};
+use clap::{Parser, Subcommand};
+use cli::blobstream_backend::FirstCommitmentTable;
+use cli::celestia_data_source::{connect_celestia_node, CelestiaTimeouts};
+use cli::event_cache::{BlobstreamEventCache, EventLookupStrategy, PersistentEventStore};
+use cli::inspect::{
+    blobstream_sync_status, decode_submitted_proof, fetch_submitted_proof, inspect_blobstream,
+    KnownNetworks,
+};
+use cli::logging_init;
+use dotenv::dotenv;
+use risc0_ethereum_contracts::alloy::providers::{Provider as _, RootProvider};
+use std::path::PathBuf;
+use toolkit::constants::canonical_blobstream_address;
+use toolkit::BlobstreamImpl;
+use url::Url;
+
+/// Read-only diagnostics of the Blobstream deployment used by the challenger, and of the proofs
+/// it submits.
+#[derive(Parser)]
+struct CliArgs {
+    /// Ethereum RPC endpoint URL
+    #[arg(long, env = "ETH_RPC_URL")]
+    eth_rpc_url: Url,
+
+    /// Address of the Blobstream contract. Defaults to the canonical deployment of the chain,
+    /// see the `networks` command.
+    #[arg(long, env = "BLOBSTREAM_ADDRESS")]
+    blobstream_address: Option<Address>,
+
+    /// Blobstream implementation of the contract. Detected if not specified.
+    #[arg(long, env = "BLOBSTREAM_IMPL")]
+    blobstream_impl: Option<BlobstreamImpl>,
+
+    /// Path to a SQLite database used to cache Blobstream events across runs.
+    #[arg(long, env = "EVENT_CACHE")]
+    event_cache: Option<PathBuf>,
+
+    /// How to find the Blobstream commitment covering a Celestia height.
+    #[arg(long, env = "EVENT_LOOKUP", value_enum, default_value_t = EventLookupStrategy::BinarySearch)]
+    event_lookup: EventLookupStrategy,
+
+    /// JSON file listing the first Blobstream commitment of SP1Blobstream deployments, by chain.
+    #[arg(long, env = "FIRST_COMMITMENTS")]
+    first_commitments: Option<PathBuf>,
+
+    /// Print the result as JSON.
+    #[arg(long)]
+    json: bool,
+
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Print the Blobstream commitment covering a Celestia height and whether blobs at this
+    /// height can currently be challenged.
+    Blobstream {
+        /// Celestia block height
+        height: u64,
+    },
+    /// Print how far Blobstream lags behind the Celestia chain head.
+    SyncStatus {
+        /// Celestia RPC endpoint URL
+        #[arg(long, env = "CELESTIA_RPC_URL")]
+        celestia_rpc_url: Url,
+
+        /// Also estimate when blobs at this Celestia height become challengeable.
+        #[arg(long)]
+        height: Option<u64>,
+    },
+    /// Decode the journal and seal of a proof submitted to the verifier contract, from its
+    /// transaction or its calldata.
+    Proof {
+        /// Hash of the transaction calling `increment()` or `incrementAggregated()`.
+        #[arg(required_unless_present = "calldata")]
+        tx_hash: Option<B256>,
+
+        /// Hex calldata of the call, decoded without fetching the transaction.
+        #[arg(long, conflicts_with = "tx_hash")]
+        calldata: Option<Bytes>,
+    },
+    /// Print the registry of known networks: the Blobstream deployment of each pair of Ethereum
+    /// chain and Celestia network, its first commitment and default namespace.
+    Networks,
+}
+
+impl CliArgs {
+    async fn blobstream_event_cache(&self) -> Result<BlobstreamEventCache> {
+        let root_provider = RootProvider::connect(self.eth_rpc_url.as_str()).await?;
+        let blobstream_address = match self.blobstream_address {
+            Some(address) => address,
+            None => {
+                let chain_id = root_provider.get_chain_id().await?;
+                canonical_blobstream_address(chain_id).with_context(|| {
+                    format!(
+                        "no known Blobstream deployment on chain ID {chain_id}, \
+                         pass --blobstream-address"
+                    )
+                })?
+            }
+        };
+
+        let mut blobstream_event_cache =
+            BlobstreamEventCache::new(blobstream_address, root_provider)
+                .with_lookup_strategy(self.event_lookup);
+        if let Some(event_cache_path) = &self.event_cache {
+            blobstream_event_cache = blobstream_event_cache
+                .with_persistent_store(PersistentEventStore::open(event_cache_path)?);
+        }
+        if let Some(blobstream_impl) = self.blobstream_impl {
+            blobstream_event_cache = blobstream_event_cache.with_blobstream_impl(blobstream_impl);
+        }
+        if let Some(first_commitments_path) = &self.first_commitments {
+            blobstream_event_cache = blobstream_event_cache
+                .with_first_commitments(FirstCommitmentTable::load(first_commitments_path)?);
+        }
+
+        Ok(blobstream_event_cache)
+    }
+}
+
+fn print<T: serde::Serialize + std::fmt::Display>(value: &T, json: bool) -> Result<()> {
+    if json {
+        println!("{}", serde_json::to_string_pretty(value)?);
+    } else {
+        print!("{value}");
+    }
+    Ok(())
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    logging_init();
+    dotenv().ok();
+
+    let args = CliArgs::parse();
+
+    match args.command {
+        Command::Blobstream { height } => {
+            let blobstream_event_cache = args.blobstream_event_cache().await?;
+            let inspection = inspect_blobstream(&blobstream_event_cache, height).await?;
+            print(&inspection, args.json)
+        }
+        Command::SyncStatus {
+            ref celestia_rpc_url,
+            height,
+        } => {
+            let celestia_client =
+                connect_celestia_node(celestia_rpc_url, CelestiaTimeouts::default()).await?;
+            let blobstream_event_cache = args.blobstream_event_cache().await?;
+            let sync_status =
+                blobstream_sync_status(&celestia_client, &blobstream_event_cache, height).await?;
+            print(&sync_status, args.json)
+        }
+        Command::Proof {
+            tx_hash,
+            ref calldata,
+        } => {
+            let submitted_proof = match (tx_hash, calldata) {
+                (_, Some(calldata)) => decode_submitted_proof(calldata)?,
+                (Some(tx_hash), None) => {
+                    let provider = RootProvider::connect(args.eth_rpc_url.as_str()).await?;
+                    fetch_submitted_proof(&provider, tx_hash).await?
+                }
+                (None, None) => unreachable!("clap requires a transaction hash or calldata"),
+            };
+            print(&submitted_proof, args.json)
+        }
+        Command::Networks => print(&KnownNetworks::builtin(), args.json),
+    }
+}
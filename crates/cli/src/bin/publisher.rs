@@ -1,16 +1,46 @@
-use alloy_primitives::Address;
-use anyhow::Result;
-use celestia_rpc::Client as CelestiaClient;
+use alloy_primitives::{Address, B256};
+use anyhow::{anyhow, bail, ensure, Context, Result};
+use celestia_types::nmt::Namespace;
 use clap::Parser;
-use cli::{challenge_da_commitment, increment_counter, logging_init, ICounter};
+use cli::blobstream_backend::FirstCommitmentTable;
+use cli::celestia_cache::CelestiaDataStore;
+use cli::celestia_data_source::{
+    connect_celestia_node, ArchiveDataSource, CachingDataSource, CelestiaDataSource,
+    CelestiaTimeouts, FallbackDataSource, MultiEndpointDataSource, RetryingDataSource,
+    ValidatingDataSource,
+};
+use cli::challenge_options::ChallengeOptions;
+use cli::coverage::{ensure_heights_covered, UncoveredHeightPolicy};
+use cli::das::sample_blob_shares;
+use cli::deployment::{parse_namespace, ChallengeRequest, Deployments};
+use cli::event_cache::{BlobstreamEventCache, EventLookupStrategy, PersistentEventStore};
+use cli::evm_input_cache::EvmInputStore;
+use cli::job_store::{JobKey, JobPhase, JobStatus, JobStore};
+use cli::notifier::{
+    explorer_tx_url, ChallengeEvent, JournalSummary, Notifiers, SlackNotifier, WebhookNotifier,
+};
+use cli::proof_cache::InclusionProofStore;
+use cli::report::{ChallengeOutcome, RunReport};
+use cli::retry::{retry_with_backoff, RetryPolicy};
+use cli::{
+    challenge_da_commitment_with_cache, confirm_transaction, logging_init, send_increment_counter,
+    transaction_outcome, ICounter, TransactionOutcome,
+};
 use dotenv::dotenv;
 use risc0_ethereum_contracts::alloy::providers::{ProviderBuilder, RootProvider};
 use risc0_steel::alloy::{network::EthereumWallet, signers::local::PrivateKeySigner};
+use risc0_steel::config::ChainSpec;
 use risc0_steel::ethereum::ETH_SEPOLIA_CHAIN_SPEC;
 use risc0_steel::host::BlockNumberOrTag;
-use std::str::FromStr;
-use toolkit::constants::BLOBSTREAM_ADDRESS;
-use toolkit::SpanSequence;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::process::ExitCode;
+use std::sync::Mutex;
+use std::time::Duration;
+use toolkit::constants::canonical_blobstream_address;
+use toolkit::errors::BlobIsAvailable;
+use toolkit::journal::DaOutcome;
+use toolkit::{BlobstreamImpl, DaChallenge, SpanSequence};
 use url::Url;
 
 /// Simple program to create a proof to increment the Counter contract.
@@ -21,8 +51,8 @@ struct CliArgs {
     eth_wallet_private_key: PrivateKeySigner,
 
     /// Ethereum RPC endpoint URL
-    #[arg(long, env = "ETH_RPC_URL")]
-    eth_rpc_url: Url,
+    #[arg(long, env = "ETH_RPC_URL", required_unless_present = "deployments")]
+    eth_rpc_url: Option<Url>,
 
     /// Beacon API endpoint URL
     ///
@@ -41,67 +71,930 @@ struct CliArgs {
     #[arg(long, env = "COMMITMENT_BLOCK")]
     commitment_block: BlockNumberOrTag,
 
-    /// Celestia RPC endpoint URL
-    #[arg(long, env = "CELESTIA_RPC_URL")]
-    celestia_rpc_url: Url,
+    /// Celestia RPC endpoint URLs, comma-separated. Requests go to the fastest endpoint and fail
+    /// over to the others on errors.
+    #[arg(long, env = "CELESTIA_RPC_URL", required = true, value_delimiter = ',')]
+    celestia_rpc_url: Vec<Url>,
+
+    /// Only accept the Celestia block headers and data root proofs that two endpoints agree on,
+    /// so that a faulty endpoint cannot trigger a bogus challenge. Needs two `--celestia-rpc-url`.
+    #[arg(long, env = "CROSS_CHECK_CELESTIA")]
+    cross_check_celestia: bool,
+
+    /// Maximum number of attempts of each Celestia request. Only transient failures (timeouts,
+    /// connection errors, rate limiting) are retried.
+    #[arg(long, env = "CELESTIA_MAX_ATTEMPTS", default_value_t = 5)]
+    celestia_max_attempts: u32,
+
+    /// Delay before the first retry of a Celestia request, in milliseconds. Doubles after every
+    /// failed attempt.
+    #[arg(long, env = "CELESTIA_INITIAL_BACKOFF_MS", default_value_t = 500)]
+    celestia_initial_backoff_ms: u64,
+
+    /// Maximum delay between two attempts of a Celestia request, in seconds.
+    #[arg(long, env = "CELESTIA_MAX_BACKOFF_SECS", default_value_t = 30)]
+    celestia_max_backoff_secs: u64,
+
+    /// Timeout of the connection to each Celestia endpoint, in seconds.
+    #[arg(long, env = "CELESTIA_CONNECT_TIMEOUT_SECS", default_value_t = 10)]
+    celestia_connect_timeout_secs: u64,
+
+    /// Timeout of each Celestia request, in seconds. Timed out requests fail over to the next
+    /// endpoint and are retried.
+    #[arg(long, env = "CELESTIA_REQUEST_TIMEOUT_SECS", default_value_t = 60)]
+    celestia_request_timeout_secs: u64,
+
+    /// Also verify that each Celestia header follows the header of the previous block, at the
+    /// cost of one more request per header. Headers are always checked for internal consistency.
+    #[arg(long, env = "CHECK_HEADER_CHAIN")]
+    check_header_chain: bool,
+
+    /// Base URL of an archive of Celestia data, read when the Celestia node fails to serve a
+    /// block, ex: after pruning it. HTTP(S), ex: an S3 snapshot, or a `file://` directory, see
+    /// `ArchiveDataSource` for the layout.
+    #[arg(long, env = "CELESTIA_ARCHIVE_URL")]
+    celestia_archive_url: Option<Url>,
+
+    /// Path to a SQLite database used to cache the Celestia headers and share proofs across
+    /// retries and runs. Can be the same file as `--proof-cache`.
+    #[arg(long, env = "CELESTIA_CACHE")]
+    celestia_cache: Option<PathBuf>,
+
+    /// Before proving that a blob is unavailable, sample N random shares of the blob from the
+    /// DAS peers, like a light node does, and give up if they can all be retrieved. 0 disables
+    /// the sampling. Skipped with `--force` and `--prove-availability`.
+    #[arg(long, env = "DAS_SAMPLES", default_value_t = 0)]
+    das_samples: usize,
+
+    /// Celestia nodes to sample the shares from, comma-separated, ex: light nodes run by other
+    /// parties. The Celestia endpoints if not set.
+    #[arg(long, env = "DAS_PEER_URL", value_delimiter = ',')]
+    das_peer_url: Vec<Url>,
 
     /// Address of the Blobstream / counter verifier contract.
-    #[arg(long)]
-    counter_address: Address,
+    #[arg(long, required_unless_present = "deployments")]
+    counter_address: Option<Address>,
+
+    /// JSON file listing the deployments (chain, Blobstream contract, verifier contract) served
+    /// by this challenger. Replaces `--eth-rpc-url` and `--counter-address`.
+    #[arg(long, env = "DEPLOYMENTS")]
+    deployments: Option<PathBuf>,
+
+    /// Deployment to submit the challenge to. Can be omitted if only one is configured.
+    #[arg(long, env = "DEPLOYMENT", requires = "deployments")]
+    deployment: Option<String>,
 
     /// Sequence of spans pointing to the index blob.
-    #[arg(long)]
-    index_blob: SpanSequence,
+    #[arg(long, required_unless_present = "challenges")]
+    index_blob: Option<SpanSequence>,
 
     /// Sequence of spans pointing to the missing blob. Can be the index blob or any blob
     /// pointed to by the contents of the index blob.
-    #[arg(long)]
-    challenged_blob: SpanSequence,
+    #[arg(long, required_unless_present_any = ["challenges", "challenge"])]
+    challenged_blob: Option<SpanSequence>,
+
+    /// Challenge to prove: `index-unavailable`, `blob-unavailable:<span sequence>` or
+    /// `blob-data-mismatch:<span sequence>`, ex: `blob-unavailable:6671289:6:4`. Replaces
+    /// `--challenged-blob` and `--blob-data-mismatch`.
+    #[arg(long, env = "CHALLENGE", conflicts_with_all = ["challenged_blob", "blob_data_mismatch"])]
+    challenge: Option<DaChallenge>,
+
+    /// JSON file listing several challenges to run one after the other, each routed to its
+    /// deployment. Replaces `--index-blob` and `--challenged-blob`.
+    #[arg(
+        long,
+        conflicts_with_all = ["index_blob", "challenged_blob", "challenge", "deployment"]
+    )]
+    challenges: Option<PathBuf>,
+
+    /// Path to the SQLite job store.
+    ///
+    /// When set, the challenge is recorded in the job store and proofs are persisted as soon as
+    /// they are generated. Running the same challenge again resumes from the last completed step.
+    #[arg(long, env = "JOB_DB")]
+    job_db: Option<PathBuf>,
+
+    /// Resets the proving and submission attempts of the challenge jobs before running them.
+    ///
+    /// Attempts are counted across runs, so a job that used them up fails right away until its
+    /// attempts are reset.
+    #[arg(long, env = "RESET_ATTEMPTS")]
+    reset_attempts: bool,
+
+    /// URL to POST a JSON notification to when a challenge is submitted or fails.
+    #[arg(long, env = "WEBHOOK_URL")]
+    webhook_url: Option<Url>,
+
+    /// Slack incoming webhook URL to notify when a challenge is submitted or fails.
+    #[arg(long, env = "SLACK_WEBHOOK_URL")]
+    slack_webhook_url: Option<Url>,
+
+    /// Block explorer URL used to link transactions in notifications,
+    /// ex: `https://sepolia.etherscan.io`.
+    #[arg(long, env = "EXPLORER_URL")]
+    explorer_url: Option<Url>,
+
+    /// Maximum number of proving attempts, and of submission attempts, for the challenge.
+    ///
+    /// Only transient failures (RPC errors, prover out of memory, underpriced transactions)
+    /// are retried. When a job store is used, attempts are counted across runs. Proving and
+    /// submission attempts are counted separately.
+    #[arg(long, env = "MAX_ATTEMPTS", default_value_t = 3)]
+    max_attempts: u32,
+
+    /// Delay before the first retry, in seconds. Doubles after every failed attempt.
+    #[arg(long, env = "INITIAL_BACKOFF_SECS", default_value_t = 5)]
+    initial_backoff_secs: u64,
+
+    /// Maximum delay between two attempts, in seconds.
+    #[arg(long, env = "MAX_BACKOFF_SECS", default_value_t = 300)]
+    max_backoff_secs: u64,
+
+    /// Time to wait for the confirmation of a submission transaction, in seconds.
+    ///
+    /// A transaction still pending after that is a transient failure: the next submission
+    /// attempt replaces it with higher fees.
+    #[arg(long, env = "CONFIRMATION_TIMEOUT_SECS", default_value_t = 600)]
+    confirmation_timeout_secs: u64,
+
+    /// Path to a SQLite database used to cache Blobstream events across runs.
+    ///
+    /// Finding the Blobstream commitment covering a Celestia height requires scanning Ethereum
+    /// logs, which is slow. Cached events are reused by later runs against the same chain and
+    /// Blobstream contract.
+    #[arg(long, env = "EVENT_CACHE")]
+    event_cache: Option<PathBuf>,
+
+    /// Path to a SQLite database used to cache the Blobstream inclusion proofs fetched from
+    /// Celestia across runs. Can be the same file as `--event-cache`.
+    #[arg(long, env = "PROOF_CACHE")]
+    proof_cache: Option<PathBuf>,
+
+    /// Path to a SQLite database used to store the Steel inputs of the Blobstream preflight calls.
+    /// Proving a challenge again at the same `--execution-block` number, ex: after a prover crash,
+    /// skips the preflight. Can be the same file as `--event-cache`.
+    #[arg(long, env = "EVM_INPUT_CACHE")]
+    evm_input_cache: Option<PathBuf>,
+
+    /// How to find the Blobstream commitment covering a Celestia height.
+    #[arg(long, env = "EVENT_LOOKUP", value_enum, default_value_t = EventLookupStrategy::BinarySearch)]
+    event_lookup: EventLookupStrategy,
+
+    /// JSON file listing the first Blobstream commitment of SP1Blobstream deployments, by chain.
+    /// Extends the built-in table (Ethereum mainnet and Sepolia).
+    #[arg(long, env = "FIRST_COMMITMENTS")]
+    first_commitments: Option<PathBuf>,
+
+    /// Check that the Blobstream proofs served by Celestia match the data commitments stored
+    /// on Ethereum before proving.
+    #[arg(long, env = "VERIFY_DATA_COMMITMENTS")]
+    verify_data_commitments: bool,
+
+    /// Prove the challenge even if the host-side pre-check predicts that no DA fraud can be
+    /// proven, ex: because the blob is available.
+    #[arg(long, env = "FORCE")]
+    force: bool,
+
+    /// Prove that the challenged blob is available instead of failing. Proofs of availability
+    /// are not submitted to the verifier contract.
+    #[arg(long, env = "PROVE_AVAILABILITY")]
+    prove_availability: bool,
+
+    /// Limit the segments of the guest execution to 2^N cycles when proving (RISC Zero default:
+    /// 20). Lower values reduce the memory used to prove large challenges.
+    #[arg(long, env = "SEGMENT_LIMIT_PO2")]
+    segment_limit_po2: Option<u32>,
+
+    /// Challenge the data of the challenged blob instead of its availability: prove that it
+    /// does not match the data hash of its index entry. Requires an index with data hashes.
+    /// Applies to all the challenges of `--challenges`.
+    #[arg(long, env = "BLOB_DATA_MISMATCH")]
+    blob_data_mismatch: bool,
+
+    /// Blobstream implementation of the contract at `--blobstream-address`: sp1, r0 or
+    /// da-oracle (any IDAOracle contract). Detected if not set.
+    #[arg(long, env = "BLOBSTREAM_IMPL")]
+    blobstream_impl: Option<BlobstreamImpl>,
+
+    /// Celestia namespace the rollup posts its index blobs to, in hex. Index blobs posted in
+    /// another namespace are proven unavailable. Not checked if not set.
+    #[arg(long, env = "NAMESPACE", value_parser = parse_namespace)]
+    namespace: Option<Namespace>,
+
+    /// Wait up to this many seconds for Blobstream to cover the challenged Celestia heights
+    /// before proving.
+    #[arg(
+        long,
+        env = "WAIT_FOR_BLOBSTREAM_SECS",
+        conflicts_with = "allow_uncovered_height"
+    )]
+    wait_for_blobstream_secs: Option<u64>,
+
+    /// Delay between two checks of the Blobstream head while waiting, in seconds.
+    #[arg(long, env = "BLOBSTREAM_POLL_INTERVAL_SECS", default_value_t = 60)]
+    blobstream_poll_interval_secs: u64,
+
+    /// Challenge Celestia heights that are not covered by Blobstream yet, proving that they are
+    /// higher than the Blobstream head. Only use this if the height will never exist.
+    #[arg(long, env = "ALLOW_UNCOVERED_HEIGHT")]
+    allow_uncovered_height: bool,
+
+    /// Path where to write a JSON report of the run (outcome, exit code, transaction, journal).
+    #[arg(long, env = "REPORT")]
+    report: Option<PathBuf>,
+
+    /// Path where to write a human-readable JSON version of the guest data before proving:
+    /// Blobstream attestations, row proofs and share proof positions. With several challenges,
+    /// holds the last one.
+    #[arg(long, env = "DUMP_GUEST_DATA")]
+    dump_guest_data: Option<PathBuf>,
+}
+
+/// Where a challenge is proven and submitted.
+#[derive(Clone)]
+struct Target {
+    eth_rpc_url: Url,
+    verifier_address: Address,
+    chain_spec: ChainSpec,
+    blobstream_event_cache: BlobstreamEventCache,
+    challenge_options: ChallengeOptions,
+    job_db: Option<PathBuf>,
+}
+
+impl CliArgs {
+    /// Returns the challenges to run, with the deployment each one is routed to.
+    fn challenge_requests(&self) -> Result<Vec<ChallengeRequest>> {
+        let mut challenge_requests = match (&self.challenges, self.index_blob, self.challenge()) {
+            (Some(challenges_path), _, _) => ChallengeRequest::load_all(challenges_path)?,
+            (None, Some(index_blob), Some(challenge)) => vec![ChallengeRequest::new(
+                self.deployment.clone(),
+                index_blob,
+                challenge,
+            )],
+            _ => {
+                return Err(anyhow::anyhow!(
+                    "--index-blob and --challenged-blob or --challenge are required"
+                ))
+            }
+        };
+        for challenge_request in &mut challenge_requests {
+            challenge_request.blob_data_mismatch |= self.blob_data_mismatch;
+        }
+
+        Ok(challenge_requests)
+    }
+
+    /// Returns the challenge passed on the command line, if any.
+    fn challenge(&self) -> Option<DaChallenge> {
+        self.challenge.or_else(|| {
+            Some(DaChallenge::availability(
+                self.index_blob?,
+                self.challenged_blob?,
+            ))
+        })
+    }
+
+    /// Creates the Blobstream event cache of a deployment. Caches of different deployments are
+    /// distinct, but can share the same on-disk stores.
+    async fn blobstream_event_cache(
+        &self,
+        eth_rpc_url: &Url,
+        blobstream_address: Address,
+        blobstream_impl: Option<BlobstreamImpl>,
+    ) -> Result<BlobstreamEventCache> {
+        // Need a different provider for now for Blobstream event filtering
+        let root_provider = RootProvider::connect(eth_rpc_url.as_str()).await?;
+
+        let mut blobstream_event_cache =
+            BlobstreamEventCache::new(blobstream_address, root_provider)
+                .with_lookup_strategy(self.event_lookup)
+                .with_data_commitment_verification(self.verify_data_commitments);
+        if let Some(event_cache_path) = &self.event_cache {
+            blobstream_event_cache = blobstream_event_cache
+                .with_persistent_store(PersistentEventStore::open(event_cache_path)?);
+        }
+        if let Some(proof_cache_path) = &self.proof_cache {
+            blobstream_event_cache = blobstream_event_cache
+                .with_inclusion_proof_store(InclusionProofStore::open(proof_cache_path)?);
+        }
+        if let Some(blobstream_impl) = blobstream_impl.or(self.blobstream_impl) {
+            blobstream_event_cache = blobstream_event_cache.with_blobstream_impl(blobstream_impl);
+        }
+        if let Some(first_commitments_path) = &self.first_commitments {
+            blobstream_event_cache = blobstream_event_cache
+                .with_first_commitments(FirstCommitmentTable::load(first_commitments_path)?);
+        }
+
+        Ok(blobstream_event_cache)
+    }
+
+    /// Returns the options of the challenges of a deployment, see `ChallengeOptions`.
+    fn challenge_options(&self, namespace: Option<Namespace>) -> Result<ChallengeOptions> {
+        let mut challenge_options = ChallengeOptions::new()
+            .with_precheck(!self.force)
+            .with_availability_proofs(self.prove_availability);
+        if let Some(evm_input_cache_path) = &self.evm_input_cache {
+            challenge_options =
+                challenge_options.with_evm_input_store(EvmInputStore::open(evm_input_cache_path)?);
+        }
+        if let Some(segment_limit_po2) = self.segment_limit_po2 {
+            challenge_options = challenge_options.with_segment_limit_po2(segment_limit_po2);
+        }
+        if let Some(namespace) = namespace.or(self.namespace) {
+            challenge_options = challenge_options.with_namespace(namespace);
+        }
+        if let Some(dump_guest_data_path) = &self.dump_guest_data {
+            challenge_options =
+                challenge_options.with_guest_data_dump(dump_guest_data_path.clone());
+        }
+
+        Ok(challenge_options)
+    }
+
+    /// Returns the targets of the challenges, by deployment name. Without a deployments file,
+    /// the single target is built from `--eth-rpc-url` and `--counter-address`.
+    async fn targets(&self) -> Result<HashMap<Option<String>, Target>> {
+        let mut targets = HashMap::new();
+
+        match &self.deployments {
+            Some(deployments_path) => {
+                let deployments = Deployments::load(deployments_path)?;
+                for deployment in deployments.iter() {
+                    let target = Target {
+                        eth_rpc_url: deployment.eth_rpc_url.clone(),
+                        verifier_address: deployment.verifier_address,
+                        chain_spec: deployment.chain_spec()?,
+                        blobstream_event_cache: self
+                            .blobstream_event_cache(
+                                &deployment.eth_rpc_url,
+                                deployment.blobstream_address,
+                                deployment.blobstream_impl,
+                            )
+                            .await?,
+                        challenge_options: self.challenge_options(deployment.namespace)?,
+                        job_db: deployment.job_db.clone().or_else(|| self.job_db.clone()),
+                    };
+                    targets.insert(Some(deployment.name.clone()), target);
+                }
+                // Challenges without a deployment go to the single configured deployment
+                if let Ok(deployment) = deployments.route(None) {
+                    let name = Some(deployment.name.clone());
+                    let target = targets[&name].clone();
+                    targets.insert(None, target);
+                }
+            }
+            None => {
+                let eth_rpc_url = self
+                    .eth_rpc_url
+                    .clone()
+                    .ok_or_else(|| anyhow::anyhow!("--eth-rpc-url is required"))?;
+                let verifier_address = self
+                    .counter_address
+                    .ok_or_else(|| anyhow::anyhow!("--counter-address is required"))?;
+                let chain_spec = ETH_SEPOLIA_CHAIN_SPEC.clone();
+                let blobstream_address = canonical_blobstream_address(chain_spec.chain_id)
+                    .context("no known Blobstream deployment on Sepolia")?;
+                let target = Target {
+                    blobstream_event_cache: self
+                        .blobstream_event_cache(&eth_rpc_url, blobstream_address, None)
+                        .await?,
+                    challenge_options: self.challenge_options(None)?,
+                    eth_rpc_url,
+                    verifier_address,
+                    chain_spec,
+                    job_db: self.job_db.clone(),
+                };
+                targets.insert(None, target);
+            }
+        }
+
+        Ok(targets)
+    }
+
+    /// Returns the source of the Celestia data: the Celestia endpoints, backed by the archive if
+    /// configured, with their headers validated, transient failures retried and the fetched data
+    /// cached if configured. Endpoints that cannot be reached are left out.
+    async fn celestia_data_source(&self) -> Result<Box<dyn CelestiaDataSource>> {
+        let timeouts = CelestiaTimeouts {
+            connect: Duration::from_secs(self.celestia_connect_timeout_secs),
+            request: Duration::from_secs(self.celestia_request_timeout_secs),
+        };
+        let mut endpoints = Vec::with_capacity(self.celestia_rpc_url.len());
+        for celestia_rpc_url in &self.celestia_rpc_url {
+            match connect_celestia_node(celestia_rpc_url, timeouts).await {
+                Ok(client) => endpoints.push(self.validating(client)),
+                Err(e) => log::warn!("Cannot connect to Celestia endpoint: {e:#}"),
+            }
+        }
+        let mut celestia_client = MultiEndpointDataSource::new(endpoints)?;
+        if self.cross_check_celestia {
+            celestia_client = celestia_client.with_cross_check()?;
+        }
+
+        let retry_policy = RetryPolicy {
+            max_attempts: self.celestia_max_attempts,
+            initial_backoff: Duration::from_millis(self.celestia_initial_backoff_ms),
+            max_backoff: Duration::from_secs(self.celestia_max_backoff_secs),
+        };
+
+        let celestia_client: Box<dyn CelestiaDataSource> = match &self.celestia_archive_url {
+            Some(archive_url) => Box::new(RetryingDataSource::new(
+                FallbackDataSource::new(
+                    celestia_client,
+                    self.validating(ArchiveDataSource::new(archive_url.clone())),
+                ),
+                retry_policy,
+            )),
+            None => Box::new(RetryingDataSource::new(celestia_client, retry_policy)),
+        };
+
+        Ok(match &self.celestia_cache {
+            Some(celestia_cache_path) => {
+                let celestia_chain_id = celestia_client.head().await?.header.chain_id;
+                Box::new(CachingDataSource::new(
+                    celestia_client,
+                    CelestiaDataStore::open(celestia_cache_path)?,
+                    celestia_chain_id.as_str(),
+                ))
+            }
+            None => celestia_client,
+        })
+    }
+
+    /// Returns the Celestia nodes to sample the challenged blobs from, see `--das-samples`.
+    /// Nodes that cannot be reached are left out.
+    async fn das_peers(&self) -> Vec<impl CelestiaDataSource> {
+        if self.das_samples == 0 {
+            return vec![];
+        }
+
+        let timeouts = CelestiaTimeouts {
+            connect: Duration::from_secs(self.celestia_connect_timeout_secs),
+            request: Duration::from_secs(self.celestia_request_timeout_secs),
+        };
+        let das_peer_urls = if self.das_peer_url.is_empty() {
+            &self.celestia_rpc_url
+        } else {
+            &self.das_peer_url
+        };
+        let mut das_peers = Vec::with_capacity(das_peer_urls.len());
+        for das_peer_url in das_peer_urls {
+            match connect_celestia_node(das_peer_url, timeouts).await {
+                Ok(client) => das_peers.push(self.validating(client)),
+                Err(e) => log::warn!("Cannot connect to DAS peer: {e:#}"),
+            }
+        }
+
+        das_peers
+    }
+
+    /// Checks the headers served by `source`, see `--check-header-chain`.
+    fn validating<S: CelestiaDataSource>(&self, source: S) -> ValidatingDataSource<S> {
+        let source = ValidatingDataSource::new(source);
+        if self.check_header_chain {
+            source.with_chain_check()
+        } else {
+            source
+        }
+    }
 }
 
 #[tokio::main]
-async fn main() -> Result<()> {
+async fn main() -> ExitCode {
     dotenv().ok();
     logging_init();
 
-    let blobstream_address = Address::from_str(BLOBSTREAM_ADDRESS)?;
-
     // Parse the command line arguments.
-    let args = CliArgs::try_parse()?;
+    let args = CliArgs::try_parse().unwrap_or_else(|e| e.exit());
+
+    let reports = match run(&args).await {
+        Ok(reports) => reports,
+        Err(e) => {
+            eprintln!("Error: {e:?}");
+            match (args.index_blob, args.challenge()) {
+                (Some(index_blob), Some(challenge)) => {
+                    let challenged_blob = challenge.challenged_blob(index_blob);
+                    vec![RunReport::failure(index_blob, challenged_blob, &e)]
+                }
+                _ => return ExitCode::from(ChallengeOutcome::InfrastructureError.exit_code()),
+            }
+        }
+    };
+
+    if let Some(report_path) = &args.report {
+        // A single challenge is reported as an object, a challenges file as a list.
+        let written = match reports.as_slice() {
+            [report] if args.challenges.is_none() => report.write(report_path),
+            _ => RunReport::write_all(&reports, report_path),
+        };
+        if let Err(e) = written {
+            eprintln!("Error: {e:?}");
+        }
+    }
+
+    // The first failure determines the exit code of a challenges file.
+    let exit_code = reports
+        .iter()
+        .map(|report| report.exit_code)
+        .find(|&exit_code| exit_code != ChallengeOutcome::FraudProven.exit_code())
+        .unwrap_or_default();
+
+    ExitCode::from(exit_code)
+}
+
+/// Runs the challenges one after the other, each against its deployment. All challenges share
+/// the same Celestia data source.
+async fn run(args: &CliArgs) -> Result<Vec<RunReport>> {
+    let challenge_requests = args.challenge_requests()?;
+    let targets = args.targets().await?;
+    let celestia_client = args.celestia_data_source().await?;
+    let das_peers = args.das_peers().await;
+
+    let mut reports = Vec::with_capacity(challenge_requests.len());
+    for challenge_request in challenge_requests {
+        let challenge = challenge_request.challenge();
+        let ChallengeRequest {
+            deployment,
+            index_blob,
+            challenged_blob,
+            ..
+        } = challenge_request;
+
+        let result = match targets.get(&deployment) {
+            Some(target) => {
+                if let Some(deployment) = &deployment {
+                    log::info!(
+                        "Challenging {challenged_blob} (index {index_blob}) on {deployment}"
+                    );
+                }
+                run_challenge(
+                    args,
+                    &celestia_client,
+                    &das_peers,
+                    target,
+                    index_blob,
+                    challenge,
+                )
+                .await
+            }
+            None => Err(anyhow::anyhow!(match &deployment {
+                Some(deployment) => format!("unknown deployment {deployment}"),
+                None => "several deployments are configured, specify the deployment of the \
+                         challenge"
+                    .to_string(),
+            })),
+        };
+
+        let report = match result {
+            Ok((tx_hash, journal)) => {
+                RunReport::success(index_blob, challenged_blob, tx_hash, journal)
+            }
+            Err(e) => {
+                eprintln!("Error: {e:?}");
+                RunReport::failure(index_blob, challenged_blob, &e)
+            }
+        };
+        reports.push(report);
+    }
 
+    Ok(reports)
+}
+
+/// Checks that a proof was generated against the Blobstream contract and chain of the target, so
+/// that it can be submitted to its verifier.
+fn check_proof_target(journal: &JournalSummary, target: &Target) -> Result<()> {
+    let blobstream_address = target.blobstream_event_cache.blobstream_address();
+    ensure!(
+        journal.blobstream_address == blobstream_address,
+        "the proof is for Blobstream contract {}, not {blobstream_address}",
+        journal.blobstream_address
+    );
+    let config_id = B256::from_slice(target.chain_spec.digest().as_bytes());
+    ensure!(
+        journal.commitment_config_id == config_id,
+        "the proof commits to chain config {}, not {config_id} (chain {})",
+        journal.commitment_config_id,
+        target.chain_spec.chain_id
+    );
+
+    Ok(())
+}
+
+/// Runs a challenge and submits the proof. Returns the hash of the submission transaction
+/// and the decoded journal of the proof.
+async fn run_challenge(
+    args: &CliArgs,
+    celestia_client: &impl CelestiaDataSource,
+    das_peers: &[impl CelestiaDataSource],
+    target: &Target,
+    index_blob: SpanSequence,
+    challenge: DaChallenge,
+) -> Result<(Option<B256>, Option<JournalSummary>)> {
+    let challenged_blob = challenge.challenged_blob(index_blob);
     // Create an alloy provider for that private key and URL.
-    let wallet = EthereumWallet::from(args.eth_wallet_private_key);
+    let wallet = EthereumWallet::from(args.eth_wallet_private_key.clone());
     let eth_provider = ProviderBuilder::new()
         .wallet(wallet)
-        .on_http(args.eth_rpc_url.clone());
+        .on_http(target.eth_rpc_url.clone());
+    let blobstream_event_cache = &target.blobstream_event_cache;
 
-    let celestia_client = CelestiaClient::new(args.celestia_rpc_url.as_str(), None).await?;
+    // Create an alloy instance of the Counter contract.
+    let counter_contract = ICounter::new(target.verifier_address, &eth_provider);
 
-    // Need a different provider for now for Blobstream event filtering
-    // TODO: import hana's find_data_commitment() into toolkit
-    let root_provider = RootProvider::connect(args.eth_rpc_url.as_str()).await?;
+    let job_key = JobKey {
+        chain_id: target.chain_spec.chain_id,
+        blobstream_address: blobstream_event_cache.blobstream_address(),
+        verifier_address: target.verifier_address,
+        index_blob,
+        challenge,
+    };
+    let job_store = target.job_db.as_ref().map(JobStore::open).transpose()?;
+    let job = job_store
+        .as_ref()
+        .map(|store| -> Result<_> {
+            let job = store.find_or_create(&job_key)?;
+            if !args.reset_attempts {
+                return Ok(job);
+            }
+            log::info!("Resetting the attempts of challenge job #{}", job.id);
+            store.reset_attempts(job.id)?;
+            store
+                .get(job.id)?
+                .context("challenge job vanished from the job store")
+        })
+        .transpose()?;
 
-    let index_blob: SpanSequence = args.index_blob;
-    let challenged_blob: SpanSequence = args.challenged_blob;
+    if let Some(job) = &job {
+        if job.status == JobStatus::Submitted {
+            log::info!(
+                "Challenge job #{} was already submitted in tx {:?}, nothing to do",
+                job.id,
+                job.tx_hash
+            );
+            return Ok((job.tx_hash, None));
+        }
+    }
 
-    // Create an alloy instance of the Counter contract.
-    let counter_contract = ICounter::new(args.counter_address, &eth_provider);
-
-    let (receipt, seal) = challenge_da_commitment(
-        &celestia_client,
-        root_provider,
-        ETH_SEPOLIA_CHAIN_SPEC.clone(),
-        args.execution_block,
-        blobstream_address,
-        index_blob,
-        challenged_blob,
-        #[cfg(any(feature = "beacon", feature = "history"))]
-        args.beacon_api_url,
-        #[cfg(feature = "history")]
-        args.commitment_block,
-    )
-    .await?;
-    increment_counter(counter_contract, receipt, seal).await?;
+    let retry_policy = RetryPolicy {
+        max_attempts: args.max_attempts,
+        initial_backoff: Duration::from_secs(args.initial_backoff_secs),
+        max_backoff: Duration::from_secs(args.max_backoff_secs),
+    };
+    let remaining_policy = |phase| {
+        retry_policy.remaining(
+            job.as_ref()
+                .map(|job| job.attempts(phase))
+                .unwrap_or_default(),
+        )
+    };
 
-    Ok(())
+    let record_attempt = |phase| -> Result<()> {
+        if let (Some(store), Some(job)) = (&job_store, &job) {
+            store.record_attempt(job.id, phase)?;
+        }
+        Ok(())
+    };
+    let record_attempt = &record_attempt;
+
+    // Proofs are bound to the Blobstream contract and chain of their Steel commitment
+    let stored_proof = job
+        .as_ref()
+        .and_then(|job| job.proof())
+        .filter(|(receipt, _)| {
+            let checked = JournalSummary::decode(&receipt.journal.bytes)
+                .map_err(anyhow::Error::from)
+                .and_then(|journal| check_proof_target(&journal, target));
+            if let Err(e) = &checked {
+                log::warn!("Discarding the stored proof of the challenge job: {e:#}");
+            }
+            checked.is_ok()
+        });
+    let (receipt, seal) = match stored_proof {
+        Some(proof) => {
+            log::info!("Resuming challenge job with the stored proof");
+            proof
+        }
+        None => {
+            let uncovered_height_policy = if args.allow_uncovered_height {
+                UncoveredHeightPolicy::Challenge
+            } else if let Some(wait_secs) = args.wait_for_blobstream_secs {
+                UncoveredHeightPolicy::Wait {
+                    timeout: Duration::from_secs(wait_secs),
+                    poll_interval: Duration::from_secs(args.blobstream_poll_interval_secs),
+                }
+            } else {
+                UncoveredHeightPolicy::Reject
+            };
+            ensure_heights_covered(
+                blobstream_event_cache,
+                index_blob,
+                challenged_blob,
+                uncovered_height_policy,
+            )
+            .await?;
+
+            let challenges_availability = !matches!(challenge, DaChallenge::BlobDataMismatch(_));
+            if challenges_availability
+                && args.das_samples > 0
+                && !args.force
+                && !args.prove_availability
+            {
+                // Blocks that are pruned or past the head of the node cannot be sampled, the
+                // challenge itself proves what is wrong with them
+                match celestia_client.header(challenged_blob.height).await {
+                    Ok(block_header) => {
+                        let sampling_report = sample_blob_shares(
+                            &block_header,
+                            das_peers,
+                            challenged_blob,
+                            args.das_samples,
+                        )
+                        .await;
+                        log::info!("Share sampling: {sampling_report}");
+                        if sampling_report.is_available() {
+                            return Err(anyhow::Error::new(BlobIsAvailable)
+                                .context(format!("share sampling {sampling_report}")));
+                        }
+                    }
+                    Err(e) => log::warn!(
+                        "Skipping share sampling, failed to fetch the header of block {}: {e:#}",
+                        challenged_blob.height
+                    ),
+                }
+            }
+
+            if let (Some(store), Some(job)) = (&job_store, &job) {
+                store.mark_proving(job.id)?;
+            }
+
+            let execution_block = args.execution_block;
+            #[cfg(any(feature = "beacon", feature = "history"))]
+            let beacon_api_url = &args.beacon_api_url;
+            #[cfg(feature = "history")]
+            let commitment_block = args.commitment_block;
+
+            let proving_policy = remaining_policy(JobPhase::Proving);
+            let result = retry_with_backoff(&proving_policy, "proving", || async move {
+                record_attempt(JobPhase::Proving)?;
+                challenge_da_commitment_with_cache(
+                    celestia_client,
+                    blobstream_event_cache,
+                    &target.challenge_options,
+                    target.chain_spec.clone(),
+                    execution_block,
+                    index_blob,
+                    challenge,
+                    #[cfg(any(feature = "beacon", feature = "history"))]
+                    beacon_api_url.clone(),
+                    #[cfg(feature = "history")]
+                    commitment_block,
+                )
+                .await
+            })
+            .await;
+            log::debug!(
+                "Blobstream event cache: {:?}",
+                blobstream_event_cache.stats()
+            );
+
+            if let (Some(store), Some(job)) = (&job_store, &job) {
+                match &result {
+                    Ok((receipt, seal)) => store.record_proof(job.id, receipt, seal)?,
+                    Err(e) => store.record_failure(job.id, &format!("{e:#}"))?,
+                }
+            }
+            result?
+        }
+    };
+
+    let journal = JournalSummary::decode(&receipt.journal.bytes).context("invalid journal")?;
+    ensure!(
+        journal.index_blob == index_blob && journal.challenge == challenge,
+        "the proof is for challenge {} (index {}), not {challenge} (index {index_blob})",
+        journal.challenge,
+        journal.index_blob,
+    );
+    check_proof_target(&journal, target)?;
+    if journal.outcome == DaOutcome::BlobAvailable {
+        log::info!("The challenged blob is available, the proof of availability is not submitted");
+        return Ok((None, Some(journal)));
+    }
+
+    // Transaction sent by a previous attempt, whose confirmation was not observed
+    let sent_tx_hash = Mutex::new(job.as_ref().and_then(|job| job.tx_hash));
+    let set_sent_tx_hash = |tx_hash: Option<B256>| -> Result<()> {
+        *sent_tx_hash.lock().unwrap() = tx_hash;
+        if let (Some(store), Some(job)) = (&job_store, &job) {
+            match tx_hash {
+                Some(tx_hash) => store.record_sent_transaction(job.id, tx_hash)?,
+                None => store.clear_sent_transaction(job.id)?,
+            }
+        }
+        Ok(())
+    };
+    let set_sent_tx_hash = &set_sent_tx_hash;
+    let sent_tx_hash = &sent_tx_hash;
+
+    let submission_policy = remaining_policy(JobPhase::Submission);
+    let confirmation_timeout = Duration::from_secs(args.confirmation_timeout_secs);
+    let submission = retry_with_backoff(&submission_policy, "proof submission", || {
+        let counter_contract = &counter_contract;
+        let receipt = receipt.clone();
+        let seal = seal.clone();
+        async move {
+            // The transaction may still land: sending the proof again would only waste gas,
+            // unless it replaces the transaction if it is still pending
+            let previous_tx_hash = *sent_tx_hash.lock().unwrap();
+            let mut replaced_tx_hash = None;
+            if let Some(tx_hash) = previous_tx_hash {
+                log::info!("Checking transaction {tx_hash} sent by a previous attempt");
+                match transaction_outcome(counter_contract.provider(), tx_hash).await? {
+                    Some(TransactionOutcome::Confirmed) => return Ok(tx_hash),
+                    Some(TransactionOutcome::Reverted) => {
+                        set_sent_tx_hash(None)?;
+                        bail!("transaction failed: {tx_hash}");
+                    }
+                    Some(TransactionOutcome::Dropped) => {
+                        log::warn!("Transaction {tx_hash} was dropped, sending the proof again");
+                        set_sent_tx_hash(None)?;
+                    }
+                    None => {
+                        log::warn!(
+                            "Transaction {tx_hash} is still pending, replacing it with higher fees"
+                        );
+                        replaced_tx_hash = Some(tx_hash);
+                    }
+                }
+            }
+
+            record_attempt(JobPhase::Submission)?;
+            let tx_hash =
+                send_increment_counter(counter_contract, receipt, seal, replaced_tx_hash).await?;
+            set_sent_tx_hash(Some(tx_hash))?;
+            match confirm_transaction(counter_contract.provider(), tx_hash, confirmation_timeout)
+                .await
+                .with_context(|| format!("transaction did not confirm: {tx_hash}"))?
+            {
+                TransactionOutcome::Confirmed => Ok(tx_hash),
+                TransactionOutcome::Reverted => {
+                    set_sent_tx_hash(None)?;
+                    Err(anyhow!("transaction failed: {tx_hash}"))
+                }
+                TransactionOutcome::Dropped => {
+                    // The replaced transaction may have landed first, with the same nonce
+                    if let Some(replaced_tx_hash) = replaced_tx_hash {
+                        let replaced_outcome =
+                            transaction_outcome(counter_contract.provider(), replaced_tx_hash)
+                                .await?;
+                        if replaced_outcome == Some(TransactionOutcome::Confirmed) {
+                            set_sent_tx_hash(Some(replaced_tx_hash))?;
+                            return Ok(replaced_tx_hash);
+                        }
+                    }
+                    set_sent_tx_hash(None)?;
+                    Err(anyhow!("transaction was dropped: {tx_hash}"))
+                }
+            }
+        }
+    })
+    .await;
+    if let (Some(store), Some(job)) = (&job_store, &job) {
+        match &submission {
+            Ok(tx_hash) => store.record_submission(job.id, *tx_hash)?,
+            Err(e) => store.record_failure(job.id, &format!("{e:#}"))?,
+        }
+    }
+
+    let mut notifiers = Notifiers::new();
+    if let Some(url) = &args.webhook_url {
+        notifiers = notifiers.with(WebhookNotifier::new(url.clone()));
+    }
+    if let Some(url) = &args.slack_webhook_url {
+        notifiers = notifiers.with(SlackNotifier::new(url.clone()));
+    }
+
+    let event = match &submission {
+        Ok(tx_hash) => ChallengeEvent::FraudProven {
+            index_blob,
+            challenged_blob,
+            journal: journal.clone(),
+            tx_hash: *tx_hash,
+            tx_url: args
+                .explorer_url
+                .as_ref()
+                .map(|explorer_url| explorer_tx_url(explorer_url, *tx_hash))
+                .transpose()?,
+        },
+        Err(e) => ChallengeEvent::SubmissionFailed {
+            index_blob,
+            challenged_blob,
+            journal: Some(journal.clone()),
+            error: format!("{e:#}"),
+        },
+    };
+    notifiers.notify(&event).await;
+
+    let tx_hash = submission?;
+
+    Ok((Some(tx_hash), Some(journal)))
 }
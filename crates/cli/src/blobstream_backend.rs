@@ -0,0 +1,316 @@
+use crate::blobstream_data_commitment::{
+    find_data_commitment_by_binary_search, find_data_commitment_by_log_scan,
+    find_first_data_commitment_event, IBlobstream0, ISP1Blobstream,
+};
+use crate::event_cache::EventLookupStrategy;
+use alloy_primitives::{Address, ChainId, U256};
+use async_trait::async_trait;
+use hana_blobstream::blobstream::SP1BlobstreamDataCommitmentStored;
+use risc0_ethereum_contracts::alloy::network::{Ethereum, Network, TransactionBuilder};
+use risc0_ethereum_contracts::alloy::providers::{Provider, RootProvider};
+use risc0_steel::alloy::sol_types::SolCall;
+use risc0_steel::host::BlockNumberOrTag;
+use std::sync::Arc;
+use toolkit::blobstream::{BinaryMerkleProof, DataRootTuple, IDAOracle};
+use toolkit::BlobstreamImpl;
+
+pub use crate::blobstream_data_commitment::{FirstCommitmentEntry, FirstCommitmentTable};
+
+/// Number of Ethereum blocks scanned to find the first data commitment of a deployment that
+/// is not in the table of known deployments.
+const FIRST_COMMITMENT_BLOCK_WINDOW: u64 = 100_000;
+
+/// Host-side view of a Blobstream deployment, independent of its implementation.
+#[async_trait]
+pub trait BlobstreamBackend: Send + Sync {
+    /// The implementation, as passed to the guest.
+    fn implementation(&self) -> BlobstreamImpl;
+
+    fn address(&self) -> Address;
+
+    fn provider(&self) -> &RootProvider;
+
+    /// Returns the latest Celestia block covered by Blobstream at the specified Ethereum block.
+    async fn latest_height_at(&self, eth_block: BlockNumberOrTag) -> Result<u64, anyhow::Error>;
+
+    /// Returns the latest Celestia block covered by Blobstream.
+    async fn latest_height(&self) -> Result<u64, anyhow::Error> {
+        self.latest_height_at(BlockNumberOrTag::Latest).await
+    }
+
+    /// Returns the highest Celestia block height that the guest considers covered by
+    /// Blobstream. Challenges for higher blocks prove that the height is too high.
+    async fn max_covered_height(&self) -> Result<u64, anyhow::Error> {
+        self.latest_height().await
+    }
+
+    /// Returns the first data commitment stored by the deployment.
+    async fn first_commitment(
+        &self,
+        _chain_id: ChainId,
+    ) -> Result<SP1BlobstreamDataCommitmentStored, anyhow::Error> {
+        find_first_data_commitment_event(
+            self.address(),
+            self.provider(),
+            FIRST_COMMITMENT_BLOCK_WINDOW,
+        )
+        .await
+    }
+
+    /// Returns the data commitment covering the specified Celestia block height.
+    async fn find_commitment(
+        &self,
+        block_height: u64,
+        lookup_strategy: EventLookupStrategy,
+    ) -> Result<SP1BlobstreamDataCommitmentStored, anyhow::Error> {
+        match lookup_strategy {
+            EventLookupStrategy::BinarySearch => {
+                match find_data_commitment_by_binary_search(block_height, self).await {
+                    Ok(event) => Ok(event),
+                    Err(e) => {
+                        log::warn!(
+                            "binary search of the Blobstream commitment failed, \
+                             scanning logs instead: {e:#}"
+                        );
+                        find_data_commitment_by_log_scan(
+                            block_height,
+                            self.address(),
+                            self.provider(),
+                        )
+                        .await
+                    }
+                }
+            }
+            EventLookupStrategy::LogScan => {
+                find_data_commitment_by_log_scan(block_height, self.address(), self.provider())
+                    .await
+            }
+        }
+    }
+}
+
+/// Succinct's SP1Blobstream.
+pub struct Sp1BlobstreamBackend {
+    address: Address,
+    provider: RootProvider,
+    first_commitments: FirstCommitmentTable,
+}
+
+impl Sp1BlobstreamBackend {
+    pub fn new(address: Address, provider: RootProvider) -> Self {
+        Self {
+            address,
+            provider,
+            first_commitments: Default::default(),
+        }
+    }
+
+    pub fn with_first_commitments(mut self, first_commitments: FirstCommitmentTable) -> Self {
+        self.first_commitments = first_commitments;
+        self
+    }
+}
+
+#[async_trait]
+impl BlobstreamBackend for Sp1BlobstreamBackend {
+    fn implementation(&self) -> BlobstreamImpl {
+        BlobstreamImpl::Sp1
+    }
+
+    fn address(&self) -> Address {
+        self.address
+    }
+
+    fn provider(&self) -> &RootProvider {
+        &self.provider
+    }
+
+    async fn latest_height_at(&self, eth_block: BlockNumberOrTag) -> Result<u64, anyhow::Error> {
+        let latest_block = ISP1Blobstream::new(self.address, &self.provider)
+            .latestBlock()
+            .block(eth_block.into())
+            .call()
+            .await?
+            ._0;
+
+        Ok(latest_block)
+    }
+
+    /// `latestBlock` is the exclusive end of the last committed range.
+    async fn max_covered_height(&self) -> Result<u64, anyhow::Error> {
+        Ok(self.latest_height().await?.saturating_sub(1))
+    }
+
+    async fn first_commitment(
+        &self,
+        chain_id: ChainId,
+    ) -> Result<SP1BlobstreamDataCommitmentStored, anyhow::Error> {
+        if let Some(event) = self.first_commitments.get(chain_id) {
+            return Ok(event);
+        }
+
+        find_first_data_commitment_event(
+            self.address,
+            &self.provider,
+            FIRST_COMMITMENT_BLOCK_WINDOW,
+        )
+        .await
+    }
+}
+
+/// RISC Zero's Blobstream0.
+pub struct Blobstream0Backend {
+    address: Address,
+    provider: RootProvider,
+}
+
+impl Blobstream0Backend {
+    pub fn new(address: Address, provider: RootProvider) -> Self {
+        Self { address, provider }
+    }
+}
+
+#[async_trait]
+impl BlobstreamBackend for Blobstream0Backend {
+    fn implementation(&self) -> BlobstreamImpl {
+        BlobstreamImpl::R0
+    }
+
+    fn address(&self) -> Address {
+        self.address
+    }
+
+    fn provider(&self) -> &RootProvider {
+        &self.provider
+    }
+
+    async fn latest_height_at(&self, eth_block: BlockNumberOrTag) -> Result<u64, anyhow::Error> {
+        let latest_height = IBlobstream0::new(self.address, &self.provider)
+            .latestHeight()
+            .block(eth_block.into())
+            .call()
+            .await?
+            ._0;
+
+        Ok(latest_height)
+    }
+}
+
+/// Any `IDAOracle` contract, ex: a rollup oracle proxying Blobstream. Data commitments are looked
+/// up from the `DataCommitmentStored` events emitted at the oracle address.
+pub struct DaOracleBackend {
+    address: Address,
+    provider: RootProvider,
+}
+
+impl DaOracleBackend {
+    pub fn new(address: Address, provider: RootProvider) -> Self {
+        Self { address, provider }
+    }
+
+    /// Checks that the contract answers `verifyAttestation()`, with a dummy attestation.
+    async fn probe(&self) -> Result<(), anyhow::Error> {
+        let verify_attestation_call = IDAOracle::verifyAttestationCall {
+            _tupleRootNonce: U256::ZERO,
+            _tuple: DataRootTuple {
+                height: U256::ZERO,
+                dataRoot: Default::default(),
+            },
+            _proof: BinaryMerkleProof {
+                sideNodes: vec![],
+                key: U256::ZERO,
+                numLeaves: U256::from(1u64),
+            },
+        };
+        let tx = <Ethereum as Network>::TransactionRequest::default()
+            .with_to(self.address)
+            .with_input(verify_attestation_call.abi_encode());
+
+        let output = self.provider.call(tx).await?;
+        IDAOracle::verifyAttestationCall::abi_decode_returns(&output, true)?;
+
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl BlobstreamBackend for DaOracleBackend {
+    fn implementation(&self) -> BlobstreamImpl {
+        BlobstreamImpl::DaOracle
+    }
+
+    fn address(&self) -> Address {
+        self.address
+    }
+
+    fn provider(&self) -> &RootProvider {
+        &self.provider
+    }
+
+    async fn latest_height_at(&self, _eth_block: BlockNumberOrTag) -> Result<u64, anyhow::Error> {
+        Err(anyhow::anyhow!(
+            "IDAOracle contracts do not expose their latest height"
+        ))
+    }
+
+    /// The guest cannot prove that a height is too high, all heights are considered covered.
+    async fn max_covered_height(&self) -> Result<u64, anyhow::Error> {
+        Ok(u64::MAX)
+    }
+
+    async fn find_commitment(
+        &self,
+        block_height: u64,
+        _lookup_strategy: EventLookupStrategy,
+    ) -> Result<SP1BlobstreamDataCommitmentStored, anyhow::Error> {
+        find_data_commitment_by_log_scan(block_height, self.address, &self.provider).await
+    }
+}
+
+/// Returns the backend for the specified Blobstream implementation.
+pub fn blobstream_backend(
+    blobstream_impl: BlobstreamImpl,
+    address: Address,
+    provider: RootProvider,
+    first_commitments: FirstCommitmentTable,
+) -> Arc<dyn BlobstreamBackend> {
+    match blobstream_impl {
+        BlobstreamImpl::Sp1 => Arc::new(
+            Sp1BlobstreamBackend::new(address, provider).with_first_commitments(first_commitments),
+        ),
+        BlobstreamImpl::R0 => Arc::new(Blobstream0Backend::new(address, provider)),
+        BlobstreamImpl::DaOracle => Arc::new(DaOracleBackend::new(address, provider)),
+    }
+}
+
+/// Probes the contract at the specified address and returns the matching backend. Contracts
+/// that are neither Blobstream0 nor SP1Blobstream are accepted as long as they implement
+/// `IDAOracle`.
+pub async fn detect_blobstream_backend(
+    address: Address,
+    provider: RootProvider,
+    first_commitments: FirstCommitmentTable,
+) -> Result<Arc<dyn BlobstreamBackend>, anyhow::Error> {
+    let blobstream0 = Blobstream0Backend::new(address, provider.clone());
+    if blobstream0.latest_height().await.is_ok() {
+        return Ok(Arc::new(blobstream0));
+    }
+
+    let sp1_blobstream = Sp1BlobstreamBackend::new(address, provider.clone())
+        .with_first_commitments(first_commitments);
+    if sp1_blobstream.latest_height().await.is_ok() {
+        return Ok(Arc::new(sp1_blobstream));
+    }
+
+    let da_oracle = DaOracleBackend::new(address, provider);
+    da_oracle.probe().await.map_err(|e| {
+        anyhow::anyhow!(
+            "{address} is neither a Blobstream0, an SP1Blobstream nor an IDAOracle: {e:#}"
+        )
+    })?;
+    log::warn!(
+        "{address} is not a known Blobstream implementation, using it as a generic IDAOracle"
+    );
+
+    Ok(Arc::new(da_oracle))
+}
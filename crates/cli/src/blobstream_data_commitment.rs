@@ -1,94 +1,511 @@
+use crate::blobstream_backend::BlobstreamBackend;
+use crate::retry::{is_transient, retry_with_backoff, RetryPolicy};
 use alloy_primitives::{Address, ChainId, B256, U256};
-use futures_util::StreamExt;
-use hana_blobstream::blobstream::SP1Blobstream::SP1BlobstreamInstance;
+use anyhow::Context;
 use hana_blobstream::blobstream::SP1BlobstreamDataCommitmentStored;
-use risc0_ethereum_contracts::alloy::contract::private::Provider;
-use risc0_steel::alloy::contract::private::Transport;
-use risc0_steel::alloy::network::Ethereum;
-use std::str::FromStr;
+use risc0_ethereum_contracts::alloy::providers::{Provider as _, RootProvider};
+use risc0_steel::alloy::contract::Error as ContractError;
+use risc0_steel::alloy::sol;
+use risc0_steel::alloy::transports::{RpcError, TransportError};
+use risc0_steel::host::BlockNumberOrTag;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::ops::ControlFlow;
+use std::path::Path;
+use std::time::Duration;
+use toolkit::constants::NETWORKS;
 
-const MAINNET_CHAIN_ID: ChainId = 1;
-const SEPOLIA_CHAIN_ID: ChainId = 11155111;
+sol! {
+    /// Subset of the SP1Blobstream interface used to look up data commitments from storage.
+    ///
+    /// Blobstream0 emits the exact same `DataCommitmentStored` event, so the event filter of this
+    /// interface is used for both implementations.
+    #[sol(rpc)]
+    interface ISP1Blobstream {
+        function latestBlock() external view returns (uint64);
 
-/// Filters the [current_block - block_window, current_block] Ethereum block range to find
-/// the first Blobstream event in the range.
-async fn find_first_data_commitment_event<T: Transport + Clone, P: Provider<T, Ethereum>>(
-    blobstream_contract: SP1BlobstreamInstance<T, P>,
-    block_window: u64,
-) -> Result<SP1BlobstreamDataCommitmentStored, anyhow::Error> {
-    let current_block = blobstream_contract.provider().get_block_number().await?;
-    let start_block = if current_block > block_window {
-        current_block - block_window
-    } else {
-        1
-    };
+        event DataCommitmentStored(
+            uint256 proofNonce,
+            uint64 indexed startBlock,
+            uint64 indexed endBlock,
+            bytes32 indexed dataCommitment
+        );
+    }
+}
 
-    let mut event_stream = blobstream_contract
-        .DataCommitmentStored_filter()
-        .from_block(start_block)
-        .to_block(current_block)
-        .watch()
-        .await?
-        .into_stream();
-
-    if let Some(evt) = event_stream.next().await {
-        let (event, _) = evt?;
-        if event.proofNonce != U256::from(1u64) {
-            return Err(anyhow::anyhow!(
-                "proofNonce != 1, block window is too small"
-            ));
+sol! {
+    /// Subset of the Blobstream0 (RISC Zero) interface used to look up data commitments.
+    #[sol(rpc)]
+    interface IBlobstream0 {
+        function latestHeight() external view returns (uint64);
+    }
+}
+
+/// Number of Ethereum blocks covered by a single `eth_getLogs` query. The chunk size is halved
+/// when a provider rejects a query, and grows back to this value after successful queries.
+const LOG_QUERY_CHUNK_SIZE: u64 = 10_000;
+
+/// JSON-RPC error code used by RPC providers to reject `eth_getLogs` queries over too many blocks
+/// or returning too many logs: limit exceeded (EIP-1474).
+const LOG_RANGE_TOO_LARGE_CODE: i64 = -32005;
+
+/// Error messages of the providers rejecting such queries with other error codes, ex: invalid
+/// params (-32602).
+const LOG_RANGE_TOO_LARGE_MESSAGES: &[&str] = &[
+    "query returned more than",
+    "log response size exceeded",
+    "block range is too large",
+    "block range too large",
+    "exceed maximum block range",
+    "block range is too wide",
+];
+
+/// Backoff policy for the `eth_getLogs` queries and `eth_call`s of the data commitment lookups
+/// failing with transient errors.
+fn rpc_retry_policy() -> RetryPolicy {
+    RetryPolicy {
+        max_attempts: 5,
+        initial_backoff: Duration::from_secs(1),
+        max_backoff: Duration::from_secs(30),
+    }
+}
+
+/// Returns true if the provider rejected the query because of the size of its block range or
+/// of its result set. The JSON-RPC error code is checked first, the error message only as a
+/// fallback for providers using other codes.
+fn is_log_range_too_large(err: &anyhow::Error) -> bool {
+    err.chain().any(|cause| {
+        let rpc_error = match cause.downcast_ref::<ContractError>() {
+            Some(ContractError::TransportError(e)) => Some(e),
+            _ => cause.downcast_ref::<TransportError>(),
+        };
+        if let Some(RpcError::ErrorResp(payload)) = rpc_error {
+            if payload.code == LOG_RANGE_TOO_LARGE_CODE {
+                return true;
+            }
         }
 
-        let sp1_event = SP1BlobstreamDataCommitmentStored {
+        let message = cause.to_string().to_lowercase();
+        LOG_RANGE_TOO_LARGE_MESSAGES
+            .iter()
+            .any(|pattern| message.contains(pattern))
+    })
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ScanDirection {
+    /// From the lowest to the highest block.
+    Forward,
+    /// From the highest to the lowest block.
+    Backward,
+}
+
+async fn query_data_commitment_events(
+    blobstream_contract: &ISP1Blobstream::ISP1BlobstreamInstance<(), &RootProvider>,
+    from_block: u64,
+    to_block: u64,
+) -> Result<Vec<SP1BlobstreamDataCommitmentStored>, anyhow::Error> {
+    let events = blobstream_contract
+        .DataCommitmentStored_filter()
+        .from_block(from_block)
+        .to_block(to_block)
+        .query()
+        .await?;
+
+    Ok(events
+        .into_iter()
+        .map(|(event, _log)| SP1BlobstreamDataCommitmentStored {
             proof_nonce: event.proofNonce,
             start_block: event.startBlock,
             end_block: event.endBlock,
             data_commitment: event.dataCommitment,
+        })
+        .collect())
+}
+
+/// Scans the `DataCommitmentStored` events emitted in the `[first_block, last_block]` Ethereum
+/// block range and returns the first event for which `visit` breaks, if any.
+///
+/// Logs are queried in chunks of blocks. Chunks are split in half every time the provider rejects
+/// a query for being too large, and queries failing with transient errors are retried
+/// with exponential backoff.
+async fn scan_data_commitment_events<F>(
+    blobstream_address: Address,
+    provider: &RootProvider,
+    first_block: u64,
+    last_block: u64,
+    direction: ScanDirection,
+    mut visit: F,
+) -> Result<Option<SP1BlobstreamDataCommitmentStored>, anyhow::Error>
+where
+    F: FnMut(&SP1BlobstreamDataCommitmentStored) -> ControlFlow<()>,
+{
+    if first_block > last_block {
+        return Ok(None);
+    }
+
+    let blobstream_contract = ISP1Blobstream::new(blobstream_address, provider);
+    let retry_policy = rpc_retry_policy();
+    let mut chunk_size = LOG_QUERY_CHUNK_SIZE;
+    let mut failed_attempts = 0;
+
+    // Next block to scan. None once the whole range is scanned.
+    let mut cursor = Some(match direction {
+        ScanDirection::Forward => first_block,
+        ScanDirection::Backward => last_block,
+    });
+
+    while let Some(position) = cursor {
+        let (from_block, to_block) = match direction {
+            ScanDirection::Forward => (
+                position,
+                position.saturating_add(chunk_size - 1).min(last_block),
+            ),
+            ScanDirection::Backward => (
+                position.saturating_sub(chunk_size - 1).max(first_block),
+                position,
+            ),
         };
 
-        log::info!("Found first DataCommitmentStored event for Blobstream: {sp1_event:?}",);
-        return Ok(sp1_event);
+        let mut events =
+            match query_data_commitment_events(&blobstream_contract, from_block, to_block).await {
+                Ok(events) => events,
+                Err(e) if chunk_size > 1 && is_log_range_too_large(&e) => {
+                    chunk_size /= 2;
+                    log::debug!(
+                        "eth_getLogs rejected for blocks {from_block}..={to_block}, \
+                         retrying with {chunk_size} blocks per query: {e:#}"
+                    );
+                    continue;
+                }
+                Err(e) if failed_attempts + 1 < retry_policy.max_attempts && is_transient(&e) => {
+                    failed_attempts += 1;
+                    let backoff = retry_policy.backoff(failed_attempts);
+                    log::warn!(
+                        "eth_getLogs failed for blocks {from_block}..={to_block}, \
+                         retrying in {:.1} s: {e:#}",
+                        backoff.as_secs_f32()
+                    );
+                    tokio::time::sleep(backoff).await;
+                    continue;
+                }
+                Err(e) => {
+                    return Err(e.context(format!(
+                        "failed to query Blobstream logs for blocks {from_block}..={to_block}"
+                    )))
+                }
+            };
+        failed_attempts = 0;
+
+        if direction == ScanDirection::Backward {
+            events.reverse();
+        }
+        if let Some(event) = events.into_iter().find(|event| visit(event).is_break()) {
+            return Ok(Some(event));
+        }
+
+        chunk_size = chunk_size.saturating_mul(2).min(LOG_QUERY_CHUNK_SIZE);
+        cursor = match direction {
+            ScanDirection::Forward => to_block.checked_add(1).filter(|b| *b <= last_block),
+            ScanDirection::Backward => from_block.checked_sub(1).filter(|b| *b >= first_block),
+        };
+    }
+
+    Ok(None)
+}
+
+/// Scans the [current_block - block_window, current_block] Ethereum block range to find
+/// the first Blobstream event in the range.
+pub(crate) async fn find_first_data_commitment_event(
+    blobstream_address: Address,
+    provider: &RootProvider,
+    block_window: u64,
+) -> Result<SP1BlobstreamDataCommitmentStored, anyhow::Error> {
+    let current_block = provider.get_block_number().await?;
+    let start_block = current_block.saturating_sub(block_window).max(1);
+
+    let event = scan_data_commitment_events(
+        blobstream_address,
+        provider,
+        start_block,
+        current_block,
+        ScanDirection::Forward,
+        |_| ControlFlow::Break(()),
+    )
+    .await?
+    .ok_or_else(|| {
+        anyhow::anyhow!("no DataCommitmentStored event in the last {block_window} blocks")
+    })?;
+
+    if event.proof_nonce != U256::from(1u64) {
+        return Err(anyhow::anyhow!(
+            "proofNonce != 1, block window is too small"
+        ));
     }
 
-    Err(anyhow::anyhow!("event stream closed before height reached"))
+    log::info!("Found first DataCommitmentStored event for Blobstream: {event:?}",);
+    Ok(event)
+}
+
+/// Entry of a first-commitment config file. Only the first commitment is listed, so the proof
+/// nonce is always 1.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FirstCommitmentEntry {
+    pub chain_id: ChainId,
+    pub start_block: u64,
+    pub end_block: u64,
+    pub data_commitment: B256,
+}
+
+impl From<FirstCommitmentEntry> for SP1BlobstreamDataCommitmentStored {
+    fn from(entry: FirstCommitmentEntry) -> Self {
+        SP1BlobstreamDataCommitmentStored {
+            proof_nonce: U256::from(1u64),
+            start_block: entry.start_block,
+            end_block: entry.end_block,
+            data_commitment: entry.data_commitment,
+        }
+    }
 }
 
-/// Finds the first data commitment event for the specified Blobstream instance.
+/// First data commitment events of known SP1Blobstream deployments, by chain ID.
 ///
 /// To make DA commitments challengeable, we need to ensure that the corresponding Celestia
 /// blocks are covered by Blobstream. As Blobstream deployments typically start at some point
 /// after the deployment of the Celestia chain itself, this block height will differ for every
 /// Celestia instance.
 ///
-/// To avoid filtering through years of events, this function uses hardcoded values for public
-/// Ethereum chains and defaults to parsing events only if the chain is not supported.
-pub async fn get_first_data_commitment_event<T: Clone + Transport, P: Provider<T, Ethereum>>(
-    chain_id: ChainId,
+/// To avoid filtering through years of events, we use the values of the network registry
+/// (`toolkit::constants::NETWORKS`) for the SP1Blobstream deployments on public Ethereum chains.
+/// More chains can be added from a JSON config file, callers default to parsing events for
+/// chains missing from the table.
+#[derive(Debug, Clone)]
+pub struct FirstCommitmentTable {
+    entries: HashMap<ChainId, SP1BlobstreamDataCommitmentStored>,
+}
+
+impl Default for FirstCommitmentTable {
+    fn default() -> Self {
+        let mut table = Self::empty();
+        for network in NETWORKS {
+            if let Some(first_commitment) = network.first_commitment {
+                table.insert(FirstCommitmentEntry {
+                    chain_id: network.chain_id,
+                    start_block: first_commitment.start_block,
+                    end_block: first_commitment.end_block,
+                    data_commitment: first_commitment.data_commitment,
+                });
+            }
+        }
+
+        table
+    }
+}
+
+impl FirstCommitmentTable {
+    /// A table without any entry, all lookups fall back to parsing events.
+    pub fn empty() -> Self {
+        Self {
+            entries: HashMap::new(),
+        }
+    }
+
+    /// Loads the built-in table, extended with the entries of the JSON file at the specified
+    /// path. The file contains a list of `FirstCommitmentEntry` and overrides built-in entries
+    /// for the same chain.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, anyhow::Error> {
+        let path = path.as_ref();
+        let contents = std::fs::read_to_string(path)
+            .with_context(|| format!("failed to read {}", path.display()))?;
+        let entries: Vec<FirstCommitmentEntry> = serde_json::from_str(&contents)
+            .with_context(|| format!("invalid first commitment table {}", path.display()))?;
+
+        let mut table = Self::default();
+        for entry in entries {
+            table.insert(entry);
+        }
+
+        Ok(table)
+    }
+
+    pub fn insert(&mut self, entry: FirstCommitmentEntry) {
+        self.entries.insert(entry.chain_id, entry.into());
+    }
+
+    pub fn get(&self, chain_id: ChainId) -> Option<SP1BlobstreamDataCommitmentStored> {
+        self.entries.get(&chain_id).cloned()
+    }
+}
+
+/// Finds the data commitment covering the specified Celestia block height by binary searching
+/// the Ethereum block at which Blobstream's latest height (`latestBlock()` for SP1Blobstream,
+/// `latestHeight()` for Blobstream0) went past that height.
+///
+/// Every data commitment moves the latest height from the start to the end of the committed range,
+/// so the first Ethereum block where `latest height > block_height` is the one that emitted
+/// the commitment we're looking for. This needs O(log n) `eth_call`s plus a single-block log
+/// query, instead of scanning logs over a large block range. Requires an archive node.
+pub async fn find_data_commitment_by_binary_search(
+    block_height: u64,
+    backend: &(impl BlobstreamBackend + ?Sized),
+) -> Result<SP1BlobstreamDataCommitmentStored, anyhow::Error> {
+    let provider = backend.provider();
+    let blobstream_contract = ISP1Blobstream::new(backend.address(), provider);
+    let retry_policy = &rpc_retry_policy();
+    // Blocks before the deployment of the contract, which has no code yet, count as 0. Other
+    // failures are retried if transient, and fail the search otherwise.
+    let latest_height = |eth_block| async move {
+        retry_with_backoff(
+            retry_policy,
+            "Blobstream latest height query",
+            move || async move {
+                let e = match backend
+                    .latest_height_at(BlockNumberOrTag::Number(eth_block))
+                    .await
+                {
+                    Ok(latest_height) => return Ok(latest_height),
+                    Err(e) => e,
+                };
+                let code = provider
+                    .get_code_at(backend.address())
+                    .number(eth_block)
+                    .await
+                    .with_context(|| {
+                        format!("failed to fetch the Blobstream code at block {eth_block}")
+                    })?;
+                if code.is_empty() {
+                    Ok(0)
+                } else {
+                    Err(e.context(format!(
+                        "failed to read the latest height of Blobstream at block {eth_block}"
+                    )))
+                }
+            },
+        )
+        .await
+    };
+
+    let mut high = provider.get_block_number().await?;
+    if latest_height(high).await? <= block_height {
+        return Err(anyhow::anyhow!(
+            "Celestia block {block_height} is not covered by Blobstream yet"
+        ));
+    }
+
+    // Invariant: latest_height(low) <= block_height < latest_height(high)
+    let mut low = 0;
+    while high - low > 1 {
+        let middle = low + (high - low) / 2;
+        if latest_height(middle).await? > block_height {
+            high = middle;
+        } else {
+            low = middle;
+        }
+    }
+
+    let events = blobstream_contract
+        .DataCommitmentStored_filter()
+        .from_block(high)
+        .to_block(high)
+        .query()
+        .await?;
+
+    events
+        .into_iter()
+        .map(|(event, _log)| SP1BlobstreamDataCommitmentStored {
+            proof_nonce: event.proofNonce,
+            start_block: event.startBlock,
+            end_block: event.endBlock,
+            data_commitment: event.dataCommitment,
+        })
+        .find(|event| event.start_block <= block_height && block_height < event.end_block)
+        .ok_or_else(|| {
+            anyhow::anyhow!(
+                "no DataCommitmentStored event covering Celestia block {block_height} \
+                 in Ethereum block {high}"
+            )
+        })
+}
+
+/// Finds the data commitment covering the specified Celestia block height by scanning
+/// `DataCommitmentStored` logs backwards from the chain head.
+///
+/// Slower than [`find_data_commitment_by_binary_search`], but does not require an archive node.
+pub async fn find_data_commitment_by_log_scan(
+    block_height: u64,
     blobstream_address: Address,
-    provider: &P,
+    provider: &RootProvider,
 ) -> Result<SP1BlobstreamDataCommitmentStored, anyhow::Error> {
-    let data_commitment = match chain_id {
-        SEPOLIA_CHAIN_ID => SP1BlobstreamDataCommitmentStored {
-            proof_nonce: U256::from(1u64),
-            start_block: 1_560_501,
-            end_block: 1_560_600,
-            data_commitment: B256::from_str(
-                "60cd79d32f2fb32ba0086c2d0f8e00d54364fa93715a4f6b28ed4080ef47f0eb",
-            )?,
-        },
-        MAINNET_CHAIN_ID => SP1BlobstreamDataCommitmentStored {
-            proof_nonce: U256::from(1u64),
-            start_block: 1_605_975,
-            end_block: 1_606_500,
-            data_commitment: B256::from_str(
-                "e0f22e19a558e8da31aa8ee05f737a3ec2a55f92dc6093f34650c69f4cbd53be",
-            )?,
+    let current_block = provider.get_block_number().await?;
+
+    // Stop at the first event ending before the height, older events cannot cover it either.
+    let event = scan_data_commitment_events(
+        blobstream_address,
+        provider,
+        0,
+        current_block,
+        ScanDirection::Backward,
+        |event| {
+            if event.start_block <= block_height {
+                ControlFlow::Break(())
+            } else {
+                ControlFlow::Continue(())
+            }
         },
-        _ => {
-            let blobstream_contract = SP1BlobstreamInstance::new(blobstream_address, provider);
-            find_first_data_commitment_event(blobstream_contract, 100_000).await?
-        }
-    };
+    )
+    .await?;
 
-    Ok(data_commitment)
+    match event {
+        Some(event) if block_height < event.end_block => Ok(event),
+        Some(_) => Err(anyhow::anyhow!(
+            "Celestia block {block_height} is not covered by Blobstream yet"
+        )),
+        None => Err(anyhow::anyhow!(
+            "no DataCommitmentStored event covering Celestia block {block_height}"
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use risc0_steel::alloy::rpc::json_rpc::ErrorPayload;
+
+    #[test]
+    fn test_is_log_range_too_large() {
+        assert!(is_log_range_too_large(&anyhow::anyhow!(
+            "server returned an error response: error code -32005: \
+             query returned more than 10000 results"
+        )));
+        assert!(is_log_range_too_large(&anyhow::anyhow!(
+            "Log response size exceeded. You can make eth_getLogs requests with up to a 2K \
+             block range"
+        )));
+        assert!(!is_log_range_too_large(&anyhow::anyhow!(
+            "HTTP 429 Too Many Requests"
+        )));
+
+        let rpc_error = |code, message: &'static str| {
+            let payload = ErrorPayload {
+                code,
+                message: message.into(),
+                data: None,
+            };
+            anyhow::Error::new(TransportError::ErrorResp(payload)).context("failed to query logs")
+        };
+        assert!(is_log_range_too_large(&rpc_error(-32005, "limit exceeded")));
+        assert!(is_log_range_too_large(&rpc_error(
+            -32602,
+            "Log response size exceeded. You can make eth_getLogs requests with up to a 2K \
+             block range"
+        )));
+        assert!(!is_log_range_too_large(&rpc_error(
+            -32602,
+            "invalid block range params"
+        )));
+        assert!(!is_log_range_too_large(&rpc_error(
+            -32000,
+            "execution reverted: limited to 10 commitments"
+        )));
+    }
 }
@@ -0,0 +1,146 @@
+use anyhow::Context;
+use celestia_types::{ExtendedHeader, ShareProof};
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::de::DeserializeOwned;
+use std::path::Path;
+
+/// On-disk store of the Celestia block headers and share proofs fetched from Celestia, keyed by
+/// Celestia chain ID, block height and, for share proofs, the range of ODS shares.
+///
+/// Finalized blocks never change, so the retries of a challenge and runs re-checking the same
+/// indexes read them from disk instead of Celestia. Entries are stored as JSON, the encoding of
+/// the Celestia RPC responses.
+pub struct CelestiaDataStore {
+    conn: Connection,
+}
+
+impl CelestiaDataStore {
+    /// Opens (or creates) the Celestia data store at the specified path.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, anyhow::Error> {
+        let path = path.as_ref();
+        let conn = Connection::open(path)
+            .with_context(|| format!("failed to open Celestia data store at {}", path.display()))?;
+        Self::init(conn)
+    }
+
+    /// Creates a Celestia data store that only lives in memory.
+    pub fn open_in_memory() -> Result<Self, anyhow::Error> {
+        Self::init(Connection::open_in_memory()?)
+    }
+
+    fn init(conn: Connection) -> Result<Self, anyhow::Error> {
+        conn.execute_batch(
+            "CREATE TABLE IF NOT EXISTS celestia_headers (
+                celestia_chain_id TEXT NOT NULL,
+                height            INTEGER NOT NULL,
+                header            TEXT NOT NULL,
+                PRIMARY KEY (celestia_chain_id, height)
+            );
+            CREATE TABLE IF NOT EXISTS celestia_share_proofs (
+                celestia_chain_id TEXT NOT NULL,
+                height            INTEGER NOT NULL,
+                start_share       INTEGER NOT NULL,
+                end_share         INTEGER NOT NULL,
+                proof             TEXT NOT NULL,
+                PRIMARY KEY (celestia_chain_id, height, start_share, end_share)
+            );",
+        )
+        .with_context(|| "failed to initialize Celestia data store")?;
+
+        Ok(Self { conn })
+    }
+
+    /// Returns the header of the block at `height`, if known.
+    pub fn header(
+        &self,
+        celestia_chain_id: &str,
+        height: u64,
+    ) -> Result<Option<ExtendedHeader>, anyhow::Error> {
+        let header: Option<String> = self
+            .conn
+            .query_row(
+                "SELECT header FROM celestia_headers
+                 WHERE celestia_chain_id = ?1 AND height = ?2",
+                params![celestia_chain_id, height],
+                |row| row.get(0),
+            )
+            .optional()?;
+
+        decode(header, "invalid stored Celestia header")
+    }
+
+    /// Stores the header of a block. Storing the same header twice is a no-op.
+    pub fn insert_header(
+        &self,
+        celestia_chain_id: &str,
+        header: &ExtendedHeader,
+    ) -> Result<(), anyhow::Error> {
+        self.conn.execute(
+            "INSERT OR IGNORE INTO celestia_headers (celestia_chain_id, height, header)
+             VALUES (?1, ?2, ?3)",
+            params![
+                celestia_chain_id,
+                header.height().value(),
+                serde_json::to_string(header)?
+            ],
+        )?;
+
+        Ok(())
+    }
+
+    /// Returns the proof of the ODS shares `start..end` of the block at `height`, if known.
+    pub fn share_proof(
+        &self,
+        celestia_chain_id: &str,
+        height: u64,
+        start: u64,
+        end: u64,
+    ) -> Result<Option<ShareProof>, anyhow::Error> {
+        let proof: Option<String> = self
+            .conn
+            .query_row(
+                "SELECT proof FROM celestia_share_proofs
+                 WHERE celestia_chain_id = ?1 AND height = ?2
+                   AND start_share = ?3 AND end_share = ?4",
+                params![celestia_chain_id, height, start, end],
+                |row| row.get(0),
+            )
+            .optional()?;
+
+        decode(proof, "invalid stored share proof")
+    }
+
+    /// Stores the proof of the ODS shares `start..end` of the block at `height`. Storing the same
+    /// proof twice is a no-op.
+    pub fn insert_share_proof(
+        &self,
+        celestia_chain_id: &str,
+        height: u64,
+        start: u64,
+        end: u64,
+        proof: &ShareProof,
+    ) -> Result<(), anyhow::Error> {
+        self.conn.execute(
+            "INSERT OR IGNORE INTO celestia_share_proofs
+             (celestia_chain_id, height, start_share, end_share, proof)
+             VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![
+                celestia_chain_id,
+                height,
+                start,
+                end,
+                serde_json::to_string(proof)?
+            ],
+        )?;
+
+        Ok(())
+    }
+}
+
+fn decode<T: DeserializeOwned>(
+    json: Option<String>,
+    error: &'static str,
+) -> Result<Option<T>, anyhow::Error> {
+    json.map(|json| serde_json::from_str(&json).context(error))
+        .transpose()
+}
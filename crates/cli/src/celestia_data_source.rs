@@ -0,0 +1,1093 @@
+use crate::celestia_cache::CelestiaDataStore;
+use crate::retry::{retry_with_backoff, RetryPolicy};
+use anyhow::Context;
+use async_trait::async_trait;
+use celestia_rpc::blobstream::BlobstreamClient;
+use celestia_rpc::{Client as CelestiaClient, HeaderClient, ShareClient};
+use celestia_types::{ExtendedHeader, MerkleProof, ShareProof};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::fmt;
+use std::future::Future;
+use std::hash::Hash;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::sync::OnceCell;
+use toolkit::share_proof_ods_range;
+use url::Url;
+
+/// Source of the Celestia data of a challenge: block headers, share proofs and the inclusion
+/// proofs of data roots in Blobstream commitments.
+///
+/// The challenge logic only reads Celestia through this trait, so that it can run against an
+/// archive, recorded data or a mock instead of a node. `celestia_rpc::Client` is the default
+/// implementation.
+#[async_trait]
+pub trait CelestiaDataSource: Send + Sync {
+    /// Returns the header of the latest block known to the source.
+    async fn head(&self) -> Result<ExtendedHeader, anyhow::Error>;
+
+    /// Returns the header of the block at the specified height.
+    async fn header(&self, height: u64) -> Result<ExtendedHeader, anyhow::Error>;
+
+    /// Returns the proof of the ODS shares `start..end` of the block.
+    async fn share_range_proof(
+        &self,
+        block_header: &ExtendedHeader,
+        start: u64,
+        end: u64,
+    ) -> Result<ShareProof, anyhow::Error>;
+
+    /// Returns the inclusion proof of the data root of the block at `height` in the Blobstream
+    /// commitment of the blocks `start_block..end_block`.
+    async fn data_root_inclusion_proof(
+        &self,
+        height: u64,
+        start_block: u64,
+        end_block: u64,
+    ) -> Result<MerkleProof, anyhow::Error>;
+}
+
+#[async_trait]
+impl CelestiaDataSource for CelestiaClient {
+    async fn head(&self) -> Result<ExtendedHeader, anyhow::Error> {
+        Ok(self.header_local_head().await?)
+    }
+
+    async fn header(&self, height: u64) -> Result<ExtendedHeader, anyhow::Error> {
+        Ok(self.header_get_by_height(height).await?)
+    }
+
+    async fn share_range_proof(
+        &self,
+        block_header: &ExtendedHeader,
+        start: u64,
+        end: u64,
+    ) -> Result<ShareProof, anyhow::Error> {
+        Ok(self.share_get_range(block_header, start, end).await?.proof)
+    }
+
+    async fn data_root_inclusion_proof(
+        &self,
+        height: u64,
+        start_block: u64,
+        end_block: u64,
+    ) -> Result<MerkleProof, anyhow::Error> {
+        Ok(self
+            .blobstream_get_data_root_tuple_inclusion_proof(height, start_block, end_block)
+            .await?)
+    }
+}
+
+#[async_trait]
+impl<S: CelestiaDataSource + ?Sized> CelestiaDataSource for Box<S> {
+    async fn head(&self) -> Result<ExtendedHeader, anyhow::Error> {
+        (**self).head().await
+    }
+
+    async fn header(&self, height: u64) -> Result<ExtendedHeader, anyhow::Error> {
+        (**self).header(height).await
+    }
+
+    async fn share_range_proof(
+        &self,
+        block_header: &ExtendedHeader,
+        start: u64,
+        end: u64,
+    ) -> Result<ShareProof, anyhow::Error> {
+        (**self).share_range_proof(block_header, start, end).await
+    }
+
+    async fn data_root_inclusion_proof(
+        &self,
+        height: u64,
+        start_block: u64,
+        end_block: u64,
+    ) -> Result<MerkleProof, anyhow::Error> {
+        (**self)
+            .data_root_inclusion_proof(height, start_block, end_block)
+            .await
+    }
+}
+
+/// Celestia data archived as JSON files, served over HTTP(S), ex: an S3 snapshot or an archival
+/// API, or stored in a local directory (`file://` URL). Nodes prune old blocks, while challenges
+/// often target them.
+///
+/// The files follow the JSON encoding of the Celestia RPC responses, under the base URL:
+/// * `head.json`: the header of the latest archived block.
+/// * `headers/<height>.json`: the header of each block.
+/// * `share_proofs/<height>/<start>-<end>.json`: the proof of the ODS shares `start..end`. The
+///   host requests one proof per row of a blob, ranges must be split at row boundaries.
+/// * `data_root_proofs/<height>/<start_block>-<end_block>.json`: the inclusion proof of the data
+///   root in the Blobstream commitment of the blocks `start_block..end_block`.
+pub struct ArchiveDataSource {
+    client: reqwest::Client,
+    base_url: Url,
+}
+
+impl ArchiveDataSource {
+    pub fn new(base_url: Url) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            base_url,
+        }
+    }
+
+    async fn get<T: DeserializeOwned>(&self, path: &str) -> Result<T, anyhow::Error> {
+        let url = self
+            .base_url
+            .join(path)
+            .with_context(|| format!("invalid archive path {path}"))?;
+
+        let contents = if url.scheme() == "file" {
+            let path = url
+                .to_file_path()
+                .map_err(|_| anyhow::anyhow!("invalid archive file URL: {url}"))?;
+            tokio::fs::read(&path)
+                .await
+                .with_context(|| format!("failed to read {}", path.display()))?
+        } else {
+            self.client
+                .get(url.clone())
+                .send()
+                .await?
+                .error_for_status()
+                .with_context(|| format!("failed to fetch {url}"))?
+                .bytes()
+                .await?
+                .to_vec()
+        };
+
+        serde_json::from_slice(&contents).with_context(|| format!("invalid archive file {url}"))
+    }
+}
+
+#[async_trait]
+impl CelestiaDataSource for ArchiveDataSource {
+    async fn head(&self) -> Result<ExtendedHeader, anyhow::Error> {
+        self.get("head.json").await
+    }
+
+    async fn header(&self, height: u64) -> Result<ExtendedHeader, anyhow::Error> {
+        self.get(&format!("headers/{height}.json")).await
+    }
+
+    async fn share_range_proof(
+        &self,
+        block_header: &ExtendedHeader,
+        start: u64,
+        end: u64,
+    ) -> Result<ShareProof, anyhow::Error> {
+        let height = block_header.height().value();
+        self.get(&format!("share_proofs/{height}/{start}-{end}.json"))
+            .await
+    }
+
+    async fn data_root_inclusion_proof(
+        &self,
+        height: u64,
+        start_block: u64,
+        end_block: u64,
+    ) -> Result<MerkleProof, anyhow::Error> {
+        self.get(&format!(
+            "data_root_proofs/{height}/{start_block}-{end_block}.json"
+        ))
+        .await
+    }
+}
+
+/// Reads Celestia data from `primary`, falling back to `fallback` for the requests that
+/// `primary` fails to serve, ex: a node that pruned old blocks backed by an archive.
+pub struct FallbackDataSource<P, F> {
+    primary: P,
+    fallback: F,
+}
+
+impl<P: CelestiaDataSource, F: CelestiaDataSource> FallbackDataSource<P, F> {
+    pub fn new(primary: P, fallback: F) -> Self {
+        Self { primary, fallback }
+    }
+}
+
+/// Returns the primary result if it succeeded, the result of `fallback()` otherwise.
+async fn or_fall_back<T, Fut>(
+    primary: Result<T, anyhow::Error>,
+    description: impl FnOnce() -> String,
+    fallback: impl FnOnce() -> Fut,
+) -> Result<T, anyhow::Error>
+where
+    Fut: Future<Output = Result<T, anyhow::Error>>,
+{
+    match primary {
+        Ok(value) => Ok(value),
+        Err(e) => {
+            let description = description();
+            log::warn!("failed to fetch {description}, trying the fallback source: {e:#}");
+            fallback()
+                .await
+                .with_context(|| format!("fallback source failed to serve {description}"))
+        }
+    }
+}
+
+#[async_trait]
+impl<P: CelestiaDataSource, F: CelestiaDataSource> CelestiaDataSource for FallbackDataSource<P, F> {
+    async fn head(&self) -> Result<ExtendedHeader, anyhow::Error> {
+        or_fall_back(
+            self.primary.head().await,
+            || "the Celestia head".to_string(),
+            || self.fallback.head(),
+        )
+        .await
+    }
+
+    async fn header(&self, height: u64) -> Result<ExtendedHeader, anyhow::Error> {
+        or_fall_back(
+            self.primary.header(height).await,
+            || format!("the header of Celestia block {height}"),
+            || self.fallback.header(height),
+        )
+        .await
+    }
+
+    async fn share_range_proof(
+        &self,
+        block_header: &ExtendedHeader,
+        start: u64,
+        end: u64,
+    ) -> Result<ShareProof, anyhow::Error> {
+        or_fall_back(
+            self.primary
+                .share_range_proof(block_header, start, end)
+                .await,
+            || {
+                format!(
+                    "the proof of shares {start}..{end} of Celestia block {}",
+                    block_header.height()
+                )
+            },
+            || self.fallback.share_range_proof(block_header, start, end),
+        )
+        .await
+    }
+
+    async fn data_root_inclusion_proof(
+        &self,
+        height: u64,
+        start_block: u64,
+        end_block: u64,
+    ) -> Result<MerkleProof, anyhow::Error> {
+        or_fall_back(
+            self.primary
+                .data_root_inclusion_proof(height, start_block, end_block)
+                .await,
+            || format!("the Blobstream proof of the data root of Celestia block {height}"),
+            || {
+                self.fallback
+                    .data_root_inclusion_proof(height, start_block, end_block)
+            },
+        )
+        .await
+    }
+}
+
+/// Latency recorded for an endpoint failing a request, so that endpoints are tried after the
+/// ones that answer.
+const FAILED_REQUEST_LATENCY: Duration = Duration::from_secs(30);
+
+/// Reads Celestia data from several endpoints, ex: Celestia nodes of different providers.
+/// Requests go to the fastest endpoint first, by average latency, and fail over to the next
+/// endpoints on errors.
+///
+/// With cross-checking, block headers and data root proofs are only accepted once two endpoints
+/// returned the same response, so that a single faulty node cannot trigger a bogus challenge.
+/// Share proofs are verified against the data root by the guest and are not cross-checked.
+pub struct MultiEndpointDataSource<S> {
+    endpoints: Vec<S>,
+    /// Average latency of each endpoint, `None` until it served a request.
+    latencies: Mutex<Vec<Option<Duration>>>,
+    cross_check: bool,
+}
+
+impl<S: CelestiaDataSource> MultiEndpointDataSource<S> {
+    pub fn new(endpoints: Vec<S>) -> Result<Self, anyhow::Error> {
+        anyhow::ensure!(!endpoints.is_empty(), "no Celestia endpoint");
+
+        Ok(Self {
+            latencies: Mutex::new(vec![None; endpoints.len()]),
+            endpoints,
+            cross_check: false,
+        })
+    }
+
+    /// Requires two endpoints to agree on block headers and data root proofs.
+    pub fn with_cross_check(mut self) -> Result<Self, anyhow::Error> {
+        anyhow::ensure!(
+            self.endpoints.len() >= 2,
+            "cross-checking Celestia responses requires at least two endpoints"
+        );
+        self.cross_check = true;
+        Ok(self)
+    }
+
+    /// Returns the endpoints by increasing average latency. Endpoints that did not serve any
+    /// request yet come first, so that every endpoint gets measured.
+    fn ranked_endpoints(&self) -> Vec<usize> {
+        let latencies = self
+            .latencies
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        let mut ranking: Vec<usize> = (0..self.endpoints.len()).collect();
+        ranking.sort_by_key(|&i| latencies[i]);
+        ranking
+    }
+
+    fn record_latency(&self, endpoint: usize, latency: Duration) {
+        let mut latencies = self
+            .latencies
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        latencies[endpoint] = Some(match latencies[endpoint] {
+            Some(average) => (average * 3 + latency) / 4,
+            None => latency,
+        });
+    }
+
+    /// Sends the request to the endpoints, fastest first, until `count` of them answer.
+    async fn fetch<'a, T, Fut>(
+        &'a self,
+        count: usize,
+        description: &str,
+        request: impl Fn(&'a S) -> Fut,
+    ) -> Result<Vec<T>, anyhow::Error>
+    where
+        Fut: Future<Output = Result<T, anyhow::Error>>,
+    {
+        let mut responses = Vec::with_capacity(count);
+        let mut last_error = None;
+        for endpoint in self.ranked_endpoints() {
+            let started_at = Instant::now();
+            match request(&self.endpoints[endpoint]).await {
+                Ok(response) => {
+                    self.record_latency(endpoint, started_at.elapsed());
+                    responses.push(response);
+                    if responses.len() == count {
+                        return Ok(responses);
+                    }
+                }
+                Err(e) => {
+                    self.record_latency(endpoint, FAILED_REQUEST_LATENCY);
+                    log::warn!(
+                        "Celestia endpoint #{endpoint} failed to serve {description}: {e:#}"
+                    );
+                    last_error = Some(e);
+                }
+            }
+        }
+
+        let error = anyhow::anyhow!(
+            "{} of {count} Celestia endpoints served {description}",
+            responses.len()
+        );
+        Err(match last_error {
+            Some(last_error) => last_error.context(error),
+            None => error,
+        })
+    }
+
+    /// Fetches a response from one endpoint, or from two agreeing endpoints with cross-checking.
+    /// Responses are compared by `key`.
+    async fn fetch_checked<'a, T, K, Fut>(
+        &'a self,
+        description: &str,
+        key: impl Fn(&T) -> Result<K, anyhow::Error>,
+        request: impl Fn(&'a S) -> Fut,
+    ) -> Result<T, anyhow::Error>
+    where
+        K: PartialEq,
+        Fut: Future<Output = Result<T, anyhow::Error>>,
+    {
+        let count = if self.cross_check { 2 } else { 1 };
+        let mut responses = self.fetch(count, description, request).await?;
+        let response = responses.remove(0);
+        for other in &responses {
+            anyhow::ensure!(
+                key(&response)? == key(other)?,
+                "Celestia endpoints disagree on {description}"
+            );
+        }
+
+        Ok(response)
+    }
+}
+
+/// Key comparing responses by their JSON encoding.
+fn json_key<T: Serialize>(response: &T) -> Result<serde_json::Value, anyhow::Error> {
+    Ok(serde_json::to_value(response)?)
+}
+
+#[async_trait]
+impl<S: CelestiaDataSource> CelestiaDataSource for MultiEndpointDataSource<S> {
+    async fn head(&self) -> Result<ExtendedHeader, anyhow::Error> {
+        // Endpoints legitimately disagree on the head, the guest checks heights against
+        // Blobstream anyway
+        let mut heads = self
+            .fetch(1, "the Celestia head", |endpoint| endpoint.head())
+            .await?;
+        Ok(heads.remove(0))
+    }
+
+    async fn header(&self, height: u64) -> Result<ExtendedHeader, anyhow::Error> {
+        self.fetch_checked(
+            &format!("the header of Celestia block {height}"),
+            |header: &ExtendedHeader| Ok(header.hash()),
+            |endpoint| endpoint.header(height),
+        )
+        .await
+    }
+
+    async fn share_range_proof(
+        &self,
+        block_header: &ExtendedHeader,
+        start: u64,
+        end: u64,
+    ) -> Result<ShareProof, anyhow::Error> {
+        let description = format!(
+            "the proof of shares {start}..{end} of Celestia block {}",
+            block_header.height()
+        );
+        let mut proofs = self
+            .fetch(1, &description, |endpoint| {
+                endpoint.share_range_proof(block_header, start, end)
+            })
+            .await?;
+        Ok(proofs.remove(0))
+    }
+
+    async fn data_root_inclusion_proof(
+        &self,
+        height: u64,
+        start_block: u64,
+        end_block: u64,
+    ) -> Result<MerkleProof, anyhow::Error> {
+        self.fetch_checked(
+            &format!("the Blobstream proof of the data root of Celestia block {height}"),
+            json_key,
+            |endpoint| endpoint.data_root_inclusion_proof(height, start_block, end_block),
+        )
+        .await
+    }
+}
+
+/// Connect and request timeouts of a Celestia node. A slow node otherwise hangs the challenge.
+#[derive(Debug, Clone, Copy)]
+pub struct CelestiaTimeouts {
+    pub connect: Duration,
+    pub request: Duration,
+}
+
+impl Default for CelestiaTimeouts {
+    fn default() -> Self {
+        Self {
+            connect: Duration::from_secs(10),
+            request: Duration::from_secs(60),
+        }
+    }
+}
+
+/// Connects to the Celestia node at `url`, with the timeouts applied to the connection and to
+/// every request.
+pub async fn connect_celestia_node(
+    url: &Url,
+    timeouts: CelestiaTimeouts,
+) -> Result<TimeoutDataSource<CelestiaClient>, anyhow::Error> {
+    let connection = CelestiaClient::new(url.as_str(), None);
+    let client = match tokio::time::timeout(timeouts.connect, connection).await {
+        Ok(client) => client.map_err(anyhow::Error::from),
+        Err(_) => Err(CelestiaTimeout(timeouts.connect).into()),
+    }
+    .with_context(|| format!("failed to connect to {url}"))?;
+
+    Ok(TimeoutDataSource::new(client, timeouts.request))
+}
+
+/// A Celestia request, or the connection to a node, took longer than its timeout. Transient
+/// error, see `crate::retry::is_transient()`.
+#[derive(Debug, Clone, Copy)]
+pub struct CelestiaTimeout(pub Duration);
+
+impl fmt::Display for CelestiaTimeout {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Celestia request timed out after {} s",
+            self.0.as_secs_f32()
+        )
+    }
+}
+
+impl std::error::Error for CelestiaTimeout {}
+
+/// Fails the requests that take longer than the timeout. Timeouts are transient errors, retried
+/// by `RetryingDataSource`, and trigger the failover of `MultiEndpointDataSource`.
+pub struct TimeoutDataSource<S> {
+    inner: S,
+    timeout: Duration,
+}
+
+impl<S: CelestiaDataSource> TimeoutDataSource<S> {
+    pub fn new(inner: S, timeout: Duration) -> Self {
+        Self { inner, timeout }
+    }
+
+    async fn with_timeout<T>(
+        &self,
+        request: impl Future<Output = Result<T, anyhow::Error>>,
+    ) -> Result<T, anyhow::Error> {
+        tokio::time::timeout(self.timeout, request)
+            .await
+            .map_err(|_| CelestiaTimeout(self.timeout))?
+    }
+}
+
+#[async_trait]
+impl<S: CelestiaDataSource> CelestiaDataSource for TimeoutDataSource<S> {
+    async fn head(&self) -> Result<ExtendedHeader, anyhow::Error> {
+        self.with_timeout(self.inner.head()).await
+    }
+
+    async fn header(&self, height: u64) -> Result<ExtendedHeader, anyhow::Error> {
+        self.with_timeout(self.inner.header(height)).await
+    }
+
+    async fn share_range_proof(
+        &self,
+        block_header: &ExtendedHeader,
+        start: u64,
+        end: u64,
+    ) -> Result<ShareProof, anyhow::Error> {
+        self.with_timeout(self.inner.share_range_proof(block_header, start, end))
+            .await
+    }
+
+    async fn data_root_inclusion_proof(
+        &self,
+        height: u64,
+        start_block: u64,
+        end_block: u64,
+    ) -> Result<MerkleProof, anyhow::Error> {
+        self.with_timeout(
+            self.inner
+                .data_root_inclusion_proof(height, start_block, end_block),
+        )
+        .await
+    }
+}
+
+/// Checks the headers served by the inner source before handing them out: each header must be
+/// internally consistent (`ExtendedHeader::validate()`: the DAH matches the data hash, the commit
+/// is for the header...) and have the requested height. With chain checking, each header is also
+/// verified against the header before it (`ExtendedHeader::verify()`), which costs a request per
+/// header.
+///
+/// A node serving a wrong header would otherwise only be caught by the guest at proving time, if
+/// at all.
+pub struct ValidatingDataSource<S> {
+    inner: S,
+    check_chain: bool,
+}
+
+impl<S: CelestiaDataSource> ValidatingDataSource<S> {
+    pub fn new(inner: S) -> Self {
+        Self {
+            inner,
+            check_chain: false,
+        }
+    }
+
+    /// Also verifies that each header follows the header of the previous block.
+    pub fn with_chain_check(mut self) -> Self {
+        self.check_chain = true;
+        self
+    }
+}
+
+/// Checks that the header is internally consistent and, if specified, at the expected height.
+fn validate_header(
+    header: &ExtendedHeader,
+    expected_height: Option<u64>,
+) -> Result<(), anyhow::Error> {
+    let height = header.height().value();
+    if let Some(expected_height) = expected_height {
+        anyhow::ensure!(
+            height == expected_height,
+            "requested the header of Celestia block {expected_height}, got block {height}"
+        );
+    }
+    header
+        .validate()
+        .with_context(|| format!("invalid header of Celestia block {height}"))
+}
+
+#[async_trait]
+impl<S: CelestiaDataSource> CelestiaDataSource for ValidatingDataSource<S> {
+    async fn head(&self) -> Result<ExtendedHeader, anyhow::Error> {
+        let header = self.inner.head().await?;
+        validate_header(&header, None)?;
+        Ok(header)
+    }
+
+    async fn header(&self, height: u64) -> Result<ExtendedHeader, anyhow::Error> {
+        let header = self.inner.header(height).await?;
+        validate_header(&header, Some(height))?;
+
+        if self.check_chain && height > 1 {
+            let previous_header = self.inner.header(height - 1).await?;
+            validate_header(&previous_header, Some(height - 1))?;
+            previous_header.verify(&header).with_context(|| {
+                format!("header of Celestia block {height} does not follow the previous block")
+            })?;
+        }
+
+        Ok(header)
+    }
+
+    async fn share_range_proof(
+        &self,
+        block_header: &ExtendedHeader,
+        start: u64,
+        end: u64,
+    ) -> Result<ShareProof, anyhow::Error> {
+        self.inner.share_range_proof(block_header, start, end).await
+    }
+
+    async fn data_root_inclusion_proof(
+        &self,
+        height: u64,
+        start_block: u64,
+        end_block: u64,
+    ) -> Result<MerkleProof, anyhow::Error> {
+        self.inner
+            .data_root_inclusion_proof(height, start_block, end_block)
+            .await
+    }
+}
+
+/// Retries the requests failing with transient errors, with exponential backoff, so that a
+/// single RPC error does not abort the whole data-gathering phase of a challenge.
+///
+/// Errors are classified by type, see `crate::retry::is_transient()`: timeouts and transport
+/// errors are retried, errors returned by the node and validation errors are not.
+pub struct RetryingDataSource<S> {
+    inner: S,
+    policy: RetryPolicy,
+}
+
+impl<S: CelestiaDataSource> RetryingDataSource<S> {
+    pub fn new(inner: S, policy: RetryPolicy) -> Self {
+        Self { inner, policy }
+    }
+}
+
+#[async_trait]
+impl<S: CelestiaDataSource> CelestiaDataSource for RetryingDataSource<S> {
+    async fn head(&self) -> Result<ExtendedHeader, anyhow::Error> {
+        retry_with_backoff(&self.policy, "fetching the Celestia head", || {
+            self.inner.head()
+        })
+        .await
+    }
+
+    async fn header(&self, height: u64) -> Result<ExtendedHeader, anyhow::Error> {
+        retry_with_backoff(
+            &self.policy,
+            &format!("fetching the header of Celestia block {height}"),
+            || self.inner.header(height),
+        )
+        .await
+    }
+
+    async fn share_range_proof(
+        &self,
+        block_header: &ExtendedHeader,
+        start: u64,
+        end: u64,
+    ) -> Result<ShareProof, anyhow::Error> {
+        retry_with_backoff(
+            &self.policy,
+            &format!(
+                "fetching the proof of shares {start}..{end} of Celestia block {}",
+                block_header.height()
+            ),
+            || self.inner.share_range_proof(block_header, start, end),
+        )
+        .await
+    }
+
+    async fn data_root_inclusion_proof(
+        &self,
+        height: u64,
+        start_block: u64,
+        end_block: u64,
+    ) -> Result<MerkleProof, anyhow::Error> {
+        retry_with_backoff(
+            &self.policy,
+            &format!("fetching the Blobstream proof of the data root of Celestia block {height}"),
+            || {
+                self.inner
+                    .data_root_inclusion_proof(height, start_block, end_block)
+            },
+        )
+        .await
+    }
+}
+
+/// Checks that the share proof proves exactly the ODS shares `start..end` of the block, against
+/// the data root of its header.
+pub fn verify_share_proof(
+    share_proof: &ShareProof,
+    block_header: &ExtendedHeader,
+    start: u64,
+    end: u64,
+) -> Result<(), anyhow::Error> {
+    let height = block_header.height().value();
+    let data_root = block_header
+        .header
+        .data_hash
+        .with_context(|| format!("header of Celestia block {height} has no data root"))?;
+    share_proof.verify(data_root).with_context(|| {
+        format!("invalid proof of shares {start}..{end} of Celestia block {height}")
+    })?;
+
+    let ods_width = block_header.dah.square_width() as u32 / 2;
+    let proven_shares = share_proof_ods_range(share_proof, ods_width)?;
+    anyhow::ensure!(
+        proven_shares.start as u64 == start && proven_shares.end as u64 == end,
+        "proof of shares {start}..{end} of Celestia block {height} covers shares {proven_shares:?}"
+    );
+
+    Ok(())
+}
+
+/// Serves the headers and share proofs already fetched from the Celestia data store, and stores
+/// the ones fetched from the inner source. The head and the data root proofs, cached by
+/// `InclusionProofStore`, are always fetched.
+///
+/// Entries are only stored once the inner source returned them, so the inner source should
+/// validate the headers, see `ValidatingDataSource`. Share proofs are verified against the header
+/// before they are stored.
+pub struct CachingDataSource<S> {
+    inner: S,
+    store: Mutex<CelestiaDataStore>,
+    celestia_chain_id: String,
+}
+
+impl<S: CelestiaDataSource> CachingDataSource<S> {
+    /// Caches the data of the Celestia network `celestia_chain_id`, ex: `celestia`. Every header
+    /// served by the inner source must be of this network.
+    pub fn new(inner: S, store: CelestiaDataStore, celestia_chain_id: impl Into<String>) -> Self {
+        Self {
+            inner,
+            store: Mutex::new(store),
+            celestia_chain_id: celestia_chain_id.into(),
+        }
+    }
+
+    fn access_store<T>(
+        &self,
+        f: impl FnOnce(&CelestiaDataStore) -> Result<T, anyhow::Error>,
+    ) -> Result<T, anyhow::Error> {
+        let store = self
+            .store
+            .lock()
+            .map_err(|_| anyhow::anyhow!("Celestia data store lock poisoned"))?;
+        f(&store)
+    }
+}
+
+#[async_trait]
+impl<S: CelestiaDataSource> CelestiaDataSource for CachingDataSource<S> {
+    async fn head(&self) -> Result<ExtendedHeader, anyhow::Error> {
+        self.inner.head().await
+    }
+
+    async fn header(&self, height: u64) -> Result<ExtendedHeader, anyhow::Error> {
+        let celestia_chain_id = self.celestia_chain_id.as_str();
+        if let Some(header) = self.access_store(|store| store.header(celestia_chain_id, height))? {
+            return Ok(header);
+        }
+
+        let header = self.inner.header(height).await?;
+        anyhow::ensure!(
+            header.header.chain_id.as_str() == celestia_chain_id,
+            "header of Celestia block {height} is for network {}, not {celestia_chain_id}",
+            header.header.chain_id
+        );
+        self.access_store(|store| store.insert_header(celestia_chain_id, &header))?;
+
+        Ok(header)
+    }
+
+    async fn share_range_proof(
+        &self,
+        block_header: &ExtendedHeader,
+        start: u64,
+        end: u64,
+    ) -> Result<ShareProof, anyhow::Error> {
+        let celestia_chain_id = block_header.header.chain_id.as_str();
+        let height = block_header.height().value();
+        if let Some(share_proof) =
+            self.access_store(|store| store.share_proof(celestia_chain_id, height, start, end))?
+        {
+            return Ok(share_proof);
+        }
+
+        let share_proof = self
+            .inner
+            .share_range_proof(block_header, start, end)
+            .await?;
+        // A bad proof would otherwise be served on every later run
+        verify_share_proof(&share_proof, block_header, start, end)?;
+        self.access_store(|store| {
+            store.insert_share_proof(celestia_chain_id, height, start, end, &share_proof)
+        })?;
+
+        Ok(share_proof)
+    }
+
+    async fn data_root_inclusion_proof(
+        &self,
+        height: u64,
+        start_block: u64,
+        end_block: u64,
+    ) -> Result<MerkleProof, anyhow::Error> {
+        self.inner
+            .data_root_inclusion_proof(height, start_block, end_block)
+            .await
+    }
+}
+
+/// Memo of the responses to requests with the same key. Concurrent requests with the same key
+/// share a single fetch.
+type Memo<K, V> = Mutex<HashMap<K, Arc<OnceCell<V>>>>;
+
+/// Returns the memoized response of the request `key`, or fetches it. Failed fetches are not
+/// memoized.
+async fn memoized<K: Eq + Hash, V: Clone, F: Future<Output = Result<V, anyhow::Error>>>(
+    memo: &Memo<K, V>,
+    key: K,
+    fetch: impl FnOnce() -> F,
+) -> Result<V, anyhow::Error> {
+    let response = memo
+        .lock()
+        .map_err(|_| anyhow::anyhow!("Celestia memo lock poisoned"))?
+        .entry(key)
+        .or_default()
+        .clone();
+    response.get_or_try_init(fetch).await.cloned()
+}
+
+/// Fetches each header and data root proof once while fetching the data of a challenge, even
+/// when several blobs of the challenge are in the same Celestia block. Concurrent requests for
+/// the same block share a single fetch.
+pub struct MemoizedDataSource<'a, S: ?Sized> {
+    inner: &'a S,
+    headers: Memo<u64, ExtendedHeader>,
+    data_root_proofs: Memo<(u64, u64, u64), MerkleProof>,
+}
+
+impl<'a, S: CelestiaDataSource + ?Sized> MemoizedDataSource<'a, S> {
+    pub fn new(inner: &'a S) -> Self {
+        Self {
+            inner,
+            headers: Mutex::default(),
+            data_root_proofs: Mutex::default(),
+        }
+    }
+}
+
+#[async_trait]
+impl<S: CelestiaDataSource + ?Sized> CelestiaDataSource for MemoizedDataSource<'_, S> {
+    async fn head(&self) -> Result<ExtendedHeader, anyhow::Error> {
+        self.inner.head().await
+    }
+
+    async fn header(&self, height: u64) -> Result<ExtendedHeader, anyhow::Error> {
+        memoized(&self.headers, height, || self.inner.header(height)).await
+    }
+
+    async fn share_range_proof(
+        &self,
+        block_header: &ExtendedHeader,
+        start: u64,
+        end: u64,
+    ) -> Result<ShareProof, anyhow::Error> {
+        self.inner.share_range_proof(block_header, start, end).await
+    }
+
+    async fn data_root_inclusion_proof(
+        &self,
+        height: u64,
+        start_block: u64,
+        end_block: u64,
+    ) -> Result<MerkleProof, anyhow::Error> {
+        memoized(
+            &self.data_root_proofs,
+            (height, start_block, end_block),
+            || {
+                self.inner
+                    .data_root_inclusion_proof(height, start_block, end_block)
+            },
+        )
+        .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    /// A node serving the same data root proof for every block, or nothing once it pruned all the
+    /// blocks.
+    struct StaticNode(Option<MerkleProof>);
+
+    #[async_trait]
+    impl CelestiaDataSource for StaticNode {
+        async fn head(&self) -> Result<ExtendedHeader, anyhow::Error> {
+            Err(anyhow::anyhow!("pruned"))
+        }
+
+        async fn header(&self, _height: u64) -> Result<ExtendedHeader, anyhow::Error> {
+            Err(anyhow::anyhow!("pruned"))
+        }
+
+        async fn share_range_proof(
+            &self,
+            _block_header: &ExtendedHeader,
+            _start: u64,
+            _end: u64,
+        ) -> Result<ShareProof, anyhow::Error> {
+            Err(anyhow::anyhow!("pruned"))
+        }
+
+        async fn data_root_inclusion_proof(
+            &self,
+            _height: u64,
+            _start_block: u64,
+            _end_block: u64,
+        ) -> Result<MerkleProof, anyhow::Error> {
+            self.0.clone().ok_or_else(|| anyhow::anyhow!("pruned"))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_fallback_reads_pruned_blocks_from_the_archive() {
+        let archive_dir =
+            std::env::temp_dir().join(format!("celestia-archive-{}", std::process::id()));
+        let proofs_dir = archive_dir.join("data_root_proofs").join("12");
+        std::fs::create_dir_all(&proofs_dir).unwrap();
+        let leaves = vec![[1u8; 32], [2u8; 32]];
+        let (proof, _) = MerkleProof::new(1, &leaves).unwrap();
+        std::fs::write(
+            proofs_dir.join("10-20.json"),
+            serde_json::to_vec(&proof).unwrap(),
+        )
+        .unwrap();
+
+        let archive_url = Url::from_directory_path(&archive_dir).unwrap();
+        let data_source =
+            FallbackDataSource::new(StaticNode(None), ArchiveDataSource::new(archive_url));
+
+        let archived_proof = data_source
+            .data_root_inclusion_proof(12, 10, 20)
+            .await
+            .unwrap();
+        assert_eq!(
+            serde_json::to_value(&archived_proof).unwrap(),
+            serde_json::to_value(&proof).unwrap()
+        );
+        // Missing from both sources
+        assert!(data_source
+            .data_root_inclusion_proof(13, 10, 20)
+            .await
+            .is_err());
+
+        std::fs::remove_dir_all(&archive_dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_multi_endpoint_fails_over_and_cross_checks() {
+        let leaves = vec![[1u8; 32], [2u8; 32]];
+        let (proof, _) = MerkleProof::new(1, &leaves).unwrap();
+        let (other_proof, _) = MerkleProof::new(0, &leaves).unwrap();
+
+        let data_source =
+            MultiEndpointDataSource::new(vec![StaticNode(None), StaticNode(Some(proof.clone()))])
+                .unwrap();
+        let served_proof = data_source
+            .data_root_inclusion_proof(12, 10, 20)
+            .await
+            .unwrap();
+        assert_eq!(json_key(&served_proof).unwrap(), json_key(&proof).unwrap());
+        // The failing endpoint is now tried last
+        assert_eq!(data_source.ranked_endpoints(), vec![1, 0]);
+
+        // A single endpoint serving the proof is not enough with cross-checking
+        let data_source = data_source.with_cross_check().unwrap();
+        assert!(data_source
+            .data_root_inclusion_proof(12, 10, 20)
+            .await
+            .is_err());
+
+        let data_source = MultiEndpointDataSource::new(vec![
+            StaticNode(Some(proof.clone())),
+            StaticNode(Some(other_proof)),
+        ])
+        .unwrap()
+        .with_cross_check()
+        .unwrap();
+        let error = data_source
+            .data_root_inclusion_proof(12, 10, 20)
+            .await
+            .unwrap_err();
+        assert!(error.to_string().contains("disagree"));
+
+        let data_source = MultiEndpointDataSource::new(vec![
+            StaticNode(Some(proof.clone())),
+            StaticNode(Some(proof)),
+        ])
+        .unwrap()
+        .with_cross_check()
+        .unwrap();
+        assert!(data_source
+            .data_root_inclusion_proof(12, 10, 20)
+            .await
+            .is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_memoized_requests_are_fetched_once() {
+        let memo: Memo<u64, u64> = Memo::default();
+        let fetches = AtomicUsize::new(0);
+        let fetch = || async {
+            fetches.fetch_add(1, Ordering::SeqCst);
+            Ok(7)
+        };
+
+        let (first, second) = tokio::join!(memoized(&memo, 12, fetch), memoized(&memo, 12, fetch));
+        assert_eq!((first.unwrap(), second.unwrap()), (7, 7));
+        assert_eq!(fetches.load(Ordering::SeqCst), 1);
+
+        // Failed fetches are tried again
+        assert!(
+            memoized(&memo, 13, || async { Err(anyhow::anyhow!("pruned")) })
+                .await
+                .is_err()
+        );
+        assert_eq!(memoized(&memo, 13, fetch).await.unwrap(), 7);
+        assert_eq!(fetches.load(Ordering::SeqCst), 2);
+    }
+}
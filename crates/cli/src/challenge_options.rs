@@ -0,0 +1,121 @@
+use crate::evm_input_cache::EvmInputStore;
+use celestia_types::nmt::Namespace;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+/// Settings of the challenges themselves: how they are pre-checked, what the guest proves and
+/// how it is proven. Unlike the `BlobstreamEventCache`, which only knows how to find Blobstream
+/// events, these are passed to each challenge entry point.
+///
+/// The options are cheap to clone and all clones share the same EVM input store, so concurrent
+/// challenge tasks can use the same options.
+#[derive(Clone)]
+pub struct ChallengeOptions {
+    precheck_challenges: bool,
+    prove_availability: bool,
+    namespace: Option<Namespace>,
+    segment_limit_po2: Option<u32>,
+    evm_input_store: Option<Arc<Mutex<EvmInputStore>>>,
+    guest_data_dump: Option<PathBuf>,
+}
+
+impl Default for ChallengeOptions {
+    fn default() -> Self {
+        Self {
+            precheck_challenges: true,
+            prove_availability: false,
+            namespace: None,
+            segment_limit_po2: None,
+            evm_input_store: None,
+            guest_data_dump: None,
+        }
+    }
+}
+
+impl ChallengeOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Enables running the guest logic natively before proving, to refuse challenges that are
+    /// bound to fail (ex: the blob is available). Enabled by default.
+    pub fn with_precheck(mut self, precheck_challenges: bool) -> Self {
+        self.precheck_challenges = precheck_challenges;
+        self
+    }
+
+    pub fn prechecks_challenges(&self) -> bool {
+        self.precheck_challenges
+    }
+
+    /// Enables proving that the challenged blob is available, instead of failing. Such proofs
+    /// commit `DaOutcome::BlobAvailable` in the journal and are rejected by the Counter contract.
+    pub fn with_availability_proofs(mut self, prove_availability: bool) -> Self {
+        self.prove_availability = prove_availability;
+        self
+    }
+
+    pub fn proves_availability(&self) -> bool {
+        self.prove_availability
+    }
+
+    /// Sets the namespace the rollup posts its index blobs to. Challenges then prove index blobs
+    /// posted to other namespaces.
+    pub fn with_namespace(mut self, namespace: Namespace) -> Self {
+        self.namespace = Some(namespace);
+        self
+    }
+
+    pub fn namespace(&self) -> Option<Namespace> {
+        self.namespace
+    }
+
+    /// Limits the size of the segments of the guest execution to `2^segment_limit_po2` cycles
+    /// when proving (RISC Zero default: 20). Smaller segments lower the peak memory of the
+    /// prover, at the cost of more segments to prove and aggregate.
+    pub fn with_segment_limit_po2(mut self, segment_limit_po2: u32) -> Self {
+        self.segment_limit_po2 = Some(segment_limit_po2);
+        self
+    }
+
+    pub fn segment_limit_po2(&self) -> Option<u32> {
+        self.segment_limit_po2
+    }
+
+    /// Keeps the Steel inputs of the Blobstream preflight calls in an on-disk store, so that
+    /// proving a challenge again at the same execution block skips the preflight.
+    pub fn with_evm_input_store(mut self, evm_input_store: EvmInputStore) -> Self {
+        self.evm_input_store = Some(Arc::new(Mutex::new(evm_input_store)));
+        self
+    }
+
+    pub fn has_evm_input_store(&self) -> bool {
+        self.evm_input_store.is_some()
+    }
+
+    /// Writes the guest data of each challenge to `path` before proving it, in the format of
+    /// `DaChallengeGuestData::to_debug_json()`.
+    pub fn with_guest_data_dump(mut self, path: PathBuf) -> Self {
+        self.guest_data_dump = Some(path);
+        self
+    }
+
+    pub fn guest_data_dump(&self) -> Option<&Path> {
+        self.guest_data_dump.as_deref()
+    }
+
+    /// Runs `f` on the EVM input store, if any.
+    pub(crate) fn access_evm_input_store<T>(
+        &self,
+        f: impl FnOnce(&EvmInputStore) -> Result<T, anyhow::Error>,
+    ) -> Result<Option<T>, anyhow::Error> {
+        let Some(evm_input_store) = &self.evm_input_store else {
+            return Ok(None);
+        };
+
+        let evm_input_store = evm_input_store
+            .lock()
+            .map_err(|_| anyhow::anyhow!("EVM input store lock poisoned"))?;
+        f(&evm_input_store).map(Some)
+    }
+}
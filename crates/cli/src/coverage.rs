@@ -0,0 +1,66 @@
+use crate::event_cache::BlobstreamEventCache;
+use std::time::{Duration, Instant};
+use toolkit::SpanSequence;
+
+/// What to do when a challenged Celestia height is not covered by Blobstream yet.
+///
+/// Blobstream lags behind Celestia, so a height above the Blobstream head is usually a valid
+/// block that will be covered later. Proving that such a height is too high is only meaningful
+/// if the height will never exist, which the host cannot tell.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UncoveredHeightPolicy {
+    /// Fail without proving anything.
+    Reject,
+    /// Poll Blobstream until the height is covered, or fail once the timeout elapsed.
+    Wait {
+        timeout: Duration,
+        poll_interval: Duration,
+    },
+    /// Prove that the height is higher than the Blobstream head.
+    Challenge,
+}
+
+/// Makes sure that the Celestia heights of the index blob and of the challenged blob are covered
+/// by Blobstream, according to the policy.
+pub async fn ensure_heights_covered(
+    blobstream_event_cache: &BlobstreamEventCache,
+    index_blob: SpanSequence,
+    challenged_blob: SpanSequence,
+    policy: UncoveredHeightPolicy,
+) -> Result<(), anyhow::Error> {
+    if policy == UncoveredHeightPolicy::Challenge {
+        return Ok(());
+    }
+
+    let backend = blobstream_event_cache.backend().await?;
+    let height = index_blob.height.max(challenged_blob.height);
+    let start_time = Instant::now();
+
+    loop {
+        let max_covered_height = backend.max_covered_height().await?;
+        if height <= max_covered_height {
+            return Ok(());
+        }
+
+        match policy {
+            UncoveredHeightPolicy::Wait {
+                timeout,
+                poll_interval,
+            } if start_time.elapsed() < timeout => {
+                log::info!(
+                    "Celestia block {height} is not covered by Blobstream yet \
+                     (latest: {max_covered_height}), checking again in {} s",
+                    poll_interval.as_secs()
+                );
+                tokio::time::sleep(poll_interval).await;
+            }
+            _ => {
+                return Err(anyhow::anyhow!(
+                    "Celestia block {height} is not covered by Blobstream yet \
+                     (latest: {max_covered_height}). Wait for Blobstream to catch up, \
+                     or explicitly allow challenging uncovered heights"
+                ))
+            }
+        }
+    }
+}
@@ -0,0 +1,115 @@
+use crate::celestia_data_source::{verify_share_proof, CelestiaDataSource};
+use celestia_types::ExtendedHeader;
+use serde::Serialize;
+use std::collections::hash_map::RandomState;
+use std::collections::BTreeSet;
+use std::fmt;
+use std::hash::{BuildHasher, Hasher};
+use toolkit::SpanSequence;
+
+/// Outcome of sampling the shares of a blob from Celestia peers, see `sample_blob_shares()`.
+#[derive(Debug, Clone, Serialize)]
+pub struct SamplingReport {
+    pub span_sequence: SpanSequence,
+    /// ODS indexes of the sampled shares.
+    pub sampled_shares: Vec<u32>,
+    /// Sampled shares that at least one peer served with a valid proof.
+    pub retrieved_shares: Vec<u32>,
+}
+
+impl SamplingReport {
+    /// Returns true if every sampled share could be retrieved, in which case challenging the
+    /// availability of the blob would most likely burn proving time for nothing.
+    pub fn is_available(&self) -> bool {
+        !self.sampled_shares.is_empty() && self.retrieved_shares.len() == self.sampled_shares.len()
+    }
+}
+
+impl fmt::Display for SamplingReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "retrieved {} of {} sampled shares of {}",
+            self.retrieved_shares.len(),
+            self.sampled_shares.len(),
+            self.span_sequence
+        )
+    }
+}
+
+/// Picks up to `samples` distinct random shares of the range.
+fn pick_shares(shares: std::ops::Range<u32>, samples: usize) -> Vec<u32> {
+    let share_count = shares.len();
+    if share_count <= samples {
+        return shares.collect();
+    }
+
+    // Randomly seeded hasher as the random source, the samples do not need to be reproducible
+    let random_state = RandomState::new();
+    let mut picked = BTreeSet::new();
+    let mut counter = 0u64;
+    while picked.len() < samples {
+        let mut hasher = random_state.build_hasher();
+        hasher.write_u64(counter);
+        counter += 1;
+        picked.insert(shares.start + (hasher.finish() % share_count as u64) as u32);
+    }
+
+    picked.into_iter().collect()
+}
+
+/// Samples random shares of the blob from each of the peers, like a light node does, to confirm
+/// that the data of the blob cannot be retrieved before challenging its availability. A share is
+/// retrieved if any peer serves it with a proof against the data root of `block_header`.
+///
+/// `block_header` is the header of the block of the blob, fetched from the trusted (primary)
+/// Celestia source: peers only serve proofs, a peer serving both a header and proofs of its own
+/// could fake the availability of the blob. Shares out of the data square cannot be retrieved by
+/// definition.
+pub async fn sample_blob_shares<S: CelestiaDataSource>(
+    block_header: &ExtendedHeader,
+    peers: &[S],
+    span_sequence: SpanSequence,
+    samples: usize,
+) -> SamplingReport {
+    let mut report = SamplingReport {
+        span_sequence,
+        sampled_shares: vec![],
+        retrieved_shares: vec![],
+    };
+    let Ok(shares) = span_sequence.ods_range() else {
+        return report;
+    };
+    report.sampled_shares = pick_shares(shares, samples);
+
+    for &share in &report.sampled_shares {
+        let (start, end) = (share as u64, share as u64 + 1);
+        for peer in peers {
+            let Ok(share_proof) = peer.share_range_proof(block_header, start, end).await else {
+                continue;
+            };
+
+            if verify_share_proof(&share_proof, block_header, start, end).is_ok() {
+                report.retrieved_shares.push(share);
+                break;
+            }
+        }
+    }
+
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pick_shares() {
+        assert_eq!(pick_shares(10..13, 5), vec![10, 11, 12]);
+
+        let picked = pick_shares(100..1100, 16);
+        assert_eq!(picked.len(), 16);
+        assert!(picked.iter().all(|share| (100..1100).contains(share)));
+        assert!(picked.windows(2).all(|pair| pair[0] < pair[1]));
+    }
+}
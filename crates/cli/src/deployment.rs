@@ -0,0 +1,283 @@
+use alloy_primitives::{Address, ChainId};
+use anyhow::Context;
+use celestia_types::nmt::{Namespace, NS_SIZE};
+use risc0_steel::config::ChainSpec;
+use serde::{Deserialize, Deserializer};
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use toolkit::constants::canonical_chain_spec;
+use toolkit::{BlobstreamImpl, DaChallenge, SpanSequence};
+use url::Url;
+
+/// A rollup deployment the challenger submits proofs to: the EVM chain, the Blobstream
+/// contract attesting Celestia data roots and the contract verifying the challenge proofs.
+#[derive(Debug, Clone, Deserialize)]
+pub struct DeploymentConfig {
+    /// Name used to route challenges to the deployment.
+    pub name: String,
+    pub chain_id: ChainId,
+    pub eth_rpc_url: Url,
+    pub blobstream_address: Address,
+    pub verifier_address: Address,
+    /// EVM specification of the chain, ex: `Cancun`. Only allowed, and required, for chains
+    /// without a built-in Steel chain spec, such as L2s hosting Blobstream.
+    #[serde(default)]
+    pub evm_spec: Option<String>,
+    /// Blobstream implementation of the contract. Detected if not set.
+    #[serde(default)]
+    pub blobstream_impl: Option<BlobstreamImpl>,
+    /// Job store of the deployment. Jobs are keyed by blob, so deployments that may challenge
+    /// the same blobs need distinct job stores.
+    #[serde(default)]
+    pub job_db: Option<PathBuf>,
+    /// Celestia namespace the rollup posts its index blobs to, in hex, ex: `abcd`. Index blobs
+    /// posted in another namespace are proven unavailable.
+    #[serde(default, deserialize_with = "deserialize_namespace")]
+    pub namespace: Option<Namespace>,
+}
+
+impl DeploymentConfig {
+    /// Returns the Steel chain spec of the deployment's chain.
+    pub fn chain_spec(&self) -> Result<ChainSpec, anyhow::Error> {
+        chain_spec(self.chain_id, self.evm_spec.as_deref())
+    }
+}
+
+/// Returns the Steel chain spec of the specified chain. Ethereum chains have a built-in spec,
+/// pinned by the guest program (see `toolkit::constants::canonical_chain_spec()`), `evm_spec` is
+/// required for other chains.
+pub fn chain_spec(chain_id: ChainId, evm_spec: Option<&str>) -> Result<ChainSpec, anyhow::Error> {
+    match (canonical_chain_spec(chain_id), evm_spec) {
+        (Some(_), Some(_)) => Err(anyhow::anyhow!(
+            "the chain spec of chain ID {chain_id} is pinned by the guest, remove its EVM spec"
+        )),
+        (Some(chain_spec), None) => Ok(chain_spec.clone()),
+        (None, Some(evm_spec)) => Ok(ChainSpec::new_single(chain_id, evm_spec.into())),
+        (None, None) => Err(anyhow::anyhow!(
+            "no built-in chain spec for chain ID {chain_id}, specify its EVM spec"
+        )),
+    }
+}
+
+/// Parses a Celestia namespace, given in hex either as the full 29-byte namespace or as the ID
+/// of a version 0 namespace, ex: `abcd`.
+pub fn parse_namespace(namespace: &str) -> Result<Namespace, anyhow::Error> {
+    let bytes = alloy_primitives::hex::decode(namespace)
+        .with_context(|| format!("invalid namespace {namespace}"))?;
+    let namespace = if bytes.len() == NS_SIZE {
+        Namespace::from_raw(&bytes)
+    } else {
+        Namespace::new_v0(&bytes)
+    };
+
+    namespace.with_context(|| {
+        format!(
+            "invalid namespace {}",
+            alloy_primitives::hex::encode(&bytes)
+        )
+    })
+}
+
+/// The deployments served by a challenger instance, loaded from a JSON list of
+/// `DeploymentConfig`.
+#[derive(Debug, Clone, Default)]
+pub struct Deployments {
+    deployments: Vec<DeploymentConfig>,
+}
+
+impl Deployments {
+    pub fn new(deployments: Vec<DeploymentConfig>) -> Result<Self, anyhow::Error> {
+        let mut names = HashSet::new();
+        for deployment in &deployments {
+            if !names.insert(deployment.name.as_str()) {
+                return Err(anyhow::anyhow!(
+                    "deployment {} is configured twice",
+                    deployment.name
+                ));
+            }
+        }
+
+        Ok(Self { deployments })
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, anyhow::Error> {
+        let path = path.as_ref();
+        let json = std::fs::read_to_string(path)
+            .with_context(|| format!("failed to read deployments from {}", path.display()))?;
+        let deployments = serde_json::from_str(&json)
+            .with_context(|| format!("invalid deployments file {}", path.display()))?;
+
+        Self::new(deployments)
+    }
+
+    /// Returns the deployment a challenge is routed to. The deployment can be omitted if only
+    /// one is configured.
+    pub fn route(&self, name: Option<&str>) -> Result<&DeploymentConfig, anyhow::Error> {
+        match (name, self.deployments.as_slice()) {
+            (None, [deployment]) => Ok(deployment),
+            (None, _) => Err(anyhow::anyhow!(
+                "{} deployments are configured, specify the deployment of the challenge",
+                self.deployments.len()
+            )),
+            (Some(name), _) => self
+                .deployments
+                .iter()
+                .find(|deployment| deployment.name == name)
+                .ok_or_else(|| {
+                    anyhow::anyhow!(
+                        "unknown deployment {name}, expected one of: {}",
+                        self.names().collect::<Vec<_>>().join(", ")
+                    )
+                }),
+        }
+    }
+
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.deployments
+            .iter()
+            .map(|deployment| deployment.name.as_str())
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &DeploymentConfig> {
+        self.deployments.iter()
+    }
+}
+
+/// A challenge to run, as listed in a challenges file.
+#[derive(Debug, Clone, Deserialize)]
+pub struct ChallengeRequest {
+    /// Deployment to submit the proof to.
+    #[serde(default)]
+    pub deployment: Option<String>,
+    #[serde(deserialize_with = "deserialize_span_sequence")]
+    pub index_blob: SpanSequence,
+    #[serde(deserialize_with = "deserialize_span_sequence")]
+    pub challenged_blob: SpanSequence,
+    /// Challenge the data of the challenged blob instead of its availability.
+    #[serde(default)]
+    pub blob_data_mismatch: bool,
+}
+
+impl ChallengeRequest {
+    /// Creates the request of a challenge.
+    pub fn new(
+        deployment: Option<String>,
+        index_blob: SpanSequence,
+        challenge: DaChallenge,
+    ) -> Self {
+        Self {
+            deployment,
+            index_blob,
+            challenged_blob: challenge.challenged_blob(index_blob),
+            blob_data_mismatch: matches!(challenge, DaChallenge::BlobDataMismatch(_)),
+        }
+    }
+
+    /// Returns the challenge to prove.
+    pub fn challenge(&self) -> DaChallenge {
+        if self.blob_data_mismatch {
+            DaChallenge::BlobDataMismatch(self.challenged_blob)
+        } else {
+            DaChallenge::availability(self.index_blob, self.challenged_blob)
+        }
+    }
+
+    /// Loads a JSON list of challenges.
+    pub fn load_all(path: impl AsRef<Path>) -> Result<Vec<Self>, anyhow::Error> {
+        let path = path.as_ref();
+        let json = std::fs::read_to_string(path)
+            .with_context(|| format!("failed to read challenges from {}", path.display()))?;
+
+        serde_json::from_str(&json)
+            .with_context(|| format!("invalid challenges file {}", path.display()))
+    }
+}
+
+/// Span sequences are written as on the command line, ex: `6671289:6:4`.
+fn deserialize_span_sequence<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<SpanSequence, D::Error> {
+    let span_sequence = String::deserialize(deserializer)?;
+    SpanSequence::from_str(&span_sequence).map_err(serde::de::Error::custom)
+}
+
+fn deserialize_namespace<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Option<Namespace>, D::Error> {
+    Option::<String>::deserialize(deserializer)?
+        .map(|namespace| parse_namespace(&namespace).map_err(serde::de::Error::custom))
+        .transpose()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn deployment(name: &str) -> DeploymentConfig {
+        DeploymentConfig {
+            name: name.to_string(),
+            chain_id: 11155111,
+            eth_rpc_url: Url::parse("http://localhost:8545").unwrap(),
+            blobstream_address: Address::repeat_byte(0x01),
+            verifier_address: Address::repeat_byte(0x02),
+            evm_spec: None,
+            blobstream_impl: None,
+            job_db: None,
+            namespace: None,
+        }
+    }
+
+    #[test]
+    fn test_route_challenges() {
+        let single = Deployments::new(vec![deployment("a")]).unwrap();
+        assert_eq!(single.route(None).unwrap().name, "a");
+
+        let deployments = Deployments::new(vec![deployment("a"), deployment("b")]).unwrap();
+        assert_eq!(deployments.route(Some("b")).unwrap().name, "b");
+        assert!(deployments.route(None).is_err());
+        assert!(deployments.route(Some("c")).is_err());
+
+        assert!(Deployments::new(vec![deployment("a"), deployment("a")]).is_err());
+    }
+
+    #[test]
+    fn test_pinned_chain_spec() {
+        assert!(chain_spec(11155111, None).is_ok());
+        assert!(chain_spec(11155111, Some("Cancun")).is_err());
+        assert!(chain_spec(31337, Some("Cancun")).is_ok());
+        assert!(chain_spec(31337, None).is_err());
+    }
+
+    #[test]
+    fn test_challenge_request() {
+        let index_blob = SpanSequence::from_str("10:0:2").unwrap();
+        for challenge in [
+            "index-unavailable",
+            "blob-unavailable:11:3:1",
+            "blob-data-mismatch:11:3:1",
+        ] {
+            let challenge = DaChallenge::from_str(challenge).unwrap();
+            assert_eq!(
+                ChallengeRequest::new(None, index_blob, challenge).challenge(),
+                challenge
+            );
+        }
+
+        let request: ChallengeRequest =
+            serde_json::from_str(r#"{"index_blob": "10:0:2", "challenged_blob": "10:0:2"}"#)
+                .unwrap();
+        assert_eq!(request.challenge(), DaChallenge::IndexIsUnavailable);
+    }
+
+    #[test]
+    fn test_parse_namespace() {
+        let short = parse_namespace("abcd").unwrap();
+        let full =
+            parse_namespace(&alloy_primitives::hex::encode_prefixed(short.as_bytes())).unwrap();
+        assert_eq!(short, full);
+
+        assert!(parse_namespace("not hex").is_err());
+        assert!(parse_namespace(&"ff".repeat(11)).is_err());
+    }
+}
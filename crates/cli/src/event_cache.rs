@@ -0,0 +1,696 @@
+use crate::blobstream_backend::{
+    blobstream_backend, detect_blobstream_backend, BlobstreamBackend, FirstCommitmentTable,
+};
+use crate::proof_cache::InclusionProofStore;
+use alloy_primitives::{Address, ChainId, B256, U256};
+use anyhow::Context;
+use hana_blobstream::blobstream::SP1BlobstreamDataCommitmentStored;
+use rangemap::RangeMap;
+use risc0_ethereum_contracts::alloy::providers::{Provider, RootProvider};
+use rusqlite::{params, Connection, OptionalExtension, Row};
+use std::collections::HashMap;
+use std::ops::Range;
+use std::path::Path;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use tokio::sync::OnceCell;
+use toolkit::BlobstreamImpl;
+
+/// On-disk store of `DataCommitmentStored` events, keyed by (chain ID, Blobstream address).
+///
+/// Blobstream events never change once emitted, so they can be kept forever. This spares
+/// repeated challenges from scanning hundreds of thousands of blocks of logs.
+pub struct PersistentEventStore {
+    conn: Connection,
+}
+
+const EVENT_COLUMNS: &str = "proof_nonce, start_block, end_block, data_commitment";
+
+impl PersistentEventStore {
+    /// Opens (or creates) the event store at the specified path.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, anyhow::Error> {
+        let path = path.as_ref();
+        let conn = Connection::open(path)
+            .with_context(|| format!("failed to open event store at {}", path.display()))?;
+        Self::init(conn)
+    }
+
+    /// Creates an event store that only lives in memory.
+    pub fn open_in_memory() -> Result<Self, anyhow::Error> {
+        Self::init(Connection::open_in_memory()?)
+    }
+
+    fn init(conn: Connection) -> Result<Self, anyhow::Error> {
+        conn.execute_batch(
+            "CREATE TABLE IF NOT EXISTS data_commitments (
+                chain_id           INTEGER NOT NULL,
+                blobstream_address TEXT NOT NULL,
+                proof_nonce        TEXT NOT NULL,
+                start_block        INTEGER NOT NULL,
+                end_block          INTEGER NOT NULL,
+                data_commitment    TEXT NOT NULL,
+                PRIMARY KEY (chain_id, blobstream_address, proof_nonce)
+            );
+            CREATE INDEX IF NOT EXISTS data_commitments_range
+                ON data_commitments (chain_id, blobstream_address, start_block, end_block);",
+        )
+        .with_context(|| "failed to initialize event store")?;
+
+        Ok(Self { conn })
+    }
+
+    /// Returns the event whose Celestia block range covers the specified height, if known.
+    pub fn get(
+        &self,
+        chain_id: ChainId,
+        blobstream_address: Address,
+        block_height: u64,
+    ) -> Result<Option<SP1BlobstreamDataCommitmentStored>, anyhow::Error> {
+        self.conn
+            .query_row(
+                &format!(
+                    "SELECT {EVENT_COLUMNS} FROM data_commitments
+                     WHERE chain_id = ?1 AND blobstream_address = ?2
+                       AND start_block <= ?3 AND ?3 < end_block"
+                ),
+                params![chain_id, blobstream_address.to_string(), block_height],
+                EventRow::from_row,
+            )
+            .optional()?
+            .map(SP1BlobstreamDataCommitmentStored::try_from)
+            .transpose()
+    }
+
+    /// Returns the event with the specified proof nonce, if known.
+    pub fn get_by_nonce(
+        &self,
+        chain_id: ChainId,
+        blobstream_address: Address,
+        proof_nonce: U256,
+    ) -> Result<Option<SP1BlobstreamDataCommitmentStored>, anyhow::Error> {
+        self.conn
+            .query_row(
+                &format!(
+                    "SELECT {EVENT_COLUMNS} FROM data_commitments
+                     WHERE chain_id = ?1 AND blobstream_address = ?2 AND proof_nonce = ?3"
+                ),
+                params![
+                    chain_id,
+                    blobstream_address.to_string(),
+                    proof_nonce.to_string()
+                ],
+                EventRow::from_row,
+            )
+            .optional()?
+            .map(SP1BlobstreamDataCommitmentStored::try_from)
+            .transpose()
+    }
+
+    /// Stores an event. Storing the same event twice is a no-op.
+    pub fn insert(
+        &self,
+        chain_id: ChainId,
+        blobstream_address: Address,
+        event: &SP1BlobstreamDataCommitmentStored,
+    ) -> Result<(), anyhow::Error> {
+        self.conn.execute(
+            "INSERT OR IGNORE INTO data_commitments
+             (chain_id, blobstream_address, proof_nonce, start_block, end_block, data_commitment)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            params![
+                chain_id,
+                blobstream_address.to_string(),
+                event.proof_nonce.to_string(),
+                event.start_block,
+                event.end_block,
+                event.data_commitment.to_string()
+            ],
+        )?;
+
+        Ok(())
+    }
+
+    /// Returns the number of events stored for the specified deployment.
+    pub fn count(
+        &self,
+        chain_id: ChainId,
+        blobstream_address: Address,
+    ) -> Result<u64, anyhow::Error> {
+        let len = self.conn.query_row(
+            "SELECT COUNT(*) FROM data_commitments WHERE chain_id = ?1 AND blobstream_address = ?2",
+            params![chain_id, blobstream_address.to_string()],
+            |row| row.get(0),
+        )?;
+
+        Ok(len)
+    }
+
+    /// Returns the events stored for the specified deployment, ordered by Celestia block range.
+    pub fn events(
+        &self,
+        chain_id: ChainId,
+        blobstream_address: Address,
+    ) -> Result<Vec<SP1BlobstreamDataCommitmentStored>, anyhow::Error> {
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT {EVENT_COLUMNS} FROM data_commitments
+             WHERE chain_id = ?1 AND blobstream_address = ?2
+             ORDER BY start_block"
+        ))?;
+        let rows = stmt.query_map(
+            params![chain_id, blobstream_address.to_string()],
+            EventRow::from_row,
+        )?;
+
+        rows.map(|row| SP1BlobstreamDataCommitmentStored::try_from(row?))
+            .collect()
+    }
+
+    /// Removes the events whose Celestia block range ends at or before `end_block`. Returns the
+    /// number of removed events. The first commitment of the deployment is always kept.
+    pub fn prune_before(
+        &self,
+        chain_id: ChainId,
+        blobstream_address: Address,
+        end_block: u64,
+    ) -> Result<usize, anyhow::Error> {
+        let removed = self.conn.execute(
+            "DELETE FROM data_commitments
+             WHERE chain_id = ?1 AND blobstream_address = ?2
+               AND end_block <= ?3 AND proof_nonce != '1'",
+            params![chain_id, blobstream_address.to_string(), end_block],
+        )?;
+
+        Ok(removed)
+    }
+}
+
+/// Raw row of the `data_commitments` table.
+struct EventRow {
+    proof_nonce: String,
+    start_block: u64,
+    end_block: u64,
+    data_commitment: String,
+}
+
+impl EventRow {
+    fn from_row(row: &Row) -> rusqlite::Result<Self> {
+        Ok(Self {
+            proof_nonce: row.get(0)?,
+            start_block: row.get(1)?,
+            end_block: row.get(2)?,
+            data_commitment: row.get(3)?,
+        })
+    }
+}
+
+impl TryFrom<EventRow> for SP1BlobstreamDataCommitmentStored {
+    type Error = anyhow::Error;
+
+    fn try_from(row: EventRow) -> Result<Self, Self::Error> {
+        Ok(SP1BlobstreamDataCommitmentStored {
+            proof_nonce: U256::from_str(&row.proof_nonce)?,
+            start_block: row.start_block,
+            end_block: row.end_block,
+            data_commitment: B256::from_str(&row.data_commitment)?,
+        })
+    }
+}
+
+/// How the event cache finds the Blobstream commitment covering a Celestia height.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum EventLookupStrategy {
+    /// Binary search over the contract state at past Ethereum blocks, then query the logs of
+    /// a single block. Falls back to scanning logs if the RPC does not serve historical state.
+    #[default]
+    BinarySearch,
+    /// Scan `DataCommitmentStored` logs.
+    LogScan,
+}
+
+/// Snapshot of the effectiveness of a `BlobstreamEventCache`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, serde::Serialize)]
+pub struct EventCacheStats {
+    /// Lookups answered from memory.
+    pub hits: u64,
+    /// Lookups that went to the persistent store or on-chain.
+    pub misses: u64,
+    /// Misses answered by the persistent store.
+    pub store_hits: u64,
+    /// Events dropped from memory to stay within the size limit.
+    pub evictions: u64,
+    /// Events currently kept in memory.
+    pub cached_events: usize,
+}
+
+#[derive(Default)]
+struct CacheCounters {
+    hits: AtomicU64,
+    misses: AtomicU64,
+    store_hits: AtomicU64,
+    evictions: AtomicU64,
+}
+
+/// In-memory events, indexed by Celestia block range, with their last access time for
+/// least-recently-used eviction.
+#[derive(Default)]
+struct CachedEvents {
+    ranges: RangeMap<u64, SP1BlobstreamDataCommitmentStored>,
+    /// Last access of each event, keyed by the start of its block range.
+    last_used: HashMap<u64, u64>,
+    clock: u64,
+}
+
+impl CachedEvents {
+    fn get(&mut self, block_height: u64) -> Option<SP1BlobstreamDataCommitmentStored> {
+        let event = self.ranges.get(&block_height)?.clone();
+        self.touch(event.start_block);
+        Some(event)
+    }
+
+    /// Inserts an event, then evicts the least recently used events until at most
+    /// `max_events` remain. Returns the number of evicted events.
+    fn insert(
+        &mut self,
+        event: SP1BlobstreamDataCommitmentStored,
+        max_events: Option<usize>,
+    ) -> u64 {
+        self.touch(event.start_block);
+        self.ranges
+            .insert(event.start_block..event.end_block, event);
+
+        let mut evicted = 0;
+        while max_events.is_some_and(|max_events| self.last_used.len() > max_events) {
+            let Some((&start_block, _)) = self.last_used.iter().min_by_key(|(_, &used)| used)
+            else {
+                break;
+            };
+            self.last_used.remove(&start_block);
+            if let Some(event) = self.ranges.get(&start_block).cloned() {
+                self.ranges.remove(event.start_block..event.end_block);
+            }
+            evicted += 1;
+        }
+
+        evicted
+    }
+
+    fn touch(&mut self, start_block: u64) {
+        self.clock += 1;
+        self.last_used.insert(start_block, self.clock);
+    }
+
+    fn len(&self) -> usize {
+        self.last_used.len()
+    }
+}
+
+/// Cache of the Blobstream `DataCommitmentStored` events, used to find the Blobstream
+/// commitment covering a given Celestia block height. Implementation-specific lookups are
+/// delegated to a `BlobstreamBackend`, detected on first use.
+///
+/// Events are kept in memory and, optionally, in a `PersistentEventStore` shared across runs.
+/// The cache is cheap to clone and all clones share the same events, so concurrent challenge
+/// tasks can use a single cache. Lookups that miss the cache are serialized so that two tasks
+/// never scan the same logs at the same time.
+///
+/// The in-memory part is unbounded by default, long-running processes should cap it with
+/// `with_max_cached_events()`. Least recently used events are evicted first.
+#[derive(Clone)]
+pub struct BlobstreamEventCache {
+    pub(crate) eth_provider: RootProvider,
+    blobstream_address: Address,
+    event_cache: Arc<Mutex<CachedEvents>>,
+    max_cached_events: Option<usize>,
+    counters: Arc<CacheCounters>,
+    persistent_store: Option<Arc<Mutex<PersistentEventStore>>>,
+    chain_id: Arc<OnceCell<ChainId>>,
+    backend: Arc<OnceCell<Arc<dyn BlobstreamBackend>>>,
+    fetch_lock: Arc<tokio::sync::Mutex<()>>,
+    lookup_strategy: EventLookupStrategy,
+    first_commitments: FirstCommitmentTable,
+    verify_data_commitments: bool,
+    blobstream_impl_override: Option<BlobstreamImpl>,
+    inclusion_proof_store: Option<Arc<Mutex<InclusionProofStore>>>,
+}
+
+impl BlobstreamEventCache {
+    pub fn new(blobstream_address: Address, eth_provider: RootProvider) -> Self {
+        Self {
+            blobstream_address,
+            eth_provider,
+            event_cache: Default::default(),
+            max_cached_events: None,
+            counters: Default::default(),
+            persistent_store: None,
+            chain_id: Default::default(),
+            backend: Default::default(),
+            fetch_lock: Default::default(),
+            lookup_strategy: Default::default(),
+            first_commitments: Default::default(),
+            verify_data_commitments: false,
+            blobstream_impl_override: None,
+            inclusion_proof_store: None,
+        }
+    }
+
+    pub fn with_lookup_strategy(mut self, lookup_strategy: EventLookupStrategy) -> Self {
+        self.lookup_strategy = lookup_strategy;
+        self
+    }
+
+    /// Limits the number of events kept in memory. Events evicted from memory are still
+    /// served by the persistent store, if any.
+    pub fn with_max_cached_events(mut self, max_cached_events: usize) -> Self {
+        self.max_cached_events = Some(max_cached_events.max(1));
+        self
+    }
+
+    /// Overrides the table of known first commitments used for SP1Blobstream deployments.
+    pub fn with_first_commitments(mut self, first_commitments: FirstCommitmentTable) -> Self {
+        self.first_commitments = first_commitments;
+        self
+    }
+
+    /// Enables checking that the data root tuple inclusion proofs fetched from Celestia lead to
+    /// the data commitments of the cached events.
+    pub fn with_data_commitment_verification(mut self, verify_data_commitments: bool) -> Self {
+        self.verify_data_commitments = verify_data_commitments;
+        self
+    }
+
+    pub fn verifies_data_commitments(&self) -> bool {
+        self.verify_data_commitments
+    }
+
+    /// Skips the detection of the Blobstream implementation, which selects how events are
+    /// looked up.
+    pub fn with_blobstream_impl(mut self, blobstream_impl: BlobstreamImpl) -> Self {
+        self.blobstream_impl_override = Some(blobstream_impl);
+        self
+    }
+
+    /// Backs the cache with an on-disk event store.
+    pub fn with_persistent_store(mut self, persistent_store: PersistentEventStore) -> Self {
+        self.persistent_store = Some(Arc::new(Mutex::new(persistent_store)));
+        self
+    }
+
+    /// Keeps the data root tuple inclusion proofs fetched from Celestia in an on-disk store.
+    pub fn with_inclusion_proof_store(
+        mut self,
+        inclusion_proof_store: InclusionProofStore,
+    ) -> Self {
+        self.inclusion_proof_store = Some(Arc::new(Mutex::new(inclusion_proof_store)));
+        self
+    }
+
+    pub fn blobstream_address(&self) -> Address {
+        self.blobstream_address
+    }
+
+    pub(crate) async fn chain_id(&self) -> Result<ChainId, anyhow::Error> {
+        let chain_id = self
+            .chain_id
+            .get_or_try_init(|| self.eth_provider.get_chain_id())
+            .await?;
+
+        Ok(*chain_id)
+    }
+
+    /// Returns the backend of the Blobstream deployment at the cache's address, detected unless
+    /// overridden with `with_blobstream_impl()`.
+    pub async fn backend(&self) -> Result<Arc<dyn BlobstreamBackend>, anyhow::Error> {
+        let backend = self
+            .backend
+            .get_or_try_init(|| async {
+                match self.blobstream_impl_override {
+                    Some(blobstream_impl) => Ok(blobstream_backend(
+                        blobstream_impl,
+                        self.blobstream_address,
+                        self.eth_provider.clone(),
+                        self.first_commitments.clone(),
+                    )),
+                    None => {
+                        detect_blobstream_backend(
+                            self.blobstream_address,
+                            self.eth_provider.clone(),
+                            self.first_commitments.clone(),
+                        )
+                        .await
+                    }
+                }
+            })
+            .await?;
+
+        Ok(backend.clone())
+    }
+
+    /// Returns the Blobstream implementation deployed at the cache's address.
+    pub async fn blobstream_impl(&self) -> Result<BlobstreamImpl, anyhow::Error> {
+        Ok(self.backend().await?.implementation())
+    }
+
+    /// Runs `f` on the persistent store, if any.
+    fn access_persistent_store<T>(
+        &self,
+        f: impl FnOnce(&PersistentEventStore) -> Result<T, anyhow::Error>,
+    ) -> Result<Option<T>, anyhow::Error> {
+        let Some(persistent_store) = &self.persistent_store else {
+            return Ok(None);
+        };
+
+        let persistent_store = persistent_store
+            .lock()
+            .map_err(|_| anyhow::anyhow!("event store lock poisoned"))?;
+        f(&persistent_store).map(Some)
+    }
+
+    /// Runs `f` on the inclusion proof store, if any.
+    pub(crate) fn access_inclusion_proof_store<T>(
+        &self,
+        f: impl FnOnce(&InclusionProofStore) -> Result<T, anyhow::Error>,
+    ) -> Result<Option<T>, anyhow::Error> {
+        let Some(inclusion_proof_store) = &self.inclusion_proof_store else {
+            return Ok(None);
+        };
+
+        let inclusion_proof_store = inclusion_proof_store
+            .lock()
+            .map_err(|_| anyhow::anyhow!("proof store lock poisoned"))?;
+        f(&inclusion_proof_store).map(Some)
+    }
+
+    pub async fn first_data_commitment_stored_event(
+        &self,
+    ) -> Result<SP1BlobstreamDataCommitmentStored, anyhow::Error> {
+        let chain_id = self.chain_id().await?;
+
+        let stored_event = self.access_persistent_store(|store| {
+            store.get_by_nonce(chain_id, self.blobstream_address, U256::from(1u64))
+        })?;
+        if let Some(event) = stored_event.flatten() {
+            return Ok(event);
+        }
+
+        let event = self.backend().await?.first_commitment(chain_id).await?;
+
+        self.access_persistent_store(|store| {
+            store.insert(chain_id, self.blobstream_address, &event)
+        })?;
+
+        Ok(event)
+    }
+
+    /// Returns the event whose Celestia block range covers the specified height.
+    pub async fn get(
+        &self,
+        block_height: u64,
+    ) -> Result<SP1BlobstreamDataCommitmentStored, anyhow::Error> {
+        if let Some(event) = self.cached(block_height)? {
+            self.counters.hits.fetch_add(1, Ordering::Relaxed);
+            return Ok(event);
+        }
+
+        // Only one lookup at a time goes to the event store / RPC. Check the cache again once
+        // the lock is acquired, another task may have found the event in the meantime.
+        let _fetch_guard = self.fetch_lock.lock().await;
+        if let Some(event) = self.cached(block_height)? {
+            self.counters.hits.fetch_add(1, Ordering::Relaxed);
+            return Ok(event);
+        }
+
+        self.counters.misses.fetch_add(1, Ordering::Relaxed);
+        let event = self.fetch(block_height).await?;
+        let evicted = self
+            .lock_cached_events()?
+            .insert(event.clone(), self.max_cached_events);
+        self.counters
+            .evictions
+            .fetch_add(evicted, Ordering::Relaxed);
+
+        Ok(event)
+    }
+
+    fn lock_cached_events(&self) -> Result<std::sync::MutexGuard<'_, CachedEvents>, anyhow::Error> {
+        self.event_cache
+            .lock()
+            .map_err(|_| anyhow::anyhow!("event cache lock poisoned"))
+    }
+
+    fn cached(
+        &self,
+        block_height: u64,
+    ) -> Result<Option<SP1BlobstreamDataCommitmentStored>, anyhow::Error> {
+        Ok(self.lock_cached_events()?.get(block_height))
+    }
+
+    /// Returns the hit/miss counters of the cache. Counters are shared by all clones.
+    pub fn stats(&self) -> EventCacheStats {
+        EventCacheStats {
+            hits: self.counters.hits.load(Ordering::Relaxed),
+            misses: self.counters.misses.load(Ordering::Relaxed),
+            store_hits: self.counters.store_hits.load(Ordering::Relaxed),
+            evictions: self.counters.evictions.load(Ordering::Relaxed),
+            cached_events: self
+                .event_cache
+                .lock()
+                .map(|cached_events| cached_events.len())
+                .unwrap_or_default(),
+        }
+    }
+
+    /// Returns the Celestia block ranges kept in memory, with the proof nonce of their event.
+    pub fn cached_ranges(&self) -> Vec<(Range<u64>, U256)> {
+        self.event_cache
+            .lock()
+            .map(|cached_events| {
+                cached_events
+                    .ranges
+                    .iter()
+                    .map(|(range, event)| (range.clone(), event.proof_nonce))
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// Looks up the event covering the block height in the persistent store, then on-chain.
+    async fn fetch(
+        &self,
+        block_height: u64,
+    ) -> Result<SP1BlobstreamDataCommitmentStored, anyhow::Error> {
+        let chain_id = match &self.persistent_store {
+            Some(_) => self.chain_id().await?,
+            None => 0,
+        };
+
+        let stored_event = self.access_persistent_store(|store| {
+            store.get(chain_id, self.blobstream_address, block_height)
+        })?;
+        if let Some(event) = stored_event.flatten() {
+            log::debug!("found DataCommitmentStored event in the event store: {event}");
+            self.counters.store_hits.fetch_add(1, Ordering::Relaxed);
+            return Ok(event);
+        }
+
+        let event = self
+            .backend()
+            .await?
+            .find_commitment(block_height, self.lookup_strategy)
+            .await
+            .context("failed to find Blobstream commitment")?;
+
+        log::info!("found DataCommitmentStored event: {event}");
+
+        self.access_persistent_store(|store| {
+            store.insert(chain_id, self.blobstream_address, &event)
+        })?;
+
+        Ok(event)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_persistent_store_lookup_by_height() {
+        let store = PersistentEventStore::open_in_memory().unwrap();
+        let blobstream_address = Address::repeat_byte(0x42);
+        let event = SP1BlobstreamDataCommitmentStored {
+            proof_nonce: U256::from(7u64),
+            start_block: 100,
+            end_block: 200,
+            data_commitment: B256::repeat_byte(0x01),
+        };
+
+        store.insert(1, blobstream_address, &event).unwrap();
+        // Inserting twice must not fail
+        store.insert(1, blobstream_address, &event).unwrap();
+
+        assert_eq!(
+            store.get(1, blobstream_address, 100).unwrap(),
+            Some(event.clone())
+        );
+        assert_eq!(
+            store.get(1, blobstream_address, 199).unwrap(),
+            Some(event.clone())
+        );
+        assert_eq!(store.get(1, blobstream_address, 200).unwrap(), None);
+        assert_eq!(store.get(2, blobstream_address, 150).unwrap(), None);
+        assert_eq!(
+            store
+                .get_by_nonce(1, blobstream_address, U256::from(7u64))
+                .unwrap(),
+            Some(event)
+        );
+    }
+
+    #[test]
+    fn test_persistent_store_prune_keeps_first_commitment() {
+        let store = PersistentEventStore::open_in_memory().unwrap();
+        let blobstream_address = Address::repeat_byte(0x42);
+        for nonce in 1..=3u64 {
+            let event = SP1BlobstreamDataCommitmentStored {
+                proof_nonce: U256::from(nonce),
+                start_block: nonce * 100,
+                end_block: (nonce + 1) * 100,
+                data_commitment: B256::repeat_byte(nonce as u8),
+            };
+            store.insert(1, blobstream_address, &event).unwrap();
+        }
+
+        assert_eq!(store.prune_before(1, blobstream_address, 300).unwrap(), 1);
+        assert_eq!(store.count(1, blobstream_address).unwrap(), 2);
+        let start_blocks: Vec<u64> = store
+            .events(1, blobstream_address)
+            .unwrap()
+            .iter()
+            .map(|event| event.start_block)
+            .collect();
+        assert_eq!(start_blocks, vec![100, 300]);
+    }
+
+    #[test]
+    fn test_cached_events_lru_eviction() {
+        let event = |nonce: u64| SP1BlobstreamDataCommitmentStored {
+            proof_nonce: U256::from(nonce),
+            start_block: nonce * 100,
+            end_block: (nonce + 1) * 100,
+            data_commitment: B256::repeat_byte(nonce as u8),
+        };
+        let mut cached_events = CachedEvents::default();
+
+        assert_eq!(cached_events.insert(event(1), Some(2)), 0);
+        assert_eq!(cached_events.insert(event(2), Some(2)), 0);
+        // Touch the first event so that the second one is evicted
+        assert_eq!(cached_events.get(150), Some(event(1)));
+        assert_eq!(cached_events.insert(event(3), Some(2)), 1);
+
+        assert_eq!(cached_events.len(), 2);
+        assert_eq!(cached_events.get(150), Some(event(1)));
+        assert_eq!(cached_events.get(250), None);
+        assert_eq!(cached_events.get(350), Some(event(3)));
+    }
+}
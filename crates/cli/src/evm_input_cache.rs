@@ -0,0 +1,139 @@
+use alloy_primitives::{Address, ChainId};
+use anyhow::Context;
+use rusqlite::{params, Connection, OptionalExtension};
+use std::path::Path;
+use toolkit::chain::BlobstreamChainInput;
+use toolkit::{BlobstreamAttestation, BlobstreamInfo};
+
+/// Identifies the Steel preflight of a set of Blobstream attestations at a given Ethereum block.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EvmInputKey {
+    pub chain_id: ChainId,
+    pub execution_block: u64,
+    pub blobstream_address: Address,
+    /// Attested Celestia heights and their nonces, ex: `100:1,2048:7`.
+    pub attestations: String,
+    /// How the execution block is committed to, ex: `block` or `beacon`. Inputs built for
+    /// different commitments are not interchangeable.
+    pub commitment: String,
+}
+
+impl EvmInputKey {
+    pub fn new<'a>(
+        chain_id: ChainId,
+        execution_block: u64,
+        blobstream_address: Address,
+        blobstream_attestations: impl IntoIterator<Item = &'a BlobstreamAttestation>,
+        commitment: impl Into<String>,
+    ) -> Self {
+        let mut attestations: Vec<_> = blobstream_attestations
+            .into_iter()
+            .map(|attestation| (attestation.height, attestation.nonce))
+            .collect();
+        attestations.sort();
+        attestations.dedup();
+
+        Self {
+            chain_id,
+            execution_block,
+            blobstream_address,
+            attestations: attestations
+                .iter()
+                .map(|(height, nonce)| format!("{height}:{nonce}"))
+                .collect::<Vec<_>>()
+                .join(","),
+            commitment: commitment.into(),
+        }
+    }
+}
+
+/// On-disk store of the Steel inputs produced by the Blobstream preflight calls.
+///
+/// The state of a past Ethereum block never changes, so proving the same challenge again at
+/// the same execution block, ex: after a prover crash, can skip the preflight entirely.
+pub struct EvmInputStore {
+    conn: Connection,
+}
+
+impl EvmInputStore {
+    /// Opens (or creates) the input store at the specified path.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, anyhow::Error> {
+        let path = path.as_ref();
+        let conn = Connection::open(path)
+            .with_context(|| format!("failed to open EVM input store at {}", path.display()))?;
+        Self::init(conn)
+    }
+
+    /// Creates an input store that only lives in memory.
+    pub fn open_in_memory() -> Result<Self, anyhow::Error> {
+        Self::init(Connection::open_in_memory()?)
+    }
+
+    fn init(conn: Connection) -> Result<Self, anyhow::Error> {
+        conn.execute_batch(
+            "CREATE TABLE IF NOT EXISTS evm_inputs (
+                chain_id           INTEGER NOT NULL,
+                execution_block    INTEGER NOT NULL,
+                blobstream_address TEXT NOT NULL,
+                attestations       TEXT NOT NULL,
+                commitment         TEXT NOT NULL,
+                input              BLOB NOT NULL,
+                PRIMARY KEY (chain_id, execution_block, blobstream_address, attestations, commitment)
+            );",
+        )
+        .with_context(|| "failed to initialize EVM input store")?;
+
+        Ok(Self { conn })
+    }
+
+    /// Returns the preflight output stored for the key, if any.
+    pub fn get(
+        &self,
+        key: &EvmInputKey,
+    ) -> Result<Option<(BlobstreamChainInput, BlobstreamInfo)>, anyhow::Error> {
+        let input: Option<Vec<u8>> = self
+            .conn
+            .query_row(
+                "SELECT input FROM evm_inputs
+                 WHERE chain_id = ?1 AND execution_block = ?2 AND blobstream_address = ?3
+                   AND attestations = ?4 AND commitment = ?5",
+                params![
+                    key.chain_id,
+                    key.execution_block,
+                    key.blobstream_address.to_string(),
+                    key.attestations,
+                    key.commitment
+                ],
+                |row| row.get(0),
+            )
+            .optional()?;
+
+        input
+            .map(|input| bincode::deserialize(&input).context("invalid stored EVM input"))
+            .transpose()
+    }
+
+    /// Stores the preflight output for the key, replacing any previous one.
+    pub fn insert(
+        &self,
+        key: &EvmInputKey,
+        evm_input: &BlobstreamChainInput,
+        blobstream_info: &BlobstreamInfo,
+    ) -> Result<(), anyhow::Error> {
+        self.conn.execute(
+            "INSERT OR REPLACE INTO evm_inputs
+             (chain_id, execution_block, blobstream_address, attestations, commitment, input)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            params![
+                key.chain_id,
+                key.execution_block,
+                key.blobstream_address.to_string(),
+                key.attestations,
+                key.commitment,
+                bincode::serialize(&(evm_input, blobstream_info))?
+            ],
+        )?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,488 @@
+use crate::celestia_data_source::CelestiaDataSource;
+use crate::event_cache::BlobstreamEventCache;
+use crate::ICounter;
+use alloy_primitives::{Address, Bytes, FixedBytes, B256, U256};
+use anyhow::{anyhow, Context};
+use risc0_ethereum_contracts::alloy::consensus::Transaction as _;
+use risc0_ethereum_contracts::alloy::providers::Provider;
+use risc0_steel::alloy::sol_types::SolCall;
+use serde::Serialize;
+use std::fmt;
+use toolkit::constants::{NetworkConstants, NETWORKS};
+use toolkit::journal::{AggregatedJournalSummary, JournalSummary};
+use toolkit::{BatchMetadata, BlobstreamImpl};
+
+/// How a challenge at a given Celestia height would currently be handled by the guest.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HeightStatus {
+    /// The height is covered by a Blobstream commitment, blobs at this height can be challenged.
+    Covered,
+    /// The height is lower than the first Blobstream commitment, a challenge proves that the
+    /// height is too low.
+    BeforeFirstCommitment,
+    /// The height is higher than the Blobstream head. A challenge proves that the height is too
+    /// high, which is only correct if the height will never exist.
+    NotCoveredYet,
+}
+
+impl fmt::Display for HeightStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let description = match self {
+            HeightStatus::Covered => "covered, blobs at this height can be challenged",
+            HeightStatus::BeforeFirstCommitment => {
+                "before the first Blobstream commitment, challenges prove the height too low"
+            }
+            HeightStatus::NotCoveredYet => "not covered yet, challenges prove the height too high",
+        };
+        f.write_str(description)
+    }
+}
+
+/// The `DataCommitmentStored` event covering a Celestia height.
+#[derive(Debug, Clone, Serialize)]
+pub struct CommitmentSummary {
+    pub proof_nonce: U256,
+    pub start_block: u64,
+    pub end_block: u64,
+    pub data_commitment: B256,
+}
+
+/// State of a Blobstream deployment with respect to a Celestia height.
+#[derive(Debug, Clone, Serialize)]
+pub struct BlobstreamInspection {
+    pub celestia_height: u64,
+    pub blobstream_address: Address,
+    pub blobstream_impl: BlobstreamImpl,
+    /// Highest Celestia height covered by Blobstream. `None` if the contract does not expose it.
+    pub max_covered_height: Option<u64>,
+    pub first_commitment_start: u64,
+    pub status: HeightStatus,
+    /// The commitment covering the height, if any.
+    pub commitment: Option<CommitmentSummary>,
+}
+
+impl fmt::Display for BlobstreamInspection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Celestia height:      {}", self.celestia_height)?;
+        writeln!(
+            f,
+            "Blobstream:           {} ({:?})",
+            self.blobstream_address, self.blobstream_impl
+        )?;
+        match self.max_covered_height {
+            Some(max_covered_height) => writeln!(f, "Latest covered block: {max_covered_height}")?,
+            None => writeln!(f, "Latest covered block: unknown")?,
+        }
+        writeln!(f, "First covered block:  {}", self.first_commitment_start)?;
+        writeln!(f, "Status:               {}", self.status)?;
+        if let Some(commitment) = &self.commitment {
+            writeln!(f, "Commitment nonce:     {}", commitment.proof_nonce)?;
+            writeln!(
+                f,
+                "Commitment range:     [{}, {})",
+                commitment.start_block, commitment.end_block
+            )?;
+            writeln!(f, "Data commitment:      {}", commitment.data_commitment)?;
+        }
+        Ok(())
+    }
+}
+
+/// The networks of the constants registry, see `toolkit::constants::NETWORKS`.
+#[derive(Debug, Clone, Serialize)]
+#[serde(transparent)]
+pub struct KnownNetworks(pub Vec<NetworkConstants>);
+
+impl KnownNetworks {
+    pub fn builtin() -> Self {
+        Self(NETWORKS.to_vec())
+    }
+}
+
+impl fmt::Display for KnownNetworks {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for network in &self.0 {
+            writeln!(
+                f,
+                "Chain {} / {}",
+                network.chain_id, network.celestia_chain_id
+            )?;
+            writeln!(f, "  Blobstream:         {}", network.blobstream_address)?;
+            match &network.first_commitment {
+                Some(first_commitment) => writeln!(
+                    f,
+                    "  First commitment:   [{}, {}) {}",
+                    first_commitment.start_block,
+                    first_commitment.end_block,
+                    first_commitment.data_commitment
+                )?,
+                None => writeln!(f, "  First commitment:   unknown")?,
+            }
+            match &network.default_namespace {
+                Some(namespace) => writeln!(
+                    f,
+                    "  Default namespace:  {}",
+                    alloy_primitives::hex::encode(namespace)
+                )?,
+                None => writeln!(f, "  Default namespace:  none")?,
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Finds the Blobstream commitment covering the Celestia height and tells whether blobs at this
+/// height can currently be challenged.
+pub async fn inspect_blobstream(
+    blobstream_event_cache: &BlobstreamEventCache,
+    celestia_height: u64,
+) -> Result<BlobstreamInspection, anyhow::Error> {
+    let backend = blobstream_event_cache.backend().await?;
+    let first_commitment = blobstream_event_cache
+        .first_data_commitment_stored_event()
+        .await?;
+
+    let max_covered_height = match backend.implementation() {
+        BlobstreamImpl::DaOracle => None,
+        _ => Some(backend.max_covered_height().await?),
+    };
+
+    let status = if celestia_height < first_commitment.start_block {
+        HeightStatus::BeforeFirstCommitment
+    } else if max_covered_height
+        .is_some_and(|max_covered_height| celestia_height > max_covered_height)
+    {
+        HeightStatus::NotCoveredYet
+    } else {
+        HeightStatus::Covered
+    };
+
+    let commitment = match status {
+        HeightStatus::Covered => {
+            let event = blobstream_event_cache.get(celestia_height).await?;
+            Some(CommitmentSummary {
+                proof_nonce: event.proof_nonce,
+                start_block: event.start_block,
+                end_block: event.end_block,
+                data_commitment: event.data_commitment,
+            })
+        }
+        _ => None,
+    };
+
+    Ok(BlobstreamInspection {
+        celestia_height,
+        blobstream_address: backend.address(),
+        blobstream_impl: backend.implementation(),
+        max_covered_height,
+        first_commitment_start: first_commitment.start_block,
+        status,
+        commitment,
+    })
+}
+
+/// Lag of a Blobstream deployment behind the Celestia chain head.
+#[derive(Debug, Clone, Serialize)]
+pub struct SyncStatus {
+    pub blobstream_address: Address,
+    pub blobstream_impl: BlobstreamImpl,
+    pub celestia_head: u64,
+    pub max_covered_height: u64,
+    pub lag_blocks: u64,
+    /// Time between the latest covered Celestia block and the chain head.
+    pub lag_secs: Option<u64>,
+    /// Average Celestia block time over the lag, used for the estimates below.
+    pub avg_block_time_secs: Option<f64>,
+    /// Celestia height whose coverage is estimated, if requested.
+    pub target_height: Option<u64>,
+    /// Estimated time before the target height is covered by Blobstream. Zero if it is already
+    /// covered. Blobstream commits ranges of blocks, so the actual wait can be longer.
+    pub target_eta_secs: Option<u64>,
+}
+
+impl fmt::Display for SyncStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "Blobstream:           {} ({:?})",
+            self.blobstream_address, self.blobstream_impl
+        )?;
+        writeln!(f, "Celestia head:        {}", self.celestia_head)?;
+        writeln!(f, "Latest covered block: {}", self.max_covered_height)?;
+        match self.lag_secs {
+            Some(lag_secs) => writeln!(
+                f,
+                "Lag:                  {} blocks (~{})",
+                self.lag_blocks,
+                format_secs(lag_secs)
+            )?,
+            None => writeln!(f, "Lag:                  {} blocks", self.lag_blocks)?,
+        }
+        if let Some(target_height) = self.target_height {
+            match self.target_eta_secs {
+                Some(0) => writeln!(f, "Block {target_height}:        covered")?,
+                Some(eta_secs) => writeln!(
+                    f,
+                    "Block {target_height}:        covered in ~{}",
+                    format_secs(eta_secs)
+                )?,
+                None => writeln!(f, "Block {target_height}:        not covered yet")?,
+            }
+        }
+        Ok(())
+    }
+}
+
+fn format_secs(secs: u64) -> String {
+    match secs {
+        0..60 => format!("{secs} s"),
+        60..3600 => format!("{} min", secs / 60),
+        _ => format!("{}h{:02}", secs / 3600, (secs % 3600) / 60),
+    }
+}
+
+/// Compares the Celestia chain head with the latest height covered by Blobstream. When
+/// `target_height` is set, also estimates when blobs at this height become challengeable.
+pub async fn blobstream_sync_status(
+    celestia_client: &impl CelestiaDataSource,
+    blobstream_event_cache: &BlobstreamEventCache,
+    target_height: Option<u64>,
+) -> Result<SyncStatus, anyhow::Error> {
+    let backend = blobstream_event_cache.backend().await?;
+    if backend.implementation() == BlobstreamImpl::DaOracle {
+        return Err(anyhow::anyhow!(
+            "IDAOracle contracts do not expose their latest height, cannot compute the lag"
+        ));
+    }
+
+    let (celestia_head, max_covered_height) =
+        tokio::try_join!(celestia_client.head(), backend.max_covered_height())?;
+    let celestia_head_height = celestia_head.height().value();
+    let lag_blocks = celestia_head_height.saturating_sub(max_covered_height);
+
+    let lag_secs = if lag_blocks > 0 {
+        let covered_header = celestia_client.header(max_covered_height).await?;
+        celestia_head
+            .time()
+            .duration_since(covered_header.time())
+            .ok()
+            .map(|lag| lag.as_secs())
+    } else {
+        Some(0)
+    };
+    let avg_block_time_secs = lag_secs
+        .filter(|_| lag_blocks > 0)
+        .map(|lag_secs| lag_secs as f64 / lag_blocks as f64);
+
+    let target_eta_secs = target_height.and_then(|target_height| {
+        if target_height <= max_covered_height {
+            return Some(0);
+        }
+        // Blocks above the head are not produced yet, they add to the wait.
+        let missing_blocks = target_height - max_covered_height;
+        avg_block_time_secs.map(|block_time| (missing_blocks as f64 * block_time).ceil() as u64)
+    });
+
+    Ok(SyncStatus {
+        blobstream_address: backend.address(),
+        blobstream_impl: backend.implementation(),
+        celestia_head: celestia_head_height,
+        max_covered_height,
+        lag_blocks,
+        lag_secs,
+        avg_block_time_secs,
+        target_height,
+        target_eta_secs,
+    })
+}
+
+/// Journal of a proof submitted to the verifier contract, see `ICounter`.
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "function", content = "journal", rename_all = "camelCase")]
+pub enum SubmittedJournal {
+    Increment(JournalSummary),
+    IncrementAggregated(AggregatedJournalSummary),
+}
+
+/// Journal and seal of a proof submitted to the verifier contract.
+#[derive(Debug, Clone, Serialize)]
+pub struct SubmittedProof {
+    #[serde(flatten)]
+    pub journal: SubmittedJournal,
+    /// Selector of the RISC Zero verifier the seal is for, its first 4 bytes.
+    pub seal_selector: Option<FixedBytes<4>>,
+    pub seal: Bytes,
+}
+
+impl fmt::Display for SubmittedProof {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let selector = self
+            .seal_selector
+            .map_or("none".to_string(), |selector| selector.to_string());
+        match &self.journal {
+            SubmittedJournal::Increment(journal) => {
+                writeln!(f, "Function:             increment")?;
+                writeln!(f, "Journal version:      {}", journal.version)?;
+                writeln!(f, "Outcome:              {:?}", journal.outcome)?;
+                writeln!(f, "Challenge:            {:?}", journal.challenge)?;
+                writeln!(f, "Index blob:           {}", journal.index_blob)?;
+                writeln!(f, "Challenged blob:      {}", journal.challenged_blob)?;
+                match journal.fraud_type {
+                    Some(fraud_type) => writeln!(f, "Fraud:                {fraud_type:?}")?,
+                    None => writeln!(f, "Fraud:                none")?,
+                }
+                for (blob, fraud_type) in journal
+                    .extra_challenged_blobs
+                    .iter()
+                    .zip(&journal.extra_fraud_types)
+                {
+                    writeln!(f, "Extra challenged blob: {blob} ({fraud_type:?})")?;
+                }
+                write_common_fields(
+                    f,
+                    journal.blobstream_address,
+                    journal.blobstream_impl,
+                    &journal.celestia_chain_id,
+                    journal.commitment_id,
+                    journal.commitment_digest,
+                    journal.index_hash,
+                )?;
+                write_batch_metadata(f, journal.batch_metadata)?;
+            }
+            SubmittedJournal::IncrementAggregated(journal) => {
+                writeln!(f, "Function:             incrementAggregated")?;
+                writeln!(f, "Journal version:      {}", journal.version)?;
+                writeln!(f, "Challenge image ID:   {}", journal.challenge_image_id)?;
+                writeln!(f, "Index blob:           {}", journal.index_blob)?;
+                for ((blob, fraud_type), challenge) in journal
+                    .challenged_blobs
+                    .iter()
+                    .zip(&journal.fraud_types)
+                    .zip(&journal.challenges)
+                {
+                    writeln!(
+                        f,
+                        "Challenged blob:      {blob} ({fraud_type:?}, {challenge:?})"
+                    )?;
+                }
+                write_common_fields(
+                    f,
+                    journal.blobstream_address,
+                    journal.blobstream_impl,
+                    &journal.celestia_chain_id,
+                    journal.commitment_id,
+                    journal.commitment_digest,
+                    journal.index_hash,
+                )?;
+                write_batch_metadata(f, journal.batch_metadata)?;
+            }
+        }
+        writeln!(
+            f,
+            "Seal:                 {} bytes, selector {selector}",
+            self.seal.len()
+        )
+    }
+}
+
+fn write_common_fields(
+    f: &mut fmt::Formatter<'_>,
+    blobstream_address: Address,
+    blobstream_impl: BlobstreamImpl,
+    celestia_chain_id: &str,
+    commitment_id: U256,
+    commitment_digest: B256,
+    index_hash: Option<B256>,
+) -> fmt::Result {
+    writeln!(
+        f,
+        "Blobstream:           {blobstream_address} ({blobstream_impl:?})"
+    )?;
+    writeln!(f, "Celestia chain:       {celestia_chain_id}")?;
+    writeln!(
+        f,
+        "Steel commitment:     {commitment_id} / {commitment_digest}"
+    )?;
+    match index_hash {
+        Some(index_hash) => writeln!(f, "Index hash:           {index_hash}"),
+        None => writeln!(f, "Index hash:           not read"),
+    }
+}
+
+fn write_batch_metadata(
+    f: &mut fmt::Formatter<'_>,
+    batch_metadata: Option<BatchMetadata>,
+) -> fmt::Result {
+    match batch_metadata {
+        Some(metadata) => writeln!(
+            f,
+            "Batch:                {} of rollup {} (timestamp {})",
+            metadata.batch_number, metadata.rollup_id, metadata.timestamp
+        ),
+        None => writeln!(f, "Batch:                none"),
+    }
+}
+
+/// Decodes the journal and seal of calldata to `increment()` or `incrementAggregated()` of the
+/// verifier contract.
+pub fn decode_submitted_proof(calldata: &[u8]) -> Result<SubmittedProof, anyhow::Error> {
+    let selector = calldata
+        .get(..4)
+        .ok_or_else(|| anyhow!("calldata is shorter than a function selector"))?;
+    let (journal, seal) = match selector {
+        selector if selector == ICounter::incrementCall::SELECTOR => {
+            let call = ICounter::incrementCall::abi_decode(calldata, true)?;
+            let journal = JournalSummary::decode(&call.journalData).context("invalid journal")?;
+            (SubmittedJournal::Increment(journal), call.seal)
+        }
+        selector if selector == ICounter::incrementAggregatedCall::SELECTOR => {
+            let call = ICounter::incrementAggregatedCall::abi_decode(calldata, true)?;
+            let journal = AggregatedJournalSummary::decode(&call.journalData)
+                .context("invalid aggregated journal")?;
+            (SubmittedJournal::IncrementAggregated(journal), call.seal)
+        }
+        selector => {
+            return Err(anyhow!(
+                "calldata calls {}, not increment() or incrementAggregated()",
+                Bytes::copy_from_slice(selector)
+            ))
+        }
+    };
+
+    Ok(SubmittedProof {
+        journal,
+        seal_selector: seal
+            .get(..4)
+            .map(|selector| FixedBytes::from_slice(selector)),
+        seal,
+    })
+}
+
+/// Fetches a transaction submitting a proof to the verifier contract and decodes its journal
+/// and seal, see `decode_submitted_proof()`. Only direct calls to the verifier contract are
+/// decoded, not calls through another contract.
+pub async fn fetch_submitted_proof(
+    provider: &impl Provider,
+    tx_hash: B256,
+) -> Result<SubmittedProof, anyhow::Error> {
+    let transaction = provider
+        .get_transaction_by_hash(tx_hash)
+        .await?
+        .ok_or_else(|| anyhow!("transaction {tx_hash} not found"))?;
+
+    decode_submitted_proof(transaction.input())
+        .with_context(|| format!("failed to decode transaction {tx_hash}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decode_rejects_other_functions() {
+        let calldata = ICounter::getCall {}.abi_encode();
+        assert!(decode_submitted_proof(&calldata).is_err());
+        assert!(decode_submitted_proof(&[0x12]).is_err());
+    }
+}
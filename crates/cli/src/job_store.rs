@@ -0,0 +1,649 @@
+use alloy_primitives::{Address, ChainId, B256};
+use anyhow::{anyhow, ensure, Context};
+use risc0_zkvm::Receipt;
+use rusqlite::{params, Connection, OptionalExtension, Row};
+use std::path::Path;
+use std::str::FromStr;
+use toolkit::{DaChallenge, SpanSequence};
+
+/// Identifier of a challenge job in the job store.
+pub type JobId = i64;
+
+/// Lifecycle of a DA challenge job.
+///
+/// A job moves from `Pending` to `Proving` to `Proven` to `Submitted`. Any step can move the
+/// job to `Failed`, in which case it is picked up again on the next run, from its stored proof if
+/// any. Attempts are counted across runs: a job that used up the attempts of a phase is only
+/// retried once they are reset with `JobStore::reset_attempts()`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum JobStatus {
+    /// The job was recorded but no work was started yet.
+    Pending,
+    /// The guest program is being executed / proven.
+    Proving,
+    /// A proof was generated but not submitted on-chain yet.
+    Proven,
+    /// The proof was submitted on-chain and the transaction was confirmed.
+    Submitted,
+    /// The last attempt failed, see `ChallengeJob::error`.
+    Failed,
+}
+
+impl JobStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            JobStatus::Pending => "pending",
+            JobStatus::Proving => "proving",
+            JobStatus::Proven => "proven",
+            JobStatus::Submitted => "submitted",
+            JobStatus::Failed => "failed",
+        }
+    }
+}
+
+impl FromStr for JobStatus {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "pending" => Ok(JobStatus::Pending),
+            "proving" => Ok(JobStatus::Proving),
+            "proven" => Ok(JobStatus::Proven),
+            "submitted" => Ok(JobStatus::Submitted),
+            "failed" => Ok(JobStatus::Failed),
+            _ => Err(anyhow!("unknown job status: {s}")),
+        }
+    }
+}
+
+/// Phase of a challenge job. Attempts are budgeted per phase, so failed submissions do not eat
+/// into the attempts left to prove the challenge, and the other way round.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum JobPhase {
+    Proving,
+    Submission,
+}
+
+impl JobPhase {
+    fn attempts_column(&self) -> &'static str {
+        match self {
+            JobPhase::Proving => "proving_attempts",
+            JobPhase::Submission => "submission_attempts",
+        }
+    }
+}
+
+/// Identifies a challenge job: the challenge and the deployment its proof is submitted to. The
+/// same blobs challenged on two deployments, or for their availability and for their data, are
+/// distinct jobs, since their proofs are not interchangeable.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct JobKey {
+    pub chain_id: ChainId,
+    pub blobstream_address: Address,
+    pub verifier_address: Address,
+    pub index_blob: SpanSequence,
+    pub challenge: DaChallenge,
+}
+
+impl JobKey {
+    pub fn challenged_blob(&self) -> SpanSequence {
+        self.challenge.challenged_blob(self.index_blob)
+    }
+}
+
+/// A DA challenge, as recorded in the job store.
+#[derive(Debug)]
+pub struct ChallengeJob {
+    pub id: JobId,
+    pub key: JobKey,
+    pub status: JobStatus,
+    pub receipt: Option<Receipt>,
+    pub seal: Option<Vec<u8>>,
+    /// Hash of the last transaction that submitted the proof. It is only confirmed once the job
+    /// is `Submitted`.
+    pub tx_hash: Option<B256>,
+    pub error: Option<String>,
+    /// Number of proving attempts made for this job so far.
+    pub proving_attempts: u32,
+    /// Number of submission attempts made for this job so far.
+    pub submission_attempts: u32,
+}
+
+impl ChallengeJob {
+    /// Returns the stored proof of the job, if one was generated.
+    pub fn proof(&self) -> Option<(Receipt, Vec<u8>)> {
+        match (&self.receipt, &self.seal) {
+            (Some(receipt), Some(seal)) => Some((receipt.clone(), seal.clone())),
+            _ => None,
+        }
+    }
+
+    /// Returns the number of attempts made for the phase so far.
+    pub fn attempts(&self, phase: JobPhase) -> u32 {
+        match phase {
+            JobPhase::Proving => self.proving_attempts,
+            JobPhase::Submission => self.submission_attempts,
+        }
+    }
+}
+
+/// Version of the job store schema, recorded in the `user_version` of the database. Stores
+/// created before the schema was versioned are at version 0, see `JobStore::migrate()`.
+const SCHEMA_VERSION: u32 = 1;
+
+const JOB_COLUMNS: &str = "id, chain_id, blobstream_address, verifier_address, index_blob, \
+                           challenged_blob, challenge_type, status, receipt, seal, tx_hash, error, \
+                           proving_attempts, submission_attempts";
+
+/// SQLite-backed record of pending, in-flight and completed DA challenges.
+///
+/// Proofs are stored as soon as they are generated, so a challenger that crashes between
+/// proving and submission can resume without generating the proof again.
+pub struct JobStore {
+    conn: Connection,
+}
+
+impl JobStore {
+    /// Opens (or creates) the job store at the specified path.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, anyhow::Error> {
+        let path = path.as_ref();
+        let conn = Connection::open(path)
+            .with_context(|| format!("failed to open job store at {}", path.display()))?;
+        Self::init(conn)
+    }
+
+    /// Creates a job store that only lives in memory.
+    pub fn open_in_memory() -> Result<Self, anyhow::Error> {
+        Self::init(Connection::open_in_memory()?)
+    }
+
+    fn init(mut conn: Connection) -> Result<Self, anyhow::Error> {
+        let version: u32 = conn.query_row("PRAGMA user_version", [], |row| row.get(0))?;
+        ensure!(
+            version <= SCHEMA_VERSION,
+            "the job store has schema version {version}, only versions up to {SCHEMA_VERSION} \
+             are supported"
+        );
+
+        let transaction = conn.transaction()?;
+        transaction
+            .execute_batch(
+                "CREATE TABLE IF NOT EXISTS da_challenge_jobs (
+                    id                 INTEGER PRIMARY KEY AUTOINCREMENT,
+                    chain_id           INTEGER NOT NULL,
+                    blobstream_address TEXT NOT NULL,
+                    verifier_address   TEXT NOT NULL,
+                    index_blob         TEXT NOT NULL,
+                    challenged_blob    TEXT NOT NULL,
+                    challenge_type     INTEGER NOT NULL,
+                    status             TEXT NOT NULL,
+                    receipt            BLOB,
+                    seal               BLOB,
+                    tx_hash            TEXT,
+                    error              TEXT,
+                    proving_attempts   INTEGER NOT NULL DEFAULT 0,
+                    submission_attempts INTEGER NOT NULL DEFAULT 0,
+                    created_at         TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
+                    updated_at         TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
+                    UNIQUE (chain_id, blobstream_address, verifier_address, index_blob,
+                            challenged_blob, challenge_type)
+                );",
+            )
+            .with_context(|| "failed to initialize job store")?;
+        Self::migrate(&transaction, version)
+            .with_context(|| format!("failed to migrate job store from version {version}"))?;
+        transaction.pragma_update(None, "user_version", SCHEMA_VERSION)?;
+        transaction.commit()?;
+
+        Ok(Self { conn })
+    }
+
+    /// Upgrades the schema of the job store from `version` to `SCHEMA_VERSION`.
+    fn migrate(conn: &Connection, version: u32) -> Result<(), anyhow::Error> {
+        // Unversioned stores may still count the attempts of both phases together
+        if version < 1 {
+            let has_phase_attempts: bool = conn.query_row(
+                "SELECT COUNT(*) > 0 FROM pragma_table_info('da_challenge_jobs')
+                 WHERE name = 'proving_attempts'",
+                [],
+                |row| row.get(0),
+            )?;
+            if !has_phase_attempts {
+                conn.execute_batch(
+                    "ALTER TABLE da_challenge_jobs
+                     ADD COLUMN proving_attempts INTEGER NOT NULL DEFAULT 0;
+                     ALTER TABLE da_challenge_jobs
+                     ADD COLUMN submission_attempts INTEGER NOT NULL DEFAULT 0;
+                     ALTER TABLE da_challenge_jobs DROP COLUMN attempts;",
+                )?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Returns the job for the specified challenge and deployment, creating it if it does not
+    /// exist yet.
+    pub fn find_or_create(&self, key: &JobKey) -> Result<ChallengeJob, anyhow::Error> {
+        let (blobstream_address, verifier_address) = (
+            key.blobstream_address.to_string(),
+            key.verifier_address.to_string(),
+        );
+        let (index_blob, challenged_blob) = (
+            key.index_blob.to_string(),
+            key.challenged_blob().to_string(),
+        );
+        self.conn.execute(
+            "INSERT OR IGNORE INTO da_challenge_jobs
+             (chain_id, blobstream_address, verifier_address, index_blob, challenged_blob,
+              challenge_type, status)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            params![
+                key.chain_id,
+                blobstream_address,
+                verifier_address,
+                index_blob,
+                challenged_blob,
+                key.challenge.as_u8(),
+                JobStatus::Pending.as_str()
+            ],
+        )?;
+
+        self.conn
+            .query_row(
+                &format!(
+                    "SELECT {JOB_COLUMNS} FROM da_challenge_jobs
+                     WHERE chain_id = ?1 AND blobstream_address = ?2 AND verifier_address = ?3
+                       AND index_blob = ?4 AND challenged_blob = ?5 AND challenge_type = ?6"
+                ),
+                params![
+                    key.chain_id,
+                    blobstream_address,
+                    verifier_address,
+                    index_blob,
+                    challenged_blob,
+                    key.challenge.as_u8()
+                ],
+                JobRow::from_row,
+            )?
+            .try_into()
+    }
+
+    /// Returns the job with the specified ID, if it exists.
+    pub fn get(&self, id: JobId) -> Result<Option<ChallengeJob>, anyhow::Error> {
+        self.conn
+            .query_row(
+                &format!("SELECT {JOB_COLUMNS} FROM da_challenge_jobs WHERE id = ?1"),
+                params![id],
+                JobRow::from_row,
+            )
+            .optional()?
+            .map(ChallengeJob::try_from)
+            .transpose()
+    }
+
+    /// Returns all the jobs that were not submitted yet, oldest first.
+    pub fn unfinished(&self) -> Result<Vec<ChallengeJob>, anyhow::Error> {
+        let mut statement = self.conn.prepare(&format!(
+            "SELECT {JOB_COLUMNS} FROM da_challenge_jobs WHERE status != ?1 ORDER BY id"
+        ))?;
+
+        let rows = statement
+            .query_map(params![JobStatus::Submitted.as_str()], JobRow::from_row)?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        rows.into_iter().map(ChallengeJob::try_from).collect()
+    }
+
+    /// Marks the job as being proven.
+    pub fn mark_proving(&self, id: JobId) -> Result<(), anyhow::Error> {
+        self.update_status(id, JobStatus::Proving)
+    }
+
+    /// Records a new attempt of the phase for the job and returns the number of attempts of the
+    /// phase.
+    pub fn record_attempt(&self, id: JobId, phase: JobPhase) -> Result<u32, anyhow::Error> {
+        let column = phase.attempts_column();
+        let attempts = self.conn.query_row(
+            &format!(
+                "UPDATE da_challenge_jobs
+                 SET {column} = {column} + 1, updated_at = CURRENT_TIMESTAMP
+                 WHERE id = ?1
+                 RETURNING {column}"
+            ),
+            params![id],
+            |row| row.get(0),
+        )?;
+
+        Ok(attempts)
+    }
+
+    /// Resets the attempts of both phases of the job, so that a job that used them up is retried.
+    pub fn reset_attempts(&self, id: JobId) -> Result<(), anyhow::Error> {
+        self.conn.execute(
+            "UPDATE da_challenge_jobs
+             SET proving_attempts = 0, submission_attempts = 0, updated_at = CURRENT_TIMESTAMP
+             WHERE id = ?1",
+            params![id],
+        )?;
+
+        Ok(())
+    }
+
+    /// Stores the proof generated for the job.
+    pub fn record_proof(
+        &self,
+        id: JobId,
+        receipt: &Receipt,
+        seal: &[u8],
+    ) -> Result<(), anyhow::Error> {
+        let serialized_receipt =
+            bincode::serialize(receipt).with_context(|| "failed to serialize receipt")?;
+
+        self.conn.execute(
+            "UPDATE da_challenge_jobs
+             SET status = ?2, receipt = ?3, seal = ?4, error = NULL, updated_at = CURRENT_TIMESTAMP
+             WHERE id = ?1",
+            params![id, JobStatus::Proven.as_str(), serialized_receipt, seal],
+        )?;
+
+        Ok(())
+    }
+
+    /// Records the hash of a transaction submitting the proof of the job, before its confirmation.
+    /// A later attempt checks whether it landed instead of sending the proof again.
+    pub fn record_sent_transaction(&self, id: JobId, tx_hash: B256) -> Result<(), anyhow::Error> {
+        self.conn.execute(
+            "UPDATE da_challenge_jobs SET tx_hash = ?2, updated_at = CURRENT_TIMESTAMP WHERE id = ?1",
+            params![id, tx_hash.to_string()],
+        )?;
+
+        Ok(())
+    }
+
+    /// Forgets the transaction sent for the job, ex: because it was dropped or reverted.
+    pub fn clear_sent_transaction(&self, id: JobId) -> Result<(), anyhow::Error> {
+        self.conn.execute(
+            "UPDATE da_challenge_jobs
+             SET tx_hash = NULL, updated_at = CURRENT_TIMESTAMP
+             WHERE id = ?1",
+            params![id],
+        )?;
+
+        Ok(())
+    }
+
+    /// Records the hash of the confirmed transaction that submitted the proof of the job.
+    pub fn record_submission(&self, id: JobId, tx_hash: B256) -> Result<(), anyhow::Error> {
+        self.conn.execute(
+            "UPDATE da_challenge_jobs
+             SET status = ?2, tx_hash = ?3, error = NULL, updated_at = CURRENT_TIMESTAMP
+             WHERE id = ?1",
+            params![id, JobStatus::Submitted.as_str(), tx_hash.to_string()],
+        )?;
+
+        Ok(())
+    }
+
+    /// Marks the job as failed. The proof, if any, is kept so it can be submitted again.
+    pub fn record_failure(&self, id: JobId, error: &str) -> Result<(), anyhow::Error> {
+        self.conn.execute(
+            "UPDATE da_challenge_jobs
+             SET status = ?2, error = ?3, updated_at = CURRENT_TIMESTAMP
+             WHERE id = ?1",
+            params![id, JobStatus::Failed.as_str(), error],
+        )?;
+
+        Ok(())
+    }
+
+    fn update_status(&self, id: JobId, status: JobStatus) -> Result<(), anyhow::Error> {
+        self.conn.execute(
+            "UPDATE da_challenge_jobs SET status = ?2, updated_at = CURRENT_TIMESTAMP WHERE id = ?1",
+            params![id, status.as_str()],
+        )?;
+
+        Ok(())
+    }
+}
+
+/// Raw row of the `da_challenge_jobs` table, before parsing our own types.
+struct JobRow {
+    id: JobId,
+    chain_id: ChainId,
+    blobstream_address: String,
+    verifier_address: String,
+    index_blob: String,
+    challenged_blob: String,
+    challenge_type: u8,
+    status: String,
+    receipt: Option<Vec<u8>>,
+    seal: Option<Vec<u8>>,
+    tx_hash: Option<String>,
+    error: Option<String>,
+    proving_attempts: u32,
+    submission_attempts: u32,
+}
+
+impl JobRow {
+    fn from_row(row: &Row) -> rusqlite::Result<Self> {
+        Ok(Self {
+            id: row.get(0)?,
+            chain_id: row.get(1)?,
+            blobstream_address: row.get(2)?,
+            verifier_address: row.get(3)?,
+            index_blob: row.get(4)?,
+            challenged_blob: row.get(5)?,
+            challenge_type: row.get(6)?,
+            status: row.get(7)?,
+            receipt: row.get(8)?,
+            seal: row.get(9)?,
+            tx_hash: row.get(10)?,
+            error: row.get(11)?,
+            proving_attempts: row.get(12)?,
+            submission_attempts: row.get(13)?,
+        })
+    }
+}
+
+impl TryFrom<JobRow> for ChallengeJob {
+    type Error = anyhow::Error;
+
+    fn try_from(row: JobRow) -> Result<Self, Self::Error> {
+        let challenged_blob =
+            SpanSequence::from_str(&row.challenged_blob).map_err(|e| anyhow!(e))?;
+        Ok(ChallengeJob {
+            id: row.id,
+            key: JobKey {
+                chain_id: row.chain_id,
+                blobstream_address: Address::from_str(&row.blobstream_address)?,
+                verifier_address: Address::from_str(&row.verifier_address)?,
+                index_blob: SpanSequence::from_str(&row.index_blob).map_err(|e| anyhow!(e))?,
+                challenge: DaChallenge::from_u8(row.challenge_type, challenged_blob)
+                    .ok_or_else(|| anyhow!("unknown challenge type {}", row.challenge_type))?,
+            },
+            status: JobStatus::from_str(&row.status)?,
+            receipt: row
+                .receipt
+                .map(|bytes| bincode::deserialize(&bytes))
+                .transpose()
+                .with_context(|| "failed to deserialize stored receipt")?,
+            seal: row.seal,
+            tx_hash: row.tx_hash.map(|hash| B256::from_str(&hash)).transpose()?,
+            error: row.error,
+            proving_attempts: row.proving_attempts,
+            submission_attempts: row.submission_attempts,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const INDEX_BLOB: SpanSequence = SpanSequence {
+        height: 10,
+        start: 4,
+        size: 2,
+    };
+    const CHALLENGED_BLOB: SpanSequence = SpanSequence {
+        height: 11,
+        start: 0,
+        size: 8,
+    };
+    const JOB_KEY: JobKey = JobKey {
+        chain_id: 11155111,
+        blobstream_address: Address::repeat_byte(1),
+        verifier_address: Address::repeat_byte(2),
+        index_blob: INDEX_BLOB,
+        challenge: DaChallenge::BlobInIndexIsUnavailable(CHALLENGED_BLOB),
+    };
+
+    #[test]
+    fn test_find_or_create_is_idempotent() {
+        let store = JobStore::open_in_memory().unwrap();
+
+        let job = store.find_or_create(&JOB_KEY).unwrap();
+        assert_eq!(job.status, JobStatus::Pending);
+        assert_eq!(job.key, JOB_KEY);
+        assert_eq!(job.key.challenged_blob(), CHALLENGED_BLOB);
+
+        let same_job = store.find_or_create(&JOB_KEY).unwrap();
+        assert_eq!(same_job.id, job.id);
+    }
+
+    #[test]
+    fn test_jobs_are_distinct_per_deployment_and_challenge_kind() {
+        let store = JobStore::open_in_memory().unwrap();
+        let job = store.find_or_create(&JOB_KEY).unwrap();
+
+        let other_keys = [
+            JobKey {
+                chain_id: 1,
+                ..JOB_KEY
+            },
+            JobKey {
+                blobstream_address: Address::repeat_byte(3),
+                ..JOB_KEY
+            },
+            JobKey {
+                verifier_address: Address::repeat_byte(3),
+                ..JOB_KEY
+            },
+            JobKey {
+                challenge: DaChallenge::BlobDataMismatch(CHALLENGED_BLOB),
+                ..JOB_KEY
+            },
+        ];
+        for other_key in other_keys {
+            let other_job = store.find_or_create(&other_key).unwrap();
+            assert_ne!(other_job.id, job.id);
+            assert_eq!(other_job.key, other_key);
+        }
+    }
+
+    #[test]
+    fn test_job_lifecycle() {
+        let store = JobStore::open_in_memory().unwrap();
+        let job = store.find_or_create(&JOB_KEY).unwrap();
+
+        store.mark_proving(job.id).unwrap();
+        assert_eq!(
+            store.get(job.id).unwrap().unwrap().status,
+            JobStatus::Proving
+        );
+
+        store.record_failure(job.id, "prover crashed").unwrap();
+        let failed_job = store.get(job.id).unwrap().unwrap();
+        assert_eq!(failed_job.status, JobStatus::Failed);
+        assert_eq!(failed_job.error.as_deref(), Some("prover crashed"));
+        assert_eq!(store.record_attempt(job.id, JobPhase::Proving).unwrap(), 1);
+        assert_eq!(store.record_attempt(job.id, JobPhase::Proving).unwrap(), 2);
+        assert_eq!(store.unfinished().unwrap().len(), 1);
+
+        // Submissions have their own budget of attempts
+        assert_eq!(
+            store.record_attempt(job.id, JobPhase::Submission).unwrap(),
+            1
+        );
+        let failed_job = store.get(job.id).unwrap().unwrap();
+        assert_eq!(failed_job.attempts(JobPhase::Proving), 2);
+        assert_eq!(failed_job.attempts(JobPhase::Submission), 1);
+
+        // Attempts persist until explicitly reset
+        let reopened_job = store.find_or_create(&JOB_KEY).unwrap();
+        assert_eq!(reopened_job.attempts(JobPhase::Proving), 2);
+        store.reset_attempts(job.id).unwrap();
+        let reset_job = store.get(job.id).unwrap().unwrap();
+        assert_eq!(reset_job.attempts(JobPhase::Proving), 0);
+        assert_eq!(reset_job.attempts(JobPhase::Submission), 0);
+        assert_eq!(reset_job.status, JobStatus::Failed);
+
+        // A sent transaction is remembered until confirmed, but does not finish the job
+        let dropped_tx_hash = B256::repeat_byte(0xCD);
+        store
+            .record_sent_transaction(job.id, dropped_tx_hash)
+            .unwrap();
+        let sent_job = store.get(job.id).unwrap().unwrap();
+        assert_eq!(sent_job.tx_hash, Some(dropped_tx_hash));
+        assert_eq!(sent_job.status, JobStatus::Failed);
+        store.clear_sent_transaction(job.id).unwrap();
+        assert!(store.get(job.id).unwrap().unwrap().tx_hash.is_none());
+
+        let tx_hash = B256::repeat_byte(0xAB);
+        store.record_submission(job.id, tx_hash).unwrap();
+        let submitted_job = store.get(job.id).unwrap().unwrap();
+        assert_eq!(submitted_job.status, JobStatus::Submitted);
+        assert_eq!(submitted_job.tx_hash, Some(tx_hash));
+        assert!(submitted_job.error.is_none());
+        assert!(store.unfinished().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_migrate_unversioned_store() {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(
+            "CREATE TABLE da_challenge_jobs (
+                id                 INTEGER PRIMARY KEY AUTOINCREMENT,
+                chain_id           INTEGER NOT NULL,
+                blobstream_address TEXT NOT NULL,
+                verifier_address   TEXT NOT NULL,
+                index_blob         TEXT NOT NULL,
+                challenged_blob    TEXT NOT NULL,
+                challenge_type     INTEGER NOT NULL,
+                status             TEXT NOT NULL,
+                receipt            BLOB,
+                seal               BLOB,
+                tx_hash            TEXT,
+                error              TEXT,
+                attempts           INTEGER NOT NULL DEFAULT 0,
+                created_at         TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
+                updated_at         TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
+                UNIQUE (chain_id, blobstream_address, verifier_address, index_blob,
+                        challenged_blob, challenge_type)
+            );",
+        )
+        .unwrap();
+
+        let store = JobStore::init(conn).unwrap();
+        let version: u32 = store
+            .conn
+            .query_row("PRAGMA user_version", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(version, SCHEMA_VERSION);
+        let job = store.find_or_create(&JOB_KEY).unwrap();
+        assert_eq!(
+            store.record_attempt(job.id, JobPhase::Submission).unwrap(),
+            1
+        );
+    }
+
+    #[test]
+    fn test_newer_schema_version_is_rejected() {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.pragma_update(None, "user_version", SCHEMA_VERSION + 1)
+            .unwrap();
+
+        assert!(JobStore::init(conn).is_err());
+    }
+}
@@ -1,46 +1,74 @@
+pub mod blobstream_backend;
 mod blobstream_data_commitment;
-
-use crate::blobstream_data_commitment::get_first_data_commitment_event;
+pub mod celestia_cache;
+pub mod celestia_data_source;
+pub mod challenge_options;
+pub mod coverage;
+pub mod das;
+pub mod deployment;
+pub mod event_cache;
+pub mod evm_input_cache;
+pub mod inspect;
+pub mod job_store;
+pub mod notifier;
+pub mod precheck;
+pub mod proof_cache;
+pub mod report;
+pub mod retry;
+
+use crate::celestia_data_source::{CelestiaDataSource, MemoizedDataSource};
+use crate::challenge_options::ChallengeOptions;
+use crate::event_cache::BlobstreamEventCache;
+use crate::evm_input_cache::EvmInputKey;
+use crate::precheck::precheck_da_challenge;
+use crate::retry::classify_prover_error;
 use crate::ICounter::ICounterInstance;
 use alloy_primitives::{Address, B256, U256};
 use anyhow::{anyhow, ensure, Context, Result};
-use celestia_rpc::blobstream::BlobstreamClient;
-use celestia_rpc::{Client as CelestiaClient, HeaderClient, ShareClient};
 use celestia_types::hash::Hash;
-use celestia_types::{AppVersion, ExtendedHeader};
-use da_challenge_guest::{DA_CHALLENGE_GUEST_ELF, DA_CHALLENGE_GUEST_ID};
+use celestia_types::nmt::{NamespacedHash, NamespacedHashExt};
+use celestia_types::{AppVersion, DataAvailabilityHeader, ExtendedHeader, MerkleProof};
+use da_challenge_guest::{
+    GuestInput, DA_CHALLENGE_AGGREGATOR_ELF, DA_CHALLENGE_AGGREGATOR_ID, DA_CHALLENGE_GUEST_ELF,
+    DA_CHALLENGE_GUEST_ID,
+};
+use futures_util::future::BoxFuture;
+use futures_util::{stream, FutureExt, StreamExt, TryStreamExt};
 use hana_blobstream::blobstream::SP1BlobstreamDataCommitmentStored;
-use hana_proofs::blobstream_inclusion::find_data_commitment;
-use rangemap::RangeMap;
 use risc0_ethereum_contracts::alloy::network::Ethereum;
 use risc0_ethereum_contracts::alloy::providers::{Provider, RootProvider};
 use risc0_ethereum_contracts::encode_seal;
+use risc0_steel::alloy::consensus::Transaction as _;
 use risc0_steel::alloy::contract::private::{
     Provider as PrivateProvider, Transport as PrivateTransport,
 };
+use risc0_steel::alloy::eips::BlockNumberOrTag as AlloyBlockNumberOrTag;
 use risc0_steel::alloy::providers::Network;
-use risc0_steel::alloy::{
-    sol,
-    sol_types::{SolCall, SolValue},
-};
+use risc0_steel::alloy::{sol, sol_types::SolCall};
 use risc0_steel::config::ChainSpec;
 use risc0_steel::host::db::{ProofDb, ProviderDb};
 use risc0_steel::host::HostCommit;
-use risc0_steel::{
-    ethereum::{EthBlockHeader, EthEvmEnv},
-    host::BlockNumberOrTag,
-    Contract, EvmBlockHeader, EvmEnv, EvmInput,
-};
+use risc0_steel::{ethereum::EthEvmEnv, host::BlockNumberOrTag, Contract, EvmBlockHeader, EvmEnv};
 use risc0_zkvm::{default_prover, Digest, ExecutorEnv, ProverOpts, Receipt, VerifierContext};
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+use std::ops::{Range, RangeInclusive};
+use std::time::Duration;
 use tokio::task;
 use toolkit::blobstream::{
     BinaryMerkleProof, Blobstream0, DataRootTuple, IDAOracle, SP1Blobstream,
 };
-use toolkit::journal::Journal;
+use toolkit::chain::BlobstreamChainInput;
+use toolkit::constants::{
+    check_blobstream_address, check_celestia_chain_id, check_chain_spec, MAX_INDEX_SHARES,
+    MAX_SUB_INDEX_DEPTH,
+};
+use toolkit::errors::{BlobIsAvailable, BLOB_IS_AVAILABLE_MESSAGE};
+use toolkit::journal::{AggregatedJournal, AggregationInput, GuestOptions, Journal};
 use toolkit::{
-    BlobIndex, BlobProofData, BlobstreamAttestation, BlobstreamAttestationAndRowProof,
-    BlobstreamImpl, BlobstreamInfo, DaChallengeGuestData, SpanSequence,
+    share_proof_ods_range, BlobIndex, BlobProofData, BlobstreamAttestation,
+    BlobstreamAttestationAndRowProof, BlobstreamImpl, BlobstreamInfo, DaChallenge,
+    DaChallengeGuestData, SpanSequence, SubIndexProofData,
 };
 use tracing_subscriber::EnvFilter;
 
@@ -49,74 +77,60 @@ sol!(
     "../../contracts/src/ICounter.sol"
 );
 
+/// Maximum number of Celestia requests in flight while fetching the challenge data.
+const MAX_CONCURRENT_CELESTIA_REQUESTS: usize = 16;
+
+/// Fetches the shares of a blob with one ranged share proof per row of the data square, see
+/// `verify_share_proofs()`.
 async fn fetch_blob_proof_data(
-    celestia_client: &CelestiaClient,
+    celestia_client: &impl CelestiaDataSource,
     span_sequence: SpanSequence,
     block_header: &ExtendedHeader,
 ) -> Result<BlobProofData, anyhow::Error> {
-    let mut share_proofs = BTreeMap::new();
-
-    let span_sequence_end = span_sequence.end_index_ods()?;
+    let ods_width = block_header.dah.square_width() as u32 / 2;
 
-    for share_index in span_sequence.start..span_sequence_end {
-        let share_proof = celestia_client
-            .share_get_range(block_header, share_index as u64, share_index as u64 + 1)
-            .await?
-            .proof;
-
-        share_proofs.insert(share_index, share_proof);
-    }
+    let share_proofs = stream::iter(row_ranges(span_sequence.ods_range()?, ods_width))
+        .map(|shares| async move {
+            let share_proof = celestia_client
+                .share_range_proof(block_header, shares.start as u64, shares.end as u64)
+                .await?;
+            // The guest rejects proofs of other shares, fail before proving instead
+            let proven_shares = share_proof_ods_range(&share_proof, ods_width)?;
+            ensure!(
+                proven_shares == shares,
+                "share proof of shares {shares:?} covers shares {proven_shares:?}"
+            );
+            Ok::<_, anyhow::Error>((shares.start, share_proof))
+        })
+        .buffer_unordered(MAX_CONCURRENT_CELESTIA_REQUESTS)
+        .try_collect::<BTreeMap<_, _>>()
+        .await?;
 
-    Ok(BlobProofData {
+    Ok(BlobProofData::new(
         share_proofs,
-        app_version: AppVersion::V2.as_u64(),
-    })
+        block_header.header.version.app,
+    )?)
 }
 
-struct BlobstreamEventCache {
-    eth_provider: RootProvider,
-    blobstream_address: Address,
-    event_cache: RangeMap<u64, SP1BlobstreamDataCommitmentStored>,
+/// Splits a range of ODS share indexes at the row boundaries of a square of width `ods_width`.
+fn row_ranges(shares: Range<u32>, ods_width: u32) -> impl Iterator<Item = Range<u32>> {
+    let end = shares.end;
+    std::iter::successors(Some(shares.start), move |&start| {
+        let row_end = (start / ods_width + 1) * ods_width;
+        (row_end < end).then_some(row_end)
+    })
+    .map(move |start| start..end.min((start / ods_width + 1) * ods_width))
 }
 
-impl BlobstreamEventCache {
-    pub fn new(blobstream_address: Address, eth_provider: RootProvider) -> Self {
-        Self {
-            blobstream_address,
-            eth_provider,
-            event_cache: RangeMap::new(),
-        }
-    }
+/// Deserializes an index from its shares, with the app version of their block.
+fn reconstruct_index(blob_proof_data: &BlobProofData) -> Result<BlobIndex, anyhow::Error> {
+    let app_version = AppVersion::from_u64(blob_proof_data.app_version)
+        .ok_or_else(|| anyhow!("unsupported app version {}", blob_proof_data.app_version))?;
 
-    pub async fn first_data_commitment_stored_event(
-        &self,
-    ) -> Result<SP1BlobstreamDataCommitmentStored, anyhow::Error> {
-        let chain_id = self.eth_provider.get_chain_id().await?;
-        get_first_data_commitment_event(chain_id, self.blobstream_address, &self.eth_provider).await
-    }
-
-    pub async fn get(
-        &mut self,
-        block_height: u64,
-    ) -> Result<&SP1BlobstreamDataCommitmentStored, anyhow::Error> {
-        if self.event_cache.get(&block_height).is_none() {
-            let event =
-                find_data_commitment(block_height, self.blobstream_address, &self.eth_provider)
-                    .await
-                    .map_err(|e| anyhow::anyhow!("failed to find Blobstream commitment: {e}"))?;
-
-            log::info!("found DataCommitmentStored event: {event}");
-
-            self.event_cache
-                .insert(event.start_block..event.end_block, event);
-        }
-
-        // expect is safe here, the entry should always exist by now.
-        Ok(self
-            .event_cache
-            .get(&block_height)
-            .expect("the Blobstream event should be in the cache"))
-    }
+    Ok(BlobIndex::reconstruct_from_raw(
+        blob_proof_data.shares(),
+        app_version,
+    )?)
 }
 
 /// Extracts the data root field from a Celestia block header and returns i-t
@@ -138,68 +152,167 @@ fn get_data_root_from_header(block_header: &ExtendedHeader) -> Result<[u8; 32],
     Ok(data_root)
 }
 
+/// Checks the attestation against the Blobstream event it was built from, see
+/// `BlobstreamAttestation::verify_against_event()`. A mismatch means that the Celestia RPC and
+/// the Blobstream contract disagree, in which case the guest would fail to verify the
+/// attestation.
+fn verify_data_commitment(
+    blobstream_attestation: &BlobstreamAttestation,
+    blobstream_event: &SP1BlobstreamDataCommitmentStored,
+) -> Result<(), anyhow::Error> {
+    blobstream_attestation
+        .verify_against_event(
+            blobstream_event.proof_nonce,
+            blobstream_event.start_block,
+            blobstream_event.end_block,
+            blobstream_event.data_commitment,
+        )
+        .with_context(|| {
+            format!(
+                "data root of Celestia block {} does not match Blobstream data commitment {} \
+                 (nonce {}, blocks {}..{})",
+                blobstream_attestation.height,
+                blobstream_event.data_commitment,
+                blobstream_event.proof_nonce,
+                blobstream_event.start_block,
+                blobstream_event.end_block,
+            )
+        })
+}
+
+/// Fetches the inclusion proof of the data root of the block in the Blobstream commitment,
+/// from the inclusion proof store if possible.
+async fn fetch_data_root_inclusion_proof(
+    celestia_client: &impl CelestiaDataSource,
+    block_header: &ExtendedHeader,
+    blobstream_event: &SP1BlobstreamDataCommitmentStored,
+    blobstream_event_cache: &BlobstreamEventCache,
+) -> Result<MerkleProof, anyhow::Error> {
+    let celestia_chain_id = block_header.header.chain_id.as_str();
+    let block_height: u64 = block_header.height().into();
+    let (start_block, end_block) = (blobstream_event.start_block, blobstream_event.end_block);
+
+    let stored_proof = blobstream_event_cache.access_inclusion_proof_store(|store| {
+        store.get(celestia_chain_id, block_height, start_block, end_block)
+    })?;
+    if let Some(proof) = stored_proof.flatten() {
+        return Ok(proof);
+    }
+
+    let proof = celestia_client
+        .data_root_inclusion_proof(block_height, start_block, end_block)
+        .await
+        .with_context(|| "Failed to fetch Blobstream proof")?;
+
+    blobstream_event_cache.access_inclusion_proof_store(|store| {
+        store.insert(
+            celestia_chain_id,
+            block_height,
+            start_block,
+            end_block,
+            &proof,
+        )
+    })?;
+
+    Ok(proof)
+}
+
 async fn get_first_blobstream_attestation(
-    celestia_client: &CelestiaClient,
-    blobstream_event_cache: &mut BlobstreamEventCache,
+    celestia_client: &impl CelestiaDataSource,
+    blobstream_event_cache: &BlobstreamEventCache,
 ) -> Result<BlobstreamAttestation, anyhow::Error> {
     let first_blobstream_event = blobstream_event_cache
         .first_data_commitment_stored_event()
         .await?;
 
     let block_header = celestia_client
-        .header_get_by_height(first_blobstream_event.start_block)
+        .header(first_blobstream_event.start_block)
         .await
         .with_context(|| "failed to fetch Celestia block header for first Blobstream event")?;
     let data_root = get_data_root_from_header(&block_header)?;
 
-    let root_inclusion_proof = celestia_client
-        .blobstream_get_data_root_tuple_inclusion_proof(
-            first_blobstream_event.start_block,
-            first_blobstream_event.start_block,
-            first_blobstream_event.end_block,
-        )
-        .await
-        .with_context(|| "Failed to fetch Blobstream proof")?;
+    let root_inclusion_proof = fetch_data_root_inclusion_proof(
+        celestia_client,
+        &block_header,
+        &first_blobstream_event,
+        blobstream_event_cache,
+    )
+    .await?;
 
-    Ok(BlobstreamAttestation {
+    let blobstream_attestation = BlobstreamAttestation {
         data_root,
         height: first_blobstream_event.start_block,
-        nonce: first_blobstream_event.proof_nonce.try_into()?,
+        nonce: first_blobstream_event.proof_nonce,
+        data_commitment: first_blobstream_event.data_commitment,
         proof: root_inclusion_proof,
-    })
+        start_block: first_blobstream_event.start_block,
+        end_block: first_blobstream_event.end_block,
+    };
+    if blobstream_event_cache.verifies_data_commitments() {
+        verify_data_commitment(&blobstream_attestation, &first_blobstream_event)?;
+    }
+
+    Ok(blobstream_attestation)
 }
 
 async fn fetch_blobstream_attestation(
-    celestia_client: &CelestiaClient,
+    celestia_client: &impl CelestiaDataSource,
     block_header: &ExtendedHeader,
-    blobstream_event_cache: &mut BlobstreamEventCache,
+    blobstream_event_cache: &BlobstreamEventCache,
 ) -> Result<BlobstreamAttestation, anyhow::Error> {
     let data_root = get_data_root_from_header(block_header)?;
     let block_height: u64 = block_header.height().into();
 
     let blobstream_event = blobstream_event_cache.get(block_height).await?;
 
-    let root_inclusion_proof = celestia_client
-        .blobstream_get_data_root_tuple_inclusion_proof(
-            block_height,
-            blobstream_event.start_block,
-            blobstream_event.end_block,
-        )
-        .await
-        .with_context(|| "Failed to fetch Blobstream proof")?;
+    let root_inclusion_proof = fetch_data_root_inclusion_proof(
+        celestia_client,
+        block_header,
+        &blobstream_event,
+        blobstream_event_cache,
+    )
+    .await?;
 
-    Ok(BlobstreamAttestation {
+    let blobstream_attestation = BlobstreamAttestation {
         data_root,
         height: block_height,
-        nonce: blobstream_event.proof_nonce.try_into()?,
+        nonce: blobstream_event.proof_nonce,
+        data_commitment: blobstream_event.data_commitment,
         proof: root_inclusion_proof,
-    })
+        start_block: blobstream_event.start_block,
+        end_block: blobstream_event.end_block,
+    };
+    if blobstream_event_cache.verifies_data_commitments() {
+        verify_data_commitment(&blobstream_attestation, &blobstream_event)?;
+    }
+
+    Ok(blobstream_attestation)
+}
+
+/// Proves the last root of the data square, the root of its last column, in the data root.
+/// The guest relies on it to check the size of the square, see
+/// `BlobstreamAttestationAndRowProof::ods_width()`.
+fn prove_last_root(
+    dah: &DataAvailabilityHeader,
+) -> Result<(MerkleProof, NamespacedHash), anyhow::Error> {
+    let roots: Vec<NamespacedHash> = dah
+        .row_roots()
+        .iter()
+        .chain(dah.column_roots())
+        .cloned()
+        .collect();
+    let leaves: Vec<_> = roots.iter().map(|root| root.to_array()).collect();
+    let last_root_index = leaves.len() - 1;
+    let (last_root_proof, _) = MerkleProof::new(last_root_index, &leaves)
+        .with_context(|| format!("Failed to generate proof for root {last_root_index}"))?;
+
+    Ok((last_root_proof, roots[last_root_index].clone()))
 }
 
 async fn fetch_block_proof(
-    celestia_client: &CelestiaClient,
+    celestia_client: &impl CelestiaDataSource,
     block_header: &ExtendedHeader,
-    blobstream_event_cache: &mut BlobstreamEventCache,
+    blobstream_event_cache: &BlobstreamEventCache,
 ) -> Result<BlobstreamAttestationAndRowProof, anyhow::Error> {
     let blobstream_attestation =
         fetch_blobstream_attestation(celestia_client, block_header, blobstream_event_cache).await?;
@@ -214,150 +327,314 @@ async fn fetch_block_proof(
         .dah
         .row_root(0)
         .expect("row root 0 should always be present");
+    let (last_root_proof, last_root_node) = prove_last_root(&block_header.dah)?;
 
     Ok(BlobstreamAttestationAndRowProof {
         blobstream_attestation,
         row_proof: row_inclusion_proof,
         row_root_node,
+        last_root_proof,
+        last_root_node,
+        app_version: block_header.header.version.app,
     })
 }
 
-async fn fetch_block_proof_for_blob_in_index(
-    celestia_client: &CelestiaClient,
-    index: &BlobIndex,
-    challenged_blob: SpanSequence,
-    blobstream_event_cache: &mut BlobstreamEventCache,
-) -> Result<Option<BlobstreamAttestationAndRowProof>, anyhow::Error> {
-    for span_sequence in &index.blobs {
-        if span_sequence == &challenged_blob {
-            let block_header = celestia_client
-                .header_get_by_height(span_sequence.height)
-                .await?;
+/// Fetches the block proofs of the specified Celestia heights concurrently.
+async fn fetch_block_proofs(
+    celestia_client: &impl CelestiaDataSource,
+    block_heights: BTreeSet<u64>,
+    blobstream_event_cache: &BlobstreamEventCache,
+) -> Result<BTreeMap<u64, BlobstreamAttestationAndRowProof>, anyhow::Error> {
+    stream::iter(block_heights)
+        .map(|block_height| async move {
+            let block_header = celestia_client.header(block_height).await?;
             let block_proof =
                 fetch_block_proof(celestia_client, &block_header, blobstream_event_cache).await?;
-            return Ok(Some(block_proof));
-        }
-    }
-
-    Ok(None)
+            Ok::<_, anyhow::Error>((block_height, block_proof))
+        })
+        .buffer_unordered(MAX_CONCURRENT_CELESTIA_REQUESTS)
+        .try_collect()
+        .await
 }
 
 /// Fetches all the data required to execute the DA challenge guest program.
 ///
 /// This function fetches all the data that it can actually fetch, as a valid DA challenge will
 /// be unable to download some data by definition.
-async fn fetch_da_challenge_guest_data(
-    celestia_client: &CelestiaClient,
+///
+/// Celestia data is read from `celestia_client`, any `CelestiaDataSource`: a Celestia node, an
+/// archive or recorded data.
+///
+/// With `fetch_challenged_blob_shares`, the share proofs of the challenged blob are fetched too,
+/// which turns the challenge into a `DaChallenge::BlobDataMismatch` challenge: its outcome tells
+/// whether the data of the blob is present and matches its index entry. Fails if the challenged
+/// blob cannot be fetched.
+pub async fn fetch_da_challenge_guest_data(
+    celestia_client: &impl CelestiaDataSource,
     index_blob: SpanSequence,
     challenged_blob: SpanSequence,
-    blobstream_event_cache: &mut BlobstreamEventCache,
+    blobstream_event_cache: &BlobstreamEventCache,
+    fetch_challenged_blob_shares: bool,
+) -> Result<DaChallengeGuestData, anyhow::Error> {
+    // Blocks are often shared by the index, its sub-indexes and the challenged blob
+    let celestia_client = &MemoizedDataSource::new(celestia_client);
+    let da_challenge_guest_data = fetch_index_guest_data(
+        celestia_client,
+        index_blob,
+        challenged_blob,
+        blobstream_event_cache,
+    )
+    .await?;
+    if !fetch_challenged_blob_shares {
+        return Ok(da_challenge_guest_data);
+    }
+
+    ensure!(
+        index_blob != challenged_blob,
+        "the data of the index blob cannot be challenged against itself"
+    );
+    let challenged_block_header = celestia_client.header(challenged_blob.height).await?;
+    let challenged_blob_proof_data =
+        fetch_blob_proof_data(celestia_client, challenged_blob, &challenged_block_header)
+            .await
+            .context("failed to fetch the shares of the challenged blob")?;
+
+    Ok(da_challenge_guest_data.with_challenged_blob_proof_data(challenged_blob_proof_data))
+}
+
+/// Fetches the guest data of an availability challenge, see `fetch_da_challenge_guest_data()`.
+async fn fetch_index_guest_data(
+    celestia_client: &impl CelestiaDataSource,
+    index_blob: SpanSequence,
+    challenged_blob: SpanSequence,
+    blobstream_event_cache: &BlobstreamEventCache,
 ) -> Result<DaChallengeGuestData, anyhow::Error> {
     // First, check the bounds on the index blob height as an invalid block height would prevent
     // us from fetching any data from Celestia.
-    let current_celestia_block_height = celestia_client.header_local_head().await?.height().value();
-    let first_blobstream_attestation =
-        get_first_blobstream_attestation(celestia_client, blobstream_event_cache).await?;
+    let (current_celestia_block_header, first_blobstream_attestation) = tokio::try_join!(
+        celestia_client.head(),
+        get_first_blobstream_attestation(celestia_client, blobstream_event_cache),
+    )?;
+    let current_celestia_block_height = current_celestia_block_header.height().value();
+    let celestia_chain_id = current_celestia_block_header.chain_id().to_string();
 
     if index_blob.height < first_blobstream_attestation.height
         || index_blob.height > current_celestia_block_height
     {
         return Ok(DaChallengeGuestData {
+            celestia_chain_id,
             index_blob,
             challenged_blob,
+            extra_challenged_blobs: vec![],
             index_blob_proof_data: None,
+            challenged_blob_proof_data: None,
+            namespace: None,
+            sub_indexes: vec![],
             block_proofs: Default::default(),
             first_blobstream_attestation,
         });
     }
 
-    let index_block_header = celestia_client
-        .header_get_by_height(index_blob.height)
-        .await?;
-
-    let index_block_proof =
-        fetch_block_proof(celestia_client, &index_block_header, blobstream_event_cache).await?;
+    let index_block_header = celestia_client.header(index_blob.height).await?;
 
-    let mut block_proofs = BTreeMap::from([(index_blob.height, index_block_proof)]);
-
-    if index_blob == challenged_blob {
+    // Only download the index blob and additional data if the challenge targets a blob inside
+    // the index. Oversized indexes are fraud without their data, which may not even be
+    // downloadable
+    if index_blob == challenged_blob || index_blob.size > MAX_INDEX_SHARES {
+        let index_block_proof =
+            fetch_block_proof(celestia_client, &index_block_header, blobstream_event_cache).await?;
         return Ok(DaChallengeGuestData {
+            celestia_chain_id,
             index_blob,
             challenged_blob,
+            extra_challenged_blobs: vec![],
             index_blob_proof_data: None,
-            block_proofs,
+            challenged_blob_proof_data: None,
+            namespace: None,
+            sub_indexes: vec![],
+            block_proofs: BTreeMap::from([(index_blob.height, index_block_proof)]),
             first_blobstream_attestation,
         });
     }
 
-    // Only download the index blob and additional data if the challenge targets a blob inside
-    // the index
-    let index_blob_proof_data =
-        fetch_blob_proof_data(celestia_client, index_blob, &index_block_header).await?;
+    let (index_block_proof, index_blob_proof_data) = tokio::try_join!(
+        fetch_block_proof(celestia_client, &index_block_header, blobstream_event_cache),
+        fetch_blob_proof_data(celestia_client, index_blob, &index_block_header),
+    )?;
+    let mut block_proofs = BTreeMap::from([(index_blob.height, index_block_proof)]);
 
     // The index may not be deserializable. We try here to fetch the Blobstream attestation
     // for the challenged blob, but failing here should not prevent the challenge from proceeding.
-    if let Ok(index) =
-        BlobIndex::reconstruct_from_raw(index_blob_proof_data.shares(), AppVersion::V2)
-    {
+    let mut sub_indexes = vec![];
+    if let Ok(mut index) = reconstruct_index(&index_blob_proof_data) {
+        // Malformed indexes are still challenged like any other, this is only informative
+        if let Err(e) = index.validate() {
+            log::warn!("Index {index_blob} is malformed: {e}");
+        }
         if challenged_blob.height < first_blobstream_attestation.height
             || challenged_blob.height > current_celestia_block_height
         {
             return Ok(DaChallengeGuestData {
+                celestia_chain_id,
                 index_blob,
                 challenged_blob,
+                extra_challenged_blobs: vec![],
                 index_blob_proof_data: Some(index_blob_proof_data),
+                challenged_blob_proof_data: None,
+                namespace: None,
+                sub_indexes: vec![],
                 block_proofs,
                 first_blobstream_attestation,
             });
         }
 
-        if let Some(block_proof) = fetch_block_proof_for_blob_in_index(
-            celestia_client,
-            &index,
-            challenged_blob,
-            blobstream_event_cache,
-        )
-        .await?
-        {
-            block_proofs.insert(challenged_blob.height, block_proof);
+        // The challenged blob may be listed in a sub-index rather than in the index itself
+        if !index.blobs.contains(&challenged_blob) {
+            if let Some((path, sub_index)) = find_sub_index_path(
+                celestia_client,
+                &index,
+                challenged_blob,
+                first_blobstream_attestation.height..=current_celestia_block_height,
+                0,
+            )
+            .await
+            {
+                sub_indexes = path;
+                index = sub_index;
+            }
         }
+
+        // Fetch the proofs of all the heights of the sub-indexes and of the index pointing to
+        // the challenged blob that we don't have yet, each height once and concurrently.
+        let sub_index_heights = sub_indexes
+            .iter()
+            .map(|sub_index| sub_index.sub_index.height);
+        let block_heights = index
+            .blobs
+            .iter()
+            .filter(|span_sequence| **span_sequence == challenged_blob)
+            .map(|span_sequence| span_sequence.height)
+            .chain(sub_index_heights)
+            .filter(|height| !block_proofs.contains_key(height))
+            .collect();
+        block_proofs.extend(
+            fetch_block_proofs(celestia_client, block_heights, blobstream_event_cache).await?,
+        );
     }
 
     Ok(DaChallengeGuestData {
+        celestia_chain_id,
         index_blob,
         challenged_blob,
+        extra_challenged_blobs: vec![],
         index_blob_proof_data: Some(index_blob_proof_data),
+        challenged_blob_proof_data: None,
+        namespace: None,
+        sub_indexes,
         block_proofs,
         first_blobstream_attestation,
     })
 }
 
+/// Searches the sub-indexes of `index`, depth-first, for an index listing the challenged blob.
+/// Returns the sub-indexes leading to it, outermost first, and that index.
+///
+/// Sub-indexes out of `celestia_heights`, larger than `MAX_INDEX_SHARES`, unavailable or not
+/// deserializable cannot lead to the challenged blob and are skipped.
+fn find_sub_index_path<'a>(
+    celestia_client: &'a impl CelestiaDataSource,
+    index: &'a BlobIndex,
+    challenged_blob: SpanSequence,
+    celestia_heights: RangeInclusive<u64>,
+    depth: usize,
+) -> BoxFuture<'a, Option<(Vec<SubIndexProofData>, BlobIndex)>> {
+    async move {
+        if depth >= MAX_SUB_INDEX_DEPTH {
+            return None;
+        }
+
+        for sub_index in index.sub_indexes() {
+            if !celestia_heights.contains(&sub_index.height) || sub_index.size > MAX_INDEX_SHARES {
+                continue;
+            }
+            let Ok(block_header) = celestia_client.header(sub_index.height).await else {
+                continue;
+            };
+            let Ok(proof_data) =
+                fetch_blob_proof_data(celestia_client, sub_index, &block_header).await
+            else {
+                continue;
+            };
+            let Ok(sub_index_content) = reconstruct_index(&proof_data) else {
+                continue;
+            };
+
+            let path = if sub_index_content.blobs.contains(&challenged_blob) {
+                Some((vec![], sub_index_content))
+            } else {
+                find_sub_index_path(
+                    celestia_client,
+                    &sub_index_content,
+                    challenged_blob,
+                    celestia_heights.clone(),
+                    depth + 1,
+                )
+                .await
+            };
+            if let Some((mut path, challenged_index)) = path {
+                path.insert(
+                    0,
+                    SubIndexProofData {
+                        sub_index,
+                        proof_data,
+                    },
+                );
+                return Some((path, challenged_index));
+            }
+        }
+
+        None
+    }
+    .boxed()
+}
+
+/// Preflights the latest height and next nonce calls of the specified Blobstream implementation,
+/// so that the guest can check the bounds of the challenged Celestia heights and nonces.
 #[allow(clippy::type_complexity)]
-async fn perform_preflight_blobstream_height_call<
+async fn perform_preflight_blobstream_state_calls<
     C,
     H: EvmBlockHeader + Clone + Send + 'static,
     N: Network,
     P: Provider<N> + 'static,
 >(
     blobstream_contract: &mut Contract<&mut EvmEnv<ProofDb<ProviderDb<N, P>>, H, HostCommit<C>>>,
-) -> Result<BlobstreamImpl, anyhow::Error> {
-    let latest_height_call = Blobstream0::latestHeightCall {};
-    let result = blobstream_contract
-        .call_builder(&latest_height_call)
-        .call()
-        .await;
-
-    if let Ok(_height) = result {
-        return Ok(BlobstreamImpl::R0);
+    blobstream_impl: BlobstreamImpl,
+) -> Result<(), anyhow::Error> {
+    match blobstream_impl {
+        BlobstreamImpl::R0 => {
+            blobstream_contract
+                .call_builder(&Blobstream0::latestHeightCall {})
+                .call()
+                .await?;
+            blobstream_contract
+                .call_builder(&Blobstream0::proofNonceCall {})
+                .call()
+                .await?;
+        }
+        BlobstreamImpl::Sp1 => {
+            blobstream_contract
+                .call_builder(&SP1Blobstream::latestBlockCall {})
+                .call()
+                .await?;
+            blobstream_contract
+                .call_builder(&SP1Blobstream::state_proofNonceCall {})
+                .call()
+                .await?;
+        }
+        BlobstreamImpl::DaOracle => {}
     }
 
-    let latest_height_call = SP1Blobstream::latestBlockCall {};
-    blobstream_contract
-        .call_builder(&latest_height_call)
-        .call()
-        .await?;
-
-    Ok(BlobstreamImpl::Sp1)
+    Ok(())
 }
 
 /// Performs calls to the Blobstream smart contract and fetches the data locally.
@@ -366,11 +643,12 @@ async fn perform_preflight_calls<'a, I, P>(
     eth_provider: P,
     chain_spec: &ChainSpec,
     blobstream_contract_address: Address,
+    blobstream_impl: BlobstreamImpl,
     blobstream_attestations: I,
     execution_block: BlockNumberOrTag,
     #[cfg(any(feature = "beacon", feature = "history"))] beacon_api_url: url::Url,
     #[cfg(feature = "history")] commitment_block: BlockNumberOrTag,
-) -> Result<(EvmInput<EthBlockHeader>, BlobstreamInfo)>
+) -> Result<(BlobstreamChainInput, BlobstreamInfo)>
 where
     I: Iterator<Item = &'a BlobstreamAttestation>,
     P: Provider<Ethereum> + 'static,
@@ -381,6 +659,8 @@ where
     #[cfg(feature = "history")]
     log::info!("History commitment to block {commitment_block}");
 
+    // Any chain with Ethereum-compatible headers works here, see `toolkit::chain`. Beacon and
+    // history commitments are only available on Ethereum L1.
     let builder = EthEvmEnv::builder()
         .provider(eth_provider)
         .block_number_or_tag(execution_block);
@@ -395,28 +675,40 @@ where
 
     let mut blobstream_contract = Contract::preflight(blobstream_contract_address, &mut env);
 
-    let blobstream_impl =
-        perform_preflight_blobstream_height_call(&mut blobstream_contract).await?;
+    perform_preflight_blobstream_state_calls(&mut blobstream_contract, blobstream_impl).await?;
 
     for blobstream_attestation in blobstream_attestations {
         let data_root_tuple = DataRootTuple {
             height: U256::from(blobstream_attestation.height),
             dataRoot: B256::from(blobstream_attestation.data_root),
         };
-        let formatted_proof = BinaryMerkleProof::from(blobstream_attestation.proof.clone());
+        let formatted_proof = BinaryMerkleProof::from(&blobstream_attestation.proof);
 
         let blobstream_call = IDAOracle::verifyAttestationCall {
-            _tupleRootNonce: U256::from(blobstream_attestation.nonce),
+            _tupleRootNonce: blobstream_attestation.nonce,
             _tuple: data_root_tuple,
             _proof: formatted_proof,
         };
 
         // Preflight the call to prepare the input that is required to execute the function in
         // the guest without RPC access. It also returns the result of the call.
-        blobstream_contract
+        let verified = blobstream_contract
             .call_builder(&blobstream_call)
             .call()
-            .await?;
+            .await?
+            ._0;
+
+        // A rejected attestation makes the guest run pointless, fail before proving.
+        ensure!(
+            verified,
+            "Blobstream at {blobstream_contract_address} rejected the attestation of Celestia block \
+             {} (nonce {}, data root {}). The nonce may not be committed at execution block \
+             {execution_block} yet, or the inclusion proof served by the Celestia node does not \
+             match the data commitment stored on-chain",
+            blobstream_attestation.height,
+            blobstream_attestation.nonce,
+            B256::from(blobstream_attestation.data_root),
+        );
     }
 
     // Finally, construct the input from the environment.
@@ -431,29 +723,27 @@ where
     Ok((evm_input, blobstream_info))
 }
 
-/// Challenges the availability of a blob in an Eclipse batch / index.
-///
-/// The caller can challenge at two levels, using the `challenged_blob` parameter:
-/// 1. The span sequence pointing to the index
-/// 2. Any span sequence in the index.
+/// Challenges a blob in an Eclipse batch / index.
 ///
 /// This function will fetch all the necessary data to process the DA challenge in ZK and then
 /// execute the DA challenge guest program. If the challenge is successful, a ZK proof is generated.
 ///
-/// This function handles 3 possible cases:
-/// 1. The index blob is not available (`challenged_blob = index_blob`)
-/// 2. A blob inside the index is not available `challenged_blob = blob inside the index`)
-/// 3. The index blob is unreadable (`challenged_blob = any span sequence other than the index`).
+/// This function handles the challenges of `DaChallenge`:
+/// 1. The index blob is not available (`DaChallenge::IndexIsUnavailable`)
+/// 2. A blob inside the index is not available (`DaChallenge::BlobInIndexIsUnavailable`). The
+///    index blob is unreadable if any span sequence other than the index can be challenged.
+/// 3. The data of a blob inside the index does not match its data hash
+///    (`DaChallenge::BlobDataMismatch`).
 ///
 /// # Arguments
 ///
-/// * `celestia_client`: Celestia RPC client.
+/// * `celestia_client`: Source of the Celestia data, ex: a Celestia RPC client.
 /// * `root_provider`: Ethereum RPC client.
 /// * `chain_spec`: Ethereum chain specification.
 /// * `execution_block`: Block number or tag for execution.
 /// * `blobstream_address`: Address of the Blobstream contract.
 /// * `index_blob`: Span sequence of the index blob.
-/// * `challenged_blob`: Span sequence of the blob to challenge.
+/// * `challenge`: What the proof must show about the index.
 ///
 /// # Returns
 ///
@@ -462,31 +752,434 @@ where
 /// * The encoded seal.
 #[allow(clippy::too_many_arguments)]
 pub async fn challenge_da_commitment(
-    celestia_client: &CelestiaClient,
+    celestia_client: &impl CelestiaDataSource,
     root_provider: RootProvider,
     chain_spec: ChainSpec,
     execution_block: BlockNumberOrTag,
     blobstream_address: Address,
     index_blob: SpanSequence,
+    challenge: DaChallenge,
+    #[cfg(any(feature = "beacon", feature = "history"))] beacon_api_url: url::Url,
+    #[cfg(feature = "history")] commitment_block: BlockNumberOrTag,
+) -> Result<(Receipt, Vec<u8>), anyhow::Error> {
+    let blobstream_event_cache = BlobstreamEventCache::new(blobstream_address, root_provider);
+
+    challenge_da_commitment_with_cache(
+        celestia_client,
+        &blobstream_event_cache,
+        &ChallengeOptions::default(),
+        chain_spec,
+        execution_block,
+        index_blob,
+        challenge,
+        #[cfg(any(feature = "beacon", feature = "history"))]
+        beacon_api_url,
+        #[cfg(feature = "history")]
+        commitment_block,
+    )
+    .await
+}
+
+/// Same as `challenge_da_commitment()`, but looks up Blobstream events in the specified cache
+/// instead of creating a new one, and runs the challenge with the specified options. Use this
+/// to reuse Blobstream events across challenges, including challenges running concurrently.
+#[allow(clippy::too_many_arguments)]
+pub async fn challenge_da_commitment_with_cache(
+    celestia_client: &impl CelestiaDataSource,
+    blobstream_event_cache: &BlobstreamEventCache,
+    challenge_options: &ChallengeOptions,
+    chain_spec: ChainSpec,
+    execution_block: BlockNumberOrTag,
+    index_blob: SpanSequence,
+    challenge: DaChallenge,
+    #[cfg(any(feature = "beacon", feature = "history"))] beacon_api_url: url::Url,
+    #[cfg(feature = "history")] commitment_block: BlockNumberOrTag,
+) -> Result<(Receipt, Vec<u8>), anyhow::Error> {
+    let challenged_blob = match challenge {
+        DaChallenge::BlobDataMismatch(challenged_blob) => {
+            return challenge_blob_data_with_cache(
+                celestia_client,
+                blobstream_event_cache,
+                challenge_options,
+                chain_spec,
+                execution_block,
+                index_blob,
+                challenged_blob,
+                #[cfg(any(feature = "beacon", feature = "history"))]
+                beacon_api_url,
+                #[cfg(feature = "history")]
+                commitment_block,
+            )
+            .await
+        }
+        challenge => challenge.challenged_blob(index_blob),
+    };
+
+    let da_challenge_guest_data = fetch_da_challenge_guest_data(
+        celestia_client,
+        index_blob,
+        challenged_blob,
+        blobstream_event_cache,
+        false,
+    )
+    .await?;
+
+    prove_da_challenge_with_cache(
+        blobstream_event_cache,
+        challenge_options,
+        chain_spec,
+        execution_block,
+        da_challenge_guest_data,
+        #[cfg(any(feature = "beacon", feature = "history"))]
+        beacon_api_url,
+        #[cfg(feature = "history")]
+        commitment_block,
+    )
+    .await
+}
+
+/// Same as `challenge_da_commitment_with_cache()`, but challenges the availability of several
+/// blobs of the same index in a single guest execution, the first one being the challenged blob.
+/// The Blobstream attestations and the index are only verified once, which makes this cheaper
+/// than one proof per blob. Every challenged blob must be DA fraud.
+#[allow(clippy::too_many_arguments)]
+pub async fn challenge_da_commitments_with_cache(
+    celestia_client: &impl CelestiaDataSource,
+    blobstream_event_cache: &BlobstreamEventCache,
+    challenge_options: &ChallengeOptions,
+    chain_spec: ChainSpec,
+    execution_block: BlockNumberOrTag,
+    index_blob: SpanSequence,
+    challenged_blobs: Vec<SpanSequence>,
+    #[cfg(any(feature = "beacon", feature = "history"))] beacon_api_url: url::Url,
+    #[cfg(feature = "history")] commitment_block: BlockNumberOrTag,
+) -> Result<(Receipt, Vec<u8>), anyhow::Error> {
+    let (&challenged_blob, extra_challenged_blobs) = challenged_blobs
+        .split_first()
+        .context("no blob to challenge")?;
+    ensure!(
+        extra_challenged_blobs.is_empty() || !challenged_blobs.contains(&index_blob),
+        "the index blob cannot be challenged along with its blobs"
+    );
+
+    let mut da_challenge_guest_data = fetch_da_challenge_guest_data(
+        celestia_client,
+        index_blob,
+        challenged_blob,
+        blobstream_event_cache,
+        false,
+    )
+    .await?;
+
+    // The sub-index path of the challenged blob is shared by the extra challenged blobs, only
+    // their block proofs are missing. Heights outside of Blobstream or of the chain are fraud
+    // without block proof.
+    let current_celestia_block_height = celestia_client.head().await?.height().value();
+    let min_block_height = da_challenge_guest_data.first_blobstream_attestation.height;
+    let block_heights = extra_challenged_blobs
+        .iter()
+        .map(|span_sequence| span_sequence.height)
+        .filter(|height| (min_block_height..=current_celestia_block_height).contains(height))
+        .filter(|height| !da_challenge_guest_data.block_proofs.contains_key(height))
+        .collect();
+    da_challenge_guest_data
+        .block_proofs
+        .extend(fetch_block_proofs(celestia_client, block_heights, blobstream_event_cache).await?);
+
+    prove_da_challenge_with_cache(
+        blobstream_event_cache,
+        challenge_options,
+        chain_spec,
+        execution_block,
+        da_challenge_guest_data.with_extra_challenged_blobs(extra_challenged_blobs.to_vec()),
+        #[cfg(any(feature = "beacon", feature = "history"))]
+        beacon_api_url,
+        #[cfg(feature = "history")]
+        commitment_block,
+    )
+    .await
+}
+
+/// Same as `challenge_da_commitment_with_cache()`, but challenges the data of a blob of the
+/// index instead of its availability: the proof shows that the challenged blob does not match
+/// the data hash or share commitment of its index entry (`DaChallenge::BlobDataMismatch`).
+#[allow(clippy::too_many_arguments)]
+pub async fn challenge_blob_data_with_cache(
+    celestia_client: &impl CelestiaDataSource,
+    blobstream_event_cache: &BlobstreamEventCache,
+    challenge_options: &ChallengeOptions,
+    chain_spec: ChainSpec,
+    execution_block: BlockNumberOrTag,
+    index_blob: SpanSequence,
     challenged_blob: SpanSequence,
     #[cfg(any(feature = "beacon", feature = "history"))] beacon_api_url: url::Url,
     #[cfg(feature = "history")] commitment_block: BlockNumberOrTag,
 ) -> Result<(Receipt, Vec<u8>), anyhow::Error> {
-    let mut blobstream_event_cache = BlobstreamEventCache::new(blobstream_address, root_provider);
+    let da_challenge_guest_data = fetch_da_challenge_guest_data(
+        celestia_client,
+        index_blob,
+        challenged_blob,
+        blobstream_event_cache,
+        true,
+    )
+    .await?;
 
+    prove_da_challenge_with_cache(
+        blobstream_event_cache,
+        challenge_options,
+        chain_spec,
+        execution_block,
+        da_challenge_guest_data,
+        #[cfg(any(feature = "beacon", feature = "history"))]
+        beacon_api_url,
+        #[cfg(feature = "history")]
+        commitment_block,
+    )
+    .await
+}
+
+/// Pre-checks and proves a DA challenge from fetched guest data, reusing the stores of the
+/// cache.
+async fn prove_da_challenge_with_cache(
+    blobstream_event_cache: &BlobstreamEventCache,
+    challenge_options: &ChallengeOptions,
+    chain_spec: ChainSpec,
+    execution_block: BlockNumberOrTag,
+    da_challenge_guest_data: DaChallengeGuestData,
+    #[cfg(any(feature = "beacon", feature = "history"))] beacon_api_url: url::Url,
+    #[cfg(feature = "history")] commitment_block: BlockNumberOrTag,
+) -> Result<(Receipt, Vec<u8>), anyhow::Error> {
+    let guest_input = guest_input_with_cache(
+        blobstream_event_cache,
+        challenge_options,
+        chain_spec,
+        execution_block,
+        da_challenge_guest_data,
+        #[cfg(any(feature = "beacon", feature = "history"))]
+        beacon_api_url,
+        #[cfg(feature = "history")]
+        commitment_block,
+    )
+    .await?;
+
+    prove_da_challenge_with_input(guest_input, challenge_options.segment_limit_po2()).await
+}
+
+/// Fetches the guest data of a DA challenge and returns the input of the guest program, without
+/// proving it. Ex: to record fixtures for the `guest-bench` binary of the methods crate.
+#[allow(clippy::too_many_arguments)]
+pub async fn fetch_guest_input_with_cache(
+    celestia_client: &impl CelestiaDataSource,
+    blobstream_event_cache: &BlobstreamEventCache,
+    challenge_options: &ChallengeOptions,
+    chain_spec: ChainSpec,
+    execution_block: BlockNumberOrTag,
+    index_blob: SpanSequence,
+    challenged_blob: SpanSequence,
+    #[cfg(any(feature = "beacon", feature = "history"))] beacon_api_url: url::Url,
+    #[cfg(feature = "history")] commitment_block: BlockNumberOrTag,
+) -> Result<GuestInput, anyhow::Error> {
     let da_challenge_guest_data = fetch_da_challenge_guest_data(
         celestia_client,
         index_blob,
         challenged_blob,
-        &mut blobstream_event_cache,
+        blobstream_event_cache,
+        false,
     )
     .await?;
 
+    guest_input_with_cache(
+        blobstream_event_cache,
+        challenge_options,
+        chain_spec,
+        execution_block,
+        da_challenge_guest_data,
+        #[cfg(any(feature = "beacon", feature = "history"))]
+        beacon_api_url,
+        #[cfg(feature = "history")]
+        commitment_block,
+    )
+    .await
+}
+
+/// Writes the guest data in its debug JSON format if the options are configured to, see
+/// `ChallengeOptions::with_guest_data_dump()`.
+fn dump_guest_data(
+    challenge_options: &ChallengeOptions,
+    da_challenge_guest_data: &DaChallengeGuestData,
+) -> Result<(), anyhow::Error> {
+    let Some(path) = challenge_options.guest_data_dump() else {
+        return Ok(());
+    };
+    let debug_json = serde_json::to_string_pretty(&da_challenge_guest_data.to_debug_json())?;
+    std::fs::write(path, debug_json)
+        .with_context(|| format!("failed to write the guest data to {}", path.display()))?;
+    log::info!("Guest data written to {}", path.display());
+
+    Ok(())
+}
+
+/// Pre-checks a DA challenge and builds the input of the guest program, reusing the stores of
+/// the cache and of the options.
+async fn guest_input_with_cache(
+    blobstream_event_cache: &BlobstreamEventCache,
+    challenge_options: &ChallengeOptions,
+    chain_spec: ChainSpec,
+    execution_block: BlockNumberOrTag,
+    mut da_challenge_guest_data: DaChallengeGuestData,
+    #[cfg(any(feature = "beacon", feature = "history"))] beacon_api_url: url::Url,
+    #[cfg(feature = "history")] commitment_block: BlockNumberOrTag,
+) -> Result<GuestInput, anyhow::Error> {
+    if let Some(namespace) = challenge_options.namespace() {
+        da_challenge_guest_data = da_challenge_guest_data.with_namespace(namespace);
+    }
+    let blobstream_address = blobstream_event_cache.blobstream_address();
+    let blobstream_impl = blobstream_event_cache.blobstream_impl().await?;
+    log::info!("Blobstream implementation at {blobstream_address}: {blobstream_impl:?}");
+    log::info!("Challenge: {:?}", da_challenge_guest_data.challenge());
+    dump_guest_data(challenge_options, &da_challenge_guest_data)?;
+
+    if challenge_options.prechecks_challenges() {
+        check_blobstream_address(blobstream_event_cache.chain_id().await?, blobstream_address)?;
+        check_chain_spec(&chain_spec)?;
+        check_celestia_chain_id(
+            blobstream_event_cache.chain_id().await?,
+            blobstream_address,
+            &da_challenge_guest_data.celestia_chain_id,
+        )?;
+        let backend = blobstream_event_cache.backend().await?;
+        match precheck_da_challenge(&da_challenge_guest_data, backend.as_ref()).await {
+            Ok(fraud) => log::info!("Host-side pre-check predicts DA fraud: {fraud}"),
+            Err(err)
+                if challenge_options.proves_availability()
+                    && err.chain().any(|cause| cause.is::<BlobIsAvailable>()) =>
+            {
+                log::info!("Host-side pre-check predicts that the blob is available")
+            }
+            Err(err) => {
+                return Err(
+                    err.context("the host-side pre-check predicts that no DA fraud can be proven")
+                )
+            }
+        }
+    }
+
+    let evm_input_key = evm_input_key(
+        blobstream_event_cache,
+        challenge_options,
+        &da_challenge_guest_data,
+        execution_block,
+        #[cfg(feature = "history")]
+        commitment_block,
+    )
+    .await?;
+    let stored_input = match &evm_input_key {
+        Some(key) => challenge_options
+            .access_evm_input_store(|store| store.get(key))?
+            .flatten(),
+        None => None,
+    };
+
+    let (evm_input, blobstream_info) = match stored_input {
+        Some(stored_input) => {
+            log::info!("Reusing the stored preflight of execution block {execution_block}");
+            stored_input
+        }
+        None => {
+            let (evm_input, blobstream_info) = perform_preflight_calls(
+                blobstream_event_cache.eth_provider.clone(),
+                &chain_spec,
+                blobstream_address,
+                blobstream_impl,
+                da_challenge_guest_data.blobstream_attestations(),
+                execution_block,
+                #[cfg(any(feature = "beacon", feature = "history"))]
+                beacon_api_url,
+                #[cfg(feature = "history")]
+                commitment_block,
+            )
+            .await?;
+            if let Some(key) = &evm_input_key {
+                challenge_options.access_evm_input_store(|store| {
+                    store.insert(key, &evm_input, &blobstream_info)
+                })?;
+            }
+            (evm_input, blobstream_info)
+        }
+    };
+
+    let guest_options = GuestOptions {
+        prove_availability: challenge_options.proves_availability(),
+    };
+    Ok(GuestInput {
+        evm_input,
+        chain_spec,
+        blobstream_info,
+        guest_options,
+        da_challenge_guest_data,
+    })
+}
+
+/// Returns the key of the preflight output in the EVM input store of the options, if the output
+/// can be stored. Only numbered blocks are stable, tags such as `latest` are not.
+async fn evm_input_key(
+    blobstream_event_cache: &BlobstreamEventCache,
+    challenge_options: &ChallengeOptions,
+    da_challenge_guest_data: &DaChallengeGuestData,
+    execution_block: BlockNumberOrTag,
+    #[cfg(feature = "history")] commitment_block: BlockNumberOrTag,
+) -> Result<Option<EvmInputKey>> {
+    let BlockNumberOrTag::Number(execution_block_number) = execution_block else {
+        return Ok(None);
+    };
+    if !challenge_options.has_evm_input_store() {
+        return Ok(None);
+    }
+
+    #[cfg(not(any(feature = "beacon", feature = "history")))]
+    let commitment = "block".to_string();
+    #[cfg(all(feature = "beacon", not(feature = "history")))]
+    let commitment = "beacon".to_string();
+    #[cfg(feature = "history")]
+    let commitment = match commitment_block {
+        BlockNumberOrTag::Number(commitment_block_number) => {
+            format!("history:{commitment_block_number}")
+        }
+        _ => return Ok(None),
+    };
+
+    Ok(Some(EvmInputKey::new(
+        blobstream_event_cache.chain_id().await?,
+        execution_block_number,
+        blobstream_event_cache.blobstream_address(),
+        da_challenge_guest_data.blobstream_attestations(),
+        commitment,
+    )))
+}
+
+/// Proves a DA challenge from already fetched guest data.
+///
+/// Unlike `challenge_da_commitment()`, this function does not access Celestia at all: the guest
+/// data can be built from externally supplied attestations and proofs with
+/// `DaChallengeGuestData::new()`. An Ethereum RPC is still required to preflight the Blobstream
+/// calls.
+#[allow(clippy::too_many_arguments)]
+pub async fn prove_da_challenge(
+    eth_provider: RootProvider,
+    chain_spec: ChainSpec,
+    blobstream_address: Address,
+    blobstream_impl: BlobstreamImpl,
+    da_challenge_guest_data: DaChallengeGuestData,
+    execution_block: BlockNumberOrTag,
+    #[cfg(any(feature = "beacon", feature = "history"))] beacon_api_url: url::Url,
+    #[cfg(feature = "history")] commitment_block: BlockNumberOrTag,
+) -> Result<(Receipt, Vec<u8>), anyhow::Error> {
     // Perform the preflight calls to Blobstream's `verifyAttestation()`
     let (evm_input, blobstream_info) = perform_preflight_calls(
-        blobstream_event_cache.eth_provider,
+        eth_provider,
         &chain_spec,
         blobstream_address,
+        blobstream_impl,
         da_challenge_guest_data.blobstream_attestations(),
         execution_block,
         #[cfg(any(feature = "beacon", feature = "history"))]
@@ -496,29 +1189,60 @@ pub async fn challenge_da_commitment(
     )
     .await?;
 
-    let serialized_da_guest_data = bincode::serialize(&da_challenge_guest_data)
-        .with_context(|| "Failed to serialize DA guest data")?;
+    let guest_input = GuestInput {
+        evm_input,
+        chain_spec,
+        blobstream_info,
+        guest_options: GuestOptions::default(),
+        da_challenge_guest_data,
+    };
+
+    prove_da_challenge_with_input(guest_input, None).await
+}
+
+/// Turns the panic of the guest on an available challenged blob into `BlobIsAvailable`. The zkVM
+/// only reports guest panics by their message, which is matched here and nowhere else.
+fn classify_guest_error(err: anyhow::Error) -> anyhow::Error {
+    if format!("{err:#}").contains(BLOB_IS_AVAILABLE_MESSAGE) {
+        log::debug!("Guest execution failed: {err:#}");
+        BlobIsAvailable.into()
+    } else {
+        classify_prover_error(err)
+    }
+}
+
+/// Proves the DA challenge guest program on its input, with the specified kind of receipt.
+///
+/// The guest execution is split in segments of at most `2^segment_limit_po2` cycles, proven one
+/// after the other and aggregated (continuations). Lower limits bound the memory of the prover
+/// for large challenges, ex: large indexes.
+async fn prove_guest_input(
+    guest_input: GuestInput,
+    prover_opts: ProverOpts,
+    segment_limit_po2: Option<u32>,
+) -> Result<Receipt, anyhow::Error> {
+    let guest_input = guest_input.encode()?;
 
     log::info!("Generating proof...");
     let start_time = std::time::Instant::now();
 
     // Create the steel proof.
     let prove_info = task::spawn_blocking(move || {
-        let env = ExecutorEnv::builder()
-            .write(&evm_input)?
-            .write(&chain_spec)?
-            .write(&blobstream_info)?
-            .write_frame(&serialized_da_guest_data)
-            .build()?;
+        let mut builder = ExecutorEnv::builder();
+        if let Some(segment_limit_po2) = segment_limit_po2 {
+            builder.segment_limit_po2(segment_limit_po2);
+        }
+        let env = builder.write_slice(&guest_input).build()?;
 
         default_prover().prove_with_ctx(
             env,
             &VerifierContext::default(),
             DA_CHALLENGE_GUEST_ELF,
-            &ProverOpts::groth16(),
+            &prover_opts,
         )
     })
     .await?
+    .map_err(classify_guest_error)
     .context("failed to create proof")?;
 
     log::info!(
@@ -531,21 +1255,188 @@ pub async fn challenge_da_commitment(
     let journal = &receipt.journal.bytes;
 
     // Decode and log the commitment
-    let journal = Journal::abi_decode(journal, true).context("invalid journal")?;
+    let journal = Journal::decode(journal).context("invalid journal")?;
     log::debug!("Steel commitment: {:?}", journal.commitment);
 
+    Ok(receipt)
+}
+
+/// Proves a DA challenge from the input of the guest program.
+async fn prove_da_challenge_with_input(
+    guest_input: GuestInput,
+    segment_limit_po2: Option<u32>,
+) -> Result<(Receipt, Vec<u8>), anyhow::Error> {
+    let receipt = prove_guest_input(guest_input, ProverOpts::groth16(), segment_limit_po2).await?;
+
     // ABI encode the seal.
     let seal = encode_seal(&receipt).context("invalid receipt")?;
 
     Ok((receipt, seal))
 }
 
-/// Increments the counter smart contract by providing a valid DA challenge ZK proof.
-pub async fn increment_counter<T: Clone + PrivateTransport, P: PrivateProvider<T, Ethereum>>(
-    counter_contract: ICounterInstance<T, P>,
+/// Resolves a block tag, ex: `latest`, to the number of the block it designates now. Several
+/// preflights at the resulting block all commit to the same block.
+async fn resolve_block_number(
+    eth_provider: &RootProvider,
+    block: BlockNumberOrTag,
+) -> Result<BlockNumberOrTag, anyhow::Error> {
+    let tag = match block {
+        BlockNumberOrTag::Number(_) => return Ok(block),
+        BlockNumberOrTag::Parent => {
+            let latest_block_number = eth_provider.get_block_number().await?;
+            return Ok(BlockNumberOrTag::Number(
+                latest_block_number.saturating_sub(1),
+            ));
+        }
+        BlockNumberOrTag::Latest => AlloyBlockNumberOrTag::Latest,
+        BlockNumberOrTag::Safe => AlloyBlockNumberOrTag::Safe,
+        BlockNumberOrTag::Finalized => AlloyBlockNumberOrTag::Finalized,
+    };
+    let resolved_block = eth_provider
+        .get_block_by_number(tag)
+        .await?
+        .with_context(|| format!("the Ethereum RPC has no {block} block"))?;
+
+    Ok(BlockNumberOrTag::Number(resolved_block.header.number))
+}
+
+/// Challenges several blobs of the same index and aggregates the proofs into a single one, see
+/// `aggregate_da_challenge_receipts()`. Each challenge is pre-checked and proven like with
+/// `challenge_da_commitment_with_cache()`, at the same execution block so that all the proofs
+/// share the same Steel commitment. Every challenge must prove DA fraud: availability proofs
+/// cannot be aggregated and are disabled.
+#[allow(clippy::too_many_arguments)]
+pub async fn challenge_da_commitments_aggregated_with_cache(
+    celestia_client: &impl CelestiaDataSource,
+    blobstream_event_cache: &BlobstreamEventCache,
+    challenge_options: &ChallengeOptions,
+    chain_spec: ChainSpec,
+    execution_block: BlockNumberOrTag,
+    index_blob: SpanSequence,
+    challenged_blobs: Vec<SpanSequence>,
+    #[cfg(any(feature = "beacon", feature = "history"))] beacon_api_url: url::Url,
+    #[cfg(feature = "history")] commitment_block: BlockNumberOrTag,
+) -> Result<(Receipt, Vec<u8>), anyhow::Error> {
+    ensure!(!challenged_blobs.is_empty(), "no blob to challenge");
+
+    let eth_provider = &blobstream_event_cache.eth_provider;
+    let execution_block = resolve_block_number(eth_provider, execution_block).await?;
+    #[cfg(feature = "history")]
+    let commitment_block = resolve_block_number(eth_provider, commitment_block).await?;
+    let challenge_options = challenge_options.clone().with_availability_proofs(false);
+
+    // The challenges share the index and often the blocks of their blobs
+    let celestia_client = &MemoizedDataSource::new(celestia_client);
+    let guest_data: Vec<DaChallengeGuestData> = stream::iter(challenged_blobs)
+        .map(|challenged_blob| {
+            fetch_da_challenge_guest_data(
+                celestia_client,
+                index_blob,
+                challenged_blob,
+                blobstream_event_cache,
+                false,
+            )
+        })
+        .buffered(MAX_CONCURRENT_CELESTIA_REQUESTS)
+        .try_collect()
+        .await?;
+
+    // Inner receipts must be succinct to be verified by the aggregator guest program
+    let mut receipts = Vec::with_capacity(guest_data.len());
+    for da_challenge_guest_data in guest_data {
+        let guest_input = guest_input_with_cache(
+            blobstream_event_cache,
+            &challenge_options,
+            chain_spec.clone(),
+            execution_block,
+            da_challenge_guest_data,
+            #[cfg(any(feature = "beacon", feature = "history"))]
+            beacon_api_url.clone(),
+            #[cfg(feature = "history")]
+            commitment_block,
+        )
+        .await?;
+        let receipt = prove_guest_input(
+            guest_input,
+            ProverOpts::succinct(),
+            challenge_options.segment_limit_po2(),
+        )
+        .await?;
+        receipts.push(receipt);
+    }
+
+    aggregate_da_challenge_receipts(receipts).await
+}
+
+/// Aggregates DA challenge proofs of blobs of the same index into a single proof, so that one
+/// on-chain submission settles all of them. The receipts must be succinct: Groth16 receipts
+/// cannot be verified by the aggregator guest program.
+///
+/// # Returns
+///
+/// A tuple containing:
+/// * The ZK proof receipt, committing to an `AggregatedJournal`
+/// * The encoded seal.
+pub async fn aggregate_da_challenge_receipts(
+    receipts: Vec<Receipt>,
+) -> Result<(Receipt, Vec<u8>), anyhow::Error> {
+    let aggregation_input = AggregationInput {
+        challenge_image_id: DA_CHALLENGE_GUEST_ID,
+        journals: receipts
+            .iter()
+            .map(|receipt| receipt.journal.bytes.clone())
+            .collect(),
+    };
+
+    log::info!("Aggregating {} DA challenge proofs...", receipts.len());
+    let start_time = std::time::Instant::now();
+
+    let prove_info = task::spawn_blocking(move || {
+        let mut builder = ExecutorEnv::builder();
+        for receipt in receipts {
+            builder.add_assumption(receipt);
+        }
+        let env = builder.write(&aggregation_input)?.build()?;
+
+        default_prover().prove_with_ctx(
+            env,
+            &VerifierContext::default(),
+            DA_CHALLENGE_AGGREGATOR_ELF,
+            &ProverOpts::groth16(),
+        )
+    })
+    .await?
+    .map_err(classify_prover_error)
+    .context("failed to create aggregated proof")?;
+
+    log::info!(
+        "Aggregated proof generated in {:.2} s",
+        start_time.elapsed().as_secs_f32()
+    );
+
+    let receipt = prove_info.receipt;
+    AggregatedJournal::decode(&receipt.journal.bytes).context("invalid aggregated journal")?;
+    let seal = encode_seal(&receipt).context("invalid receipt")?;
+
+    Ok((receipt, seal))
+}
+
+/// Sends a transaction incrementing the counter smart contract by providing a valid DA challenge
+/// ZK proof. Returns the hash of the transaction, see `confirm_transaction()` to wait for its
+/// confirmation.
+///
+/// If `replaced_tx_hash` is a pending transaction, ex: one that did not confirm in time, the new
+/// transaction replaces it: it reuses its nonce, with fees raised by
+/// `REPLACEMENT_FEE_BUMP_PERCENT`.
+pub async fn send_increment_counter<
+    T: Clone + PrivateTransport,
+    P: PrivateProvider<T, Ethereum>,
+>(
+    counter_contract: &ICounterInstance<T, P>,
     receipt: Receipt,
     seal: Vec<u8>,
-) -> Result<(), anyhow::Error> {
+    replaced_tx_hash: Option<B256>,
+) -> Result<B256, anyhow::Error> {
     // Call ICounter::imageID() to check that the contract has been deployed correctly.
     let contract_image_id = Digest::from(counter_contract.imageID().call().await?._0.0);
     ensure!(contract_image_id == DA_CHALLENGE_GUEST_ID.into());
@@ -556,13 +1447,145 @@ pub async fn increment_counter<T: Clone + PrivateTransport, P: PrivateProvider<T
         ICounter::incrementCall::SIGNATURE,
         counter_contract.address()
     );
-    let call_builder = counter_contract.increment(receipt.journal.bytes.into(), seal.into());
+    let mut call_builder = counter_contract.increment(receipt.journal.bytes.into(), seal.into());
+    if let Some(replaced_tx_hash) = replaced_tx_hash {
+        let replaced_tx = counter_contract
+            .provider()
+            .get_transaction_by_hash(replaced_tx_hash)
+            .await
+            .with_context(|| format!("failed to fetch transaction {replaced_tx_hash}"))?;
+        // A transaction dropped in the meantime does not need to be replaced
+        if let Some(replaced_tx) = replaced_tx {
+            log::info!(
+                "Replacing transaction {replaced_tx_hash} (nonce {})",
+                replaced_tx.nonce()
+            );
+            let bump = |fee: u128| fee + fee * REPLACEMENT_FEE_BUMP_PERCENT / 100 + 1;
+            call_builder = call_builder
+                .nonce(replaced_tx.nonce())
+                .max_fee_per_gas(bump(replaced_tx.max_fee_per_gas()))
+                .max_priority_fee_per_gas(bump(
+                    replaced_tx.max_priority_fee_per_gas().unwrap_or_default(),
+                ));
+        }
+    }
     log::debug!(
         "Send {} {}",
         counter_contract.address(),
         call_builder.calldata()
     );
     let pending_tx = call_builder.send().await?;
+
+    Ok(*pending_tx.tx_hash())
+}
+
+/// Percentage by which the fees of a transaction are raised when replacing it, see
+/// `send_increment_counter()`. Nodes only accept replacements raising both fees by at least 10%.
+const REPLACEMENT_FEE_BUMP_PERCENT: u128 = 25;
+
+/// Delay between two checks of a transaction waiting for confirmation, about an Ethereum block.
+const TRANSACTION_POLL_INTERVAL: Duration = Duration::from_secs(12);
+
+/// Outcome of a transaction sent on-chain, see `confirm_transaction()`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TransactionOutcome {
+    Confirmed,
+    Reverted,
+    /// The node does not know the transaction (anymore), it can be sent again.
+    Dropped,
+}
+
+/// A transaction was still pending when its confirmation timed out. Transient error, see
+/// `crate::retry::is_transient()`: the next attempt replaces the transaction with higher fees.
+#[derive(Debug, Clone, Copy)]
+pub struct ConfirmationTimeout {
+    pub tx_hash: B256,
+    pub timeout: Duration,
+}
+
+impl fmt::Display for ConfirmationTimeout {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "transaction {} not confirmed after {} s",
+            self.tx_hash,
+            self.timeout.as_secs()
+        )
+    }
+}
+
+impl std::error::Error for ConfirmationTimeout {}
+
+/// Returns the outcome of the transaction, `None` while it is pending.
+pub async fn transaction_outcome<T: Clone + PrivateTransport, P: PrivateProvider<T, Ethereum>>(
+    provider: &P,
+    tx_hash: B256,
+) -> Result<Option<TransactionOutcome>, anyhow::Error> {
+    let receipt = provider
+        .get_transaction_receipt(tx_hash)
+        .await
+        .with_context(|| format!("failed to fetch the receipt of transaction {tx_hash}"))?;
+    if let Some(receipt) = receipt {
+        return Ok(Some(if receipt.status() {
+            TransactionOutcome::Confirmed
+        } else {
+            TransactionOutcome::Reverted
+        }));
+    }
+
+    // Mined transactions are known too, the receipt is checked again before giving up
+    let transaction = provider
+        .get_transaction_by_hash(tx_hash)
+        .await
+        .with_context(|| format!("failed to fetch transaction {tx_hash}"))?;
+    Ok(transaction.is_none().then_some(TransactionOutcome::Dropped))
+}
+
+/// Waits until the transaction is included in a block or dropped by the node, for at most
+/// `timeout`. Fails with `ConfirmationTimeout` if the transaction is still pending by then.
+///
+/// Unlike sending it again, this is safe after a failed wait for the confirmation of the
+/// transaction: it may still land.
+pub async fn confirm_transaction<T: Clone + PrivateTransport, P: PrivateProvider<T, Ethereum>>(
+    provider: &P,
+    tx_hash: B256,
+    timeout: Duration,
+) -> Result<TransactionOutcome, anyhow::Error> {
+    let confirmation = async {
+        loop {
+            if let Some(outcome) = transaction_outcome(provider, tx_hash).await? {
+                return Ok::<_, anyhow::Error>(outcome);
+            }
+            tokio::time::sleep(TRANSACTION_POLL_INTERVAL).await;
+        }
+    };
+
+    tokio::time::timeout(timeout, confirmation)
+        .await
+        .map_err(|_| ConfirmationTimeout { tx_hash, timeout })?
+}
+
+/// Increments the counter smart contract once per challenged blob of an aggregated DA challenge
+/// proof, see `aggregate_da_challenge_receipts()`. Returns the hash of the confirmed transaction.
+pub async fn increment_counter_aggregated<
+    T: Clone + PrivateTransport,
+    P: PrivateProvider<T, Ethereum>,
+>(
+    counter_contract: ICounterInstance<T, P>,
+    receipt: Receipt,
+    seal: Vec<u8>,
+) -> Result<B256, anyhow::Error> {
+    let contract_image_id = Digest::from(counter_contract.aggregatorImageID().call().await?._0.0);
+    ensure!(contract_image_id == DA_CHALLENGE_AGGREGATOR_ID.into());
+
+    log::info!(
+        "Sending Tx calling {} Function of {:#}...",
+        ICounter::incrementAggregatedCall::SIGNATURE,
+        counter_contract.address()
+    );
+    let call_builder =
+        counter_contract.incrementAggregated(receipt.journal.bytes.into(), seal.into());
+    let pending_tx = call_builder.send().await?;
     let tx_hash = *pending_tx.tx_hash();
     let receipt = pending_tx
         .get_receipt()
@@ -570,7 +1593,7 @@ pub async fn increment_counter<T: Clone + PrivateTransport, P: PrivateProvider<T
         .with_context(|| format!("transaction did not confirm: {tx_hash}"))?;
     ensure!(receipt.status(), "transaction failed: {}", tx_hash);
 
-    Ok(())
+    Ok(tx_hash)
 }
 
 /// Initializes logging.
@@ -0,0 +1,276 @@
+use alloy_primitives::B256;
+use anyhow::Context;
+use async_trait::async_trait;
+use serde::Serialize;
+use std::time::Duration;
+pub use toolkit::journal::JournalSummary;
+use toolkit::SpanSequence;
+use url::Url;
+
+/// Outcome of a challenge that operators should be notified about.
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum ChallengeEvent {
+    /// The DA fraud proof was generated and accepted on-chain.
+    FraudProven {
+        index_blob: SpanSequence,
+        challenged_blob: SpanSequence,
+        journal: JournalSummary,
+        tx_hash: B256,
+        tx_url: Option<Url>,
+    },
+    /// The DA fraud proof was generated but could not be submitted on-chain.
+    SubmissionFailed {
+        index_blob: SpanSequence,
+        challenged_blob: SpanSequence,
+        journal: Option<JournalSummary>,
+        error: String,
+    },
+}
+
+impl ChallengeEvent {
+    /// Returns a one-line description of the event, for chat integrations.
+    pub fn summary(&self) -> String {
+        match self {
+            ChallengeEvent::FraudProven {
+                challenged_blob,
+                tx_hash,
+                tx_url,
+                ..
+            } => {
+                let tx = tx_url
+                    .as_ref()
+                    .map(Url::to_string)
+                    .unwrap_or_else(|| tx_hash.to_string());
+                format!("DA fraud proven for blob {challenged_blob}: {tx}")
+            }
+            ChallengeEvent::SubmissionFailed {
+                challenged_blob,
+                error,
+                ..
+            } => format!("Failed to submit DA fraud proof for blob {challenged_blob}: {error}"),
+        }
+    }
+}
+
+/// Returns the block explorer link of a transaction, ex: `https://sepolia.etherscan.io/tx/0x...`.
+pub fn explorer_tx_url(explorer_url: &Url, tx_hash: B256) -> Result<Url, anyhow::Error> {
+    let mut tx_url = explorer_url.clone();
+    tx_url
+        .path_segments_mut()
+        .map_err(|_| anyhow::anyhow!("invalid explorer URL: {explorer_url}"))?
+        .pop_if_empty()
+        .push("tx")
+        .push(&tx_hash.to_string());
+
+    Ok(tx_url)
+}
+
+/// Timeout of each notification request, so that an unresponsive endpoint cannot hold up the
+/// publisher.
+const NOTIFICATION_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Returns the HTTP client of the notifiers.
+fn notification_client() -> reqwest::Client {
+    // Like `reqwest::Client::new()`, only fails if the TLS backend cannot be initialized.
+    reqwest::Client::builder()
+        .timeout(NOTIFICATION_TIMEOUT)
+        .build()
+        .expect("failed to build the notification HTTP client")
+}
+
+/// A sink for challenge events.
+#[async_trait]
+pub trait Notifier: Send + Sync {
+    async fn notify(&self, event: &ChallengeEvent) -> Result<(), anyhow::Error>;
+}
+
+/// Posts challenge events as JSON to an arbitrary HTTP endpoint.
+pub struct WebhookNotifier {
+    client: reqwest::Client,
+    url: Url,
+}
+
+impl WebhookNotifier {
+    pub fn new(url: Url) -> Self {
+        Self {
+            client: notification_client(),
+            url,
+        }
+    }
+}
+
+#[async_trait]
+impl Notifier for WebhookNotifier {
+    async fn notify(&self, event: &ChallengeEvent) -> Result<(), anyhow::Error> {
+        self.client
+            .post(self.url.clone())
+            .json(event)
+            .send()
+            .await?
+            .error_for_status()
+            .with_context(|| "webhook returned an error")?;
+
+        Ok(())
+    }
+}
+
+/// Posts challenge events to a Slack incoming webhook.
+pub struct SlackNotifier {
+    client: reqwest::Client,
+    url: Url,
+}
+
+impl SlackNotifier {
+    pub fn new(url: Url) -> Self {
+        Self {
+            client: notification_client(),
+            url,
+        }
+    }
+}
+
+#[async_trait]
+impl Notifier for SlackNotifier {
+    async fn notify(&self, event: &ChallengeEvent) -> Result<(), anyhow::Error> {
+        let payload = serde_json::json!({
+            "text": event.summary(),
+            "attachments": [{
+                "text": format!("```{}```", serde_json::to_string_pretty(event)?),
+            }],
+        });
+
+        self.client
+            .post(self.url.clone())
+            .json(&payload)
+            .send()
+            .await?
+            .error_for_status()
+            .with_context(|| "Slack webhook returned an error")?;
+
+        Ok(())
+    }
+}
+
+/// A set of notifiers that all receive the same events.
+#[derive(Default)]
+pub struct Notifiers {
+    notifiers: Vec<Box<dyn Notifier>>,
+}
+
+impl Notifiers {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with(mut self, notifier: impl Notifier + 'static) -> Self {
+        self.notifiers.push(Box::new(notifier));
+        self
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.notifiers.is_empty()
+    }
+
+    /// Sends the event to all notifiers. Notification failures are logged but never interrupt
+    /// the challenge.
+    pub async fn notify(&self, event: &ChallengeEvent) {
+        for notifier in &self.notifiers {
+            if let Err(e) = notifier.notify(event).await {
+                log::warn!("failed to send challenge notification: {e:#}");
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_primitives::{Address, U256};
+    use toolkit::errors::DaFraudKind;
+    use toolkit::journal::DaOutcome;
+    use toolkit::{BlobstreamImpl, DaChallenge};
+
+    fn journal(challenged_blob: SpanSequence) -> JournalSummary {
+        JournalSummary {
+            version: 1,
+            commitment_id: U256::from(1),
+            commitment_digest: B256::ZERO,
+            commitment_config_id: B256::ZERO,
+            blobstream_address: Address::ZERO,
+            blobstream_impl: BlobstreamImpl::Sp1,
+            outcome: DaOutcome::FraudProven,
+            index_blob: "10:0:4".parse().unwrap(),
+            challenged_blob,
+            challenge: DaChallenge::BlobInIndexIsUnavailable(challenged_blob),
+            fraud_type: Some(DaFraudKind::ShareIndexOutOfBounds),
+            namespace: None,
+            extra_challenged_blobs: vec![],
+            extra_fraud_types: vec![],
+            celestia_chain_id: "celestia".to_string(),
+            index_hash: None,
+            batch_metadata: None,
+        }
+    }
+
+    #[test]
+    fn test_explorer_tx_url() {
+        let tx_hash = B256::repeat_byte(0xab);
+        let expected = format!("https://sepolia.etherscan.io/tx/{tx_hash}");
+
+        for explorer_url in [
+            "https://sepolia.etherscan.io",
+            "https://sepolia.etherscan.io/",
+        ] {
+            let explorer_url = Url::parse(explorer_url).unwrap();
+            assert_eq!(
+                explorer_tx_url(&explorer_url, tx_hash).unwrap().as_str(),
+                expected
+            );
+        }
+
+        let explorer_url = Url::parse("https://explorer.example.com/sepolia/").unwrap();
+        assert_eq!(
+            explorer_tx_url(&explorer_url, tx_hash).unwrap().as_str(),
+            format!("https://explorer.example.com/sepolia/tx/{tx_hash}")
+        );
+
+        let explorer_url = Url::parse("mailto:ops@example.com").unwrap();
+        assert!(explorer_tx_url(&explorer_url, tx_hash).is_err());
+    }
+
+    #[test]
+    fn test_summary() {
+        let index_blob: SpanSequence = "10:0:4".parse().unwrap();
+        let challenged_blob: SpanSequence = "12:8:16".parse().unwrap();
+        let tx_hash = B256::repeat_byte(0xab);
+        let tx_url = Url::parse(&format!("https://sepolia.etherscan.io/tx/{tx_hash}")).unwrap();
+
+        let fraud_proven = |tx_url| ChallengeEvent::FraudProven {
+            index_blob,
+            challenged_blob,
+            journal: journal(challenged_blob),
+            tx_hash,
+            tx_url,
+        };
+        assert_eq!(
+            fraud_proven(Some(tx_url.clone())).summary(),
+            format!("DA fraud proven for blob 12:8:16: {tx_url}")
+        );
+        assert_eq!(
+            fraud_proven(None).summary(),
+            format!("DA fraud proven for blob 12:8:16: {tx_hash}")
+        );
+
+        let submission_failed = ChallengeEvent::SubmissionFailed {
+            index_blob,
+            challenged_blob,
+            journal: None,
+            error: "execution reverted".to_string(),
+        };
+        assert_eq!(
+            submission_failed.summary(),
+            "Failed to submit DA fraud proof for blob 12:8:16: execution reverted"
+        );
+    }
+}
@@ -0,0 +1,53 @@
+use crate::blobstream_backend::BlobstreamBackend;
+use toolkit::challenge::{check_da_challenges, BlobstreamState};
+use toolkit::errors::{BlobIsAvailable, DaFraud, DaGuestError, InputError};
+use toolkit::{BlobstreamAttestation, DaChallengeGuestData};
+
+/// Host view of the Blobstream contract. Attestations are verified on-chain during the preflight
+/// calls, so they are trusted here.
+struct HostBlobstream {
+    max_block_height: u64,
+}
+
+impl BlobstreamState for HostBlobstream {
+    fn verify_attestation(
+        &self,
+        _blobstream_attestation: &BlobstreamAttestation,
+    ) -> Result<(), InputError> {
+        Ok(())
+    }
+
+    fn max_block_height(&self) -> u64 {
+        self.max_block_height
+    }
+}
+
+/// Runs the guest logic natively on the guest data, to predict the outcome of the challenge
+/// before spending minutes proving it. Returns the DA fraud that the guest is expected to prove
+/// for the challenged blob, or an error if the guest would reject the challenge: `BlobIsAvailable`
+/// if any challenged blob is available, the `InputError` of the guest otherwise. Every extra
+/// challenged blob must be DA fraud too.
+///
+/// The Blobstream height bound is read at the latest Ethereum block, which can differ from the
+/// execution block used by the guest.
+pub async fn precheck_da_challenge(
+    da_challenge_guest_data: &DaChallengeGuestData,
+    backend: &dyn BlobstreamBackend,
+) -> Result<DaFraud, anyhow::Error> {
+    let blobstream = HostBlobstream {
+        max_block_height: backend.max_covered_height().await?,
+    };
+
+    match check_da_challenges(da_challenge_guest_data, &blobstream) {
+        // `None` if any of the challenged blobs is available
+        Ok(outcomes) => outcomes
+            .outcomes
+            .into_iter()
+            .map(Result::err)
+            .collect::<Option<Vec<DaFraud>>>()
+            .and_then(|frauds| frauds.into_iter().next())
+            .ok_or_else(|| BlobIsAvailable.into()),
+        Err(DaGuestError::Input(err)) => Err(err.into()),
+        Err(DaGuestError::Fraud(fraud)) => Ok(fraud),
+    }
+}
@@ -0,0 +1,94 @@
+use anyhow::Context;
+use celestia_types::MerkleProof;
+use rusqlite::{params, Connection, OptionalExtension};
+use std::path::Path;
+
+/// On-disk store of Blobstream data root tuple inclusion proofs, keyed by Celestia chain ID,
+/// block height and the Celestia block range of the Blobstream commitment.
+///
+/// The proof of a height within a committed range never changes, so auditors re-running
+/// challenges against the same batches can skip the Celestia RPC entirely.
+pub struct InclusionProofStore {
+    conn: Connection,
+}
+
+impl InclusionProofStore {
+    /// Opens (or creates) the proof store at the specified path.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, anyhow::Error> {
+        let path = path.as_ref();
+        let conn = Connection::open(path)
+            .with_context(|| format!("failed to open proof store at {}", path.display()))?;
+        Self::init(conn)
+    }
+
+    /// Creates a proof store that only lives in memory.
+    pub fn open_in_memory() -> Result<Self, anyhow::Error> {
+        Self::init(Connection::open_in_memory()?)
+    }
+
+    fn init(conn: Connection) -> Result<Self, anyhow::Error> {
+        conn.execute_batch(
+            "CREATE TABLE IF NOT EXISTS data_root_inclusion_proofs (
+                celestia_chain_id TEXT NOT NULL,
+                height            INTEGER NOT NULL,
+                start_block       INTEGER NOT NULL,
+                end_block         INTEGER NOT NULL,
+                proof             BLOB NOT NULL,
+                PRIMARY KEY (celestia_chain_id, height, start_block, end_block)
+            );",
+        )
+        .with_context(|| "failed to initialize proof store")?;
+
+        Ok(Self { conn })
+    }
+
+    /// Returns the inclusion proof of `height` in the `[start_block, end_block)` commitment,
+    /// if known.
+    pub fn get(
+        &self,
+        celestia_chain_id: &str,
+        height: u64,
+        start_block: u64,
+        end_block: u64,
+    ) -> Result<Option<MerkleProof>, anyhow::Error> {
+        let proof: Option<Vec<u8>> = self
+            .conn
+            .query_row(
+                "SELECT proof FROM data_root_inclusion_proofs
+                 WHERE celestia_chain_id = ?1 AND height = ?2
+                   AND start_block = ?3 AND end_block = ?4",
+                params![celestia_chain_id, height, start_block, end_block],
+                |row| row.get(0),
+            )
+            .optional()?;
+
+        proof
+            .map(|proof| bincode::deserialize(&proof).context("invalid stored inclusion proof"))
+            .transpose()
+    }
+
+    /// Stores an inclusion proof. Storing the same proof twice is a no-op.
+    pub fn insert(
+        &self,
+        celestia_chain_id: &str,
+        height: u64,
+        start_block: u64,
+        end_block: u64,
+        proof: &MerkleProof,
+    ) -> Result<(), anyhow::Error> {
+        self.conn.execute(
+            "INSERT OR IGNORE INTO data_root_inclusion_proofs
+             (celestia_chain_id, height, start_block, end_block, proof)
+             VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![
+                celestia_chain_id,
+                height,
+                start_block,
+                end_block,
+                bincode::serialize(proof)?
+            ],
+        )?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,211 @@
+use crate::notifier::JournalSummary;
+use alloy_primitives::B256;
+use anyhow::Context;
+use serde::Serialize;
+use std::path::Path;
+use toolkit::errors::{BlobIsAvailable, InputError};
+use toolkit::journal::DaOutcome;
+use toolkit::SpanSequence;
+
+/// Final outcome of a challenger run. Each outcome maps to a distinct process exit code so that
+/// automation does not have to parse logs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ChallengeOutcome {
+    /// The DA fraud was proven and the proof was submitted on-chain.
+    FraudProven,
+    /// Any error unrelated to the challenge itself: RPC failures, prover errors, etc.
+    InfrastructureError,
+    /// The challenged blob is available, no fraud could be proven. With `--prove-availability`,
+    /// the availability is proven but not submitted.
+    BlobAvailable,
+    /// The challenged blob is not part of the index.
+    BlobNotInIndex,
+}
+
+impl ChallengeOutcome {
+    /// Returns the process exit code of the outcome. Exit code 2 is reserved for command-line
+    /// usage errors.
+    pub fn exit_code(&self) -> u8 {
+        match self {
+            ChallengeOutcome::FraudProven => 0,
+            ChallengeOutcome::InfrastructureError => 1,
+            ChallengeOutcome::BlobAvailable => 3,
+            ChallengeOutcome::BlobNotInIndex => 4,
+        }
+    }
+
+    /// Determines the outcome of a failed challenge from the types of the errors of its chain,
+    /// never from their messages.
+    pub fn from_error(err: &anyhow::Error) -> Self {
+        err.chain()
+            .find_map(|cause| {
+                if cause.is::<BlobIsAvailable>() {
+                    Some(ChallengeOutcome::BlobAvailable)
+                } else if let Some(InputError::ChallengedBlobNotInIndex) = cause.downcast_ref() {
+                    Some(ChallengeOutcome::BlobNotInIndex)
+                } else {
+                    None
+                }
+            })
+            .unwrap_or(ChallengeOutcome::InfrastructureError)
+    }
+}
+
+/// Machine-readable summary of a challenger run.
+#[derive(Debug, Clone, Serialize)]
+pub struct RunReport {
+    pub outcome: ChallengeOutcome,
+    pub exit_code: u8,
+    pub index_blob: SpanSequence,
+    pub challenged_blob: SpanSequence,
+    pub tx_hash: Option<B256>,
+    pub journal: Option<JournalSummary>,
+    pub error: Option<String>,
+    /// Structured version of `error` when the host rejected the inputs of the challenge, ex:
+    /// `{"kind": "challenged_blob_not_in_index"}`, see `InputError`.
+    pub input_error: Option<serde_json::Value>,
+}
+
+impl RunReport {
+    pub fn success(
+        index_blob: SpanSequence,
+        challenged_blob: SpanSequence,
+        tx_hash: Option<B256>,
+        journal: Option<JournalSummary>,
+    ) -> Self {
+        let outcome = match journal.as_ref().map(|journal| journal.outcome) {
+            Some(DaOutcome::BlobAvailable) => ChallengeOutcome::BlobAvailable,
+            _ => ChallengeOutcome::FraudProven,
+        };
+        Self {
+            outcome,
+            exit_code: outcome.exit_code(),
+            index_blob,
+            challenged_blob,
+            tx_hash,
+            journal,
+            error: None,
+            input_error: None,
+        }
+    }
+
+    pub fn failure(
+        index_blob: SpanSequence,
+        challenged_blob: SpanSequence,
+        err: &anyhow::Error,
+    ) -> Self {
+        let outcome = ChallengeOutcome::from_error(err);
+        Self {
+            outcome,
+            exit_code: outcome.exit_code(),
+            index_blob,
+            challenged_blob,
+            tx_hash: None,
+            journal: None,
+            error: Some(format!("{err:#}")),
+            input_error: err
+                .chain()
+                .find_map(|cause| cause.downcast_ref::<InputError>())
+                .and_then(|input_error| serde_json::to_value(input_error).ok()),
+        }
+    }
+
+    /// Writes the report as JSON at the specified path.
+    pub fn write(&self, path: &Path) -> Result<(), anyhow::Error> {
+        let json = serde_json::to_string_pretty(self)?;
+        std::fs::write(path, json)
+            .with_context(|| format!("failed to write run report to {}", path.display()))
+    }
+
+    /// Writes the reports of several challenges as a JSON list at the specified path.
+    pub fn write_all(reports: &[RunReport], path: &Path) -> Result<(), anyhow::Error> {
+        let json = serde_json::to_string_pretty(reports)?;
+        std::fs::write(path, json)
+            .with_context(|| format!("failed to write run report to {}", path.display()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use toolkit::errors::BLOB_IS_AVAILABLE_MESSAGE;
+
+    const BLOB: SpanSequence = SpanSequence {
+        height: 10,
+        start: 0,
+        size: 4,
+    };
+
+    #[test]
+    fn test_exit_codes_are_distinct() {
+        assert_eq!(ChallengeOutcome::FraudProven.exit_code(), 0);
+        assert_eq!(ChallengeOutcome::InfrastructureError.exit_code(), 1);
+        assert_eq!(ChallengeOutcome::BlobAvailable.exit_code(), 3);
+        assert_eq!(ChallengeOutcome::BlobNotInIndex.exit_code(), 4);
+    }
+
+    #[test]
+    fn test_outcome_of_an_available_blob() {
+        let err = anyhow::Error::new(BlobIsAvailable)
+            .context("share sampling")
+            .context("challenge failed");
+        assert_eq!(
+            ChallengeOutcome::from_error(&err),
+            ChallengeOutcome::BlobAvailable
+        );
+
+        let report = RunReport::failure(BLOB, BLOB, &err);
+        assert_eq!(report.outcome, ChallengeOutcome::BlobAvailable);
+        assert_eq!(report.exit_code, 3);
+        assert!(report.input_error.is_none());
+    }
+
+    #[test]
+    fn test_outcome_of_a_blob_not_in_index() {
+        let err = anyhow::Error::new(InputError::ChallengedBlobNotInIndex)
+            .context("the host-side pre-check predicts that no DA fraud can be proven");
+        assert_eq!(
+            ChallengeOutcome::from_error(&err),
+            ChallengeOutcome::BlobNotInIndex
+        );
+
+        let report = RunReport::failure(BLOB, BLOB, &err);
+        assert_eq!(report.exit_code, 4);
+        assert_eq!(
+            report.input_error,
+            Some(serde_json::json!({"kind": "challenged_blob_not_in_index"}))
+        );
+    }
+
+    #[test]
+    fn test_outcome_of_other_errors() {
+        let input_error = anyhow::Error::new(InputError::InvalidNumberOfLeavesInProof);
+        assert_eq!(
+            ChallengeOutcome::from_error(&input_error),
+            ChallengeOutcome::InfrastructureError
+        );
+        assert_eq!(RunReport::failure(BLOB, BLOB, &input_error).exit_code, 1);
+
+        // Messages do not determine the outcome, only error types do
+        let message_only = anyhow::anyhow!("{BLOB_IS_AVAILABLE_MESSAGE}").context("RPC failed");
+        assert_eq!(
+            ChallengeOutcome::from_error(&message_only),
+            ChallengeOutcome::InfrastructureError
+        );
+        let message_only = anyhow::anyhow!("{}", InputError::ChallengedBlobNotInIndex);
+        assert_eq!(
+            ChallengeOutcome::from_error(&message_only),
+            ChallengeOutcome::InfrastructureError
+        );
+    }
+
+    #[test]
+    fn test_outcome_of_a_submitted_proof() {
+        let tx_hash = Some(B256::repeat_byte(1));
+        let report = RunReport::success(BLOB, BLOB, tx_hash, None);
+        assert_eq!(report.outcome, ChallengeOutcome::FraudProven);
+        assert_eq!(report.exit_code, 0);
+        assert!(report.error.is_none());
+    }
+}
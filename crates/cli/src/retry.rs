@@ -0,0 +1,309 @@
+use crate::celestia_data_source::CelestiaTimeout;
+use crate::ConfirmationTimeout;
+use jsonrpsee::core::ClientError;
+use reqwest::StatusCode;
+use risc0_steel::alloy::contract::Error as ContractError;
+use risc0_steel::alloy::providers::PendingTransactionError;
+use risc0_steel::alloy::transports::{RpcError, TransportError, TransportErrorKind};
+use std::future::Future;
+use std::io;
+use std::time::Duration;
+use tokio::time::error::Elapsed;
+
+/// Errors of the geth transaction pool (JSON-RPC code -32000) that another attempt can get
+/// past, ex: by pricing the transaction again.
+const RETRYABLE_TX_POOL_ERRORS: &[&str] = &[
+    "nonce too low",
+    "transaction underpriced",
+    "replacement transaction underpriced",
+];
+
+/// Returns true if the error is likely to go away by itself, false if retrying is pointless
+/// (ex: invalid inputs, blob is available).
+///
+/// Errors are classified by type: I/O errors by kind, HTTP errors by status (rate limiting,
+/// gateway errors), RPC errors by transport failure or JSON-RPC error code, and timeouts. Errors
+/// of other types, ex: invalid Celestia headers or proofs, are not transient.
+pub fn is_transient(err: &anyhow::Error) -> bool {
+    err.chain().any(|cause| {
+        if let Some(e) = cause.downcast_ref::<io::Error>() {
+            is_transient_io_error(e.kind())
+        } else if let Some(e) = cause.downcast_ref::<reqwest::Error>() {
+            e.is_timeout() || e.is_connect() || e.status().is_some_and(is_transient_status)
+        } else if let Some(e) = cause.downcast_ref::<ClientError>() {
+            is_transient_celestia_error(e)
+        } else if let Some(e) = cause.downcast_ref::<TransportError>() {
+            is_transient_rpc_error(e)
+        } else if let Some(ContractError::TransportError(e)) = cause.downcast_ref::<ContractError>()
+        {
+            is_transient_rpc_error(e)
+        } else if let Some(PendingTransactionError::TransportError(e)) =
+            cause.downcast_ref::<PendingTransactionError>()
+        {
+            is_transient_rpc_error(e)
+        } else {
+            cause.is::<Elapsed>()
+                || cause.is::<CelestiaTimeout>()
+                || cause.is::<ConfirmationTimeout>()
+        }
+    })
+}
+
+fn is_transient_io_error(kind: io::ErrorKind) -> bool {
+    matches!(
+        kind,
+        io::ErrorKind::TimedOut
+            | io::ErrorKind::ConnectionRefused
+            | io::ErrorKind::ConnectionReset
+            | io::ErrorKind::ConnectionAborted
+            | io::ErrorKind::NotConnected
+            | io::ErrorKind::BrokenPipe
+            | io::ErrorKind::UnexpectedEof
+            | io::ErrorKind::Interrupted
+            | io::ErrorKind::OutOfMemory
+    )
+}
+
+fn is_transient_status(status: StatusCode) -> bool {
+    matches!(
+        status,
+        StatusCode::REQUEST_TIMEOUT
+            | StatusCode::TOO_MANY_REQUESTS
+            | StatusCode::INTERNAL_SERVER_ERROR
+            | StatusCode::BAD_GATEWAY
+            | StatusCode::SERVICE_UNAVAILABLE
+            | StatusCode::GATEWAY_TIMEOUT
+    )
+}
+
+/// Errors of the JSON-RPC client of Celestia nodes. Errors returned by the node itself (ex: a
+/// pruned or unknown block) are not transient, failures to reach it are.
+fn is_transient_celestia_error(err: &ClientError) -> bool {
+    match err {
+        ClientError::Transport(_) | ClientError::RestartNeeded(_) | ClientError::RequestTimeout => {
+            true
+        }
+        ClientError::Call(e) => e.code() == 429,
+        _ => false,
+    }
+}
+
+fn is_transient_rpc_error(err: &TransportError) -> bool {
+    match err {
+        RpcError::Transport(TransportErrorKind::HttpError(e)) => {
+            StatusCode::from_u16(e.status).is_ok_and(is_transient_status)
+        }
+        RpcError::Transport(TransportErrorKind::BackendGone) => true,
+        RpcError::Transport(TransportErrorKind::Custom(e)) => e
+            .downcast_ref::<reqwest::Error>()
+            .is_some_and(|e| e.is_timeout() || e.is_connect()),
+        RpcError::ErrorResp(payload) => match payload.code {
+            // Rate limited, limit exceeded (EIP-1474)
+            429 | -32005 => true,
+            -32000 => RETRYABLE_TX_POOL_ERRORS.contains(&&*payload.message),
+            _ => false,
+        },
+        _ => false,
+    }
+}
+
+/// Turns a failure of the prover into an error classified as transient if the prover ran out
+/// of memory, which a later attempt may not, ex: once concurrent proofs completed. The prover
+/// only reports it in its error message.
+pub fn classify_prover_error(err: anyhow::Error) -> anyhow::Error {
+    let message = format!("{err:#}").to_lowercase();
+    if message.contains("out of memory") || message.contains("out_of_memory") {
+        io::Error::new(io::ErrorKind::OutOfMemory, message).into()
+    } else {
+        err
+    }
+}
+
+/// Exponential backoff policy for retrying failed proving and submission attempts, and Celestia
+/// requests.
+#[derive(Debug, Clone)]
+pub struct RetryPolicy {
+    /// Maximum number of attempts, including the first one.
+    pub max_attempts: u32,
+    /// Delay before the first retry. Doubles after every failed attempt.
+    pub initial_backoff: Duration,
+    /// Upper bound of the delay between two attempts.
+    pub max_backoff: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            initial_backoff: Duration::from_secs(5),
+            max_backoff: Duration::from_secs(300),
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// A policy that never retries.
+    pub fn no_retry() -> Self {
+        Self {
+            max_attempts: 1,
+            ..Default::default()
+        }
+    }
+
+    /// Returns the delay to wait after `failed_attempts` failed attempts.
+    pub fn backoff(&self, failed_attempts: u32) -> Duration {
+        let exponent = failed_attempts.saturating_sub(1).min(31);
+        self.initial_backoff
+            .saturating_mul(1 << exponent)
+            .min(self.max_backoff)
+    }
+
+    /// Returns the policy left once `used_attempts` attempts were already spent, ex: by a
+    /// previous run of the same challenge job.
+    pub fn remaining(&self, used_attempts: u32) -> Self {
+        Self {
+            max_attempts: self.max_attempts.saturating_sub(used_attempts),
+            ..self.clone()
+        }
+    }
+}
+
+/// Runs `attempt_fn` until it succeeds, fails with a non-transient error or the policy runs
+/// out of attempts.
+pub async fn retry_with_backoff<T, F, Fut>(
+    policy: &RetryPolicy,
+    operation: &str,
+    mut attempt_fn: F,
+) -> Result<T, anyhow::Error>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T, anyhow::Error>>,
+{
+    if policy.max_attempts == 0 {
+        return Err(anyhow::anyhow!(
+            "{operation}: maximum number of attempts reached"
+        ));
+    }
+
+    let mut failed_attempts = 0;
+    loop {
+        match attempt_fn().await {
+            Ok(value) => return Ok(value),
+            Err(e) => {
+                failed_attempts += 1;
+                if failed_attempts >= policy.max_attempts || !is_transient(&e) {
+                    return Err(e);
+                }
+
+                let backoff = policy.backoff(failed_attempts);
+                log::warn!(
+                    "{operation} failed (attempt {failed_attempts}/{}), retrying in {:.1} s: {e:#}",
+                    policy.max_attempts,
+                    backoff.as_secs_f32()
+                );
+                tokio::time::sleep(backoff).await;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use risc0_steel::alloy::rpc::json_rpc::ErrorPayload;
+    use risc0_steel::alloy::transports::HttpError;
+
+    #[test]
+    fn test_backoff_is_exponential_and_capped() {
+        let policy = RetryPolicy {
+            max_attempts: 10,
+            initial_backoff: Duration::from_secs(1),
+            max_backoff: Duration::from_secs(10),
+        };
+
+        assert_eq!(policy.backoff(1), Duration::from_secs(1));
+        assert_eq!(policy.backoff(2), Duration::from_secs(2));
+        assert_eq!(policy.backoff(4), Duration::from_secs(8));
+        assert_eq!(policy.backoff(5), Duration::from_secs(10));
+        assert_eq!(policy.backoff(100), Duration::from_secs(10));
+    }
+
+    #[test]
+    fn test_is_transient() {
+        let timeout = io::Error::from(io::ErrorKind::TimedOut);
+        assert!(is_transient(
+            &anyhow::Error::new(timeout).context("failed to fetch")
+        ));
+        assert!(!is_transient(&anyhow::Error::new(io::Error::from(
+            io::ErrorKind::PermissionDenied
+        ))));
+
+        let http_error = |status| {
+            anyhow::Error::new(TransportError::Transport(TransportErrorKind::HttpError(
+                HttpError {
+                    status,
+                    body: String::new(),
+                },
+            )))
+        };
+        assert!(is_transient(&http_error(429)));
+        assert!(is_transient(&http_error(503)));
+        assert!(!is_transient(&http_error(401)));
+
+        let rpc_error = |code, message: &'static str| {
+            let payload = ErrorPayload {
+                code,
+                message: message.into(),
+                data: None,
+            };
+            anyhow::Error::new(TransportError::ErrorResp(payload)).context("failed to send")
+        };
+        assert!(is_transient(&rpc_error(
+            -32000,
+            "replacement transaction underpriced"
+        )));
+        assert!(is_transient(&rpc_error(-32005, "query limit exceeded")));
+        assert!(!is_transient(&rpc_error(-32000, "insufficient funds")));
+        assert!(!is_transient(&rpc_error(3, "execution reverted")));
+
+        let celestia_timeout = CelestiaTimeout(Duration::from_secs(60));
+        assert!(is_transient(
+            &anyhow::Error::new(celestia_timeout).context("failed to fetch the header")
+        ));
+        let confirmation_timeout = ConfirmationTimeout {
+            tx_hash: Default::default(),
+            timeout: Duration::from_secs(600),
+        };
+        assert!(is_transient(
+            &anyhow::Error::new(confirmation_timeout).context("transaction did not confirm")
+        ));
+        assert!(is_transient(&anyhow::Error::new(
+            ClientError::RequestTimeout
+        )));
+        assert!(is_transient(&anyhow::Error::new(ClientError::Transport(
+            Box::new(io::Error::from(io::ErrorKind::ConnectionReset))
+        ))));
+        assert!(!is_transient(&anyhow::Error::new(ClientError::Custom(
+            "header: not found".to_string()
+        ))));
+
+        // Messages alone do not make an error transient
+        assert!(!is_transient(&anyhow::anyhow!(
+            "HTTP 429 Too Many Requests"
+        )));
+        assert!(!is_transient(&anyhow::anyhow!(
+            "the specified blob is available, DA challenge failed"
+        )));
+    }
+
+    #[test]
+    fn test_prover_out_of_memory_is_transient() {
+        let out_of_memory = classify_prover_error(anyhow::anyhow!("CUDA_ERROR_OUT_OF_MEMORY"));
+        assert!(is_transient(
+            &out_of_memory.context("failed to create proof")
+        ));
+        assert!(!is_transient(&classify_prover_error(anyhow::anyhow!(
+            "guest panicked: invalid input"
+        ))));
+    }
+}
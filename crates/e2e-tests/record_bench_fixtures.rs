@@ -0,0 +1,186 @@
+//! Records the guest inputs executed by the `guest-bench` binary of the methods crate, one per
+//! kind of challenge. Not run by default:
+//!
+//! `cargo test -p e2e-tests --test record-bench-fixtures -- --ignored`
+
+use alloy::providers::Provider;
+use celestia_rpc::HeaderClient;
+use cli::challenge_options::ChallengeOptions;
+use cli::event_cache::BlobstreamEventCache;
+use cli::fetch_guest_input_with_cache;
+use risc0_steel::host::BlockNumberOrTag;
+use rstest::rstest;
+use std::path::PathBuf;
+use test_toolkit::blobstream::wait_for_blobstream_inclusion_with_timeout;
+use test_toolkit::index_blob::{
+    create_and_publish_index_blob, publish_index, publish_index_blob_with_bad_blob_position,
+    publish_single_blob, DEFAULT_NAMESPACE,
+};
+use test_toolkit::test_env::{test_env, TestEnv};
+use toolkit::{BlobIndex, SpanSequence};
+
+const FIXTURE_DIR: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/../methods/fixtures");
+
+/// Number of entries of the large index, about 700 shares.
+const LARGE_INDEX_ENTRIES: u32 = 20_000;
+
+/// Waits for the index blob to be covered by Blobstream, then writes the guest input of the
+/// challenge to `<FIXTURE_DIR>/<name>.bin`.
+async fn record_fixture(
+    test_env: TestEnv,
+    name: &str,
+    index_blob: SpanSequence,
+    challenged_blob: SpanSequence,
+) {
+    let TestEnv {
+        provider,
+        counter_contract: _counter_contract,
+        blobstream_contract,
+        celestia_client,
+    } = test_env;
+
+    wait_for_blobstream_inclusion_with_timeout(
+        &blobstream_contract,
+        index_blob.height,
+        std::time::Duration::from_secs(120),
+    )
+    .await
+    .expect("failed or timed out waiting for blobstream inclusion");
+
+    let blobstream_event_cache =
+        BlobstreamEventCache::new(*blobstream_contract.address(), provider.root().clone());
+    let challenge_options = ChallengeOptions::default();
+    let guest_input = fetch_guest_input_with_cache(
+        &celestia_client,
+        &blobstream_event_cache,
+        &challenge_options,
+        TestEnv::chain_spec(),
+        BlockNumberOrTag::Latest,
+        index_blob,
+        challenged_blob,
+    )
+    .await
+    .expect("failed to build the guest input");
+
+    std::fs::create_dir_all(FIXTURE_DIR).expect("failed to create the fixture directory");
+    let path = PathBuf::from(FIXTURE_DIR).join(format!("{name}.bin"));
+    let guest_input = guest_input
+        .encode()
+        .expect("failed to encode the guest input");
+    std::fs::write(&path, guest_input).expect("failed to write the fixture");
+}
+
+/// Returns the number of shares of the ODS of the block.
+async fn ods_size(celestia_client: &celestia_rpc::Client, height: u64) -> u32 {
+    let block_header = celestia_client
+        .header_get_by_height(height)
+        .await
+        .expect("failed to get block header");
+    let ods_width = block_header.dah.square_width() as u32 / 2;
+    ods_width * ods_width
+}
+
+#[rstest]
+#[ignore = "records benchmark fixtures"]
+#[tokio::test]
+async fn index_unavailable(#[future] test_env: TestEnv) {
+    let test_env = test_env.await;
+
+    let (_index, index_span_sequence) =
+        create_and_publish_index_blob(&test_env.celestia_client, 4, 1024, 4)
+            .await
+            .expect("failed to publish blobs");
+    let bad_span_sequence = SpanSequence {
+        start: ods_size(&test_env.celestia_client, index_span_sequence.height).await + 1,
+        ..index_span_sequence
+    };
+
+    record_fixture(
+        test_env,
+        "index_unavailable",
+        bad_span_sequence,
+        bad_span_sequence,
+    )
+    .await;
+}
+
+#[rstest]
+#[ignore = "records benchmark fixtures"]
+#[tokio::test]
+async fn blob_in_index_unavailable(#[future] test_env: TestEnv) {
+    let test_env = test_env.await;
+
+    let (index, index_span_sequence) =
+        publish_index_blob_with_bad_blob_position(&test_env.celestia_client)
+            .await
+            .expect("failed to publish blobs");
+
+    record_fixture(
+        test_env,
+        "blob_in_index_unavailable",
+        index_span_sequence,
+        index.blobs[0],
+    )
+    .await;
+}
+
+#[rstest]
+#[ignore = "records benchmark fixtures"]
+#[tokio::test]
+async fn unreadable_index(#[future] test_env: TestEnv) {
+    let test_env = test_env.await;
+
+    let bad_index_span_sequence = publish_single_blob(&test_env.celestia_client, 1024)
+        .await
+        .expect("failed to publish fake index blob");
+    let challenged_span_sequence = SpanSequence {
+        height: bad_index_span_sequence.height,
+        start: 0,
+        size: 1,
+    };
+
+    record_fixture(
+        test_env,
+        "unreadable_index",
+        bad_index_span_sequence,
+        challenged_span_sequence,
+    )
+    .await;
+}
+
+/// The challenged blob is the last entry of a large index, so that the whole index is read.
+#[rstest]
+#[ignore = "records benchmark fixtures"]
+#[tokio::test]
+async fn large_index(#[future] test_env: TestEnv) {
+    let test_env = test_env.await;
+
+    let head = test_env
+        .celestia_client
+        .header_local_head()
+        .await
+        .expect("failed to get the Celestia head");
+    let height = head.height().value();
+    let ods_size = ods_size(&test_env.celestia_client, height).await;
+    let index = BlobIndex::new(
+        (0..LARGE_INDEX_ENTRIES)
+            .map(|i| SpanSequence {
+                height,
+                start: ods_size + 1 + i,
+                size: 1,
+            })
+            .collect(),
+    );
+    let index_span_sequence = publish_index(&test_env.celestia_client, &index, DEFAULT_NAMESPACE)
+        .await
+        .expect("failed to publish index");
+    let challenged_blob = *index.blobs.last().expect("the index is not empty");
+
+    record_fixture(
+        test_env,
+        "large_index",
+        index_span_sequence,
+        challenged_blob,
+    )
+    .await;
+}
@@ -1,16 +1,25 @@
 //! End-to-end smoke test: prove the contract really lives on chain.
 
 use alloy::primitives::Address;
-use alloy::providers::Provider;
+use alloy::providers::{DynProvider, Provider};
 use celestia_rpc::Client as CelestiaClient;
-use cli::{challenge_da_commitment, logging_init};
+use cli::challenge_options::ChallengeOptions;
+use cli::event_cache::BlobstreamEventCache;
+use cli::{
+    challenge_blob_data_with_cache, challenge_da_commitment, fetch_da_challenge_guest_data,
+    logging_init, prove_da_challenge,
+};
 use risc0_steel::config::ChainSpec;
 use risc0_steel::host::BlockNumberOrTag;
 use rstest::rstest;
 use test_toolkit::blobstream::wait_for_blobstream_inclusion_with_timeout;
-use test_toolkit::index_blob::{create_and_publish_index_blob, publish_single_blob};
+use test_toolkit::contracts::Blobstream0::Blobstream0Instance;
+use test_toolkit::index_blob::{
+    create_and_publish_index_blob, create_and_publish_index_blob_with_data_hashes,
+    publish_index_blob_with_bad_blob_position, publish_single_blob,
+};
 use test_toolkit::test_env::{test_env, TestEnv};
-use toolkit::SpanSequence;
+use toolkit::{BlobIndex, BlobstreamImpl, DaChallenge, DaChallengeGuestData, SpanSequence};
 
 const BLOBS_PER_BLOCK: usize = 10;
 
@@ -37,7 +46,7 @@ async fn assert_challenge_error<P: Provider>(
         BlockNumberOrTag::Number(current_eth_block),
         blobstream_address,
         index_span_sequence,
-        challenged_span_sequence,
+        DaChallenge::availability(index_span_sequence, challenged_span_sequence),
     )
     .await;
 
@@ -139,6 +148,57 @@ async fn challenge_valid_index_blob(#[future] test_env: TestEnv) {
     }
 }
 
+/// Challenges the data of blobs that match the data hashes of their index entries. This test
+/// expects that the challenge will fail as the data is correct.
+#[rstest]
+#[tokio::test]
+async fn challenge_valid_blob_data(#[future] test_env: TestEnv) {
+    logging_init();
+
+    let TestEnv {
+        provider,
+        counter_contract: _counter_contract,
+        blobstream_contract,
+        celestia_client,
+    } = test_env.await;
+
+    let (index, index_span_sequence) =
+        create_and_publish_index_blob_with_data_hashes(&celestia_client, 3, 1024, 3, None)
+            .await
+            .expect("failed to publish index blob");
+
+    wait_for_blobstream_inclusion_with_timeout(
+        &blobstream_contract,
+        index_span_sequence.height,
+        std::time::Duration::from_secs(120),
+    )
+    .await
+    .expect("failed or timed out waiting for blobstream inclusion");
+
+    let blobstream_event_cache =
+        BlobstreamEventCache::new(*blobstream_contract.address(), provider.root().clone());
+    let challenge_options = ChallengeOptions::default();
+    let result = challenge_blob_data_with_cache(
+        &celestia_client,
+        &blobstream_event_cache,
+        &challenge_options,
+        TestEnv::chain_spec(),
+        BlockNumberOrTag::Latest,
+        index_span_sequence,
+        index.blobs[0],
+    )
+    .await;
+
+    let err = result.expect_err("the blob data is correct");
+    assert!(
+        err.root_cause()
+            .to_string()
+            .contains("the specified blob is available, DA challenge failed"),
+        "unexpected error: {}",
+        err.root_cause()
+    );
+}
+
 /// Challenges a blob that is not part of the index blob. This test expects that the challenge
 /// will fail as the blob is not part of the index blob.
 #[rstest]
@@ -187,20 +247,231 @@ async fn challenge_blob_not_in_index(#[future] test_env: TestEnv) {
     .await;
 }
 
+/// Challenges a blob of a valid index, with guest data altered by a malicious host. This test
+/// expects that the guest rejects the altered data.
+async fn assert_altered_challenge_error<P: Provider>(
+    celestia_client: &CelestiaClient,
+    provider: &P,
+    blobstream_address: Address,
+    index_span_sequence: SpanSequence,
+    challenged_span_sequence: SpanSequence,
+    alter: impl FnOnce(&mut DaChallengeGuestData),
+    error_message: &str,
+) {
+    let current_eth_block = provider
+        .get_block_number()
+        .await
+        .expect("failed to get ETH block height");
+
+    let root_provider = provider.root().clone();
+    let blobstream_event_cache =
+        BlobstreamEventCache::new(blobstream_address, root_provider.clone());
+    let mut da_challenge_guest_data = fetch_da_challenge_guest_data(
+        celestia_client,
+        index_span_sequence,
+        challenged_span_sequence,
+        &blobstream_event_cache,
+        false,
+    )
+    .await
+    .expect("failed to fetch guest data");
+    alter(&mut da_challenge_guest_data);
+
+    let result = prove_da_challenge(
+        root_provider,
+        TestEnv::chain_spec(),
+        blobstream_address,
+        BlobstreamImpl::R0,
+        da_challenge_guest_data,
+        BlockNumberOrTag::Number(current_eth_block),
+    )
+    .await;
+
+    assert!(result.is_err());
+    let err = result.unwrap_err();
+    assert!(
+        err.root_cause().to_string().contains(error_message),
+        "unexpected error: {}",
+        err.root_cause()
+    );
+}
+
+/// Publishes a valid index of `n_blobs` blobs and waits for Blobstream to cover it.
+async fn publish_covered_index(
+    celestia_client: &CelestiaClient,
+    blobstream_contract: &Blobstream0Instance<(), DynProvider>,
+    n_blobs: usize,
+) -> (BlobIndex, SpanSequence) {
+    let blob_size = 64;
+    let (index, index_span_sequence) =
+        create_and_publish_index_blob(celestia_client, n_blobs, blob_size, BLOBS_PER_BLOCK)
+            .await
+            .expect("failed to publish index blob");
+
+    wait_for_blobstream_inclusion_with_timeout(
+        blobstream_contract,
+        index_span_sequence.height,
+        std::time::Duration::from_secs(120),
+    )
+    .await
+    .expect("failed or timed out waiting for blobstream inclusion");
+
+    (index, index_span_sequence)
+}
+
+/// Challenges a blob of a valid index, with the share proof of the last row of the index blob
+/// withheld by the host. The guest must not reconstruct the index from a subset of its shares.
 #[rstest]
-#[ignore = "not implemented yet"]
 #[tokio::test]
 async fn challenge_altered_with_incomplete_index_shares(#[future] test_env: TestEnv) {
-    let _test_env = test_env.await;
     logging_init();
-    todo!()
+
+    let TestEnv {
+        provider,
+        counter_contract: _counter_contract,
+        blobstream_contract,
+        celestia_client,
+    } = test_env.await;
+
+    let (index, index_span_sequence) =
+        publish_covered_index(&celestia_client, &blobstream_contract, 3).await;
+
+    assert_altered_challenge_error(
+        &celestia_client,
+        &provider,
+        *blobstream_contract.address(),
+        index_span_sequence,
+        index.blobs[0],
+        |guest_data| {
+            let index_blob_proof_data = guest_data.index_blob_proof_data.as_mut().unwrap();
+            index_blob_proof_data.share_proofs.pop_last();
+        },
+        "shares in share proofs, got",
+    )
+    .await;
 }
 
+/// Challenges a blob of a valid index, with the share proof of the first share of the index blob
+/// moved to the second share by the host.
 #[rstest]
-#[ignore = "not implemented yet"]
 #[tokio::test]
 async fn challenge_with_index_shares_out_of_order(#[future] test_env: TestEnv) {
-    let _test_env = test_env.await;
     logging_init();
-    todo!()
+
+    let TestEnv {
+        provider,
+        counter_contract: _counter_contract,
+        blobstream_contract,
+        celestia_client,
+    } = test_env.await;
+
+    // Index entries take 16 bytes, 40 of them do not fit in a single share.
+    let (index, index_span_sequence) =
+        publish_covered_index(&celestia_client, &blobstream_contract, 40).await;
+    assert!(
+        index_span_sequence.size >= 2,
+        "the index blob must span several shares"
+    );
+    let (first, second) = (index_span_sequence.start, index_span_sequence.start + 1);
+
+    assert_altered_challenge_error(
+        &celestia_client,
+        &provider,
+        *blobstream_contract.address(),
+        index_span_sequence,
+        index.blobs[0],
+        |guest_data| {
+            let share_proofs = &mut guest_data
+                .index_blob_proof_data
+                .as_mut()
+                .unwrap()
+                .share_proofs;
+            let first_proof = share_proofs.remove(&first).unwrap();
+            // Share proofs cover whole rows, the second share starts a proof only if it starts a
+            // row
+            if let Some(second_proof) = share_proofs.remove(&second) {
+                share_proofs.insert(first, second_proof);
+            }
+            share_proofs.insert(second, first_proof);
+        },
+        "invalid share proof start index",
+    )
+    .await;
+}
+
+/// Challenges a blob of a valid index, with the block proof of the index blob keyed by the host
+/// at another height.
+#[rstest]
+#[tokio::test]
+async fn challenge_with_mismatched_block_proof(#[future] test_env: TestEnv) {
+    logging_init();
+
+    let TestEnv {
+        provider,
+        counter_contract: _counter_contract,
+        blobstream_contract,
+        celestia_client,
+    } = test_env.await;
+
+    let (index, index_span_sequence) =
+        publish_covered_index(&celestia_client, &blobstream_contract, 3).await;
+
+    assert_altered_challenge_error(
+        &celestia_client,
+        &provider,
+        *blobstream_contract.address(),
+        index_span_sequence,
+        index.blobs[0],
+        |guest_data| {
+            let height = index_span_sequence.height;
+            let block_proof = guest_data.block_proofs.remove(&height).unwrap();
+            guest_data.block_proofs.insert(height + 1, block_proof);
+        },
+        "the block proof of block",
+    )
+    .await;
+}
+
+/// Challenges an available index blob, with the block proof of a blob of the index left in the
+/// guest data by the host. Block proofs off the path of the challenge are rejected.
+#[rstest]
+#[tokio::test]
+async fn challenge_with_extraneous_block_proof(#[future] test_env: TestEnv) {
+    logging_init();
+
+    let TestEnv {
+        provider,
+        counter_contract: _counter_contract,
+        blobstream_contract,
+        celestia_client,
+    } = test_env.await;
+
+    let (index, index_span_sequence) = publish_index_blob_with_bad_blob_position(&celestia_client)
+        .await
+        .expect("failed to publish blobs");
+    wait_for_blobstream_inclusion_with_timeout(
+        &blobstream_contract,
+        index_span_sequence.height,
+        std::time::Duration::from_secs(120),
+    )
+    .await
+    .expect("failed or timed out waiting for blobstream inclusion");
+
+    assert_altered_challenge_error(
+        &celestia_client,
+        &provider,
+        *blobstream_contract.address(),
+        index_span_sequence,
+        index.blobs[0],
+        |guest_data| {
+            assert!(
+                guest_data.block_proofs.contains_key(&index.blobs[0].height)
+                    && index.blobs[0].height != index_span_sequence.height,
+                "the guest data must hold the block proof of the blob"
+            );
+            guest_data.challenged_blob = guest_data.index_blob;
+        },
+        "unexpected block proof for block",
+    )
+    .await;
 }
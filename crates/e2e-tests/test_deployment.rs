@@ -3,8 +3,9 @@
 use alloy::primitives::U256;
 use alloy::providers::Provider;
 use celestia_rpc::{BlobClient, HeaderClient, TxConfig};
-use celestia_types::{nmt::Namespace, AppVersion, Blob};
+use celestia_types::{nmt::Namespace, Blob};
 use rstest::rstest;
+use test_toolkit::index_blob::current_app_version;
 use test_toolkit::test_env::{test_env, TestEnv};
 
 #[rstest]
@@ -58,7 +59,10 @@ async fn celestia_submit_blob(#[future] test_env: TestEnv) {
     let celestia_client = test_env.celestia_client;
 
     let namespace = Namespace::new_v0(&[0xDE, 0xAD, 0xBE, 0xEF]).expect("invalid namespace");
-    let blob = Blob::new(namespace, vec![0xCA, 0xFE], AppVersion::V2).expect("invalid blob");
+    let app_version = current_app_version(&celestia_client)
+        .await
+        .expect("failed to fetch app version");
+    let blob = Blob::new(namespace, vec![0xCA, 0xFE], app_version).expect("invalid blob");
 
     let height = celestia_client
         .blob_submit(&[blob], TxConfig::default())
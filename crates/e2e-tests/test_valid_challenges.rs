@@ -3,17 +3,25 @@
 use alloy::providers::Provider;
 use celestia_rpc::{BlobClient, HeaderClient, TxConfig};
 use celestia_types::nmt::Namespace;
-use celestia_types::{AppVersion, Blob};
-use cli::challenge_da_commitment;
+use celestia_types::Blob;
+use cli::challenge_options::ChallengeOptions;
+use cli::event_cache::BlobstreamEventCache;
+use cli::{
+    challenge_blob_data_with_cache, challenge_da_commitment, challenge_da_commitment_with_cache,
+    challenge_da_commitments_aggregated_with_cache, challenge_da_commitments_with_cache,
+};
 use risc0_steel::host::BlockNumberOrTag;
 use rstest::rstest;
 use test_toolkit::blobstream::wait_for_blobstream_inclusion_with_timeout;
 use test_toolkit::index_blob::{
-    create_and_publish_index_blob, publish_index, publish_index_blob_with_bad_blob_position,
-    publish_single_blob, DEFAULT_NAMESPACE,
+    create_and_publish_index_blob, create_and_publish_index_blob_with_data_hashes,
+    current_app_version, find_empty_block, publish_canonical_index, publish_index,
+    publish_index_blob_with_bad_blob_position, publish_nested_index, publish_single_blob,
+    DEFAULT_NAMESPACE,
 };
 use test_toolkit::test_env::{test_env, TestEnv};
-use toolkit::{eds_index_to_ods, BlobIndex, SpanSequence};
+use toolkit::journal::Journal;
+use toolkit::{BlobIndex, DaChallenge, SpanSequence, SquareWidth};
 
 /// Size of the user payload in single-share blobs.
 const BLOB_USER_DATA_SIZE: usize = 478;
@@ -42,7 +50,7 @@ async fn invalid_block_height(#[future] test_env: TestEnv, #[case] span_sequence
         BlockNumberOrTag::Latest,
         *blobstream_contract.address(),
         span_sequence,
-        span_sequence,
+        DaChallenge::IndexIsUnavailable,
     )
     .await
     .expect("challenge should succeed");
@@ -88,7 +96,50 @@ async fn invalid_block_height_in_index(
         BlockNumberOrTag::Latest,
         *blobstream_contract.address(),
         index_span_sequence,
-        span_sequence,
+        DaChallenge::BlobInIndexIsUnavailable(span_sequence),
+    )
+    .await
+    .expect("challenge should succeed");
+}
+
+/// Challenges a blob of an index in the canonical encoding, at a Celestia block height out of
+/// the Blobstream range.
+#[rstest]
+#[tokio::test]
+async fn invalid_block_height_in_canonical_index(#[future] test_env: TestEnv) {
+    let TestEnv {
+        provider,
+        counter_contract: _counter_contract,
+        blobstream_contract,
+        celestia_client,
+    } = test_env.await;
+
+    let span_sequence = SpanSequence {
+        height: 1_000_000,
+        start: 1,
+        size: 1,
+    };
+    let index = BlobIndex::new(vec![span_sequence]);
+    let index_span_sequence = publish_canonical_index(&celestia_client, &index, DEFAULT_NAMESPACE)
+        .await
+        .expect("failed to publish index");
+
+    wait_for_blobstream_inclusion_with_timeout(
+        &blobstream_contract,
+        index_span_sequence.height,
+        std::time::Duration::from_secs(120),
+    )
+    .await
+    .expect("failed or timed out waiting for blobstream inclusion");
+
+    challenge_da_commitment(
+        &celestia_client,
+        provider.root().clone(),
+        TestEnv::chain_spec(),
+        BlockNumberOrTag::Latest,
+        *blobstream_contract.address(),
+        index_span_sequence,
+        DaChallenge::BlobInIndexIsUnavailable(span_sequence),
     )
     .await
     .expect("challenge should succeed");
@@ -140,7 +191,7 @@ async fn index_start_out_of_square(#[future] test_env: TestEnv) {
         BlockNumberOrTag::Latest,
         *blobstream_contract.address(),
         bad_span_sequence,
-        bad_span_sequence,
+        DaChallenge::IndexIsUnavailable,
     )
     .await
     .expect("challenge should succeed");
@@ -192,7 +243,7 @@ async fn index_end_out_of_square(#[future] test_env: TestEnv) {
         BlockNumberOrTag::Latest,
         *blobstream_contract.address(),
         bad_span_sequence,
-        bad_span_sequence,
+        DaChallenge::IndexIsUnavailable,
     )
     .await
     .expect("challenge should succeed");
@@ -238,7 +289,7 @@ async fn index_end_u32_overflow(#[future] test_env: TestEnv) {
         BlockNumberOrTag::Latest,
         *blobstream_contract.address(),
         bad_span_sequence,
-        bad_span_sequence,
+        DaChallenge::IndexIsUnavailable,
     )
     .await
     .expect("challenge should succeed");
@@ -280,7 +331,52 @@ async fn blob_in_index_out_of_square(#[future] test_env: TestEnv) {
         BlockNumberOrTag::Latest,
         *blobstream_contract.address(),
         index_span_sequence,
-        challenged_span_sequence,
+        DaChallenge::BlobInIndexIsUnavailable(challenged_span_sequence),
+    )
+    .await
+    .expect("challenge should succeed");
+}
+
+/// Challenges a blob of an index that points into a block without blobs. The blob fits in the
+/// minimal data square of the block, which is too small to hold any blob.
+#[rstest]
+#[tokio::test]
+async fn blob_in_empty_block(#[future] test_env: TestEnv) {
+    let TestEnv {
+        provider,
+        counter_contract: _counter_contract,
+        blobstream_contract,
+        celestia_client,
+    } = test_env.await;
+
+    let empty_block_height = find_empty_block(&celestia_client)
+        .await
+        .expect("failed to find an empty block");
+    let index = BlobIndex::new(vec![SpanSequence {
+        height: empty_block_height,
+        start: 0,
+        size: 1,
+    }]);
+    let index_span_sequence = publish_index(&celestia_client, &index, DEFAULT_NAMESPACE)
+        .await
+        .expect("failed to publish index");
+
+    wait_for_blobstream_inclusion_with_timeout(
+        &blobstream_contract,
+        index_span_sequence.height,
+        std::time::Duration::from_secs(120),
+    )
+    .await
+    .expect("failed or timed out waiting for blobstream inclusion");
+
+    challenge_da_commitment(
+        &celestia_client,
+        provider.root().clone(),
+        TestEnv::chain_spec(),
+        BlockNumberOrTag::Latest,
+        *blobstream_contract.address(),
+        index_span_sequence,
+        DaChallenge::BlobInIndexIsUnavailable(index.blobs[0]),
     )
     .await
     .expect("challenge should succeed");
@@ -331,10 +427,13 @@ async fn index_spans_multiple_namespaces(#[future] test_env: TestEnv) {
     .map(|ns| Namespace::new_v0(ns).expect("failed to create namespace"))
     .collect();
 
+    let app_version = current_app_version(&celestia_client)
+        .await
+        .expect("failed to fetch app version");
     let blobs = serialized_index
         .chunks(BLOB_USER_DATA_SIZE)
         .zip(namespaces.iter().cycle())
-        .map(|(chunk, namespace)| Blob::new(*namespace, chunk.to_vec(), AppVersion::V2))
+        .map(|(chunk, namespace)| Blob::new(*namespace, chunk.to_vec(), app_version))
         .collect::<Result<Vec<_>, _>>()
         .expect("failed to create blobs");
 
@@ -355,11 +454,10 @@ async fn index_spans_multiple_namespaces(#[future] test_env: TestEnv) {
         .await
         .expect("failed to get block header");
 
-    let eds_width = block_header.dah.square_width() as u32;
-    let start = eds_index_to_ods(
-        first_blob.index.expect("blob should have an index") as u32,
-        eds_width,
-    );
+    let square_width = SquareWidth::from_eds(block_header.dah.square_width() as u32);
+    let start = square_width
+        .eds_to_ods(first_blob.index.expect("blob should have an index") as u32)
+        .expect("the first blob should be in the ODS");
 
     let index_span_sequence = SpanSequence {
         height: block_height,
@@ -385,7 +483,7 @@ async fn index_spans_multiple_namespaces(#[future] test_env: TestEnv) {
         BlockNumberOrTag::Latest,
         *blobstream_contract.address(),
         index_span_sequence,
-        challenged_span_sequence,
+        DaChallenge::BlobInIndexIsUnavailable(challenged_span_sequence),
     )
     .await
     .expect("challenge should succeed");
@@ -433,7 +531,7 @@ async fn index_blob_not_deserializable(#[future] test_env: TestEnv) {
         BlockNumberOrTag::Latest,
         *blobstream_contract.address(),
         bad_index_span_sequence,
-        challenged_span_sequence,
+        DaChallenge::BlobInIndexIsUnavailable(challenged_span_sequence),
     )
     .await
     .expect("challenge should succeed");
@@ -478,8 +576,324 @@ async fn index_blob_spans_zero_shares(#[future] test_env: TestEnv) {
         BlockNumberOrTag::Latest,
         *blobstream_contract.address(),
         bad_span_sequence,
-        bad_span_sequence,
+        DaChallenge::IndexIsUnavailable,
+    )
+    .await
+    .expect("challenge should succeed");
+}
+
+/// Challenges the data of a blob whose data hash in the index does not match its data.
+#[rstest]
+#[tokio::test]
+async fn blob_data_mismatch(#[future] test_env: TestEnv) {
+    let TestEnv {
+        provider,
+        counter_contract: _counter_contract,
+        blobstream_contract,
+        celestia_client,
+    } = test_env.await;
+
+    let corrupted_blob = 1;
+    let (index, index_span_sequence) = create_and_publish_index_blob_with_data_hashes(
+        &celestia_client,
+        3,
+        1024,
+        3,
+        Some(corrupted_blob),
+    )
+    .await
+    .expect("failed to publish blobs");
+
+    wait_for_blobstream_inclusion_with_timeout(
+        &blobstream_contract,
+        index_span_sequence.height,
+        std::time::Duration::from_secs(120),
+    )
+    .await
+    .expect("failed or timed out waiting for blobstream inclusion");
+
+    let root_provider = provider.root().clone();
+    let blobstream_event_cache =
+        BlobstreamEventCache::new(*blobstream_contract.address(), root_provider);
+    let challenge_options = ChallengeOptions::default();
+
+    challenge_blob_data_with_cache(
+        &celestia_client,
+        &blobstream_event_cache,
+        &challenge_options,
+        TestEnv::chain_spec(),
+        BlockNumberOrTag::Latest,
+        index_span_sequence,
+        index.blobs[corrupted_blob],
+    )
+    .await
+    .expect("challenge should succeed");
+}
+
+/// Challenges an index blob posted outside the namespace of the rollup.
+#[rstest]
+#[tokio::test]
+async fn index_blob_wrong_namespace(#[future] test_env: TestEnv) {
+    let TestEnv {
+        provider,
+        counter_contract: _counter_contract,
+        blobstream_contract,
+        celestia_client,
+    } = test_env.await;
+
+    let (index, index_span_sequence) = create_and_publish_index_blob(&celestia_client, 2, 1024, 2)
+        .await
+        .expect("failed to publish blobs");
+
+    wait_for_blobstream_inclusion_with_timeout(
+        &blobstream_contract,
+        index_span_sequence.height,
+        std::time::Duration::from_secs(120),
+    )
+    .await
+    .expect("failed or timed out waiting for blobstream inclusion");
+
+    // The test blobs are posted to `DEFAULT_NAMESPACE`
+    let rollup_namespace = Namespace::new_v0(&[0xCA, 0xFE]).unwrap();
+    assert_ne!(rollup_namespace, DEFAULT_NAMESPACE);
+
+    let root_provider = provider.root().clone();
+    let blobstream_event_cache =
+        BlobstreamEventCache::new(*blobstream_contract.address(), root_provider);
+    let challenge_options = ChallengeOptions::new().with_namespace(rollup_namespace);
+
+    challenge_da_commitment_with_cache(
+        &celestia_client,
+        &blobstream_event_cache,
+        &challenge_options,
+        TestEnv::chain_spec(),
+        BlockNumberOrTag::Latest,
+        index_span_sequence,
+        DaChallenge::BlobInIndexIsUnavailable(index.blobs[0]),
     )
     .await
     .expect("challenge should succeed");
 }
+
+/// Challenges a blob listed in a sub-index that starts out of the data square. The index blob
+/// only lists the sub-index.
+#[rstest]
+#[tokio::test]
+async fn blob_in_sub_index_out_of_square(#[future] test_env: TestEnv) {
+    let TestEnv {
+        provider,
+        counter_contract: _counter_contract,
+        blobstream_contract,
+        celestia_client,
+    } = test_env.await;
+
+    let current_celestia_head = celestia_client
+        .header_local_head()
+        .await
+        .expect("failed to fetch Celestia head");
+    let ods_width = current_celestia_head.dah.square_width() as u32 / 2;
+    let challenged_span_sequence = SpanSequence {
+        height: current_celestia_head.height().value(),
+        start: ods_width * ods_width + 1,
+        size: 1,
+    };
+    let sub_index = BlobIndex::new(vec![challenged_span_sequence]);
+
+    let (_index, index_span_sequence, sub_index_span_sequence) =
+        publish_nested_index(&celestia_client, &sub_index)
+            .await
+            .expect("failed to publish blobs");
+
+    wait_for_blobstream_inclusion_with_timeout(
+        &blobstream_contract,
+        index_span_sequence
+            .height
+            .max(sub_index_span_sequence.height),
+        std::time::Duration::from_secs(120),
+    )
+    .await
+    .expect("failed or timed out waiting for blobstream inclusion");
+
+    let root_provider = provider.root().clone();
+    let chain_spec = TestEnv::chain_spec();
+
+    challenge_da_commitment(
+        &celestia_client,
+        root_provider,
+        chain_spec,
+        BlockNumberOrTag::Latest,
+        *blobstream_contract.address(),
+        index_span_sequence,
+        DaChallenge::BlobInIndexIsUnavailable(challenged_span_sequence),
+    )
+    .await
+    .expect("challenge should succeed");
+}
+
+/// Challenges an index blob whose span sequence starts in the middle of the posted blob, so
+/// that its first share continues a sequence instead of starting one.
+#[rstest]
+#[tokio::test]
+async fn index_blob_starts_mid_sequence(#[future] test_env: TestEnv) {
+    let TestEnv {
+        provider,
+        counter_contract: _counter_contract,
+        blobstream_contract,
+        celestia_client,
+    } = test_env.await;
+
+    // 40 span sequences do not fit in a single share
+    let (index, index_span_sequence) = create_and_publish_index_blob(&celestia_client, 40, 64, 40)
+        .await
+        .expect("failed to publish blobs");
+    assert!(index_span_sequence.size > 1);
+
+    let shifted_span_sequence = SpanSequence {
+        height: index_span_sequence.height,
+        start: index_span_sequence.start + 1,
+        size: index_span_sequence.size - 1,
+    };
+
+    wait_for_blobstream_inclusion_with_timeout(
+        &blobstream_contract,
+        index_span_sequence.height,
+        std::time::Duration::from_secs(120),
+    )
+    .await
+    .expect("failed or timed out waiting for blobstream inclusion");
+
+    let root_provider = provider.root().clone();
+    let chain_spec = TestEnv::chain_spec();
+
+    challenge_da_commitment(
+        &celestia_client,
+        root_provider,
+        chain_spec,
+        BlockNumberOrTag::Latest,
+        *blobstream_contract.address(),
+        shifted_span_sequence,
+        DaChallenge::BlobInIndexIsUnavailable(index.blobs[0]),
+    )
+    .await
+    .expect("challenge should succeed");
+}
+
+/// Challenges several blobs of the same index, all out of the data square, with a single
+/// aggregated proof.
+#[rstest]
+#[tokio::test]
+async fn aggregated_blobs_out_of_square(#[future] test_env: TestEnv) {
+    let TestEnv {
+        provider,
+        counter_contract: _counter_contract,
+        blobstream_contract,
+        celestia_client,
+    } = test_env.await;
+
+    let current_celestia_head = celestia_client
+        .header_local_head()
+        .await
+        .expect("failed to get the Celestia head");
+    let ods_width = current_celestia_head.dah.square_width() as u32 / 2;
+    let index = BlobIndex::new(
+        (1..=3)
+            .map(|i| SpanSequence {
+                height: current_celestia_head.height().value(),
+                start: ods_width * ods_width + i,
+                size: 1,
+            })
+            .collect(),
+    );
+    let index_span_sequence = publish_index(&celestia_client, &index, DEFAULT_NAMESPACE)
+        .await
+        .expect("failed to publish index");
+
+    wait_for_blobstream_inclusion_with_timeout(
+        &blobstream_contract,
+        index_span_sequence.height,
+        std::time::Duration::from_secs(120),
+    )
+    .await
+    .expect("failed or timed out waiting for blobstream inclusion");
+
+    let blobstream_event_cache =
+        BlobstreamEventCache::new(*blobstream_contract.address(), provider.root().clone());
+    let challenge_options = ChallengeOptions::default();
+    challenge_da_commitments_aggregated_with_cache(
+        &celestia_client,
+        &blobstream_event_cache,
+        &challenge_options,
+        TestEnv::chain_spec(),
+        BlockNumberOrTag::Latest,
+        index_span_sequence,
+        index.blobs.clone(),
+    )
+    .await
+    .expect("aggregated challenge should succeed");
+}
+
+/// Challenges several blobs of the same index, all out of the data square, in a single guest
+/// execution.
+#[rstest]
+#[tokio::test]
+async fn several_blobs_out_of_square(#[future] test_env: TestEnv) {
+    let TestEnv {
+        provider,
+        counter_contract: _counter_contract,
+        blobstream_contract,
+        celestia_client,
+    } = test_env.await;
+
+    let current_celestia_head = celestia_client
+        .header_local_head()
+        .await
+        .expect("failed to get the Celestia head");
+    let ods_width = current_celestia_head.dah.square_width() as u32 / 2;
+    let index = BlobIndex::new(
+        (1..=3)
+            .map(|i| SpanSequence {
+                height: current_celestia_head.height().value(),
+                start: ods_width * ods_width + i,
+                size: 1,
+            })
+            .collect(),
+    );
+    let index_span_sequence = publish_index(&celestia_client, &index, DEFAULT_NAMESPACE)
+        .await
+        .expect("failed to publish index");
+
+    wait_for_blobstream_inclusion_with_timeout(
+        &blobstream_contract,
+        index_span_sequence.height,
+        std::time::Duration::from_secs(120),
+    )
+    .await
+    .expect("failed or timed out waiting for blobstream inclusion");
+
+    let blobstream_event_cache =
+        BlobstreamEventCache::new(*blobstream_contract.address(), provider.root().clone());
+    let challenge_options = ChallengeOptions::default();
+    let (receipt, _seal) = challenge_da_commitments_with_cache(
+        &celestia_client,
+        &blobstream_event_cache,
+        &challenge_options,
+        TestEnv::chain_spec(),
+        BlockNumberOrTag::Latest,
+        index_span_sequence,
+        index.blobs.clone(),
+    )
+    .await
+    .expect("challenge should succeed");
+
+    let journal = Journal::decode(&receipt.journal.bytes).expect("invalid journal");
+    assert_eq!(journal.extraChallengedBlobs.len(), 2);
+    assert!(journal
+        .extraFraudTypes
+        .iter()
+        .all(|&fraud_type| fraud_type != 0));
+    assert_eq!(
+        journal.celestiaChainId,
+        current_celestia_head.chain_id().to_string()
+    );
+    assert_eq!(journal.indexHash, index.canonical_hash().unwrap());
+}
@@ -25,6 +25,8 @@ fn main() {
     // Builds can be made deterministic, and thereby reproducible, by using Docker to build the
     // guest. Check the RISC0_USE_DOCKER variable and use Docker to build the guest if set.
     println!("cargo:rerun-if-env-changed=RISC0_USE_DOCKER");
+    println!("cargo:rerun-if-env-changed=RISC0_GUEST_ANY_BLOBSTREAM");
+    println!("cargo:rerun-if-env-changed=RISC0_GUEST_DEBUG");
     println!("cargo:rerun-if-changed=build.rs");
     let manifest_dir = PathBuf::from(env::var_os("CARGO_MANIFEST_DIR").unwrap());
     let mut builder = GuestOptionsBuilder::default();
@@ -35,6 +37,17 @@ fn main() {
             .unwrap();
         builder.use_docker(docker_options);
     }
+    // Lifts the canonical Blobstream address check of the guest, ex: to test against a
+    // Blobstream contract deployed on a Sepolia fork.
+    let mut features = vec![];
+    if env::var("RISC0_GUEST_ANY_BLOBSTREAM").is_ok() {
+        features.push("any-blobstream".to_string());
+    }
+    // Logs and per-phase cycle reports of the guest, see the `debug-guest` feature.
+    if env::var("RISC0_GUEST_DEBUG").is_ok() {
+        features.push("debug-guest".to_string());
+    }
+    builder.features(features);
     let guest_options = builder.build().unwrap();
 
     // Generate Rust source files for the methods crate.
@@ -0,0 +1,31 @@
+#![no_main]
+
+use alloy_primitives::B256;
+use risc0_zkvm::guest::env;
+use risc0_zkvm::sha::Digest;
+use toolkit::journal::{AggregatedJournal, AggregationInput, Journal};
+
+risc0_zkvm::guest::entry!(main);
+
+fn main() {
+    let AggregationInput {
+        challenge_image_id,
+        journals,
+    }: AggregationInput = env::read();
+    let challenge_image_id = Digest::from(challenge_image_id);
+
+    // Each journal must come with the receipt of a DA challenge proof, passed as an assumption.
+    let journals: Vec<Journal> = journals
+        .iter()
+        .map(|journal_bytes| {
+            env::verify(challenge_image_id, journal_bytes.as_slice())
+                .expect("failed to verify journal");
+            Journal::decode(journal_bytes).unwrap_or_else(|err| panic!("invalid input: {err}"))
+        })
+        .collect();
+
+    let aggregated_journal =
+        AggregatedJournal::aggregate(B256::from_slice(challenge_image_id.as_bytes()), &journals)
+            .unwrap_or_else(|err| panic!("invalid input: {err}"));
+    env::commit_slice(&aggregated_journal.encode());
+}
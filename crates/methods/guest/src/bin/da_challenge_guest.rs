@@ -2,34 +2,95 @@
 #![no_main]
 
 use alloy_primitives::{B256, U256};
-use alloy_sol_types::SolValue;
-use celestia_types::hash::Hash;
-use celestia_types::{AppVersion, MerkleProof};
 use risc0_steel::config::ChainSpec;
-use risc0_steel::ethereum::EthBlockHeader;
-use risc0_steel::{ethereum::EthEvmInput, Commitment, Contract, EvmEnv, StateDb};
+use risc0_steel::{Commitment, Contract, EvmEnv, StateDb};
 use risc0_zkvm::guest::env;
+#[cfg(feature = "debug-guest")]
+use std::io::Write;
 use toolkit::blobstream::{
     BinaryMerkleProof, Blobstream0, DataRootTuple, IDAOracle, SP1Blobstream,
 };
-use toolkit::errors::{compute_ods_width_from_row_proof, DaFraud, DaGuestError, InputError};
-use toolkit::journal::Journal;
+use toolkit::chain::{BlobstreamChainHeader, BlobstreamChainInput};
+use toolkit::challenge::{
+    check_block_proofs, check_da_challenges, BlobstreamState, DaChallengeOutcomes,
+};
+#[cfg(not(feature = "any-blobstream"))]
+use toolkit::constants::check_blobstream_address;
+#[cfg(feature = "debug-guest")]
+use toolkit::constants::CYCLE_REPORT_PREFIX;
+use toolkit::constants::{check_celestia_chain_id, check_chain_spec};
+use toolkit::errors::{DaFraudKind, DaGuestError, InputError, BLOB_IS_AVAILABLE_MESSAGE};
+use toolkit::journal::{DaOutcome, GuestOptions, Journal, JOURNAL_VERSION};
 use toolkit::{
-    share_proof_start_index_ods, BlobIndex, BlobProofData, BlobstreamAttestation,
-    BlobstreamAttestationAndRowProof, BlobstreamImpl, BlobstreamInfo, DaChallengeGuestData,
-    SpanSequence,
+    BatchMetadata, BlobstreamAttestation, BlobstreamAttestationAndRowProof, BlobstreamImpl,
+    BlobstreamInfo, DaChallengeGuestData,
 };
 
 risc0_zkvm::guest::entry!(main);
 
+type GuestEvmEnv = EvmEnv<StateDb, BlobstreamChainHeader, Commitment>;
+
+/// Logs a diagnostic message of the guest program. Only built with the `debug-guest` feature, so
+/// that production proofs do not pay for formatting the message.
+macro_rules! debug_log {
+    ($($arg:tt)*) => {
+        #[cfg(feature = "debug-guest")]
+        env::log(&format!($($arg)*));
+    };
+}
+
+/// Reports the cycles spent in each phase of the guest program on stderr, for benchmarks. Does
+/// nothing without the `debug-guest` feature.
+struct CycleReport {
+    #[cfg(feature = "debug-guest")]
+    last_cycle_count: u64,
+}
+
+impl CycleReport {
+    fn new() -> Self {
+        Self {
+            #[cfg(feature = "debug-guest")]
+            last_cycle_count: 0,
+        }
+    }
+
+    #[cfg(not(feature = "debug-guest"))]
+    fn end_phase(&mut self, _phase: &str) {}
+
+    #[cfg(feature = "debug-guest")]
+    fn end_phase(&mut self, phase: &str) {
+        let cycle_count = env::cycle_count();
+        // Reporting is best effort, it must never fail the guest
+        let _ = writeln!(
+            env::stderr(),
+            "{CYCLE_REPORT_PREFIX}{phase}:{}",
+            cycle_count - self.last_cycle_count
+        );
+        self.last_cycle_count = cycle_count;
+    }
+}
+
 fn verify_blobstream_attestation(
-    blobstream_contract: &Contract<&EvmEnv<StateDb, EthBlockHeader, Commitment>>,
+    blobstream_contract: &Contract<&GuestEvmEnv>,
+    next_proof_nonce: Option<U256>,
     blobstream_attestation: &BlobstreamAttestation,
-) {
-    let formatted_proof = BinaryMerkleProof::from(blobstream_attestation.proof.clone());
+) -> Result<(), InputError> {
+    blobstream_attestation.verify_block_range()?;
+
+    // Do not trust the nonce provided by the host: it must be committed on-chain already.
+    if let Some(next_proof_nonce) = next_proof_nonce {
+        if blobstream_attestation.nonce >= next_proof_nonce {
+            return Err(InputError::UncommittedAttestationNonce {
+                nonce: blobstream_attestation.nonce,
+                next_nonce: next_proof_nonce,
+            });
+        }
+    }
+
+    let formatted_proof = BinaryMerkleProof::from(&blobstream_attestation.proof);
 
     let blobstream_call = IDAOracle::verifyAttestationCall {
-        _tupleRootNonce: U256::from(blobstream_attestation.nonce),
+        _tupleRootNonce: blobstream_attestation.nonce,
         _tuple: DataRootTuple {
             height: U256::from(blobstream_attestation.height),
             dataRoot: B256::from_slice(&blobstream_attestation.data_root),
@@ -37,15 +98,24 @@ fn verify_blobstream_attestation(
         _proof: formatted_proof,
     };
 
-    // `verifyAttestation()` returns nothing, discard the return value
-    let _blobstream_return = blobstream_contract.call_builder(&blobstream_call).call();
+    // `verifyAttestation()` returns false, rather than reverting, when the data root or its
+    // inclusion proof does not match the data commitment of the nonce.
+    if !blobstream_contract.call_builder(&blobstream_call).call()._0 {
+        return Err(InputError::AttestationRejected {
+            height: blobstream_attestation.height,
+            nonce: blobstream_attestation.nonce,
+        });
+    }
+
+    Ok(())
 }
 
 fn get_current_blobstream_height(
-    blobstream_contract: &Contract<&EvmEnv<StateDb, EthBlockHeader, Commitment>>,
+    blobstream_contract: &Contract<&GuestEvmEnv>,
     blobstream_impl: BlobstreamImpl,
 ) -> u64 {
     match blobstream_impl {
+        // SP1Blobstream's `latestBlock` is the exclusive end of the last committed range.
         BlobstreamImpl::Sp1 => {
             let height_call = SP1Blobstream::latestBlockCall {};
             blobstream_contract.call_builder(&height_call).call()._0 - 1
@@ -55,218 +125,253 @@ fn get_current_blobstream_height(
             let height_call = Blobstream0::latestHeightCall {};
             blobstream_contract.call_builder(&height_call).call()._0
         }
+
+        // Generic oracles do not expose their latest height: no height can be proven too high.
+        BlobstreamImpl::DaOracle => u64::MAX,
+    }
+}
+
+/// Returns the nonce of the next data commitment of the Blobstream contract, if exposed.
+fn get_next_proof_nonce(
+    blobstream_contract: &Contract<&GuestEvmEnv>,
+    blobstream_impl: BlobstreamImpl,
+) -> Option<U256> {
+    match blobstream_impl {
+        BlobstreamImpl::Sp1 => {
+            let nonce_call = SP1Blobstream::state_proofNonceCall {};
+            Some(blobstream_contract.call_builder(&nonce_call).call()._0)
+        }
+
+        BlobstreamImpl::R0 => {
+            let nonce_call = Blobstream0::proofNonceCall {};
+            Some(blobstream_contract.call_builder(&nonce_call).call()._0)
+        }
+
+        // Attestations of generic oracles are only authenticated by their `verifyAttestation()`
+        BlobstreamImpl::DaOracle => None,
     }
 }
 
 fn verify_blobstream_attestation_and_row_proof(
-    blobstream_contract: &Contract<&EvmEnv<StateDb, EthBlockHeader, Commitment>>,
+    blobstream_contract: &Contract<&GuestEvmEnv>,
+    next_proof_nonce: Option<U256>,
     BlobstreamAttestationAndRowProof {
         blobstream_attestation,
         row_proof,
         row_root_node,
+        last_root_proof,
+        last_root_node,
+        ..
     }: &BlobstreamAttestationAndRowProof,
-) {
-    verify_blobstream_attestation(blobstream_contract, blobstream_attestation);
+) -> Result<(), InputError> {
+    verify_blobstream_attestation(
+        blobstream_contract,
+        next_proof_nonce,
+        blobstream_attestation,
+    )?;
+    let height = blobstream_attestation.height;
 
     // TODO: this serialization can be performed on the host side
     let serialized_row_root_node =
-        borsh::to_vec(&row_root_node).expect("failed to serialize row root");
-
+        borsh::to_vec(&row_root_node).map_err(|_| InputError::InvalidRowProof(height))?;
     row_proof
         .verify(&serialized_row_root_node, blobstream_attestation.data_root)
-        .expect("failed to verify row proof");
-}
-
-fn verify_span_sequence_inclusion(
-    span_sequence: &SpanSequence,
-    row_proof: &MerkleProof,
-) -> Result<(), DaGuestError> {
-    let ods_width = compute_ods_width_from_row_proof(row_proof)?;
-    let ods_size = ods_width * ods_width;
-
-    let last_share_index = span_sequence.end_index_ods()?;
+        .map_err(|_| InputError::InvalidRowProof(height))?;
 
-    env::log(&format!("last_share_index: {last_share_index}"));
-
-    if last_share_index > ods_size {
-        env::log(&format!(
-            "invalid blob commitment end index: {last_share_index} > {ods_size}",
-        ));
-        return Err(DaFraud::ShareIndexOutOfBounds {
-            share_index: last_share_index,
-            ods_size,
-        }
-        .into());
-    }
-
-    Ok(())
+    let serialized_last_root_node =
+        borsh::to_vec(&last_root_node).map_err(|_| InputError::InvalidLastRootProof(height))?;
+    last_root_proof
+        .verify(&serialized_last_root_node, blobstream_attestation.data_root)
+        .map_err(|_| InputError::InvalidLastRootProof(height))
 }
 
-fn verify_share_proofs(
-    span_sequence: &SpanSequence,
-    blobstream_attestation: &BlobstreamAttestation,
-    blob_proof_data: &BlobProofData,
-) -> Result<(), DaGuestError> {
-    let span_sequence_end = span_sequence.end_index_ods()?;
-
-    for share_index in span_sequence.start..span_sequence_end {
-        let share_proof = &blob_proof_data.share_proofs[&share_index];
-        // Check that the share belongs to the expected Celestia block
-        share_proof
-            .verify(Hash::Sha256(blobstream_attestation.data_root))
-            .expect("failed to verify share proof");
-
-        // Check that the share matches the expected index
-        let proof_start_index_ods = share_proof_start_index_ods(share_proof);
-        assert_eq!(
-            proof_start_index_ods, share_index,
-            "invalid share proof start index"
-        );
-    }
-
-    Ok(())
+/// Guest view of the Blobstream contract, through Steel.
+struct SteelBlobstream<'a> {
+    contract: Contract<&'a GuestEvmEnv>,
+    implementation: BlobstreamImpl,
+    next_proof_nonce: Option<U256>,
 }
 
-fn check_block_height_bounds(
-    span_sequence: SpanSequence,
-    blobstream_contract: &Contract<&EvmEnv<StateDb, EthBlockHeader, Commitment>>,
-    blobstream_impl: BlobstreamImpl,
-    first_blobstream_attestation: BlobstreamAttestation,
-) -> Result<(), DaGuestError> {
-    // Assert that the proof is for the first Blobstream event by checking the nonce.
-    // Nonces start at 1 in both SP1 and RISC Zero Blobstream contracts.
-    if first_blobstream_attestation.nonce != 1 {
-        return Err(InputError::InvalidFirstBlobstreamAttestationNonce.into());
-    }
-    // Assert that the proof is for the first Celestia block to guarantee that this is truly
-    // the lower bound.
-    if first_blobstream_attestation.proof.index != 0 {
-        return Err(InputError::InvalidFirstBlobstreamAttestationIndex.into());
+impl BlobstreamState for SteelBlobstream<'_> {
+    fn verify_attestation(
+        &self,
+        blobstream_attestation: &BlobstreamAttestation,
+    ) -> Result<(), InputError> {
+        verify_blobstream_attestation(
+            &self.contract,
+            self.next_proof_nonce,
+            blobstream_attestation,
+        )
     }
-    verify_blobstream_attestation(blobstream_contract, &first_blobstream_attestation);
 
-    let min_block_height = first_blobstream_attestation.height;
-    if span_sequence.height < min_block_height {
-        return Err(DaFraud::BlockHeightTooLow {
-            block_height: span_sequence.height,
-            min_block_height,
-        }
-        .into());
+    fn max_block_height(&self) -> u64 {
+        get_current_blobstream_height(&self.contract, self.implementation)
     }
-
-    let max_block_height = get_current_blobstream_height(blobstream_contract, blobstream_impl);
-    if span_sequence.height > max_block_height {
-        return Err(DaFraud::BlockHeightTooLow {
-            block_height: span_sequence.height,
-            min_block_height,
-        }
-        .into());
-    }
-
-    Ok(())
 }
 
 fn check_da_challenge(
-    evm_env: &EvmEnv<StateDb, EthBlockHeader, Commitment>,
+    evm_env: &GuestEvmEnv,
     blobstream_info: BlobstreamInfo,
-    serialized_da_guest_data: Vec<u8>,
-) -> Result<(), DaGuestError> {
-    let DaChallengeGuestData {
-        index_blob,
-        challenged_blob,
-        index_blob_proof_data: index_blob_data,
-        block_proofs,
-        first_blobstream_attestation,
-    } = bincode::deserialize(&serialized_da_guest_data).expect("failed to deserialize guest data");
-
+    da_challenge_guest_data: &DaChallengeGuestData,
+    cycle_report: &mut CycleReport,
+) -> Result<DaChallengeOutcomes, DaGuestError> {
     let BlobstreamInfo {
         address: blobstream_address,
         implementation: blobstream_impl,
     } = blobstream_info;
-    let blobstream_contract = Contract::new(blobstream_address, evm_env);
-
-    // Verify the authenticity of all the provided block proofs.
-    for (block_height, block_proof) in &block_proofs {
-        assert_eq!(
-            *block_height, block_proof.blobstream_attestation.height,
-            "invalid block height"
-        );
-        verify_blobstream_attestation_and_row_proof(&blobstream_contract, block_proof);
-    }
+    let contract = Contract::new(blobstream_address, evm_env);
+    let next_proof_nonce = get_next_proof_nonce(&contract, blobstream_impl);
+    let blobstream = SteelBlobstream {
+        contract,
+        implementation: blobstream_impl,
+        next_proof_nonce,
+    };
 
-    // If the index blob is the missing blob, verify exclusion immediately.
-    if challenged_blob == index_blob {
-        // Verify that the index blob is excluded
-        check_block_height_bounds(
-            index_blob,
-            &blobstream_contract,
-            blobstream_impl,
-            first_blobstream_attestation,
+    // Verify the authenticity of all the provided block proofs, once they are known to be
+    // needed by the challenge.
+    check_block_proofs(da_challenge_guest_data)?;
+    for block_proof in da_challenge_guest_data.block_proofs.values() {
+        verify_blobstream_attestation_and_row_proof(
+            &blobstream.contract,
+            blobstream.next_proof_nonce,
+            block_proof,
         )?;
-        return verify_span_sequence_inclusion(
-            &index_blob,
-            &block_proofs[&index_blob.height].row_proof,
-        );
-    }
-
-    // To go any further, the index blob data must be present.
-    let index_blob_data = index_blob_data.ok_or(InputError::MissingIndexBlobData)?;
-
-    // Verify the share proofs of the index blob
-    verify_share_proofs(
-        &index_blob,
-        &block_proofs[&index_blob.height].blobstream_attestation,
-        &index_blob_data,
-    )?;
-    // Deserialize the index blob
-    let app_version =
-        AppVersion::from_u64(index_blob_data.app_version).expect("invalid app version");
-    let index = BlobIndex::reconstruct_from_raw(index_blob_data.shares(), app_version)?;
-
-    // Iterate over the blobs in the index and check if they're the missing blob.
-    for blob_commitment in index.blobs {
-        if challenged_blob == blob_commitment {
-            check_block_height_bounds(
-                challenged_blob,
-                &blobstream_contract,
-                blobstream_impl,
-                first_blobstream_attestation,
-            )?;
-            return verify_span_sequence_inclusion(
-                &blob_commitment,
-                &block_proofs[&blob_commitment.height].row_proof,
-            );
-        }
     }
+    cycle_report.end_phase("block_proofs");
 
-    Err(InputError::ChallengedBlobNotInIndex.into())
+    let result = check_da_challenges(da_challenge_guest_data, &blobstream);
+    cycle_report.end_phase("challenge");
+    result
 }
 
 fn main() {
+    let mut cycle_report = CycleReport::new();
+
     // Read the input from the guest environment.
-    let input: EthEvmInput = env::read();
+    let input: BlobstreamChainInput = env::read();
     let chain_spec: ChainSpec = env::read();
     let blobstream_info: BlobstreamInfo = env::read();
+    let guest_options: GuestOptions = env::read();
     let serialized_da_guest_data: Vec<u8> = env::read_frame();
+    let da_challenge_guest_data: DaChallengeGuestData =
+        DaChallengeGuestData::decode(&serialized_da_guest_data)
+            .map_err(InputError::from)
+            .unwrap_or_else(|err| panic!("invalid input: {err}"));
+    cycle_report.end_phase("input");
+
+    // The digest of the chain spec is committed as the `configID` of the Steel commitment, but
+    // pinned chains only accept their own spec.
+    check_chain_spec(&chain_spec).unwrap_or_else(|err| panic!("invalid input: {err}"));
 
     // Converts the input into a `EvmEnv` for execution. The `with_chain_spec` method is used
     // to specify the chain configuration. It checks that the state matches the state root in the
     // header provided in the input.
     let evm_env = input.into_env().with_chain_spec(&chain_spec);
     let blobstream_address = blobstream_info.address;
-
-    match check_da_challenge(&evm_env, blobstream_info, serialized_da_guest_data) {
-        Ok(()) => panic!("the specified blob is available, DA challenge failed"),
+    let blobstream_impl = blobstream_info.implementation;
+
+    // The chain ID is bound to the Steel commitment through the chain spec.
+    #[cfg(not(feature = "any-blobstream"))]
+    check_blobstream_address(chain_spec.chain_id, blobstream_address)
+        .unwrap_or_else(|err| panic!("invalid input: {err}"));
+
+    // Heights are only meaningful on the Celestia network tracked by the Blobstream contract.
+    check_celestia_chain_id(
+        chain_spec.chain_id,
+        blobstream_address,
+        &da_challenge_guest_data.celestia_chain_id,
+    )
+    .unwrap_or_else(|err| panic!("invalid input: {err}"));
+
+    // Bind the claimed implementation to the contract: the height getter of the implementation
+    // reverts if the contract is not of that kind. Generic oracles cannot be checked, verifier
+    // contracts can reject them from the journal.
+    get_current_blobstream_height(
+        &Contract::new(blobstream_address, &evm_env),
+        blobstream_impl,
+    );
+    cycle_report.end_phase("evm_env");
+
+    // `DaFraudKind` of each challenged blob, `None` if the blob is available, and the hash and
+    // batch metadata of the index if it was read
+    let (fraud_kinds, index_hash, batch_metadata): (
+        Vec<Option<DaFraudKind>>,
+        Option<B256>,
+        Option<BatchMetadata>,
+    ) = match check_da_challenge(
+        &evm_env,
+        blobstream_info,
+        &da_challenge_guest_data,
+        &mut cycle_report,
+    ) {
+        Ok(DaChallengeOutcomes {
+            index_hash,
+            batch_metadata,
+            outcomes,
+        }) => (
+            outcomes
+                .into_iter()
+                .map(|outcome| {
+                    outcome.err().map(|err| {
+                        debug_log!("DA challenge success: {err}");
+                        err.kind()
+                    })
+                })
+                .collect(),
+            index_hash,
+            batch_metadata,
+        ),
         Err(DaGuestError::Input(err)) => {
             panic!("invalid input: {err}")
         }
-        Err(DaGuestError::Fraud(err)) => env::log(&format!("DA challenge success: {err}")),
-    }
+        // Fraud of the index holds for every challenged blob
+        Err(DaGuestError::Fraud(err)) => {
+            debug_log!("DA challenge success: {err}");
+            (
+                vec![Some(err.kind()); da_challenge_guest_data.challenged_blobs().count()],
+                None,
+                None,
+            )
+        }
+    };
+    let outcome = if fraud_kinds.iter().all(Option::is_some) {
+        DaOutcome::FraudProven
+    } else if guest_options.prove_availability {
+        debug_log!("DA challenge failed: a specified blob is available");
+        DaOutcome::BlobAvailable
+    } else {
+        panic!("{BLOB_IS_AVAILABLE_MESSAGE}")
+    };
+    let fraud_types: Vec<u8> = fraud_kinds
+        .iter()
+        .map(|kind| kind.map(|kind| kind.as_u8()).unwrap_or_default())
+        .collect();
 
     // Commit the block hash and number used when deriving `view_call_env` to the journal.
     let journal = Journal {
+        version: JOURNAL_VERSION,
         commitment: evm_env.into_commitment(),
         blobstreamAddress: blobstream_address,
+        blobstreamImpl: blobstream_impl.as_u8(),
+        outcome: outcome.as_u8(),
+        indexBlob: da_challenge_guest_data.index_blob.into(),
+        challengedBlob: da_challenge_guest_data.challenged_blob.into(),
+        fraudType: fraud_types[0],
+        namespace: da_challenge_guest_data
+            .namespace
+            .map(|namespace| namespace.as_bytes().to_vec().into())
+            .unwrap_or_default(),
+        extraChallengedBlobs: da_challenge_guest_data
+            .extra_challenged_blobs
+            .iter()
+            .map(|&blob| blob.into())
+            .collect(),
+        extraFraudTypes: fraud_types[1..].to_vec(),
+        celestiaChainId: da_challenge_guest_data.celestia_chain_id.clone(),
+        challengeType: da_challenge_guest_data.challenge().as_u8(),
+        indexHash: index_hash.unwrap_or_default(),
+        batchMetadata: batch_metadata.into(),
     };
-    env::commit_slice(&journal.abi_encode());
+    env::commit_slice(&journal.encode());
+    cycle_report.end_phase("journal");
 }
@@ -0,0 +1,110 @@
+//! Executes the DA challenge guest program over recorded inputs, without proving, and reports
+//! the cycles spent in each phase of the guest.
+//!
+//! Usage: `cargo run --release -p da-challenge-guest --bin guest-bench [FIXTURE...]`
+//!
+//! Fixtures are encoded guest inputs, see `GuestInput::encode()`. Without arguments, all the
+//! `.bin` files of `crates/methods/fixtures` are executed. Record them against the local devnet
+//! with `cargo test -p e2e-tests --test record-bench-fixtures -- --ignored`.
+//!
+//! The guest only reports its phases when built with `RISC0_GUEST_DEBUG=1`, only the total
+//! cycles are reported otherwise.
+
+use anyhow::{bail, Context, Result};
+use da_challenge_guest::DA_CHALLENGE_GUEST_ELF;
+use risc0_zkvm::{default_executor, ExecutorEnv};
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+use toolkit::constants::CYCLE_REPORT_PREFIX;
+
+const DEFAULT_FIXTURE_DIR: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/fixtures");
+
+/// Cycles spent by the guest on a fixture.
+struct BenchResult {
+    /// User cycles of each phase, as reported by the guest.
+    phases: Vec<(String, u64)>,
+    /// Total cycles of the session, including paging.
+    total_cycles: u64,
+    segments: usize,
+}
+
+fn fixture_paths() -> Result<Vec<PathBuf>> {
+    let args: Vec<PathBuf> = std::env::args_os().skip(1).map(PathBuf::from).collect();
+    if !args.is_empty() {
+        return Ok(args);
+    }
+
+    let mut paths = std::fs::read_dir(DEFAULT_FIXTURE_DIR)
+        .with_context(|| format!("failed to read fixture directory {DEFAULT_FIXTURE_DIR}"))?
+        .map(|entry| Ok(entry?.path()))
+        .collect::<Result<Vec<_>>>()?;
+    paths.retain(|path| path.extension().is_some_and(|ext| ext == "bin"));
+    paths.sort();
+    if paths.is_empty() {
+        bail!("no fixture found in {DEFAULT_FIXTURE_DIR}");
+    }
+
+    Ok(paths)
+}
+
+fn run_fixture(path: &Path) -> Result<BenchResult> {
+    let guest_input =
+        std::fs::read(path).with_context(|| format!("failed to read {}", path.display()))?;
+
+    let mut guest_stderr = Vec::new();
+    let env = ExecutorEnv::builder()
+        .write_slice(&guest_input)
+        .stderr(&mut guest_stderr)
+        .build()?;
+    let session = default_executor()
+        .execute(env, DA_CHALLENGE_GUEST_ELF)
+        .context("guest execution failed")?;
+
+    let phases = String::from_utf8_lossy(&guest_stderr)
+        .lines()
+        .filter_map(|line| {
+            let (phase, cycles) = line.strip_prefix(CYCLE_REPORT_PREFIX)?.rsplit_once(':')?;
+            Some((phase.to_string(), cycles.parse().ok()?))
+        })
+        .collect();
+
+    Ok(BenchResult {
+        phases,
+        total_cycles: session.cycles(),
+        segments: session.segments.len(),
+    })
+}
+
+fn main() -> ExitCode {
+    let paths = match fixture_paths() {
+        Ok(paths) => paths,
+        Err(err) => {
+            eprintln!("{err:#}");
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let mut exit_code = ExitCode::SUCCESS;
+    for path in paths {
+        let name = path.file_stem().unwrap_or_default().to_string_lossy();
+        match run_fixture(&path) {
+            Ok(result) => {
+                println!(
+                    "{name}: {} cycles, {} segments",
+                    result.total_cycles, result.segments
+                );
+                for (phase, cycles) in result.phases {
+                    // Share of the session, ex: to weigh the decoding of the input
+                    let share = cycles as f64 * 100.0 / result.total_cycles.max(1) as f64;
+                    println!("  {phase:<14} {cycles:>12} {share:>6.1}%");
+                }
+            }
+            Err(err) => {
+                println!("{name}: {err:#}");
+                exit_code = ExitCode::FAILURE;
+            }
+        }
+    }
+
+    exit_code
+}
@@ -0,0 +1,96 @@
+use crate::{GuestInput, DA_CHALLENGE_GUEST_ELF};
+use risc0_zkvm::{default_executor, ExecutorEnv};
+use toolkit::journal::Journal;
+
+/// Executes the guest program in the RISC Zero executor, without proving it, and returns its
+/// journal. Guest panics, ex: on invalid inputs, are returned as errors carrying the panic
+/// message.
+pub fn execute_guest(guest_input: &GuestInput) -> Result<Journal, anyhow::Error> {
+    let guest_input = guest_input.encode()?;
+    let env = ExecutorEnv::builder().write_slice(&guest_input).build()?;
+    let session = default_executor().execute(env, DA_CHALLENGE_GUEST_ELF)?;
+
+    Ok(Journal::decode(&session.journal.bytes)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use toolkit::errors::DaFraudKind;
+    use toolkit::journal::DaOutcome;
+
+    /// Guest input of a challenge of a blob listed out of the square by its index, recorded by
+    /// the `record-bench-fixtures` e2e test. The Steel input only covers the Blobstream calls of
+    /// this challenge: the guest data can be altered as long as it keeps the same attestations.
+    const FIXTURE: &str = concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/fixtures/blob_in_index_unavailable.bin"
+    );
+
+    fn fixture() -> GuestInput {
+        let guest_input = std::fs::read(FIXTURE).expect("failed to read the fixture");
+        GuestInput::decode(&guest_input).expect("failed to decode the fixture")
+    }
+
+    #[test]
+    #[ignore = "requires the fixtures recorded by the record-bench-fixtures e2e test"]
+    fn proves_blob_out_of_square() {
+        let journal = execute_guest(&fixture()).expect("guest execution failed");
+
+        assert_eq!(
+            DaOutcome::from_u8(journal.outcome),
+            Some(DaOutcome::FraudProven)
+        );
+        assert_eq!(
+            DaFraudKind::from_u8(journal.fraudType),
+            Some(DaFraudKind::ShareIndexOutOfBounds)
+        );
+    }
+
+    #[test]
+    #[ignore = "requires the fixtures recorded by the record-bench-fixtures e2e test"]
+    fn rejects_blob_not_in_index() {
+        let mut guest_input = fixture();
+        guest_input.da_challenge_guest_data.challenged_blob.size += 1;
+
+        let err = execute_guest(&guest_input).expect_err("the guest should panic");
+        assert!(
+            format!("{err:#}").contains("is not part of the specified index"),
+            "{err:#}"
+        );
+    }
+
+    #[test]
+    #[ignore = "requires the fixtures recorded by the record-bench-fixtures e2e test"]
+    fn rejects_missing_block_proof() {
+        let mut guest_input = fixture();
+        guest_input.da_challenge_guest_data.block_proofs.clear();
+
+        let err = execute_guest(&guest_input).expect_err("the guest should panic");
+        assert!(
+            format!("{err:#}").contains("missing Blobstream attestation and row proof"),
+            "{err:#}"
+        );
+    }
+
+    /// A data root made up by the host under a nonce committed on-chain must be rejected by
+    /// Blobstream, the nonce bound alone does not authenticate it.
+    #[test]
+    #[ignore = "requires the fixtures recorded by the record-bench-fixtures e2e test"]
+    fn rejects_fabricated_data_root() {
+        let mut guest_input = fixture();
+        let block_proof = guest_input
+            .da_challenge_guest_data
+            .block_proofs
+            .values_mut()
+            .next()
+            .expect("the fixture has block proofs");
+        block_proof.blobstream_attestation.data_root[0] ^= 1;
+
+        let err = execute_guest(&guest_input).expect_err("the guest should panic");
+        assert!(
+            format!("{err:#}").contains("Blobstream rejected the attestation"),
+            "{err:#}"
+        );
+    }
+}
@@ -0,0 +1,82 @@
+use anyhow::Context;
+use risc0_steel::config::ChainSpec;
+use risc0_zkvm::serde::Deserializer;
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use toolkit::chain::BlobstreamChainInput;
+use toolkit::journal::GuestOptions;
+use toolkit::{BlobstreamInfo, DaChallengeGuestData};
+
+/// Input of the guest program, in the order in which the guest reads it.
+pub struct GuestInput {
+    pub evm_input: BlobstreamChainInput,
+    pub chain_spec: ChainSpec,
+    pub blobstream_info: BlobstreamInfo,
+    pub guest_options: GuestOptions,
+    pub da_challenge_guest_data: DaChallengeGuestData,
+}
+
+impl GuestInput {
+    /// Encodes the input as the guest reads it from stdin, see `ExecutorEnvBuilder::write_slice()`.
+    pub fn encode(&self) -> Result<Vec<u8>, anyhow::Error> {
+        let serialized_da_guest_data = self
+            .da_challenge_guest_data
+            .encode()
+            .with_context(|| "Failed to serialize DA guest data")?;
+
+        let words = [
+            risc0_zkvm::serde::to_vec(&self.evm_input)?,
+            risc0_zkvm::serde::to_vec(&self.chain_spec)?,
+            risc0_zkvm::serde::to_vec(&self.blobstream_info)?,
+            risc0_zkvm::serde::to_vec(&self.guest_options)?,
+        ];
+        let mut guest_input: Vec<u8> = words
+            .iter()
+            .flatten()
+            .flat_map(|word| word.to_le_bytes())
+            .collect();
+        // Same framing as `ExecutorEnvBuilder::write_frame()`
+        guest_input.extend((serialized_da_guest_data.len() as u32).to_le_bytes());
+        guest_input.extend(serialized_da_guest_data);
+
+        Ok(guest_input)
+    }
+
+    /// Decodes an input encoded by `encode()`, ex: a recorded fixture.
+    pub fn decode(guest_input: &[u8]) -> Result<Self, anyhow::Error> {
+        // The DA guest data frame is not padded, only the values before it are words
+        let words: Vec<u32> = guest_input
+            .chunks_exact(4)
+            .map(|word| u32::from_le_bytes(word.try_into().expect("chunks are 4 bytes")))
+            .collect();
+        let mut reader = words.as_slice();
+
+        let evm_input = read(&mut reader).context("invalid EVM input")?;
+        let chain_spec = read(&mut reader).context("invalid chain spec")?;
+        let blobstream_info = read(&mut reader).context("invalid Blobstream info")?;
+        let guest_options = read(&mut reader).context("invalid guest options")?;
+
+        let frame_size = *reader.first().context("missing DA guest data frame")? as usize;
+        let frame_start = (words.len() - reader.len() + 1) * 4;
+        let serialized_da_guest_data = guest_input
+            .get(frame_start..frame_start + frame_size)
+            .context("truncated DA guest data frame")?;
+        let da_challenge_guest_data = DaChallengeGuestData::decode(serialized_da_guest_data)
+            .context("invalid DA guest data")?;
+
+        Ok(Self {
+            evm_input,
+            chain_spec,
+            blobstream_info,
+            guest_options,
+            da_challenge_guest_data,
+        })
+    }
+}
+
+/// Reads the next value written with `ExecutorEnvBuilder::write()`.
+fn read<T: DeserializeOwned>(reader: &mut &[u32]) -> Result<T, anyhow::Error> {
+    Ok(<T as Deserialize>::deserialize(&mut Deserializer::new(
+        reader,
+    ))?)
+}
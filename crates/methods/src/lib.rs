@@ -14,3 +14,9 @@
 
 //! Generated crate containing the image ID and ELF binary of the build guest.
 include!(concat!(env!("OUT_DIR"), "/methods.rs"));
+
+mod executor;
+mod input;
+
+pub use executor::execute_guest;
+pub use input::GuestInput;
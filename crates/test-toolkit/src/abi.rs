@@ -0,0 +1,83 @@
+use alloy::sol_types::SolStruct;
+use std::collections::HashMap;
+
+/// Returns the ABI layout of a `sol!` struct: its tuple type with nested structs expanded, ex:
+/// `(uint64,uint32,uint32)`. Field names are left out, the encoding does not depend on them.
+///
+/// Comparing the layouts of the Rust definition of a struct and of the bindings generated from
+/// the forge artifact of a contract catches fields added to one side only.
+pub fn sol_struct_layout<T: SolStruct>() -> String {
+    // `Name(type field,...)Component(type field,...)...`, see EIP-712
+    let encode_type = T::eip712_encode_type();
+    let structs: HashMap<&str, Vec<&str>> = encode_type
+        .split_inclusive(')')
+        .map(|definition| {
+            let (name, fields) = definition
+                .strip_suffix(')')
+                .and_then(|definition| definition.split_once('('))
+                .unwrap_or_else(|| panic!("invalid EIP-712 type {encode_type}"));
+            let field_types = fields
+                .split(',')
+                .filter(|field| !field.is_empty())
+                .map(|field| {
+                    field
+                        .split_once(' ')
+                        .map_or(field, |(field_type, _)| field_type)
+                })
+                .collect();
+            (name, field_types)
+        })
+        .collect();
+
+    expand_struct(T::NAME, &structs)
+}
+
+fn expand_struct(name: &str, structs: &HashMap<&str, Vec<&str>>) -> String {
+    let fields: Vec<String> = structs[name]
+        .iter()
+        .map(|field_type| {
+            // Arrays of structs keep their suffix, ex: `SpanSequence[]`
+            let base_type = field_type.split('[').next().unwrap_or(field_type);
+            if structs.contains_key(base_type) {
+                let array_suffix = &field_type[base_type.len()..];
+                format!("{}{array_suffix}", expand_struct(base_type, structs))
+            } else {
+                field_type.to_string()
+            }
+        })
+        .collect();
+
+    format!("({})", fields.join(","))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::contracts::Counter;
+
+    #[test]
+    fn journal_layout_matches_the_verifier_contract() {
+        assert_eq!(
+            sol_struct_layout::<toolkit::journal::Journal>(),
+            sol_struct_layout::<Counter::Journal>()
+        );
+    }
+
+    #[test]
+    fn aggregated_journal_layout_matches_the_verifier_contract() {
+        assert_eq!(
+            sol_struct_layout::<toolkit::journal::AggregatedJournal>(),
+            sol_struct_layout::<Counter::AggregatedJournal>()
+        );
+    }
+
+    #[test]
+    fn nested_structs_are_expanded() {
+        assert_eq!(
+            sol_struct_layout::<toolkit::journal::SpanSequence>(),
+            "(uint64,uint32,uint32)"
+        );
+        assert!(sol_struct_layout::<toolkit::journal::Journal>()
+            .starts_with("(uint16,(uint256,bytes32,bytes32),address,uint8,uint8,"));
+    }
+}
@@ -2,6 +2,7 @@ use crate::contracts::Blobstream0::Blobstream0Instance;
 use alloy::primitives::Address;
 use alloy_contract::private::{Provider, Transport};
 use futures_util::StreamExt;
+use std::time::Duration;
 
 /// Parses deployment output to extract verifier and contract addresses.
 ///
@@ -57,6 +58,102 @@ pub fn get_blobstream_address() -> Address {
 
 const BLOBSTREAM_BATCH_SIZE: u64 = 4;
 
+/// Interval between two `latestHeight()` calls when event filters are not available.
+pub const DEFAULT_POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+/// How to detect that Blobstream reached a height.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WaitStrategy {
+    /// Watch `HeadUpdate` events, falling back to polling if event filters are unavailable.
+    Events,
+    /// Call `latestHeight()` at a fixed interval.
+    Polling { interval: Duration },
+}
+
+/// Configuration of `wait_for_blobstream_inclusion_with_config()`.
+#[derive(Debug, Clone)]
+pub struct BlobstreamInclusionConfig {
+    /// Number of Celestia blocks to wait for past the target height.
+    ///
+    /// Sometimes Anvil does not return the data from the RPC despite sending us
+    /// the corresponding event, so the default is a margin of one Blobstream batch size.
+    /// TODO: determine what's causing this timing issue between event and RPC data availability.
+    pub safety_margin: u64,
+    pub strategy: WaitStrategy,
+    /// Number of Ethereum blocks to wait for once the target height is reached, to protect
+    /// against reorgs on public networks.
+    pub confirmations: u64,
+}
+
+impl Default for BlobstreamInclusionConfig {
+    fn default() -> Self {
+        Self {
+            safety_margin: BLOBSTREAM_BATCH_SIZE,
+            strategy: WaitStrategy::Events,
+            confirmations: 0,
+        }
+    }
+}
+
+impl BlobstreamInclusionConfig {
+    /// Settings suited to a public network like Sepolia: no Anvil margin, polling as
+    /// public RPCs often drop event filters, and a few confirmations.
+    pub fn public_network() -> Self {
+        Self {
+            safety_margin: 0,
+            strategy: WaitStrategy::Polling {
+                interval: Duration::from_secs(12),
+            },
+            confirmations: 3,
+        }
+    }
+
+    /// Reads the configuration from the environment, using the defaults for unset variables:
+    /// - `BLOBSTREAM_SAFETY_MARGIN`: margin in Celestia blocks
+    /// - `BLOBSTREAM_WAIT_STRATEGY`: `events` or `polling`
+    /// - `BLOBSTREAM_POLL_INTERVAL_MS`: polling interval
+    /// - `BLOBSTREAM_CONFIRMATIONS`: number of Ethereum confirmations.
+    pub fn from_env() -> anyhow::Result<Self> {
+        fn parse_var<T: std::str::FromStr>(name: &str) -> anyhow::Result<Option<T>> {
+            std::env::var(name)
+                .ok()
+                .map(|value| {
+                    value
+                        .parse()
+                        .map_err(|_| anyhow::anyhow!("invalid value for {name}: {value}"))
+                })
+                .transpose()
+        }
+
+        let mut config = Self::default();
+        if let Some(safety_margin) = parse_var("BLOBSTREAM_SAFETY_MARGIN")? {
+            config.safety_margin = safety_margin;
+        }
+        if let Some(confirmations) = parse_var("BLOBSTREAM_CONFIRMATIONS")? {
+            config.confirmations = confirmations;
+        }
+
+        let poll_interval = parse_var("BLOBSTREAM_POLL_INTERVAL_MS")?
+            .map(Duration::from_millis)
+            .unwrap_or(DEFAULT_POLL_INTERVAL);
+        config.strategy = match std::env::var("BLOBSTREAM_WAIT_STRATEGY").as_deref() {
+            Ok("polling") => WaitStrategy::Polling {
+                interval: poll_interval,
+            },
+            Ok("events") | Err(_) => WaitStrategy::Events,
+            Ok(other) => {
+                return Err(anyhow::anyhow!(
+                    "invalid value for BLOBSTREAM_WAIT_STRATEGY: {other}"
+                ))
+            }
+        };
+
+        Ok(config)
+    }
+}
+
+/// Waits for Blobstream to cover the target height, configured from the environment
+/// (see `BlobstreamInclusionConfig::from_env()`).
 pub async fn wait_for_blobstream_inclusion<
     T: Clone + Transport,
     P: Provider<T, alloy::network::Ethereum>,
@@ -64,12 +161,49 @@ pub async fn wait_for_blobstream_inclusion<
     blobstream_contract: &Blobstream0Instance<T, P>,
     target_height: u64,
 ) -> anyhow::Result<()> {
-    let current_eth_block = blobstream_contract.provider().get_block_number().await?;
+    let config = BlobstreamInclusionConfig::from_env()?;
+    wait_for_blobstream_inclusion_with_config(blobstream_contract, target_height, &config).await
+}
 
-    // Sometimes Anvil does not return the data from the RPC despite sending us the corresponding
-    // event, so we add a margin of one Blobstream batch size to be safe.
-    // TODO: determine what's causing this timing issue between event and RPC data availability.
-    let target_height = target_height + BLOBSTREAM_BATCH_SIZE;
+pub async fn wait_for_blobstream_inclusion_with_config<
+    T: Clone + Transport,
+    P: Provider<T, alloy::network::Ethereum>,
+>(
+    blobstream_contract: &Blobstream0Instance<T, P>,
+    target_height: u64,
+    config: &BlobstreamInclusionConfig,
+) -> anyhow::Result<()> {
+    let target_height = target_height + config.safety_margin;
+
+    match config.strategy {
+        WaitStrategy::Events => watch_blobstream_height(blobstream_contract, target_height).await?,
+        WaitStrategy::Polling { interval } => {
+            poll_blobstream_height(blobstream_contract, target_height, interval).await?
+        }
+    }
+
+    if config.confirmations > 0 {
+        let provider = blobstream_contract.provider();
+        let inclusion_block = provider.get_block_number().await?;
+        println!(
+            "Waiting for {} confirmations of Ethereum block {inclusion_block}",
+            config.confirmations
+        );
+        while provider.get_block_number().await? < inclusion_block + config.confirmations {
+            tokio::time::sleep(DEFAULT_POLL_INTERVAL).await;
+        }
+    }
+
+    Ok(())
+}
+
+/// Waits until Blobstream's `latestHeight()` reaches the target height by watching `HeadUpdate`
+/// events. Falls back to polling if event filters are unavailable.
+async fn watch_blobstream_height<T: Clone + Transport, P: Provider<T, alloy::network::Ethereum>>(
+    blobstream_contract: &Blobstream0Instance<T, P>,
+    target_height: u64,
+) -> anyhow::Result<()> {
+    let current_eth_block = blobstream_contract.provider().get_block_number().await?;
 
     let current: u64 = blobstream_contract.latestHeight().call().await?._0;
     println!("Current Blobstream height: {current}");
@@ -77,15 +211,38 @@ pub async fn wait_for_blobstream_inclusion<
         return Ok(());
     }
 
-    let mut event_stream = blobstream_contract
+    let event_poller = match blobstream_contract
         .HeadUpdate_filter()
         .from_block(current_eth_block) // block number or tag
         .watch() // ↳ yields `HeaderSynced` structs
-        .await?
-        .into_stream();
+        .await
+    {
+        Ok(event_poller) => event_poller,
+        Err(e) => {
+            println!("Event filters not available ({e}), polling latestHeight() instead");
+            return poll_blobstream_height(
+                blobstream_contract,
+                target_height,
+                DEFAULT_POLL_INTERVAL,
+            )
+            .await;
+        }
+    };
+    let mut event_stream = event_poller.into_stream();
 
     while let Some(evt) = event_stream.next().await {
-        let evt = evt?; // unwrap provider errors
+        let evt = match evt {
+            Ok(evt) => evt,
+            Err(e) => {
+                println!("Failed to decode Blobstream event ({e}), polling latestHeight() instead");
+                return poll_blobstream_height(
+                    blobstream_contract,
+                    target_height,
+                    DEFAULT_POLL_INTERVAL,
+                )
+                .await;
+            }
+        };
         println!("Blobstream head update: {}", evt.0.blockNumber);
 
         if evt.0.blockNumber >= target_height {
@@ -93,8 +250,29 @@ pub async fn wait_for_blobstream_inclusion<
         }
     }
 
-    // Sub-stream ended unexpectedly (provider closed) - treat as error.
-    Err(anyhow::anyhow!("event stream closed before height reached"))
+    // The filter stream ended (ex: the provider dropped the filter), keep waiting by polling.
+    poll_blobstream_height(blobstream_contract, target_height, DEFAULT_POLL_INTERVAL).await
+}
+
+/// Waits until Blobstream's `latestHeight()` reaches the target height by calling it every
+/// `poll_interval`. Works with any provider, unlike event filters.
+pub async fn poll_blobstream_height<
+    T: Clone + Transport,
+    P: Provider<T, alloy::network::Ethereum>,
+>(
+    blobstream_contract: &Blobstream0Instance<T, P>,
+    target_height: u64,
+    poll_interval: Duration,
+) -> anyhow::Result<()> {
+    loop {
+        let current: u64 = blobstream_contract.latestHeight().call().await?._0;
+        if current >= target_height {
+            println!("Blobstream height reached: {current}");
+            return Ok(());
+        }
+
+        tokio::time::sleep(poll_interval).await;
+    }
 }
 
 pub async fn wait_for_blobstream_inclusion_with_timeout<T, P>(
@@ -2,19 +2,30 @@ use anyhow::Context;
 use celestia_rpc::{BlobClient, Client as CelestiaClient, HeaderClient, TxConfig};
 use celestia_types::nmt::Namespace;
 use celestia_types::{AppVersion, Blob};
-use toolkit::{eds_index_to_ods, BlobIndex, SpanSequence};
+use toolkit::{blob_data_hash, BlobIndex, BlobIndexBuilder, SpanSequence, SquareWidth};
 
 /// Namespace used for all blobs in this test.
 pub const DEFAULT_NAMESPACE: Namespace =
     Namespace::const_v0([0, 0, 0, 0, 0, 0, 0xDE, 0xAD, 0xBE, 0xEF]);
 
+/// Returns the app version of the Celestia network, to create blobs with.
+pub async fn current_app_version(
+    celestia_client: &CelestiaClient,
+) -> Result<AppVersion, anyhow::Error> {
+    let head = celestia_client.header_local_head().await?;
+    let app_version = head.header.version.app;
+
+    AppVersion::from_u64(app_version)
+        .ok_or_else(|| anyhow::anyhow!("unsupported app version {app_version}"))
+}
+
 async fn _publish_single_blob(
     celestia_client: &CelestiaClient,
     data: Vec<u8>,
     namespace: Namespace,
 ) -> Result<SpanSequence, anyhow::Error> {
-    let blob =
-        Blob::new(namespace, data, AppVersion::V2).with_context(|| "blob creation failed")?;
+    let app_version = current_app_version(celestia_client).await?;
+    let blob = Blob::new(namespace, data, app_version).with_context(|| "blob creation failed")?;
     let blob_commitment = blob.commitment;
     let height = celestia_client
         .blob_submit(&[blob], TxConfig::default())
@@ -27,9 +38,9 @@ async fn _publish_single_blob(
         .with_context(|| "failed to fetch blob")?;
 
     let block_header = celestia_client.header_get_by_height(height).await?;
-    let eds_width = block_header.dah.square_width() as u32;
+    let square_width = SquareWidth::from_eds(block_header.dah.square_width() as u32);
 
-    let start = eds_index_to_ods(posted_blob.index.unwrap() as u32, eds_width);
+    let start = square_width.eds_to_ods(posted_blob.index.unwrap() as u32)?;
 
     Ok(SpanSequence {
         height,
@@ -70,7 +81,7 @@ pub async fn publish_blobs(
         println!("Blob batch was included at height {height}");
 
         let block_header = celestia_client.header_get_by_height(height).await?;
-        let eds_width = block_header.dah.square_width() as u32;
+        let square_width = SquareWidth::from_eds(block_header.dah.square_width() as u32);
 
         for blob in batch {
             let posted_blob = celestia_client
@@ -82,10 +93,8 @@ pub async fn publish_blobs(
                         blob.commitment, height
                     )
                 })?;
-            let start = eds_index_to_ods(
-                posted_blob.index.expect("posted blob should have an index") as u32,
-                eds_width,
-            );
+            let start = square_width
+                .eds_to_ods(posted_blob.index.expect("posted blob should have an index") as u32)?;
             blob_spans.push(SpanSequence {
                 height,
                 start,
@@ -110,11 +119,40 @@ pub async fn publish_index(
     index: &BlobIndex,
     namespace: Namespace,
 ) -> Result<SpanSequence, anyhow::Error> {
-    let encoded_index =
-        bincode::serialize(index).with_context(|| "failed to serialize blob spans")?;
+    let encoded_index = index
+        .encode()
+        .with_context(|| "failed to serialize blob spans")?;
     _publish_single_blob(celestia_client, encoded_index, namespace).await
 }
 
+/// Same as `publish_index()`, with the canonical encoding of the index, see
+/// `BlobIndex::encode_canonical()`.
+pub async fn publish_canonical_index(
+    celestia_client: &CelestiaClient,
+    index: &BlobIndex,
+    namespace: Namespace,
+) -> Result<SpanSequence, anyhow::Error> {
+    let encoded_index = index
+        .encode_canonical()
+        .with_context(|| "failed to serialize blob spans")?;
+    _publish_single_blob(celestia_client, encoded_index, namespace).await
+}
+
+/// Returns the height of the latest block without blobs, ie. with the minimal data square of
+/// width 1.
+pub async fn find_empty_block(celestia_client: &CelestiaClient) -> Result<u64, anyhow::Error> {
+    let head_height = celestia_client.header_local_head().await?.height().value();
+
+    for height in (1..=head_height).rev() {
+        let block_header = celestia_client.header_get_by_height(height).await?;
+        if block_header.dah.square_width() == 2 {
+            return Ok(height);
+        }
+    }
+
+    anyhow::bail!("no empty block up to height {head_height}")
+}
+
 /// Publishes a bunch of blobs and an index blob that points to them.
 pub async fn publish_index_blob_with_bad_blob_position(
     celestia_client: &CelestiaClient,
@@ -141,16 +179,80 @@ pub async fn create_and_publish_index_blob(
     blob_size: usize,
     blobs_per_block: usize,
 ) -> Result<(BlobIndex, SpanSequence), anyhow::Error> {
+    let app_version = current_app_version(celestia_client).await?;
     let blobs = (0..n_blobs)
         .map(|x| {
-            Blob::new(DEFAULT_NAMESPACE, vec![x as u8; blob_size], AppVersion::V2)
+            Blob::new(DEFAULT_NAMESPACE, vec![x as u8; blob_size], app_version)
+                .with_context(|| "blob creation failed")
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let mut blob_spans = publish_blobs(celestia_client, &blobs, blobs_per_block).await?;
+    // Blobs of the same block are not necessarily laid out in the order they were posted in
+    blob_spans.sort();
+
+    let index = blob_spans
+        .into_iter()
+        .fold(BlobIndexBuilder::new(), BlobIndexBuilder::with_blob)
+        .build()?;
+    let index_span_sequence = publish_index(celestia_client, &index, DEFAULT_NAMESPACE).await?;
+    Ok((index, index_span_sequence))
+}
+
+/// Publishes a bunch of blobs and an index blob that commits to their data. The data hash of the
+/// blob at position `corrupted_blob`, if any, does not match the published data.
+pub async fn create_and_publish_index_blob_with_data_hashes(
+    celestia_client: &CelestiaClient,
+    n_blobs: usize,
+    blob_size: usize,
+    blobs_per_block: usize,
+    corrupted_blob: Option<usize>,
+) -> Result<(BlobIndex, SpanSequence), anyhow::Error> {
+    let app_version = current_app_version(celestia_client).await?;
+    let blobs = (0..n_blobs)
+        .map(|x| {
+            Blob::new(DEFAULT_NAMESPACE, vec![x as u8; blob_size], app_version)
                 .with_context(|| "blob creation failed")
         })
         .collect::<Result<Vec<_>, _>>()?;
 
     let blob_spans = publish_blobs(celestia_client, &blobs, blobs_per_block).await?;
+    let mut entries: Vec<_> = blob_spans
+        .into_iter()
+        .zip(&blobs)
+        .map(|(span_sequence, blob)| (span_sequence, blob_data_hash(&blob.data)))
+        .collect();
+    // Blobs of the same block are not necessarily laid out in the order they were posted in
+    entries.sort();
+    if let Some(corrupted_blob) = corrupted_blob {
+        entries[corrupted_blob].1[0] ^= 0xff;
+    }
 
-    let index = BlobIndex::new(blob_spans);
+    let index = entries
+        .into_iter()
+        .fold(
+            BlobIndexBuilder::new(),
+            |builder, (span_sequence, data_hash)| {
+                builder.with_blob_and_data_hash(span_sequence, data_hash)
+            },
+        )
+        .build()?;
     let index_span_sequence = publish_index(celestia_client, &index, DEFAULT_NAMESPACE).await?;
     Ok((index, index_span_sequence))
 }
+
+/// Publishes `sub_index`, then an index blob that lists it as its only sub-index. Returns the
+/// top-level index, its span sequence and the span sequence of the sub-index.
+pub async fn publish_nested_index(
+    celestia_client: &CelestiaClient,
+    sub_index: &BlobIndex,
+) -> Result<(BlobIndex, SpanSequence, SpanSequence), anyhow::Error> {
+    let sub_index_span_sequence =
+        publish_index(celestia_client, sub_index, DEFAULT_NAMESPACE).await?;
+
+    let index = BlobIndexBuilder::new()
+        .with_sub_index(sub_index_span_sequence)
+        .build()?;
+    let index_span_sequence = publish_index(celestia_client, &index, DEFAULT_NAMESPACE).await?;
+    Ok((index, index_span_sequence, sub_index_span_sequence))
+}
@@ -1,3 +1,4 @@
+pub mod abi;
 pub mod blobstream;
 pub mod contracts;
 pub mod index_blob;
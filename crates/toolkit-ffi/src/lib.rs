@@ -0,0 +1,510 @@
+//! C ABI of the toolkit, for sequencers written in C, C++ or Go that post challengeable indexes
+//! and for tools reading the journals of on-chain proofs.
+//!
+//! The header is generated by the build script in `$OUT_DIR/toolkit.h`. Functions return a
+//! `DaStatus`; on failure, `da_last_error()` describes the error. Memory allocated by the library
+//! is released with the matching `*_free()` function.
+
+use std::cell::RefCell;
+use std::ffi::{c_char, CString};
+use std::ptr;
+use toolkit::constants::NAMESPACE_SIZE;
+use toolkit::journal::Journal;
+use toolkit::{BatchMetadata, BlobIndex, BlobIndexBuilder, SpanSequence};
+
+thread_local! {
+    static LAST_ERROR: RefCell<Option<CString>> = const { RefCell::new(None) };
+}
+
+/// Result of the functions of the library.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DaStatus {
+    Ok = 0,
+    NullPointer = 1,
+    /// The index is malformed, see `BlobIndex::validate()`.
+    InvalidIndex = 2,
+    /// The index could not be decoded or encoded.
+    InvalidEncoding = 3,
+    /// The journal could not be decoded.
+    InvalidJournal = 4,
+}
+
+/// Encoding of an index, see `BlobIndex::encode()` and `BlobIndex::encode_canonical()`.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DaIndexEncoding {
+    Bincode = 0,
+    Canonical = 1,
+}
+
+/// A range of shares of a Celestia block, see `toolkit::SpanSequence`.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DaSpanSequence {
+    pub height: u64,
+    pub start: u32,
+    pub size: u32,
+}
+
+impl From<SpanSequence> for DaSpanSequence {
+    fn from(span_sequence: SpanSequence) -> Self {
+        Self {
+            height: span_sequence.height,
+            start: span_sequence.start,
+            size: span_sequence.size,
+        }
+    }
+}
+
+impl From<DaSpanSequence> for SpanSequence {
+    fn from(span_sequence: DaSpanSequence) -> Self {
+        Self {
+            height: span_sequence.height,
+            start: span_sequence.start,
+            size: span_sequence.size,
+        }
+    }
+}
+
+/// Bytes allocated by the library, released with `da_buffer_free()`.
+#[repr(C)]
+#[derive(Debug)]
+pub struct DaBuffer {
+    pub data: *mut u8,
+    pub len: usize,
+}
+
+impl DaBuffer {
+    const EMPTY: Self = Self {
+        data: ptr::null_mut(),
+        len: 0,
+    };
+
+    fn new(data: Vec<u8>) -> Self {
+        let data = Box::into_raw(data.into_boxed_slice());
+        Self {
+            data: data as *mut u8,
+            len: data.len(),
+        }
+    }
+}
+
+/// Index under construction, see `BlobIndexBuilder`.
+pub struct DaIndexBuilder(BlobIndexBuilder);
+
+/// Journal of a DA challenge proof, see `toolkit::journal::Journal`. Big integers and hashes are
+/// big-endian. The arrays are owned by the journal, released with `da_journal_free()`.
+#[repr(C)]
+#[derive(Debug)]
+pub struct DaJournal {
+    pub version: u16,
+    /// `DaOutcome` identifier.
+    pub outcome: u8,
+    /// `DaChallenge` identifier.
+    pub challenge_type: u8,
+    /// `DaFraudKind` identifier, 0 if no fraud is proven.
+    pub fraud_type: u8,
+    /// `BlobstreamImpl` identifier.
+    pub blobstream_impl: u8,
+    pub blobstream_address: [u8; 20],
+    pub commitment_id: [u8; 32],
+    pub commitment_digest: [u8; 32],
+    pub commitment_config_id: [u8; 32],
+    pub index_blob: DaSpanSequence,
+    pub challenged_blob: DaSpanSequence,
+    /// Canonical hash of the index, zero if the index was not read.
+    pub index_hash: [u8; 32],
+    /// Batch metadata of the index, zero if the index was not read or carries none.
+    pub rollup_id: [u8; 32],
+    pub batch_number: u64,
+    pub batch_timestamp: u64,
+    /// Namespace the index blob was required to be posted to, empty if not checked.
+    pub namespace: DaBuffer,
+    /// Chain ID of the Celestia network, UTF-8 without NUL terminator.
+    pub celestia_chain_id: DaBuffer,
+    /// `extra_len` other challenged blobs, and the fraud proven for each of them.
+    pub extra_challenged_blobs: *mut DaSpanSequence,
+    pub extra_fraud_types: *mut u8,
+    pub extra_len: usize,
+}
+
+fn set_last_error(error: impl ToString) {
+    let error = CString::new(error.to_string()).unwrap_or_default();
+    LAST_ERROR.with(|last_error| *last_error.borrow_mut() = Some(error));
+}
+
+fn fail(status: DaStatus, error: impl ToString) -> DaStatus {
+    set_last_error(error);
+    status
+}
+
+/// Returns a description of the last error of the calling thread, or NULL if none. The string
+/// is valid until the next call of the library on the thread.
+#[no_mangle]
+pub extern "C" fn da_last_error() -> *const c_char {
+    LAST_ERROR.with(|last_error| {
+        last_error
+            .borrow()
+            .as_ref()
+            .map_or(ptr::null(), |error| error.as_ptr())
+    })
+}
+
+/// Releases a buffer returned by the library.
+///
+/// # Safety
+/// `buffer` must have been returned by the library and not released before.
+#[no_mangle]
+pub unsafe extern "C" fn da_buffer_free(buffer: DaBuffer) {
+    if !buffer.data.is_null() {
+        drop(Box::from_raw(ptr::slice_from_raw_parts_mut(
+            buffer.data,
+            buffer.len,
+        )));
+    }
+}
+
+/// Creates an empty index builder, accepting up to `MAX_INDEX_ENTRIES` entries.
+#[no_mangle]
+pub extern "C" fn da_index_builder_new() -> *mut DaIndexBuilder {
+    Box::into_raw(Box::new(DaIndexBuilder(BlobIndexBuilder::new())))
+}
+
+/// Releases a builder without building its index.
+///
+/// # Safety
+/// `builder` must be NULL or returned by `da_index_builder_new()`, and not released before.
+#[no_mangle]
+pub unsafe extern "C" fn da_index_builder_free(builder: *mut DaIndexBuilder) {
+    if !builder.is_null() {
+        drop(Box::from_raw(builder));
+    }
+}
+
+/// Sets the maximum number of entries of the index.
+///
+/// # Safety
+/// `builder` must be NULL or a live builder.
+#[no_mangle]
+pub unsafe extern "C" fn da_index_builder_set_max_entries(
+    builder: *mut DaIndexBuilder,
+    max_entries: usize,
+) -> DaStatus {
+    let Some(builder) = builder.as_mut() else {
+        return fail(DaStatus::NullPointer, "null builder");
+    };
+    builder.0 = std::mem::take(&mut builder.0).with_max_entries(max_entries);
+    DaStatus::Ok
+}
+
+/// Appends an entry to the index. `data_hash` is the SHA-256 hash of the data of the blob, or
+/// NULL: it must be set for all the entries or for none. `sub_index` flags entries pointing to
+/// another index.
+///
+/// # Safety
+/// `builder` must be NULL or a live builder, `data_hash` NULL or 32 readable bytes.
+#[no_mangle]
+pub unsafe extern "C" fn da_index_builder_add_entry(
+    builder: *mut DaIndexBuilder,
+    blob: DaSpanSequence,
+    data_hash: *const u8,
+    sub_index: bool,
+) -> DaStatus {
+    let Some(builder) = builder.as_mut() else {
+        return fail(DaStatus::NullPointer, "null builder");
+    };
+    let data_hash = (!data_hash.is_null()).then(|| *(data_hash as *const [u8; 32]));
+    builder.0 = std::mem::take(&mut builder.0).with_entry(blob.into(), data_hash, sub_index);
+    DaStatus::Ok
+}
+
+/// Appends an entry to the index like `da_index_builder_add_entry()`, with the 29-byte namespace
+/// the blob is posted to. Namespaces must be set for all the entries or for none.
+///
+/// # Safety
+/// `builder` must be NULL or a live builder, `data_hash` NULL or 32 readable bytes, `namespace`
+/// NULL or 29 readable bytes.
+#[no_mangle]
+pub unsafe extern "C" fn da_index_builder_add_namespaced_entry(
+    builder: *mut DaIndexBuilder,
+    blob: DaSpanSequence,
+    data_hash: *const u8,
+    sub_index: bool,
+    namespace: *const u8,
+) -> DaStatus {
+    let Some(builder) = builder.as_mut() else {
+        return fail(DaStatus::NullPointer, "null builder");
+    };
+    if namespace.is_null() {
+        return fail(DaStatus::NullPointer, "null namespace");
+    }
+    let data_hash = (!data_hash.is_null()).then(|| *(data_hash as *const [u8; 32]));
+    let namespace = *(namespace as *const [u8; NAMESPACE_SIZE]);
+    builder.0 = std::mem::take(&mut builder.0)
+        .with_entry(blob.into(), data_hash, sub_index)
+        .with_namespace(namespace);
+    DaStatus::Ok
+}
+
+/// Sets the 32-byte share commitment of the last appended entry. Commitments must be set for
+/// all the entries or for none.
+///
+/// # Safety
+/// `builder` must be NULL or a live builder, `commitment` NULL or 32 readable bytes.
+#[no_mangle]
+pub unsafe extern "C" fn da_index_builder_set_commitment(
+    builder: *mut DaIndexBuilder,
+    commitment: *const u8,
+) -> DaStatus {
+    let Some(builder) = builder.as_mut() else {
+        return fail(DaStatus::NullPointer, "null builder");
+    };
+    if commitment.is_null() {
+        return fail(DaStatus::NullPointer, "null commitment");
+    }
+    if builder.0.is_empty() {
+        return fail(DaStatus::InvalidIndex, "no entry to set the commitment of");
+    }
+    let commitment = *(commitment as *const [u8; 32]);
+    builder.0 = std::mem::take(&mut builder.0).with_commitment(commitment);
+    DaStatus::Ok
+}
+
+/// Sets the batch the index is posted for, see `toolkit::BatchMetadata`.
+///
+/// # Safety
+/// `builder` must be NULL or a live builder, `rollup_id` NULL or 32 readable bytes.
+#[no_mangle]
+pub unsafe extern "C" fn da_index_builder_set_metadata(
+    builder: *mut DaIndexBuilder,
+    rollup_id: *const u8,
+    batch_number: u64,
+    timestamp: u64,
+) -> DaStatus {
+    let Some(builder) = builder.as_mut() else {
+        return fail(DaStatus::NullPointer, "null builder");
+    };
+    if rollup_id.is_null() {
+        return fail(DaStatus::NullPointer, "null rollup ID");
+    }
+    let metadata = BatchMetadata {
+        rollup_id: (*(rollup_id as *const [u8; 32])).into(),
+        batch_number,
+        timestamp,
+    };
+    builder.0 = std::mem::take(&mut builder.0).with_metadata(metadata);
+    DaStatus::Ok
+}
+
+/// Builds and encodes the index, checking that it is well-formed. The builder is released,
+/// whatever the outcome.
+///
+/// # Safety
+/// `builder` must be NULL or a live builder, `encoded` writable.
+#[no_mangle]
+pub unsafe extern "C" fn da_index_builder_build(
+    builder: *mut DaIndexBuilder,
+    encoding: DaIndexEncoding,
+    encoded: *mut DaBuffer,
+) -> DaStatus {
+    if builder.is_null() {
+        return fail(DaStatus::NullPointer, "null builder");
+    }
+    let builder = Box::from_raw(builder);
+    let Some(encoded) = encoded.as_mut() else {
+        return fail(DaStatus::NullPointer, "null output");
+    };
+    *encoded = DaBuffer::EMPTY;
+
+    let index = match builder.0.build() {
+        Ok(index) => index,
+        Err(e) => return fail(DaStatus::InvalidIndex, e),
+    };
+    let data = match encoding {
+        DaIndexEncoding::Bincode => match index.encode() {
+            Ok(data) => data,
+            Err(e) => return fail(DaStatus::InvalidEncoding, e),
+        },
+        DaIndexEncoding::Canonical => match index.encode_canonical() {
+            Ok(data) => data,
+            Err(e) => return fail(DaStatus::InvalidIndex, e),
+        },
+    };
+    *encoded = DaBuffer::new(data);
+
+    DaStatus::Ok
+}
+
+/// Decodes an index posted on Celestia, in any encoding, and checks that it is well-formed. On
+/// success, writes its canonical hash to `canonical_hash` if not NULL.
+///
+/// # Safety
+/// `data` must point to `len` readable bytes, `canonical_hash` be NULL or 32 writable bytes.
+#[no_mangle]
+pub unsafe extern "C" fn da_index_validate(
+    data: *const u8,
+    len: usize,
+    canonical_hash: *mut u8,
+) -> DaStatus {
+    if data.is_null() {
+        return fail(DaStatus::NullPointer, "null index data");
+    }
+    let index = match BlobIndex::decode(std::slice::from_raw_parts(data, len)) {
+        Ok(index) => index,
+        Err(e) => return fail(DaStatus::InvalidEncoding, e),
+    };
+    if let Err(e) = index.validate() {
+        return fail(DaStatus::InvalidIndex, e);
+    }
+    if !canonical_hash.is_null() {
+        match index.canonical_hash() {
+            Ok(hash) => *(canonical_hash as *mut [u8; 32]) = hash.0,
+            Err(e) => return fail(DaStatus::InvalidIndex, e),
+        }
+    }
+
+    DaStatus::Ok
+}
+
+/// Decodes the ABI-encoded journal of a DA challenge proof, rejecting unsupported layout
+/// versions.
+///
+/// # Safety
+/// `data` must point to `len` readable bytes, `journal` be writable.
+#[no_mangle]
+pub unsafe extern "C" fn da_journal_decode(
+    data: *const u8,
+    len: usize,
+    journal: *mut DaJournal,
+) -> DaStatus {
+    if data.is_null() || journal.is_null() {
+        return fail(DaStatus::NullPointer, "null journal data or output");
+    }
+    let decoded = match Journal::decode(std::slice::from_raw_parts(data, len)) {
+        Ok(decoded) => decoded,
+        Err(e) => return fail(DaStatus::InvalidJournal, e),
+    };
+
+    let extra_challenged_blobs: Box<[DaSpanSequence]> = decoded
+        .extraChallengedBlobs
+        .into_iter()
+        .map(|blob| SpanSequence::from(blob).into())
+        .collect();
+    let extra_fraud_types = decoded.extraFraudTypes.into_boxed_slice();
+    if extra_fraud_types.len() != extra_challenged_blobs.len() {
+        return fail(
+            DaStatus::InvalidJournal,
+            "one fraud type per extra challenged blob is required",
+        );
+    }
+
+    *journal = DaJournal {
+        version: decoded.version,
+        outcome: decoded.outcome,
+        challenge_type: decoded.challengeType,
+        fraud_type: decoded.fraudType,
+        blobstream_impl: decoded.blobstreamImpl,
+        blobstream_address: decoded.blobstreamAddress.into(),
+        commitment_id: decoded.commitment.id.to_be_bytes::<32>(),
+        commitment_digest: decoded.commitment.digest.0,
+        commitment_config_id: decoded.commitment.configID.0,
+        index_blob: SpanSequence::from(decoded.indexBlob).into(),
+        challenged_blob: SpanSequence::from(decoded.challengedBlob).into(),
+        index_hash: decoded.indexHash.0,
+        rollup_id: decoded.batchMetadata.rollupId.0,
+        batch_number: decoded.batchMetadata.batchNumber,
+        batch_timestamp: decoded.batchMetadata.timestamp,
+        namespace: DaBuffer::new(decoded.namespace.to_vec()),
+        celestia_chain_id: DaBuffer::new(decoded.celestiaChainId.into_bytes()),
+        extra_len: extra_challenged_blobs.len(),
+        extra_challenged_blobs: Box::into_raw(extra_challenged_blobs) as *mut DaSpanSequence,
+        extra_fraud_types: Box::into_raw(extra_fraud_types) as *mut u8,
+    };
+
+    DaStatus::Ok
+}
+
+/// Releases the arrays of a journal decoded by `da_journal_decode()`.
+///
+/// # Safety
+/// `journal` must be NULL or decoded by `da_journal_decode()`, and not released before.
+#[no_mangle]
+pub unsafe extern "C" fn da_journal_free(journal: *mut DaJournal) {
+    let Some(journal) = journal.as_mut() else {
+        return;
+    };
+    da_buffer_free(std::mem::replace(&mut journal.namespace, DaBuffer::EMPTY));
+    da_buffer_free(std::mem::replace(
+        &mut journal.celestia_chain_id,
+        DaBuffer::EMPTY,
+    ));
+    if !journal.extra_challenged_blobs.is_null() {
+        drop(Box::from_raw(ptr::slice_from_raw_parts_mut(
+            journal.extra_challenged_blobs,
+            journal.extra_len,
+        )));
+    }
+    if !journal.extra_fraud_types.is_null() {
+        drop(Box::from_raw(ptr::slice_from_raw_parts_mut(
+            journal.extra_fraud_types,
+            journal.extra_len,
+        )));
+    }
+    journal.extra_challenged_blobs = ptr::null_mut();
+    journal.extra_fraud_types = ptr::null_mut();
+    journal.extra_len = 0;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn build_and_validate_index() {
+        unsafe {
+            let builder = da_index_builder_new();
+            for start in [0, 4] {
+                let blob = DaSpanSequence {
+                    height: 10,
+                    start,
+                    size: 4,
+                };
+                let status = da_index_builder_add_entry(builder, blob, ptr::null(), false);
+                assert_eq!(status, DaStatus::Ok);
+            }
+            let mut encoded = DaBuffer::EMPTY;
+            let status = da_index_builder_build(builder, DaIndexEncoding::Canonical, &mut encoded);
+            assert_eq!(status, DaStatus::Ok);
+
+            let mut canonical_hash = [0u8; 32];
+            let status = da_index_validate(encoded.data, encoded.len, canonical_hash.as_mut_ptr());
+            assert_eq!(status, DaStatus::Ok);
+            let index =
+                BlobIndex::decode_canonical(std::slice::from_raw_parts(encoded.data, encoded.len))
+                    .unwrap();
+            assert_eq!(canonical_hash, index.canonical_hash().unwrap().0);
+            da_buffer_free(encoded);
+        }
+    }
+
+    #[test]
+    fn build_rejects_overlapping_entries() {
+        unsafe {
+            let builder = da_index_builder_new();
+            for start in [0, 2] {
+                let blob = DaSpanSequence {
+                    height: 10,
+                    start,
+                    size: 4,
+                };
+                da_index_builder_add_entry(builder, blob, ptr::null(), false);
+            }
+            let mut encoded = DaBuffer::EMPTY;
+            let status = da_index_builder_build(builder, DaIndexEncoding::Bincode, &mut encoded);
+            assert_eq!(status, DaStatus::InvalidIndex);
+            assert!(encoded.data.is_null());
+            assert!(!da_last_error().is_null());
+        }
+    }
+}
@@ -0,0 +1,277 @@
+//! Python bindings of the toolkit, the `da_challenge_toolkit` module, for scripts reading posted
+//! indexes and on-chain journals.
+//!
+//! Build and install in the current virtualenv with
+//! `maturin develop -m crates/toolkit-py/Cargo.toml`.
+
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use pyo3::types::PyBytes;
+use toolkit::constants::NETWORKS;
+use toolkit::journal::JournalSummary;
+use toolkit::{BatchMetadata, BlobIndex};
+
+fn value_error(error: impl ToString) -> PyErr {
+    PyValueError::new_err(error.to_string())
+}
+
+/// A range of shares of a Celestia block.
+#[pyclass(
+    name = "SpanSequence",
+    module = "da_challenge_toolkit",
+    frozen,
+    eq,
+    hash
+)]
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+struct PySpanSequence(toolkit::SpanSequence);
+
+#[pymethods]
+impl PySpanSequence {
+    #[new]
+    fn new(height: u64, start: u32, size: u32) -> Self {
+        Self(toolkit::SpanSequence {
+            height,
+            start,
+            size,
+        })
+    }
+
+    /// Parses a span sequence formatted as `height:start:size`.
+    #[staticmethod]
+    fn parse(span_sequence: &str) -> PyResult<Self> {
+        span_sequence.parse().map(Self).map_err(value_error)
+    }
+
+    #[getter]
+    fn height(&self) -> u64 {
+        self.0.height
+    }
+
+    #[getter]
+    fn start(&self) -> u32 {
+        self.0.start
+    }
+
+    #[getter]
+    fn size(&self) -> u32 {
+        self.0.size
+    }
+
+    /// Returns whether the share at `share_index` in the ODS of the block at `height` is one of
+    /// the shares of the blob.
+    fn contains_share(&self, height: u64, share_index: u32) -> bool {
+        self.0.contains_share(height, share_index)
+    }
+
+    /// Returns whether the blob shares at least one share with `other`.
+    fn overlaps(&self, other: &Self) -> bool {
+        self.0.overlaps(&other.0)
+    }
+
+    fn __str__(&self) -> String {
+        self.0.to_string()
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "SpanSequence(height={}, start={}, size={})",
+            self.0.height, self.0.start, self.0.size
+        )
+    }
+}
+
+/// An index blob: the blobs of a batch, with the SHA-256 hashes of their data if the index
+/// commits to it, the positions of the entries that are sub-indexes, the namespace and share
+/// commitment of each blob if the index lists them, and the batch it was posted for.
+#[pyclass(name = "BlobIndex", module = "da_challenge_toolkit", eq)]
+#[derive(Clone, PartialEq)]
+struct PyBlobIndex(BlobIndex);
+
+#[pymethods]
+impl PyBlobIndex {
+    #[new]
+    #[pyo3(signature = (
+        blobs,
+        data_hashes = None,
+        sub_indexes = None,
+        namespaces = None,
+        commitments = None,
+        metadata = None
+    ))]
+    fn new(
+        blobs: Vec<PySpanSequence>,
+        data_hashes: Option<Vec<Vec<u8>>>,
+        sub_indexes: Option<Vec<u32>>,
+        namespaces: Option<Vec<Vec<u8>>>,
+        commitments: Option<Vec<Vec<u8>>>,
+        metadata: Option<(Vec<u8>, u64, u64)>,
+    ) -> PyResult<Self> {
+        let data_hashes = data_hashes
+            .unwrap_or_default()
+            .into_iter()
+            .map(|data_hash| {
+                data_hash
+                    .try_into()
+                    .map_err(|_| value_error("data hashes are 32 bytes"))
+            })
+            .collect::<PyResult<_>>()?;
+        let namespaces = namespaces
+            .unwrap_or_default()
+            .into_iter()
+            .map(|namespace| {
+                namespace
+                    .try_into()
+                    .map_err(|_| value_error("namespaces are 29 bytes"))
+            })
+            .collect::<PyResult<_>>()?;
+        let commitments = commitments
+            .unwrap_or_default()
+            .into_iter()
+            .map(|commitment| {
+                commitment
+                    .try_into()
+                    .map_err(|_| value_error("share commitments are 32 bytes"))
+            })
+            .collect::<PyResult<_>>()?;
+        let metadata = metadata
+            .map(|(rollup_id, batch_number, timestamp)| {
+                let rollup_id: [u8; 32] = rollup_id
+                    .try_into()
+                    .map_err(|_| value_error("rollup IDs are 32 bytes"))?;
+                Ok::<_, PyErr>(BatchMetadata {
+                    rollup_id: rollup_id.into(),
+                    batch_number,
+                    timestamp,
+                })
+            })
+            .transpose()?;
+
+        Ok(Self(BlobIndex {
+            blobs: blobs.into_iter().map(|blob| blob.0).collect(),
+            data_hashes,
+            sub_indexes: sub_indexes.unwrap_or_default().into_iter().collect(),
+            namespaces,
+            commitments,
+            metadata,
+        }))
+    }
+
+    /// Decodes an index posted on Celestia, in any encoding.
+    #[staticmethod]
+    fn decode(data: &[u8]) -> PyResult<Self> {
+        BlobIndex::decode(data).map(Self).map_err(value_error)
+    }
+
+    /// Encodes the index as posted by the sequencer. Raises `ValueError` if it is malformed.
+    fn encode<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyBytes>> {
+        self.validate()?;
+        let data = self.0.encode().map_err(value_error)?;
+        Ok(PyBytes::new(py, &data))
+    }
+
+    /// Encodes the index in the canonical encoding. Raises `ValueError` if it is malformed.
+    fn encode_canonical<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyBytes>> {
+        self.validate()?;
+        let data = self.0.encode_canonical().map_err(value_error)?;
+        Ok(PyBytes::new(py, &data))
+    }
+
+    /// Returns the Keccak-256 hash of the canonical encoding, committed in journals.
+    fn canonical_hash<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyBytes>> {
+        self.validate()?;
+        let canonical_hash = self.0.canonical_hash().map_err(value_error)?;
+        Ok(PyBytes::new(py, canonical_hash.as_slice()))
+    }
+
+    /// Raises `ValueError` if the index is malformed: empty, unsorted or overlapping entries,
+    /// too many entries, or data hashes, namespaces or commitments missing for some entries.
+    fn validate(&self) -> PyResult<()> {
+        self.0.validate().map_err(value_error)
+    }
+
+    #[getter]
+    fn blobs(&self) -> Vec<PySpanSequence> {
+        self.0.blobs.iter().copied().map(PySpanSequence).collect()
+    }
+
+    #[getter]
+    fn data_hashes<'py>(&self, py: Python<'py>) -> Vec<Bound<'py, PyBytes>> {
+        self.0
+            .data_hashes
+            .iter()
+            .map(|data_hash| PyBytes::new(py, data_hash))
+            .collect()
+    }
+
+    #[getter]
+    fn sub_indexes(&self) -> Vec<u32> {
+        self.0.sub_indexes.iter().copied().collect()
+    }
+
+    #[getter]
+    fn namespaces<'py>(&self, py: Python<'py>) -> Vec<Bound<'py, PyBytes>> {
+        self.0
+            .namespaces
+            .iter()
+            .map(|namespace| PyBytes::new(py, namespace))
+            .collect()
+    }
+
+    #[getter]
+    fn commitments<'py>(&self, py: Python<'py>) -> Vec<Bound<'py, PyBytes>> {
+        self.0
+            .commitments
+            .iter()
+            .map(|commitment| PyBytes::new(py, commitment))
+            .collect()
+    }
+
+    /// `(rollup_id, batch_number, timestamp)` of the batch the index was posted for, if set.
+    #[getter]
+    fn metadata<'py>(&self, py: Python<'py>) -> Option<(Bound<'py, PyBytes>, u64, u64)> {
+        self.0.metadata.map(|metadata| {
+            (
+                PyBytes::new(py, metadata.rollup_id.as_slice()),
+                metadata.batch_number,
+                metadata.timestamp,
+            )
+        })
+    }
+
+    fn __len__(&self) -> usize {
+        self.0.blobs.len()
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "BlobIndex({} entries, {} sub-indexes, data hashes: {})",
+            self.0.blobs.len(),
+            self.0.sub_indexes.len(),
+            !self.0.data_hashes.is_empty()
+        )
+    }
+}
+
+/// Decodes the ABI-encoded journal of a DA challenge proof into a dict, with the fields of the
+/// journal summary of the notifier.
+#[pyfunction]
+fn decode_journal<'py>(py: Python<'py>, data: &[u8]) -> PyResult<Bound<'py, PyAny>> {
+    let journal = JournalSummary::decode(data).map_err(value_error)?;
+    Ok(pythonize::pythonize(py, &journal)?)
+}
+
+/// Returns the registry of known networks as a list of dicts, see `toolkit::constants::NETWORKS`.
+#[pyfunction]
+fn networks(py: Python<'_>) -> PyResult<Bound<'_, PyAny>> {
+    Ok(pythonize::pythonize(py, NETWORKS)?)
+}
+
+#[pymodule]
+fn da_challenge_toolkit(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_class::<PySpanSequence>()?;
+    m.add_class::<PyBlobIndex>()?;
+    m.add_function(wrap_pyfunction!(decode_journal, m)?)?;
+    m.add_function(wrap_pyfunction!(networks, m)?)?;
+    Ok(())
+}
@@ -0,0 +1,346 @@
+//! WebAssembly bindings of the toolkit, for block explorers and dashboards that parse posted
+//! indexes and on-chain journals in the browser.
+//!
+//! Build with `wasm-pack build crates/toolkit-wasm --target web`. Indexes and journals are
+//! exchanged as plain JS objects, with camelCase fields and hashes, addresses and namespaces as
+//! `0x`-prefixed hex strings.
+
+use alloy_primitives::{Address, Bytes, FixedBytes, B256, U256};
+use serde::{Deserialize, Serialize};
+use toolkit::constants::NAMESPACE_SIZE;
+use toolkit::errors::{DaFraudKind, IndexError};
+use toolkit::journal::{AggregatedJournal, DaOutcome, Journal};
+use toolkit::{BatchMetadata, BlobIndex, BlobstreamImpl, DaChallenge};
+use wasm_bindgen::prelude::*;
+
+/// A range of shares of a Celestia block, see `toolkit::SpanSequence`.
+#[wasm_bindgen]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SpanSequence(toolkit::SpanSequence);
+
+#[wasm_bindgen]
+impl SpanSequence {
+    #[wasm_bindgen(constructor)]
+    pub fn new(height: u64, start: u32, size: u32) -> Self {
+        Self(toolkit::SpanSequence {
+            height,
+            start,
+            size,
+        })
+    }
+
+    /// Parses a span sequence formatted as `height:start:size`.
+    pub fn parse(span_sequence: &str) -> Result<SpanSequence, JsError> {
+        span_sequence
+            .parse()
+            .map(Self)
+            .map_err(|e: String| JsError::new(&e))
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn height(&self) -> u64 {
+        self.0.height
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn start(&self) -> u32 {
+        self.0.start
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn size(&self) -> u32 {
+        self.0.size
+    }
+
+    /// Returns whether the share at `share_index` in the ODS of the block at `height` is one of
+    /// the shares of the blob.
+    #[wasm_bindgen(js_name = containsShare)]
+    pub fn contains_share(&self, height: u64, share_index: u32) -> bool {
+        self.0.contains_share(height, share_index)
+    }
+
+    /// Returns whether the blob shares at least one share with `other`.
+    pub fn overlaps(&self, other: &SpanSequence) -> bool {
+        self.0.overlaps(&other.0)
+    }
+
+    /// Formats the span sequence as `height:start:size`.
+    #[wasm_bindgen(js_name = toString)]
+    pub fn to_js_string(&self) -> String {
+        self.0.to_string()
+    }
+}
+
+/// JS version of `BlobIndex`.
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct IndexObject {
+    blobs: Vec<toolkit::SpanSequence>,
+    /// Empty if the index does not commit to the data of its blobs.
+    #[serde(default)]
+    data_hashes: Vec<B256>,
+    /// Positions in `blobs` of the sub-indexes.
+    #[serde(default)]
+    sub_indexes: Vec<u32>,
+    /// Namespace of each blob. Empty if the blobs live in the namespace of the index.
+    #[serde(default)]
+    namespaces: Vec<FixedBytes<NAMESPACE_SIZE>>,
+    /// Share commitment of each blob. Empty if the index does not carry them.
+    #[serde(default)]
+    commitments: Vec<B256>,
+    /// Batch the index was posted for, if set.
+    #[serde(default)]
+    metadata: Option<BatchMetadataObject>,
+    /// `BlobIndex::canonical_hash()`, ignored when encoding.
+    #[serde(default, skip_deserializing)]
+    canonical_hash: B256,
+}
+
+impl TryFrom<BlobIndex> for IndexObject {
+    type Error = IndexError;
+
+    fn try_from(index: BlobIndex) -> Result<Self, Self::Error> {
+        Ok(Self {
+            canonical_hash: index.canonical_hash()?,
+            data_hashes: index.data_hashes.into_iter().map(B256::from).collect(),
+            sub_indexes: index.sub_indexes.into_iter().collect(),
+            namespaces: index.namespaces.into_iter().map(FixedBytes).collect(),
+            commitments: index.commitments.into_iter().map(B256::from).collect(),
+            metadata: index.metadata.map(Into::into),
+            blobs: index.blobs,
+        })
+    }
+}
+
+impl From<IndexObject> for BlobIndex {
+    fn from(index: IndexObject) -> Self {
+        Self {
+            blobs: index.blobs,
+            data_hashes: index
+                .data_hashes
+                .into_iter()
+                .map(|data_hash| data_hash.0)
+                .collect(),
+            sub_indexes: index.sub_indexes.into_iter().collect(),
+            namespaces: index
+                .namespaces
+                .into_iter()
+                .map(|namespace| namespace.0)
+                .collect(),
+            commitments: index
+                .commitments
+                .into_iter()
+                .map(|commitment| commitment.0)
+                .collect(),
+            metadata: index.metadata.map(Into::into),
+        }
+    }
+}
+
+/// JS version of `BatchMetadata`.
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct BatchMetadataObject {
+    rollup_id: B256,
+    batch_number: u64,
+    timestamp: u64,
+}
+
+impl From<BatchMetadata> for BatchMetadataObject {
+    fn from(metadata: BatchMetadata) -> Self {
+        Self {
+            rollup_id: metadata.rollup_id,
+            batch_number: metadata.batch_number,
+            timestamp: metadata.timestamp,
+        }
+    }
+}
+
+impl From<BatchMetadataObject> for BatchMetadata {
+    fn from(metadata: BatchMetadataObject) -> Self {
+        Self {
+            rollup_id: metadata.rollup_id,
+            batch_number: metadata.batch_number,
+            timestamp: metadata.timestamp,
+        }
+    }
+}
+
+/// Reads an index from a JS object, checking it like `BlobIndexBuilder::build()`.
+fn index_from_js(index: JsValue) -> Result<BlobIndex, JsError> {
+    let index: BlobIndex = serde_wasm_bindgen::from_value::<IndexObject>(index)?.into();
+    index.validate()?;
+    Ok(index)
+}
+
+/// Decodes an index posted on Celestia, in any encoding, see `BlobIndex::decode()`.
+#[wasm_bindgen(js_name = decodeIndex)]
+pub fn decode_index(data: &[u8]) -> Result<JsValue, JsError> {
+    let index = BlobIndex::decode(data)?;
+    Ok(serde_wasm_bindgen::to_value(&IndexObject::try_from(
+        index,
+    )?)?)
+}
+
+/// Encodes an index as posted on Celestia by the sequencer, see `BlobIndex::encode()`.
+#[wasm_bindgen(js_name = encodeIndex)]
+pub fn encode_index(index: JsValue) -> Result<Vec<u8>, JsError> {
+    Ok(index_from_js(index)?.encode()?)
+}
+
+/// Encodes an index in the canonical encoding, see `BlobIndex::encode_canonical()`.
+#[wasm_bindgen(js_name = encodeIndexCanonical)]
+pub fn encode_index_canonical(index: JsValue) -> Result<Vec<u8>, JsError> {
+    Ok(index_from_js(index)?.encode_canonical()?)
+}
+
+/// JS version of `Journal`, like the journal summary of the notifier of the host.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct JournalObject {
+    version: u16,
+    commitment_id: U256,
+    commitment_digest: B256,
+    commitment_config_id: B256,
+    blobstream_address: Address,
+    blobstream_impl: BlobstreamImpl,
+    outcome: DaOutcome,
+    index_blob: toolkit::SpanSequence,
+    challenged_blob: toolkit::SpanSequence,
+    challenge: DaChallenge,
+    /// `None` if no fraud is proven.
+    fraud_type: Option<DaFraudKind>,
+    /// `None` if the namespace was not checked.
+    namespace: Option<Bytes>,
+    extra_challenged_blobs: Vec<toolkit::SpanSequence>,
+    extra_fraud_types: Vec<Option<DaFraudKind>>,
+    celestia_chain_id: String,
+    /// `None` if the index was not read.
+    index_hash: Option<B256>,
+    /// `None` if the index was not read or carries no metadata.
+    batch_metadata: Option<BatchMetadataObject>,
+}
+
+/// JS version of `AggregatedJournal`.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct AggregatedJournalObject {
+    version: u16,
+    challenge_image_id: B256,
+    commitment_id: U256,
+    commitment_digest: B256,
+    commitment_config_id: B256,
+    blobstream_address: Address,
+    blobstream_impl: BlobstreamImpl,
+    index_blob: toolkit::SpanSequence,
+    namespace: Option<Bytes>,
+    challenged_blobs: Vec<toolkit::SpanSequence>,
+    fraud_types: Vec<Option<DaFraudKind>>,
+    challenges: Vec<DaChallenge>,
+    celestia_chain_id: String,
+    index_hash: Option<B256>,
+    batch_metadata: Option<BatchMetadataObject>,
+}
+
+fn fraud_type(fraud_type: u8) -> Result<Option<DaFraudKind>, JsError> {
+    match fraud_type {
+        0 => Ok(None),
+        fraud_type => DaFraudKind::from_u8(fraud_type)
+            .map(Some)
+            .ok_or_else(|| JsError::new(&format!("unknown fraud type: {fraud_type}"))),
+    }
+}
+
+fn blobstream_impl(blobstream_impl: u8) -> Result<BlobstreamImpl, JsError> {
+    BlobstreamImpl::from_u8(blobstream_impl).ok_or_else(|| {
+        JsError::new(&format!(
+            "unknown Blobstream implementation: {blobstream_impl}"
+        ))
+    })
+}
+
+fn challenge(
+    challenge_type: u8,
+    challenged_blob: toolkit::SpanSequence,
+) -> Result<DaChallenge, JsError> {
+    DaChallenge::from_u8(challenge_type, challenged_blob)
+        .ok_or_else(|| JsError::new(&format!("unknown challenge type: {challenge_type}")))
+}
+
+/// Decodes the ABI-encoded journal of a DA challenge proof, see `Journal::decode()`.
+#[wasm_bindgen(js_name = decodeJournal)]
+pub fn decode_journal(journal_bytes: &[u8]) -> Result<JsValue, JsError> {
+    let journal = Journal::decode(journal_bytes)?;
+    let challenged_blob: toolkit::SpanSequence = journal.challengedBlob.into();
+
+    let journal = JournalObject {
+        version: journal.version,
+        commitment_id: journal.commitment.id,
+        commitment_digest: journal.commitment.digest,
+        commitment_config_id: journal.commitment.configID,
+        blobstream_address: journal.blobstreamAddress,
+        blobstream_impl: blobstream_impl(journal.blobstreamImpl)?,
+        outcome: DaOutcome::from_u8(journal.outcome)
+            .ok_or_else(|| JsError::new(&format!("unknown outcome: {}", journal.outcome)))?,
+        index_blob: journal.indexBlob.into(),
+        challenged_blob,
+        challenge: challenge(journal.challengeType, challenged_blob)?,
+        fraud_type: fraud_type(journal.fraudType)?,
+        namespace: (!journal.namespace.is_empty()).then_some(journal.namespace),
+        extra_challenged_blobs: journal
+            .extraChallengedBlobs
+            .into_iter()
+            .map(Into::into)
+            .collect(),
+        extra_fraud_types: journal
+            .extraFraudTypes
+            .into_iter()
+            .map(fraud_type)
+            .collect::<Result<_, _>>()?,
+        celestia_chain_id: journal.celestiaChainId,
+        index_hash: (!journal.indexHash.is_zero()).then_some(journal.indexHash),
+        batch_metadata: Option::<BatchMetadata>::from(journal.batchMetadata).map(Into::into),
+    };
+
+    Ok(serde_wasm_bindgen::to_value(&journal)?)
+}
+
+/// Decodes the ABI-encoded journal of an aggregated proof, see `AggregatedJournal::decode()`.
+#[wasm_bindgen(js_name = decodeAggregatedJournal)]
+pub fn decode_aggregated_journal(journal_bytes: &[u8]) -> Result<JsValue, JsError> {
+    let journal = AggregatedJournal::decode(journal_bytes)?;
+    let challenged_blobs: Vec<toolkit::SpanSequence> = journal
+        .challengedBlobs
+        .into_iter()
+        .map(Into::into)
+        .collect();
+
+    let journal = AggregatedJournalObject {
+        version: journal.version,
+        challenge_image_id: journal.challengeImageId,
+        commitment_id: journal.commitment.id,
+        commitment_digest: journal.commitment.digest,
+        commitment_config_id: journal.commitment.configID,
+        blobstream_address: journal.blobstreamAddress,
+        blobstream_impl: blobstream_impl(journal.blobstreamImpl)?,
+        index_blob: journal.indexBlob.into(),
+        namespace: (!journal.namespace.is_empty()).then_some(journal.namespace),
+        fraud_types: journal
+            .fraudTypes
+            .into_iter()
+            .map(fraud_type)
+            .collect::<Result<_, _>>()?,
+        challenges: journal
+            .challengeTypes
+            .into_iter()
+            .zip(&challenged_blobs)
+            .map(|(challenge_type, blob)| challenge(challenge_type, *blob))
+            .collect::<Result<_, _>>()?,
+        challenged_blobs,
+        celestia_chain_id: journal.celestiaChainId,
+        index_hash: (!journal.indexHash.is_zero()).then_some(journal.indexHash),
+        batch_metadata: Option::<BatchMetadata>::from(journal.batchMetadata).map(Into::into),
+    };
+
+    Ok(serde_wasm_bindgen::to_value(&journal)?)
+}
@@ -60,10 +60,14 @@ sol! {
 
     contract Blobstream0 {
         function latestHeight() external view returns (uint64);
+        /// Nonce of the next data commitment.
+        function proofNonce() external view returns (uint256);
     }
 
     contract SP1Blobstream {
         function latestBlock() external view returns (uint64);
+        /// Nonce of the next data commitment.
+        function state_proofNonce() external view returns (uint256);
     }
 }
 
@@ -115,6 +119,12 @@ impl<'de> Deserialize<'de> for BinaryMerkleProof {
 
 impl From<MerkleProof> for BinaryMerkleProof {
     fn from(proof: MerkleProof) -> Self {
+        Self::from(&proof)
+    }
+}
+
+impl From<&MerkleProof> for BinaryMerkleProof {
+    fn from(proof: &MerkleProof) -> Self {
         // 1.  Vec<Hash> ➜ Vec<B256>
         //
         //     `Hash` in the Lumina crates is an opaque wrapper around
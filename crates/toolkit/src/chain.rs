@@ -0,0 +1,14 @@
+//! Steel types of the EVM chain hosting the Blobstream contract.
+//!
+//! Steel's Ethereum header covers all the chains whose block headers follow the Ethereum RLP
+//! format, including L2s such as Arbitrum or Base. Supporting a chain with a different header
+//! format only requires changing these aliases, the guest and the host use them everywhere.
+
+use risc0_steel::ethereum::EthBlockHeader;
+use risc0_steel::EvmInput;
+
+/// Block header of the chain hosting the Blobstream contract.
+pub type BlobstreamChainHeader = EthBlockHeader;
+
+/// Steel input of the Blobstream calls, as passed to the guest.
+pub type BlobstreamChainInput = EvmInput<BlobstreamChainHeader>;
@@ -0,0 +1,678 @@
+use crate::constants::{
+    MAX_INDEX_ENTRIES, MAX_INDEX_SHARES, MAX_SUB_INDEX_DEPTH, MIN_BLOB_SQUARE_WIDTH,
+};
+use crate::errors::{DaFraud, DaGuestError, InputError};
+use crate::{
+    blob_data_hash_from_shares, reconstruct_blob_from_raw, share_proof_ods_range, BatchMetadata,
+    BlobIndex, BlobProofData, BlobstreamAttestation, BlobstreamAttestationAndRowProof,
+    DaChallengeGuestData, SpanSequence, SubIndexProofData, SIGNER_SIZE,
+};
+use alloy_primitives::{Bytes, B256, U256};
+use celestia_types::consts::appconsts::{
+    CONTINUATION_SPARSE_SHARE_CONTENT_SIZE, FIRST_SPARSE_SHARE_CONTENT_SIZE, SEQUENCE_LEN_BYTES,
+    SHARE_INFO_BYTES, SHARE_SIZE,
+};
+use celestia_types::hash::Hash;
+use celestia_types::nmt::{Namespace, NS_SIZE};
+use celestia_types::{AppVersion, Share};
+use std::collections::{BTreeMap, BTreeSet};
+
+/// The Blobstream contract state needed to evaluate a DA challenge. The guest program reads it
+/// through Steel, the host from an Ethereum RPC.
+pub trait BlobstreamState {
+    /// Verifies that the attestation was committed by the Blobstream contract.
+    fn verify_attestation(
+        &self,
+        blobstream_attestation: &BlobstreamAttestation,
+    ) -> Result<(), InputError>;
+
+    /// Returns the highest Celestia block height covered by the Blobstream contract.
+    fn max_block_height(&self) -> u64;
+}
+
+/// Evaluates a DA challenge. Returns `Ok(())` if the challenged blob is available, or the DA
+/// fraud that it proves. The extra challenged blobs are checked too, see `check_da_challenges()`.
+///
+/// The Blobstream attestations and row proofs of `block_proofs` are trusted, callers must verify
+/// them against the data roots beforehand. Their positions in the data square are checked here.
+pub fn check_da_challenge(
+    guest_data: &DaChallengeGuestData,
+    blobstream: &impl BlobstreamState,
+) -> Result<(), DaGuestError> {
+    let mut outcomes = check_da_challenges(guest_data, blobstream)?.outcomes;
+    Ok(outcomes.remove(0)?)
+}
+
+/// Outcomes of a DA challenge over all the challenged blobs, see `check_da_challenges()`.
+#[derive(Debug)]
+pub struct DaChallengeOutcomes {
+    /// `BlobIndex::canonical_hash()` of the index blob. `None` if the index blob is the
+    /// challenged blob.
+    pub index_hash: Option<B256>,
+    /// `BlobIndex::metadata` of the index blob. `None` if the index blob is the challenged blob
+    /// or carries no metadata.
+    pub batch_metadata: Option<BatchMetadata>,
+    /// Outcome of each challenged blob, the challenged blob first: `Ok(())` if the blob is
+    /// available, or the DA fraud that it proves.
+    pub outcomes: Vec<Result<(), DaFraud>>,
+}
+
+/// Evaluates a DA challenge over all the challenged blobs of the guest data. Returns the outcome
+/// of each challenged blob, and the hash of the index listing them. DA fraud of the index itself,
+/// or of one of the sub-indexes leading to the challenged blobs, holds for all the challenged
+/// blobs and is returned as an error, like invalid inputs.
+///
+/// See `check_da_challenge()` for the trust assumptions on `block_proofs`.
+pub fn check_da_challenges(
+    guest_data: &DaChallengeGuestData,
+    blobstream: &impl BlobstreamState,
+) -> Result<DaChallengeOutcomes, DaGuestError> {
+    let DaChallengeGuestData {
+        celestia_chain_id: _,
+        index_blob,
+        challenged_blob,
+        extra_challenged_blobs,
+        index_blob_proof_data,
+        challenged_blob_proof_data,
+        namespace,
+        sub_indexes,
+        block_proofs,
+        first_blobstream_attestation,
+    } = guest_data;
+
+    check_block_proofs(guest_data)?;
+
+    // If the index blob is the missing blob, verify exclusion immediately.
+    if challenged_blob == index_blob {
+        if !extra_challenged_blobs.is_empty() {
+            return Err(InputError::ExtraChallengedBlobsOfIndexBlob.into());
+        }
+        // Verify that the index blob is excluded
+        check_block_height_bounds(*index_blob, blobstream, first_blobstream_attestation)?;
+        verify_span_sequence_inclusion(index_blob, block_proof(block_proofs, index_blob.height)?)?;
+        return Ok(DaChallengeOutcomes {
+            index_hash: None,
+            batch_metadata: None,
+            outcomes: vec![Ok(())],
+        });
+    }
+
+    // Each blob is challenged once, so that the outcomes match the challenged blobs
+    let mut seen_blobs = BTreeSet::from([*index_blob]);
+    for blob in guest_data.challenged_blobs() {
+        if !seen_blobs.insert(blob) {
+            return Err(InputError::DuplicateChallengedBlob(blob).into());
+        }
+    }
+
+    // Oversized indexes are fraud whatever their content, their data is not needed.
+    check_index_size(index_blob)?;
+
+    // To go any further, the index blob data must be present.
+    let index_blob_data = index_blob_proof_data
+        .as_ref()
+        .ok_or(InputError::MissingIndexBlobData)?;
+
+    // Verify the shares of the index blob, which must be posted to the namespace of the rollup
+    verify_blob_shares(
+        index_blob,
+        block_proof(block_proofs, index_blob.height)?,
+        index_blob_data,
+        *namespace,
+    )?;
+    // Deserialize the index blob
+    let mut index = decode_index(index_blob, index_blob_data)?;
+    // Decoding sets the optional fields of all the entries or none, which is all encoding needs
+    let index_hash = index
+        .canonical_hash()
+        .expect("decoded indexes have consistent entries");
+    let batch_metadata = index.metadata;
+
+    // Walk down the sub-indexes to the index listing the challenged blob. Sub-indexes are
+    // blobs of their parent index, so they are checked like challenged blobs.
+    if sub_indexes.len() > MAX_SUB_INDEX_DEPTH {
+        return Err(InputError::TooManySubIndexes {
+            depth: sub_indexes.len(),
+            max_depth: MAX_SUB_INDEX_DEPTH,
+        }
+        .into());
+    }
+    for SubIndexProofData {
+        sub_index,
+        proof_data,
+    } in sub_indexes
+    {
+        let position = index
+            .sub_indexes
+            .iter()
+            .map(|position| *position as usize)
+            .find(|position| index.blobs.get(*position) == Some(sub_index))
+            .ok_or(InputError::SubIndexNotInIndex(*sub_index))?;
+        check_index_size(sub_index)?;
+        check_block_height_bounds(*sub_index, blobstream, first_blobstream_attestation)?;
+        let sub_index_block_proof = block_proof(block_proofs, sub_index.height)?;
+        verify_span_sequence_inclusion(sub_index, sub_index_block_proof)?;
+        let sub_index_namespace = entry_namespace(&index, position, *namespace)?;
+        verify_blob_shares(
+            sub_index,
+            sub_index_block_proof,
+            proof_data,
+            sub_index_namespace,
+        )?;
+        index = decode_index(sub_index, proof_data)?;
+    }
+
+    // All the challenged blobs must be listed in the same index. Only the challenged blob can
+    // have its data challenged.
+    let challenges = [(challenged_blob, challenged_blob_proof_data.as_ref())]
+        .into_iter()
+        .chain(extra_challenged_blobs.iter().map(|blob| (blob, None)));
+    let mut outcomes = Vec::with_capacity(1 + extra_challenged_blobs.len());
+    for (blob, blob_proof_data) in challenges {
+        let outcome = match check_challenged_blob(
+            &index,
+            blob,
+            blob_proof_data,
+            *namespace,
+            block_proofs,
+            blobstream,
+            first_blobstream_attestation,
+        ) {
+            Ok(()) => Ok(()),
+            Err(DaGuestError::Fraud(fraud)) => Err(fraud),
+            Err(DaGuestError::Input(err)) => return Err(err.into()),
+        };
+        outcomes.push(outcome);
+    }
+
+    Ok(DaChallengeOutcomes {
+        index_hash: Some(index_hash),
+        batch_metadata,
+        outcomes,
+    })
+}
+
+/// Checks that the block proofs are keyed by the height of their attestation, and that they are
+/// all on the path of the challenge, see `DaChallengeGuestData::challenge_block_heights()`. Each
+/// block proof costs an attestation and two Merkle proofs to verify, extra ones would only
+/// inflate the cost of the proof. Missing block proofs are reported where they are needed.
+pub fn check_block_proofs(guest_data: &DaChallengeGuestData) -> Result<(), InputError> {
+    let challenge_block_heights = guest_data.challenge_block_heights();
+
+    for (&height, block_proof) in &guest_data.block_proofs {
+        if block_proof.blobstream_attestation.height != height {
+            return Err(InputError::BlockProofHeightMismatch {
+                key: height,
+                height: block_proof.blobstream_attestation.height,
+            });
+        }
+        if !challenge_block_heights.contains(&height) {
+            return Err(InputError::UnexpectedBlockProof(height));
+        }
+    }
+
+    Ok(())
+}
+
+/// Evaluates the challenge of one blob of a verified index. Returns `Ok(())` if the blob is
+/// available, or the DA fraud that it proves.
+fn check_challenged_blob(
+    index: &BlobIndex,
+    challenged_blob: &SpanSequence,
+    challenged_blob_proof_data: Option<&BlobProofData>,
+    namespace: Option<Namespace>,
+    block_proofs: &BTreeMap<u64, BlobstreamAttestationAndRowProof>,
+    blobstream: &impl BlobstreamState,
+    first_blobstream_attestation: &BlobstreamAttestation,
+) -> Result<(), DaGuestError> {
+    // Iterate over the blobs in the index and check if they're the missing blob.
+    for (i, blob_commitment) in index.blobs.iter().enumerate() {
+        if challenged_blob == blob_commitment {
+            check_block_height_bounds(*challenged_blob, blobstream, first_blobstream_attestation)?;
+            let challenged_block_proof = block_proof(block_proofs, blob_commitment.height)?;
+            verify_span_sequence_inclusion(blob_commitment, challenged_block_proof)?;
+
+            return match challenged_blob_proof_data {
+                Some(challenged_blob_data) => verify_blob_data(
+                    blob_commitment,
+                    index.data_hash(i),
+                    index.commitment(i),
+                    challenged_block_proof,
+                    challenged_blob_data,
+                    entry_namespace(index, i, namespace)?,
+                ),
+                None => Ok(()),
+            };
+        }
+    }
+
+    Err(InputError::ChallengedBlobNotInIndex.into())
+}
+
+/// Returns the namespace the blob at position `i` of the index must be posted to: its own if
+/// the index lists one, the namespace of the index otherwise. A namespace Celestia cannot hold
+/// is DA fraud, no blob can be posted there.
+fn entry_namespace(
+    index: &BlobIndex,
+    i: usize,
+    index_namespace: Option<Namespace>,
+) -> Result<Option<Namespace>, DaFraud> {
+    let Some(raw_namespace) = index.namespace(i) else {
+        return Ok(index_namespace);
+    };
+    let blob = index.blobs[i];
+    Namespace::from_raw(raw_namespace)
+        .map(Some)
+        .map_err(|_| DaFraud::InvalidEntryNamespace {
+            blob,
+            namespace: Bytes::copy_from_slice(raw_namespace),
+        })
+}
+
+/// Authenticates the shares of a blob and checks their layout, so that the data of the blob can
+/// be read from them in place. See `verify_share_proofs()` for `expected_namespace`.
+pub fn verify_blob_shares(
+    span_sequence: &SpanSequence,
+    block_proof: &BlobstreamAttestationAndRowProof,
+    blob_proof_data: &BlobProofData,
+    expected_namespace: Option<Namespace>,
+) -> Result<(), DaGuestError> {
+    verify_share_proofs(
+        span_sequence,
+        block_proof,
+        blob_proof_data,
+        expected_namespace,
+    )?;
+    let app_version = verify_app_version(blob_proof_data, block_proof)?;
+    verify_share_layout(span_sequence, blob_proof_data, app_version)
+}
+
+/// Deserializes an index from its verified shares, see `verify_blob_shares()`. Indexes with
+/// more than `MAX_INDEX_ENTRIES` entries are fraud.
+fn decode_index(
+    span_sequence: &SpanSequence,
+    blob_proof_data: &BlobProofData,
+) -> Result<BlobIndex, DaGuestError> {
+    let index = BlobIndex::decode_from_shares(blob_proof_data.shares()).map_err(DaFraud::from)?;
+
+    if index.blobs.len() > MAX_INDEX_ENTRIES {
+        return Err(DaFraud::TooManyIndexEntries {
+            index: *span_sequence,
+            entries: index.blobs.len(),
+            max_entries: MAX_INDEX_ENTRIES,
+        }
+        .into());
+    }
+
+    Ok(index)
+}
+
+/// Checks that the index fits in `MAX_INDEX_SHARES` shares. This bounds the cost of verifying
+/// and deserializing the index, whatever the rollup posted.
+pub fn check_index_size(span_sequence: &SpanSequence) -> Result<(), DaFraud> {
+    if span_sequence.size > MAX_INDEX_SHARES {
+        return Err(DaFraud::IndexTooLarge {
+            index: *span_sequence,
+            max_size: MAX_INDEX_SHARES,
+        });
+    }
+
+    Ok(())
+}
+
+/// Returns the app version to reconstruct the shares with, checking that it is the app version
+/// of their block.
+pub fn verify_app_version(
+    blob_proof_data: &BlobProofData,
+    block_proof: &BlobstreamAttestationAndRowProof,
+) -> Result<AppVersion, InputError> {
+    if blob_proof_data.app_version != block_proof.app_version {
+        return Err(InputError::AppVersionMismatch {
+            height: block_proof.blobstream_attestation.height,
+            expected: block_proof.app_version,
+            actual: blob_proof_data.app_version,
+        });
+    }
+
+    AppVersion::from_u64(blob_proof_data.app_version)
+        .ok_or(InputError::InvalidAppVersion(blob_proof_data.app_version))
+}
+
+fn block_proof(
+    block_proofs: &BTreeMap<u64, BlobstreamAttestationAndRowProof>,
+    block_height: u64,
+) -> Result<&BlobstreamAttestationAndRowProof, InputError> {
+    block_proofs
+        .get(&block_height)
+        .ok_or(InputError::MissingBlockProof(block_height))
+}
+
+/// Checks that the span sequence fits in the ODS of its block, and that the ODS is large enough
+/// to hold a blob at all. Span sequences pointing into empty blocks are fraud either way.
+pub fn verify_span_sequence_inclusion(
+    span_sequence: &SpanSequence,
+    block_proof: &BlobstreamAttestationAndRowProof,
+) -> Result<(), DaGuestError> {
+    let ods_width = block_proof.ods_width()?;
+    let ods_size = ods_width * ods_width;
+
+    let last_share_index = span_sequence.end_index_ods()?;
+
+    if last_share_index > ods_size {
+        return Err(DaFraud::ShareIndexOutOfBounds {
+            share_index: last_share_index,
+            ods_size,
+        }
+        .into());
+    }
+
+    if ods_width < MIN_BLOB_SQUARE_WIDTH {
+        return Err(DaFraud::SquareTooSmallForBlob {
+            blob: *span_sequence,
+            ods_width,
+        }
+        .into());
+    }
+
+    Ok(())
+}
+
+/// Checks that the shares of the span sequence belong to the attested Celestia block, at the
+/// expected indexes. Each share proof covers consecutive shares of one or more rows, see
+/// `share_proof_ods_range()`, and the proofs must cover the span sequence exactly, in order, so
+/// that `BlobProofData::shares()` returns the shares of the span sequence in order. Ranged proofs
+/// keep the cost of large blobs down in wide squares: one row proof and one range proof per row
+/// instead of per share. The row proofs of each share proof must agree with the block proof on
+/// the size of the data square, which pins the position of the shares wherever their row is.
+///
+/// If `expected_namespace` is set, the shares must also belong to that namespace: shares of
+/// another namespace at the committed indexes are DA fraud.
+pub fn verify_share_proofs(
+    span_sequence: &SpanSequence,
+    block_proof: &BlobstreamAttestationAndRowProof,
+    blob_proof_data: &BlobProofData,
+    expected_namespace: Option<Namespace>,
+) -> Result<(), DaGuestError> {
+    let span_sequence_end = span_sequence.end_index_ods()?;
+    // Validates the square size against the proof of the last root of the square
+    let ods_width = block_proof.ods_width()?;
+    let blobstream_attestation = &block_proof.blobstream_attestation;
+
+    // Every index of the span sequence is checked below, extra shares would be shares from
+    // outside of the span sequence.
+    let proven_shares: usize = blob_proof_data
+        .share_proofs
+        .values()
+        .map(|share_proof| share_proof.shares().len())
+        .sum();
+    if proven_shares != span_sequence.size as usize {
+        return Err(InputError::UnexpectedShareProofCount {
+            expected: span_sequence.size,
+            actual: proven_shares,
+        }
+        .into());
+    }
+
+    let mut next_share_index = span_sequence.start;
+    for (&share_index, share_proof) in &blob_proof_data.share_proofs {
+        let proof_range = share_proof_ods_range(share_proof, ods_width)
+            .map_err(|error| InputError::InvalidShareProofRange { share_index, error })?;
+        // Check that the shares belong to the expected Celestia block
+        share_proof
+            .verify(Hash::Sha256(blobstream_attestation.data_root))
+            .map_err(|_| InputError::InvalidShareProof(share_index))?;
+        for row_proof in share_proof.row_proof.proofs() {
+            if row_proof.total != block_proof.row_proof.total {
+                return Err(InputError::ShareProofSquareSizeMismatch {
+                    share_index,
+                    total: row_proof.total as u64,
+                    expected: block_proof.row_proof.total as u64,
+                }
+                .into());
+            }
+        }
+
+        // Check that the shares match the expected indexes: the proofs follow each other
+        // without gap nor overlap, starting at the span sequence.
+        if proof_range.start != share_index {
+            return Err(InputError::InvalidShareProofIndex {
+                share_index,
+                proof_start_index: proof_range.start,
+            }
+            .into());
+        }
+        if share_index != next_share_index {
+            return Err(InputError::NonContiguousShareProofs {
+                expected: next_share_index,
+                actual: share_index,
+            }
+            .into());
+        }
+        next_share_index = proof_range.end;
+
+        // Check that the shares belong to the expected namespace. The namespace is read from
+        // the shares themselves, which the share proof authenticates.
+        if let Some(expected) = expected_namespace {
+            for (share_index, raw_share) in (share_index..).zip(share_proof.shares()) {
+                if &raw_share[..NS_SIZE] == expected.as_bytes() {
+                    continue;
+                }
+                let actual = Share::from_raw(raw_share)
+                    .map_err(|_| InputError::InvalidShareProof(share_index))?
+                    .namespace();
+                return Err(DaFraud::WrongNamespace {
+                    blob: *span_sequence,
+                    share_index,
+                    expected,
+                    actual,
+                }
+                .into());
+            }
+        }
+    }
+    // Contiguous proofs with as many shares as the span sequence cover it exactly, checked
+    // anyway as the rest of the challenge reads the shares in place.
+    if next_share_index != span_sequence_end {
+        return Err(InputError::ShareProofCoverageMismatch {
+            start: span_sequence.start,
+            end: span_sequence_end,
+            covered_end: next_share_index,
+        }
+        .into());
+    }
+
+    Ok(())
+}
+
+/// Checks that the shares of the span sequence form exactly one sequence of a single namespace:
+/// the first share starts it, the next ones continue it, and the sequence length declared by the
+/// first share fills all the shares and no more. The share proofs must be verified beforehand,
+/// so that the shares are those of the span sequence, in order.
+pub fn verify_share_layout(
+    span_sequence: &SpanSequence,
+    blob_proof_data: &BlobProofData,
+    app_version: AppVersion,
+) -> Result<(), DaGuestError> {
+    let Some(first_share) = blob_proof_data.shares().next() else {
+        return Err(InputError::UnexpectedShareProofCount {
+            expected: span_sequence.size,
+            actual: 0,
+        }
+        .into());
+    };
+    let mut first_share_content_size = FIRST_SPARSE_SHARE_CONTENT_SIZE;
+    let mut sequence_length = 0;
+    let mut n_shares = 0;
+
+    for (share_index, raw_share) in (span_sequence.start..).zip(blob_proof_data.shares()) {
+        n_shares += 1;
+        if raw_share[..NS_SIZE] != first_share[..NS_SIZE] {
+            let namespace_of = |raw_share: &[u8; SHARE_SIZE]| {
+                Share::from_raw(raw_share)
+                    .map(|share| share.namespace())
+                    .map_err(|_| InputError::InvalidShareProof(share_index))
+            };
+            return Err(DaFraud::WrongNamespace {
+                blob: *span_sequence,
+                share_index,
+                expected: namespace_of(first_share)?,
+                actual: namespace_of(raw_share)?,
+            }
+            .into());
+        }
+
+        let info_byte = raw_share[NS_SIZE];
+        let sequence_start = info_byte & 1 == 1;
+        let is_first_share = share_index == span_sequence.start;
+        if sequence_start != is_first_share {
+            return Err(DaFraud::UnexpectedSequenceStart {
+                blob: *span_sequence,
+                share_index,
+                sequence_start,
+            }
+            .into());
+        }
+
+        if is_first_share {
+            // Share version 1, which carries the signer of the blob, exists since app version 3
+            first_share_content_size = match info_byte >> 1 {
+                0 => FIRST_SPARSE_SHARE_CONTENT_SIZE,
+                1 if app_version.as_u64() >= 3 => FIRST_SPARSE_SHARE_CONTENT_SIZE - SIGNER_SIZE,
+                share_version => {
+                    return Err(DaFraud::UnsupportedShareVersion {
+                        blob: *span_sequence,
+                        share_version,
+                    }
+                    .into())
+                }
+            };
+            let length_offset = NS_SIZE + SHARE_INFO_BYTES;
+            let length_bytes = &raw_share[length_offset..length_offset + SEQUENCE_LEN_BYTES];
+            sequence_length =
+                u32::from_be_bytes(length_bytes.try_into().expect("sequence length is 4 bytes"));
+        }
+    }
+
+    // The data is read from these shares only, there must be exactly one per share of the span
+    // sequence
+    if n_shares != span_sequence.size as usize {
+        return Err(InputError::UnexpectedShareProofCount {
+            expected: span_sequence.size,
+            actual: n_shares,
+        }
+        .into());
+    }
+
+    // The first share holds the beginning of the data, continuation shares the rest
+    let remaining_length = (sequence_length as u64).saturating_sub(first_share_content_size as u64);
+    let expected_size =
+        1 + remaining_length.div_ceil(CONTINUATION_SPARSE_SHARE_CONTENT_SIZE as u64);
+    if expected_size != span_sequence.size as u64 {
+        return Err(DaFraud::SequenceLengthMismatch {
+            blob: *span_sequence,
+            sequence_length,
+            expected_size,
+        }
+        .into());
+    }
+
+    Ok(())
+}
+
+/// Checks that the data of a blob hashes to the data hash of its index entry, and that the blob
+/// matches the share commitment of the entry. Either is checked if the index carries it, the
+/// index must carry at least one.
+pub fn verify_blob_data(
+    span_sequence: &SpanSequence,
+    expected_data_hash: Option<&[u8; 32]>,
+    expected_commitment: Option<&[u8; 32]>,
+    block_proof: &BlobstreamAttestationAndRowProof,
+    blob_proof_data: &BlobProofData,
+    expected_namespace: Option<Namespace>,
+) -> Result<(), DaGuestError> {
+    if expected_data_hash.is_none() && expected_commitment.is_none() {
+        return Err(InputError::MissingDataHash(*span_sequence).into());
+    }
+
+    verify_blob_shares(
+        span_sequence,
+        block_proof,
+        blob_proof_data,
+        expected_namespace,
+    )?;
+
+    if let Some(expected_data_hash) = expected_data_hash {
+        let data_hash = blob_data_hash_from_shares(blob_proof_data.shares());
+        if data_hash != *expected_data_hash {
+            return Err(DaFraud::BlobDataMismatch {
+                blob: *span_sequence,
+                expected: B256::from(*expected_data_hash),
+                actual: B256::from(data_hash),
+            }
+            .into());
+        }
+    }
+
+    // The share commitment covers the namespace, share version and signer of the blob besides
+    // its data, so the blob is reconstructed to recompute it.
+    if let Some(expected_commitment) = expected_commitment {
+        let app_version = verify_app_version(blob_proof_data, block_proof)?;
+        let blob =
+            reconstruct_blob_from_raw(blob_proof_data.shares(), app_version).map_err(|error| {
+                DaFraud::FailedBlobReconstruction {
+                    blob: *span_sequence,
+                    error,
+                }
+            })?;
+        if blob.commitment.hash() != expected_commitment {
+            return Err(DaFraud::BlobCommitmentMismatch {
+                blob: *span_sequence,
+                expected: B256::from(*expected_commitment),
+                actual: B256::from(*blob.commitment.hash()),
+            }
+            .into());
+        }
+    }
+
+    Ok(())
+}
+
+/// Checks that the block height of the span sequence is covered by Blobstream, from the first
+/// attestation of the contract to its latest height.
+pub fn check_block_height_bounds(
+    span_sequence: SpanSequence,
+    blobstream: &impl BlobstreamState,
+    first_blobstream_attestation: &BlobstreamAttestation,
+) -> Result<(), DaGuestError> {
+    // Assert that the proof is for the first Blobstream event by checking the nonce.
+    // Nonces start at 1 in both SP1 and RISC Zero Blobstream contracts.
+    if first_blobstream_attestation.nonce != U256::from(1u64) {
+        return Err(InputError::InvalidFirstBlobstreamAttestationNonce.into());
+    }
+    // Assert that the proof is for the first Celestia block to guarantee that this is truly
+    // the lower bound.
+    if first_blobstream_attestation.proof.index != 0 {
+        return Err(InputError::InvalidFirstBlobstreamAttestationIndex.into());
+    }
+    blobstream.verify_attestation(first_blobstream_attestation)?;
+
+    let min_block_height = first_blobstream_attestation.height;
+    if span_sequence.height < min_block_height {
+        return Err(DaFraud::BlockHeightTooLow {
+            block_height: span_sequence.height,
+            min_block_height,
+        }
+        .into());
+    }
+
+    let max_block_height = blobstream.max_block_height();
+    if span_sequence.height > max_block_height {
+        return Err(DaFraud::BlockHeightTooHigh {
+            block_height: span_sequence.height,
+            max_block_height,
+        }
+        .into());
+    }
+
+    Ok(())
+}
@@ -1,3 +1,234 @@
-/// Address of the Blobstream contract on Sepolia.
+#[cfg(feature = "std")]
+use crate::errors::InputError;
+use alloy_primitives::{address, b256, Address, B256};
+#[cfg(feature = "std")]
+use risc0_steel::config::ChainSpec;
+#[cfg(feature = "std")]
+use risc0_steel::ethereum::{
+    ETH_HOLESKY_CHAIN_SPEC, ETH_MAINNET_CHAIN_SPEC, ETH_SEPOLIA_CHAIN_SPEC,
+};
+use serde::Serialize;
+
+/// Maximum number of shares of an index blob, about 2 MB of data. Larger indexes are DA fraud:
+/// proving them would cost more than the challenge is worth.
+pub const MAX_INDEX_SHARES: u32 = 4096;
+
+/// Maximum number of entries of an index. Larger indexes are DA fraud.
+pub const MAX_INDEX_ENTRIES: usize = 65_536;
+
+/// Maximum number of nested sub-indexes between the index blob and the index listing the
+/// challenged blob.
+pub const MAX_SUB_INDEX_DEPTH: usize = 2;
+
+/// Smallest ODS width of a data square that can hold a blob: a blob takes at least one share,
+/// plus one for the `PayForBlobs` transaction paying for it. Blocks without blobs have the
+/// minimal data square, of width 1.
+pub const MIN_BLOB_SQUARE_WIDTH: u32 = 2;
+
+/// Largest ODS width of a data square, celestia-app's `SquareSizeUpperBound`. Data square widths
+/// are powers of two up to this bound.
+pub const MAX_ODS_WIDTH: u32 = 512;
+
+/// Size of a Celestia namespace: a version byte and a 28-byte ID. Index entries store namespaces
+/// as raw bytes, so that the index types do not need Celestia.
+pub const NAMESPACE_SIZE: usize = 29;
+
+/// Prefix of the lines reporting the cycles spent in each phase of the guest program on stderr,
+/// ex: `cycles:block_proofs:123456`.
+pub const CYCLE_REPORT_PREFIX: &str = "cycles:";
+
+/// First `DataCommitmentStored` event of a SP1Blobstream deployment. Its proof nonce is always 1.
+/// Knowing it saves scanning the logs of the contract from its deployment block.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct FirstCommitment {
+    pub start_block: u64,
+    pub end_block: u64,
+    pub data_commitment: B256,
+}
+
+/// Constants of a Blobstream deployment tracking a Celestia network from an Ethereum chain.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct NetworkConstants {
+    /// Chain ID of the Ethereum chain.
+    pub chain_id: u64,
+    /// Chain ID of the Celestia network, ex: `mocha-4`.
+    pub celestia_chain_id: &'static str,
+    /// Address of the Blobstream contract.
+    pub blobstream_address: Address,
+    /// First data commitment stored by the Blobstream contract, if known.
+    pub first_commitment: Option<FirstCommitment>,
+    /// Namespace that index blobs are posted to by default on this network, if any.
+    pub default_namespace: Option<[u8; NAMESPACE_SIZE]>,
+}
+
+/// Canonical Blobstream deployments, by Ethereum chain ID and Celestia network. The guest program
+/// refuses other Blobstream contracts on these chains, so verifier contracts do not have to check
+/// the address committed in the journal. Chains missing from this table accept any Blobstream
+/// contract.
 /// Source: https://docs.celestia.org/how-to-guides/blobstream#deployed-contracts.
-pub const BLOBSTREAM_ADDRESS: &str = "0xF0c6429ebAB2e7DC6e05DaFB61128bE21f13cb1e";
+pub const NETWORKS: &[NetworkConstants] = &[
+    // Ethereum mainnet, Celestia mainnet
+    NetworkConstants {
+        chain_id: 1,
+        celestia_chain_id: "celestia",
+        blobstream_address: address!("7Cf3876F681Dbb6EdA8f6FfC45D66B996Df08fAe"),
+        first_commitment: Some(FirstCommitment {
+            start_block: 1_605_975,
+            end_block: 1_606_500,
+            data_commitment: b256!(
+                "e0f22e19a558e8da31aa8ee05f737a3ec2a55f92dc6093f34650c69f4cbd53be"
+            ),
+        }),
+        default_namespace: None,
+    },
+    // Sepolia, Mocha testnet
+    NetworkConstants {
+        chain_id: 11155111,
+        celestia_chain_id: "mocha-4",
+        blobstream_address: address!("F0c6429ebAB2e7DC6e05DaFB61128bE21f13cb1e"),
+        first_commitment: Some(FirstCommitment {
+            start_block: 1_560_501,
+            end_block: 1_560_600,
+            data_commitment: b256!(
+                "60cd79d32f2fb32ba0086c2d0f8e00d54364fa93715a4f6b28ed4080ef47f0eb"
+            ),
+        }),
+        default_namespace: None,
+    },
+];
+
+/// Returns the constants of the Blobstream deployment tracking `celestia_chain_id` from the
+/// chain, if it is a known network.
+pub fn network(chain_id: u64, celestia_chain_id: &str) -> Option<&'static NetworkConstants> {
+    NETWORKS.iter().find(|network| {
+        network.chain_id == chain_id && network.celestia_chain_id == celestia_chain_id
+    })
+}
+
+/// Returns the constants of the canonical Blobstream deployment of the chain, if any.
+pub fn network_of_chain(chain_id: u64) -> Option<&'static NetworkConstants> {
+    NETWORKS.iter().find(|network| network.chain_id == chain_id)
+}
+
+/// Returns the canonical Blobstream address of the chain, if any.
+pub fn canonical_blobstream_address(chain_id: u64) -> Option<Address> {
+    network_of_chain(chain_id).map(|network| network.blobstream_address)
+}
+
+/// Returns the chain ID of the Celestia network tracked by the Blobstream contract, if it is
+/// a canonical deployment.
+pub fn canonical_celestia_chain_id(
+    chain_id: u64,
+    blobstream_address: Address,
+) -> Option<&'static str> {
+    NETWORKS
+        .iter()
+        .find(|network| {
+            network.chain_id == chain_id && network.blobstream_address == blobstream_address
+        })
+        .map(|network| network.celestia_chain_id)
+}
+
+/// Checks that `blobstream_address` is the canonical Blobstream deployment of the chain, if the
+/// chain has one.
+#[cfg(feature = "std")]
+pub fn check_blobstream_address(
+    chain_id: u64,
+    blobstream_address: Address,
+) -> Result<(), InputError> {
+    match canonical_blobstream_address(chain_id) {
+        Some(expected) if expected != blobstream_address => {
+            Err(InputError::NonCanonicalBlobstreamAddress {
+                chain_id,
+                address: blobstream_address,
+                expected,
+            })
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Checks that the Celestia network of the challenge is the one tracked by the Blobstream
+/// contract, if it is a canonical deployment. Other contracts accept any Celestia network:
+/// verifier contracts must then check the Celestia chain ID committed in the journal.
+#[cfg(feature = "std")]
+pub fn check_celestia_chain_id(
+    chain_id: u64,
+    blobstream_address: Address,
+    celestia_chain_id: &str,
+) -> Result<(), InputError> {
+    if celestia_chain_id.is_empty() {
+        return Err(InputError::MissingCelestiaChainId);
+    }
+
+    match canonical_celestia_chain_id(chain_id, blobstream_address) {
+        Some(expected) if expected != celestia_chain_id => {
+            Err(InputError::CelestiaChainIdMismatch {
+                celestia_chain_id: celestia_chain_id.to_string(),
+                address: blobstream_address,
+                expected: expected.to_string(),
+            })
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Returns the pinned Steel chain spec of the chain, if any. The guest program refuses other
+/// chain specs on these chains, so that a prover cannot execute the Blobstream calls under
+/// bogus fork rules. Chains without a pinned spec accept any spec: verifier contracts must then
+/// check the digest of the spec, committed in the journal as the `configID` of the Steel
+/// commitment.
+#[cfg(feature = "std")]
+pub fn canonical_chain_spec(chain_id: u64) -> Option<&'static ChainSpec> {
+    match chain_id {
+        1 => Some(&*ETH_MAINNET_CHAIN_SPEC),
+        11155111 => Some(&*ETH_SEPOLIA_CHAIN_SPEC),
+        17000 => Some(&*ETH_HOLESKY_CHAIN_SPEC),
+        _ => None,
+    }
+}
+
+/// Checks that `chain_spec` is the pinned chain spec of its chain, if the chain has one.
+#[cfg(feature = "std")]
+pub fn check_chain_spec(chain_spec: &ChainSpec) -> Result<(), InputError> {
+    match canonical_chain_spec(chain_spec.chain_id) {
+        Some(expected) if expected.digest() != chain_spec.digest() => {
+            Err(InputError::NonCanonicalChainSpec {
+                chain_id: chain_spec.chain_id,
+                digest: B256::from_slice(chain_spec.digest().as_bytes()),
+                expected: B256::from_slice(expected.digest().as_bytes()),
+            })
+        }
+        _ => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn canonical_deployments_are_looked_up_in_the_network_registry() {
+        for registered in NETWORKS {
+            assert_eq!(
+                network(registered.chain_id, registered.celestia_chain_id),
+                Some(registered)
+            );
+            assert_eq!(
+                canonical_blobstream_address(registered.chain_id),
+                Some(registered.blobstream_address)
+            );
+            assert_eq!(
+                canonical_celestia_chain_id(registered.chain_id, registered.blobstream_address),
+                Some(registered.celestia_chain_id)
+            );
+        }
+
+        assert!(network(11155111, "celestia").is_none());
+        assert!(network_of_chain(31337).is_none());
+        assert_eq!(
+            canonical_celestia_chain_id(11155111, Address::repeat_byte(0x01)),
+            None
+        );
+    }
+}
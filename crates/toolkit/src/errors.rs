@@ -1,16 +1,64 @@
-use crate::SpanSequence;
+#[cfg(feature = "std")]
+use crate::constants::MAX_ODS_WIDTH;
+#[cfg(feature = "std")]
+use crate::SquareWidth;
+use crate::{SpanSequence, SpanSequenceV2};
+#[cfg(feature = "std")]
+use alloy_primitives::{Address, Bytes, B256, U256};
+#[cfg(feature = "std")]
+use celestia_types::nmt::Namespace;
+#[cfg(feature = "std")]
 use celestia_types::MerkleProof;
+#[cfg(feature = "std")]
+use core::ops::Range;
+#[cfg(feature = "std")]
+use serde::Serializer;
+use serde::{Deserialize, Serialize};
+
+/// Panic message of the guest program when the challenged blob turns out to be available.
+/// No proof can be generated in this case.
+pub const BLOB_IS_AVAILABLE_MESSAGE: &str = "the specified blob is available, DA challenge failed";
+
+/// The challenged blob is available, no DA fraud can be proven. Returned by the host when it
+/// finds out before or while proving, so callers match on the type rather than on
+/// `BLOB_IS_AVAILABLE_MESSAGE`.
+#[cfg(feature = "std")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, thiserror::Error)]
+#[error("{}", BLOB_IS_AVAILABLE_MESSAGE)]
+pub struct BlobIsAvailable;
+
+/// Serializes errors of other crates, which do not implement `Serialize`, as their message.
+#[cfg(feature = "std")]
+fn serialize_display<T: core::fmt::Display, S: Serializer>(
+    value: &T,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    serializer.collect_str(value)
+}
 
 /// An error in the inputs passed to the guest program or in the guest program itself.
 /// An error of this type should not lead to the generation of a valid proof.
-#[derive(Debug, thiserror::Error)]
+///
+/// Serialized as `{"kind": "<variant>", "details": ...}`, with the variant in snake_case.
+#[cfg(feature = "std")]
+#[derive(Debug, thiserror::Error, Serialize)]
+#[serde(tag = "kind", content = "details", rename_all = "snake_case")]
 pub enum InputError {
     #[error("invalid number of leaves in proof")]
     InvalidNumberOfLeavesInProof,
 
+    #[error("invalid data square width {0}, not a power of two within the legal bounds")]
+    InvalidSquareWidth(usize),
+
     #[error("the blob under challenge is not part of the specified index")]
     ChallengedBlobNotInIndex,
 
+    #[error("blob {0} is challenged more than once")]
+    DuplicateChallengedBlob(SpanSequence),
+
+    #[error("extra challenged blobs must be blobs of the index, not the index blob itself")]
+    ExtraChallengedBlobsOfIndexBlob,
+
     #[error("missing index blob data")]
     MissingIndexBlobData,
 
@@ -19,16 +67,172 @@ pub enum InputError {
 
     #[error("first Blobstream attestation index != 0")]
     InvalidFirstBlobstreamAttestationIndex,
+
+    #[error(
+        "Blobstream attestation of block {height} is outside of its commitment range \
+         {start_block}..{end_block}"
+    )]
+    AttestationHeightOutOfRange {
+        height: u64,
+        start_block: u64,
+        end_block: u64,
+    },
+
+    #[error("Blobstream attestation proof of block {height} does not match its commitment range")]
+    AttestationProofRangeMismatch { height: u64 },
+
+    #[error(
+        "Blobstream attestation proof of block {height} does not lead to the data commitment of \
+         nonce {nonce}"
+    )]
+    AttestationDataCommitmentMismatch { height: u64, nonce: U256 },
+
+    #[error(
+        "Blobstream attestation of block {height} does not match its event: different {field}"
+    )]
+    AttestationEventMismatch { height: u64, field: &'static str },
+
+    #[error("attestation nonce {nonce} is not committed yet (next nonce: {next_nonce})")]
+    UncommittedAttestationNonce { nonce: U256, next_nonce: U256 },
+
+    #[error("Blobstream rejected the attestation of block {height} (nonce {nonce})")]
+    AttestationRejected { height: u64, nonce: U256 },
+
+    #[error("invalid row proof for block {0}")]
+    InvalidRowProof(u64),
+
+    #[error("invalid proof of the last root of the data square of block {0}")]
+    InvalidLastRootProof(u64),
+
+    #[error("failed to deserialize guest data: {0}")]
+    InvalidGuestData(
+        #[from]
+        #[serde(serialize_with = "serialize_display")]
+        postcard::Error,
+    ),
+
+    #[error("missing Blobstream attestation and row proof for block {0}")]
+    MissingBlockProof(u64),
+
+    #[error("the block proof of block {key} is for block {height}")]
+    BlockProofHeightMismatch { key: u64, height: u64 },
+
+    #[error("unexpected block proof for block {0}, outside of the path of the challenge")]
+    UnexpectedBlockProof(u64),
+
+    #[error("missing share proof for share {0}")]
+    MissingShareProof(u32),
+
+    #[error("the row proofs of block {height} disagree on the size of its data square")]
+    InconsistentRowProofs { height: u64 },
+
+    #[error("invalid share proof for share {0}")]
+    InvalidShareProof(u32),
+
+    #[error("share proof of share {share_index} does not cover a range of the ODS: {error}")]
+    InvalidShareProofRange {
+        share_index: u32,
+        error: ShareProofRangeError,
+    },
+
+    #[error("expected {expected} shares in share proofs, got {actual}")]
+    UnexpectedShareProofCount { expected: u32, actual: usize },
+
+    #[error("invalid share proof start index: expected {share_index}, got {proof_start_index}")]
+    InvalidShareProofIndex {
+        share_index: u32,
+        proof_start_index: u32,
+    },
+
+    #[error("share proofs cover shares {start}..{covered_end} instead of {start}..{end}")]
+    ShareProofCoverageMismatch {
+        start: u32,
+        end: u32,
+        covered_end: u32,
+    },
+
+    #[error(
+        "share proofs are not contiguous: expected a proof starting at share {expected}, got \
+         {actual}"
+    )]
+    NonContiguousShareProofs { expected: u32, actual: u32 },
+
+    #[error(
+        "share proof of share {share_index} is for a square of {total} rows, expected {expected}"
+    )]
+    ShareProofSquareSizeMismatch {
+        share_index: u32,
+        total: u64,
+        expected: u64,
+    },
+
+    #[error("{depth} nested sub-indexes, at most {max_depth} are supported")]
+    TooManySubIndexes { depth: usize, max_depth: usize },
+
+    #[error("{0} is not a sub-index of its parent index")]
+    SubIndexNotInIndex(SpanSequence),
+
+    #[error("the index commits to neither the data hash nor the share commitment of blob {0}")]
+    MissingDataHash(SpanSequence),
+
+    #[error("invalid app version: {0}")]
+    InvalidAppVersion(u64),
+
+    #[error("app version {actual} of the shares of block {height} does not match its header ({expected})")]
+    AppVersionMismatch {
+        height: u64,
+        expected: u64,
+        actual: u64,
+    },
+
+    #[error("Blobstream contract {address} is not the canonical deployment of chain {chain_id} ({expected})")]
+    NonCanonicalBlobstreamAddress {
+        chain_id: u64,
+        address: Address,
+        expected: Address,
+    },
+
+    #[error("missing Celestia chain ID")]
+    MissingCelestiaChainId,
+
+    #[error(
+        "Blobstream contract {address} tracks Celestia chain {expected}, not {celestia_chain_id}"
+    )]
+    CelestiaChainIdMismatch {
+        celestia_chain_id: String,
+        address: Address,
+        expected: String,
+    },
+
+    #[error("chain spec {digest} is not the pinned chain spec of chain {chain_id} ({expected})")]
+    NonCanonicalChainSpec {
+        chain_id: u64,
+        digest: B256,
+        expected: B256,
+    },
 }
 
 /// An error that implies DA fraud.
-#[derive(Debug, thiserror::Error)]
+///
+/// Serialized like `InputError`. The kinds are those of `DaFraudKind`, so that the `kind` of a
+/// serialized fraud deserializes into its `DaFraudKind`.
+#[cfg(feature = "std")]
+#[derive(Debug, thiserror::Error, Serialize)]
+#[serde(tag = "kind", content = "details", rename_all = "snake_case")]
 pub enum DaFraud {
     #[error("Failed to reconstruct index blob from shares: {0}")]
-    FailedIndexBlobReconstruction(#[from] celestia_types::Error),
+    FailedIndexBlobReconstruction(
+        #[from]
+        #[serde(serialize_with = "serialize_display")]
+        celestia_types::Error,
+    ),
 
     #[error("Failed to deserialize index blob: {0}")]
-    FailedIndexBlobDeserialization(#[from] bincode::Error),
+    FailedIndexBlobDeserialization(
+        #[from]
+        #[serde(serialize_with = "serialize_display")]
+        bincode::Error,
+    ),
 
     #[error("Share index out of bounds: {share_index} > {ods_size}")]
     ShareIndexOutOfBounds { share_index: u32, ods_size: u32 },
@@ -42,7 +246,7 @@ pub enum DaFraud {
     },
 
     #[error(
-        "Block height higher than current Blobstream height: {block_height} < {max_block_height}"
+        "Block height higher than current Blobstream height: {block_height} > {max_block_height}"
     )]
     BlockHeightTooHigh {
         block_height: u64,
@@ -54,9 +258,375 @@ pub enum DaFraud {
 
     #[error("Sequence of spans is empty: {0:?}")]
     EmptySpanSequence(SpanSequence),
+
+    #[error("Data of blob {blob} does not match its index entry: expected hash {expected}, got {actual}")]
+    BlobDataMismatch {
+        blob: SpanSequence,
+        expected: B256,
+        actual: B256,
+    },
+
+    #[error(
+        "Share {share_index} of blob {blob} is in namespace {actual:?} instead of {expected:?}"
+    )]
+    WrongNamespace {
+        blob: SpanSequence,
+        share_index: u32,
+        expected: Namespace,
+        actual: Namespace,
+    },
+
+    #[error("Share {share_index} of blob {blob} has an unexpected sequence start flag: {sequence_start}")]
+    UnexpectedSequenceStart {
+        blob: SpanSequence,
+        share_index: u32,
+        sequence_start: bool,
+    },
+
+    #[error("Blob {blob} has unsupported share version {share_version}")]
+    UnsupportedShareVersion {
+        blob: SpanSequence,
+        share_version: u8,
+    },
+
+    #[error(
+        "Blob {blob} declares a sequence of {sequence_length} bytes, which spans \
+         {expected_size} shares instead of {}",
+        .blob.size
+    )]
+    SequenceLengthMismatch {
+        blob: SpanSequence,
+        sequence_length: u32,
+        expected_size: u64,
+    },
+
+    #[error("Index {index} spans more than {max_size} shares")]
+    IndexTooLarge { index: SpanSequence, max_size: u32 },
+
+    #[error("Index {index} has {entries} entries, more than {max_entries}")]
+    TooManyIndexEntries {
+        index: SpanSequence,
+        entries: usize,
+        max_entries: usize,
+    },
+
+    #[error("Failed to reconstruct blob {blob} from shares: {error}")]
+    FailedBlobReconstruction {
+        blob: SpanSequence,
+        #[serde(serialize_with = "serialize_display")]
+        error: celestia_types::Error,
+    },
+
+    #[error(
+        "Blob {blob} points into a data square of width {ods_width}, too small to hold a blob"
+    )]
+    SquareTooSmallForBlob { blob: SpanSequence, ods_width: u32 },
+
+    #[error("Blob {blob} is listed in the invalid namespace {namespace}")]
+    InvalidEntryNamespace {
+        blob: SpanSequence,
+        namespace: Bytes,
+    },
+
+    #[error(
+        "Blob {blob} does not match the share commitment of its index entry: expected \
+         {expected}, got {actual}"
+    )]
+    BlobCommitmentMismatch {
+        blob: SpanSequence,
+        expected: B256,
+        actual: B256,
+    },
+}
+
+#[cfg(feature = "std")]
+impl DaFraud {
+    /// Returns the kind of the fraud, committed in the journal.
+    pub fn kind(&self) -> DaFraudKind {
+        match self {
+            DaFraud::FailedIndexBlobReconstruction(_) => DaFraudKind::FailedIndexBlobReconstruction,
+            DaFraud::FailedIndexBlobDeserialization(_) => {
+                DaFraudKind::FailedIndexBlobDeserialization
+            }
+            DaFraud::ShareIndexOutOfBounds { .. } => DaFraudKind::ShareIndexOutOfBounds,
+            DaFraud::BlockHeightTooLow { .. } => DaFraudKind::BlockHeightTooLow,
+            DaFraud::BlockHeightTooHigh { .. } => DaFraudKind::BlockHeightTooHigh,
+            DaFraud::SpanSequenceOverflow(_) => DaFraudKind::SpanSequenceOverflow,
+            DaFraud::EmptySpanSequence(_) => DaFraudKind::EmptySpanSequence,
+            DaFraud::BlobDataMismatch { .. } => DaFraudKind::BlobDataMismatch,
+            DaFraud::FailedBlobReconstruction { .. } => DaFraudKind::FailedBlobReconstruction,
+            DaFraud::WrongNamespace { .. } => DaFraudKind::WrongNamespace,
+            DaFraud::UnexpectedSequenceStart { .. } => DaFraudKind::UnexpectedSequenceStart,
+            DaFraud::UnsupportedShareVersion { .. } => DaFraudKind::UnsupportedShareVersion,
+            DaFraud::SequenceLengthMismatch { .. } => DaFraudKind::SequenceLengthMismatch,
+            DaFraud::IndexTooLarge { .. } => DaFraudKind::IndexTooLarge,
+            DaFraud::TooManyIndexEntries { .. } => DaFraudKind::TooManyIndexEntries,
+            DaFraud::SquareTooSmallForBlob { .. } => DaFraudKind::SquareTooSmallForBlob,
+            DaFraud::InvalidEntryNamespace { .. } => DaFraudKind::InvalidEntryNamespace,
+            DaFraud::BlobCommitmentMismatch { .. } => DaFraudKind::BlobCommitmentMismatch,
+        }
+    }
+}
+
+/// Discriminant of `DaFraud`, without the details of the fraud.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DaFraudKind {
+    FailedIndexBlobReconstruction,
+    FailedIndexBlobDeserialization,
+    ShareIndexOutOfBounds,
+    BlockHeightTooLow,
+    BlockHeightTooHigh,
+    SpanSequenceOverflow,
+    EmptySpanSequence,
+    BlobDataMismatch,
+    FailedBlobReconstruction,
+    WrongNamespace,
+    UnexpectedSequenceStart,
+    UnsupportedShareVersion,
+    SequenceLengthMismatch,
+    IndexTooLarge,
+    TooManyIndexEntries,
+    SquareTooSmallForBlob,
+    InvalidEntryNamespace,
+    BlobCommitmentMismatch,
 }
 
+impl DaFraudKind {
+    /// Returns the identifier of the fraud kind committed in the journal. 0 is reserved for
+    /// challenges that prove no fraud.
+    pub fn as_u8(&self) -> u8 {
+        match self {
+            DaFraudKind::FailedIndexBlobReconstruction => 1,
+            DaFraudKind::FailedIndexBlobDeserialization => 2,
+            DaFraudKind::ShareIndexOutOfBounds => 3,
+            DaFraudKind::BlockHeightTooLow => 4,
+            DaFraudKind::BlockHeightTooHigh => 5,
+            DaFraudKind::SpanSequenceOverflow => 6,
+            DaFraudKind::EmptySpanSequence => 7,
+            DaFraudKind::BlobDataMismatch => 8,
+            DaFraudKind::FailedBlobReconstruction => 9,
+            DaFraudKind::WrongNamespace => 10,
+            DaFraudKind::UnexpectedSequenceStart => 11,
+            DaFraudKind::UnsupportedShareVersion => 12,
+            DaFraudKind::SequenceLengthMismatch => 13,
+            DaFraudKind::IndexTooLarge => 14,
+            DaFraudKind::TooManyIndexEntries => 15,
+            DaFraudKind::SquareTooSmallForBlob => 16,
+            DaFraudKind::InvalidEntryNamespace => 17,
+            DaFraudKind::BlobCommitmentMismatch => 18,
+        }
+    }
+
+    /// Parses a fraud kind identifier committed in the journal. Returns `None` for 0 and unknown
+    /// identifiers.
+    pub fn from_u8(value: u8) -> Option<Self> {
+        match value {
+            1 => Some(DaFraudKind::FailedIndexBlobReconstruction),
+            2 => Some(DaFraudKind::FailedIndexBlobDeserialization),
+            3 => Some(DaFraudKind::ShareIndexOutOfBounds),
+            4 => Some(DaFraudKind::BlockHeightTooLow),
+            5 => Some(DaFraudKind::BlockHeightTooHigh),
+            6 => Some(DaFraudKind::SpanSequenceOverflow),
+            7 => Some(DaFraudKind::EmptySpanSequence),
+            8 => Some(DaFraudKind::BlobDataMismatch),
+            9 => Some(DaFraudKind::FailedBlobReconstruction),
+            10 => Some(DaFraudKind::WrongNamespace),
+            11 => Some(DaFraudKind::UnexpectedSequenceStart),
+            12 => Some(DaFraudKind::UnsupportedShareVersion),
+            13 => Some(DaFraudKind::SequenceLengthMismatch),
+            14 => Some(DaFraudKind::IndexTooLarge),
+            15 => Some(DaFraudKind::TooManyIndexEntries),
+            16 => Some(DaFraudKind::SquareTooSmallForBlob),
+            17 => Some(DaFraudKind::InvalidEntryNamespace),
+            18 => Some(DaFraudKind::BlobCommitmentMismatch),
+            _ => None,
+        }
+    }
+}
+
+/// An error while decoding the journal of a DA challenge proof.
 #[derive(Debug, thiserror::Error)]
+pub enum JournalError {
+    #[error("journal is too short")]
+    Truncated,
+
+    #[error("unsupported journal version: {0}")]
+    UnsupportedVersion(u16),
+
+    #[error("invalid journal encoding: {0}")]
+    Abi(#[from] alloy_sol_types::Error),
+
+    #[error("unknown {field} in journal: {value}")]
+    UnknownIdentifier { field: &'static str, value: u8 },
+}
+
+/// An error while building, validating or encoding an index, see `BlobIndexBuilder`.
+#[derive(Debug, thiserror::Error)]
+pub enum IndexError {
+    #[error("entry {position} ({blob}) is empty")]
+    EmptyEntry { position: usize, blob: SpanSequence },
+
+    #[error("entry {position} ({blob}) ends past the largest share index")]
+    EntryOverflow { position: usize, blob: SpanSequence },
+
+    #[error("entry {position} ({blob}) is not sorted by height and start after {previous}")]
+    UnsortedEntry {
+        position: usize,
+        blob: SpanSequence,
+        previous: SpanSequence,
+    },
+
+    #[error("entry {position} ({blob}) overlaps {previous}")]
+    OverlappingEntries {
+        position: usize,
+        blob: SpanSequence,
+        previous: SpanSequence,
+    },
+
+    #[error("{entries} entries, at most {max_entries} are allowed")]
+    TooManyEntries { entries: usize, max_entries: usize },
+
+    #[error("entry {0} has no data hash, data hashes must be set for all the entries or none")]
+    MissingDataHash(usize),
+
+    #[error("{data_hashes} data hashes for {entries} entries")]
+    DataHashCountMismatch { entries: usize, data_hashes: usize },
+
+    #[error("entry {0} has no namespace, namespaces must be set for all the entries or none")]
+    MissingNamespace(usize),
+
+    #[error("{namespaces} namespaces for {entries} entries")]
+    NamespaceCountMismatch { entries: usize, namespaces: usize },
+
+    #[error(
+        "entry {0} has no share commitment, commitments must be set for all the entries or none"
+    )]
+    MissingCommitment(usize),
+
+    #[error("{commitments} share commitments for {entries} entries")]
+    CommitmentCountMismatch { entries: usize, commitments: usize },
+
+    #[error("sub-index position {0} is not an entry of the index")]
+    SubIndexOutOfRange(u32),
+
+    #[error("{0} set before any entry was appended")]
+    NoEntry(&'static str),
+
+    #[error("span sequence {0} does not fit in 32-bit share indexes")]
+    SpanSequenceOutOfRange(SpanSequenceV2),
+
+    #[error("invalid canonical index encoding: {0}")]
+    InvalidCanonicalEncoding(&'static str),
+
+    #[cfg(feature = "bincode")]
+    #[error("failed to encode the index: {0}")]
+    Bincode(#[from] bincode::Error),
+}
+
+/// A share proof that does not cover consecutive shares of the ODS, see
+/// `share_proof_ods_range()`.
+#[cfg(feature = "std")]
+#[derive(Debug, PartialEq, Eq, thiserror::Error, Serialize)]
+#[serde(tag = "kind", content = "details", rename_all = "snake_case")]
+pub enum ShareProofRangeError {
+    #[error("{row_proofs} row proofs for {namespace_proofs} namespace proofs")]
+    RowCountMismatch {
+        row_proofs: usize,
+        namespace_proofs: usize,
+    },
+
+    #[error("columns {start}..{end} of row {row_index} are outside of the ODS")]
+    OutsideOds {
+        row_index: usize,
+        start: u32,
+        end: u32,
+    },
+
+    #[error("row {row_index} does not follow row {previous}")]
+    NonConsecutiveRows { row_index: u32, previous: u32 },
+
+    #[error("columns {start}..{end} of row {row_index} leave a gap with the next or previous row")]
+    PartialRow {
+        row_index: u32,
+        start: u32,
+        end: u32,
+    },
+
+    #[error("{actual} shares for {expected} proven indexes")]
+    ShareCountMismatch { expected: usize, actual: usize },
+}
+
+/// Share proofs that do not make a valid `BlobProofData`, see `BlobProofData::new()`.
+#[cfg(feature = "std")]
+#[derive(Debug, PartialEq, Eq, thiserror::Error)]
+pub enum BlobProofDataError {
+    #[error("no share proof")]
+    NoShareProofs,
+
+    #[error("share proof of share {share_index} is for {total} roots, not a legal data square")]
+    InvalidSquareSize { share_index: u32, total: usize },
+
+    #[error(
+        "share proof of share {share_index} is for a square of {total} roots, expected {expected}"
+    )]
+    SquareSizeMismatch {
+        share_index: u32,
+        total: usize,
+        expected: usize,
+    },
+
+    #[error("share proof of share {share_index} does not cover a range of the ODS: {error}")]
+    InvalidRange {
+        share_index: u32,
+        error: ShareProofRangeError,
+    },
+
+    #[error("share proof keyed by share {share_index} proves shares {proven:?}")]
+    KeyMismatch {
+        share_index: u32,
+        proven: Range<u32>,
+    },
+
+    #[error(
+        "share proof of share {share_index} overlaps the previous one, ending at {previous_end}"
+    )]
+    OverlappingProofs { share_index: u32, previous_end: u32 },
+}
+
+/// An error while converting share indexes between the ODS and the EDS, see `SquareWidth`.
+#[derive(Debug, PartialEq, Eq, thiserror::Error)]
+pub enum SquareIndexError {
+    #[error("share {index} is out of a square {width} shares wide")]
+    OutOfSquare { index: u32, width: u32 },
+
+    #[error("EDS share {eds_index} is a parity share, it has no ODS index")]
+    ParityShare { eds_index: u32 },
+}
+
+/// An error while aggregating the journals of DA challenge proofs.
+#[derive(Debug, thiserror::Error)]
+pub enum AggregationError {
+    #[error("no journal to aggregate")]
+    NoJournals,
+
+    #[error("journal {0} does not prove DA fraud")]
+    NotFraud(usize),
+
+    #[error("journal {index} does not match the first journal: different {field}")]
+    Mismatch { index: usize, field: &'static str },
+
+    #[error("blob {0} is challenged more than once")]
+    DuplicateChallengedBlob(SpanSequence),
+
+    #[error(transparent)]
+    Journal(#[from] JournalError),
+}
+
+/// Serialized as `{"type": "input" | "fraud", "error": ...}`.
+#[cfg(feature = "std")]
+#[derive(Debug, thiserror::Error, Serialize)]
+#[serde(tag = "type", content = "error", rename_all = "snake_case")]
 pub enum DaGuestError {
     #[error(transparent)]
     Input(#[from] InputError),
@@ -64,11 +634,32 @@ pub enum DaGuestError {
     Fraud(#[from] DaFraud),
 }
 
-pub fn compute_ods_width_from_row_proof(row_proof: &MerkleProof) -> Result<u32, DaGuestError> {
-    if (row_proof.total % 4) != 0 {
-        return Err(InputError::InvalidNumberOfLeavesInProof.into());
+/// Returns the widths of the data square from a proof of a row root in the data root, see
+/// `square_width_from_root_count()`.
+#[cfg(feature = "std")]
+pub fn compute_square_width_from_row_proof(
+    row_proof: &MerkleProof,
+) -> Result<SquareWidth, DaGuestError> {
+    Ok(square_width_from_root_count(row_proof.total)?)
+}
+
+/// Returns the widths of the data square whose data root commits to `root_count` roots. The
+/// data root commits to the row and column roots of the EDS, 4 roots per share of ODS width.
+/// Even empty blocks have a data square, of width 1, so a data root over no root is invalid, and
+/// widths are powers of two up to `MAX_ODS_WIDTH`.
+#[cfg(feature = "std")]
+pub fn square_width_from_root_count(root_count: usize) -> Result<SquareWidth, InputError> {
+    if root_count == 0 || (root_count % 4) != 0 {
+        return Err(InputError::InvalidNumberOfLeavesInProof);
+    }
+
+    let ods_width = root_count / 4;
+    if !ods_width.is_power_of_two() || ods_width > MAX_ODS_WIDTH as usize {
+        return Err(InputError::InvalidSquareWidth(ods_width));
     }
 
-    let square_size = row_proof.total / 4;
-    Ok(square_size as u32)
+    Ok(SquareWidth {
+        ods: ods_width as u32,
+        eds: 2 * ods_width as u32,
+    })
 }
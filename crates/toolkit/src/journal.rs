@@ -1,10 +1,690 @@
-use alloy_sol_types::sol;
+use crate::errors::{AggregationError, DaFraudKind, JournalError};
+use crate::{BlobstreamImpl, DaChallenge};
+use alloc::collections::BTreeSet;
+use alloc::string::String;
+use alloc::vec::Vec;
+use alloy_primitives::{Address, Bytes, B256, U256};
+use alloy_sol_types::{sol, SolValue};
+#[cfg(feature = "std")]
 use risc0_steel::Commitment;
+use serde::{Deserialize, Serialize};
+
+/// Version of the journal layout written by the guest program. Bumped on any change of the
+/// `Journal` struct.
+pub const JOURNAL_VERSION: u16 = 7;
+
+/// Version of the journal layout written by the aggregator guest program. Bumped on any change of
+/// the `AggregatedJournal` struct.
+pub const AGGREGATED_JOURNAL_VERSION: u16 = 5;
+
+// Same layout as `risc0_steel::Commitment`, which requires `std`.
+#[cfg(not(feature = "std"))]
+sol! {
+    #[derive(Debug, PartialEq, Eq)]
+    struct Commitment {
+        uint256 id;
+        bytes32 digest;
+        bytes32 configID;
+    }
+}
 
 // ABI encodable journal data.
 sol! {
+    /// ABI version of `crate::SpanSequence`.
+    struct SpanSequence {
+        uint64 height;
+        uint32 start;
+        uint32 size;
+    }
+
+    /// ABI version of `crate::SpanSequenceV2`.
+    struct SpanSequenceV2 {
+        uint64 height;
+        uint64 start;
+        uint64 size;
+    }
+
+    /// ABI version of `crate::BatchMetadata`. All zero if the index was not read or carries no
+    /// metadata.
+    struct BatchMetadata {
+        bytes32 rollupId;
+        uint64 batchNumber;
+        uint64 timestamp;
+    }
+
     struct Journal {
+        /// Layout version, see `JOURNAL_VERSION`. Always the first field, see
+        /// `Journal::version()`.
+        uint16 version;
         Commitment commitment;
         address blobstreamAddress;
+        /// `BlobstreamImpl` of the contract, see `BlobstreamImpl::from_u8()`. Proofs against
+        /// a generic `IDAOracle` (2) cannot prove `DaFraudKind::BlockHeightTooHigh`, and their
+        /// attestations are only authenticated by the `verifyAttestation()` of the contract.
+        uint8 blobstreamImpl;
+        /// `DaOutcome` of the challenge, see `DaOutcome::from_u8()`.
+        uint8 outcome;
+        SpanSequence indexBlob;
+        SpanSequence challengedBlob;
+        /// `DaFraudKind` of the proven fraud, see `DaFraudKind::from_u8()`. 0 if no fraud is
+        /// proven.
+        uint8 fraudType;
+        /// Celestia namespace the index blob was required to be posted to, empty if not checked.
+        bytes namespace;
+        /// Other blobs of the index challenged in the same execution.
+        SpanSequence[] extraChallengedBlobs;
+        /// `DaFraudKind` of the fraud proven for each extra challenged blob, 0 if none.
+        uint8[] extraFraudTypes;
+        /// Chain ID of the Celestia network of the challenged blobs, ex: `celestia`.
+        string celestiaChainId;
+        /// `DaChallenge` of the challenged blob, see `DaChallenge::from_u8()`. Extra challenged
+        /// blobs are always `DaChallenge::BlobInIndexIsUnavailable`.
+        uint8 challengeType;
+        /// `BlobIndex::canonical_hash()` of the index blob. Zero if the index was not read: the
+        /// index blob is the challenged blob, or the index or a sub-index is DA fraud.
+        bytes32 indexHash;
+        /// `BlobIndex::metadata` of the index, see `BatchMetadata`.
+        BatchMetadata batchMetadata;
+    }
+
+    /// Journal of the aggregator guest program: DA frauds proven for several blobs of the same
+    /// index, under the same Steel commitment.
+    struct AggregatedJournal {
+        /// Layout version, see `AGGREGATED_JOURNAL_VERSION`. Always the first field, see
+        /// `Journal::version()`.
+        uint16 version;
+        /// Image ID of the DA challenge guest program that proved the aggregated journals.
+        bytes32 challengeImageId;
+        Commitment commitment;
+        address blobstreamAddress;
+        uint8 blobstreamImpl;
+        SpanSequence indexBlob;
+        bytes namespace;
+        SpanSequence[] challengedBlobs;
+        /// `DaFraudKind` of the fraud proven for each challenged blob.
+        uint8[] fraudTypes;
+        string celestiaChainId;
+        /// `DaChallenge` of each challenged blob, see `DaChallenge::from_u8()`.
+        uint8[] challengeTypes;
+        /// `Journal::indexHash` of the aggregated journals, zero if none of them read the index.
+        bytes32 indexHash;
+        /// `Journal::batchMetadata` of the aggregated journals that read the index.
+        BatchMetadata batchMetadata;
+    }
+}
+
+impl Journal {
+    /// Reads the layout version of an ABI-encoded journal, without decoding the rest. Journals
+    /// are dynamic structs: their encoding starts with the offset of their fields, the first of
+    /// which is the version. Also reads the version of aggregated journals.
+    pub fn version(journal_bytes: &[u8]) -> Result<u16, JournalError> {
+        let word = |offset: usize| {
+            offset
+                .checked_add(32)
+                .and_then(|end| journal_bytes.get(offset..end))
+                .ok_or(JournalError::Truncated)
+        };
+        let fields_offset = usize::try_from(U256::abi_decode(word(0)?, true)?)
+            .map_err(|_| JournalError::Truncated)?;
+        Ok(u16::abi_decode(word(fields_offset)?, true)?)
+    }
+
+    /// ABI-encodes the journal, as committed by the guest program and decoded by the verifier
+    /// contract with `abi.decode(journalData, (Journal))`.
+    pub fn encode(&self) -> Vec<u8> {
+        self.abi_encode()
+    }
+
+    /// Decodes an ABI-encoded journal, rejecting layouts other than `JOURNAL_VERSION`.
+    pub fn decode(journal_bytes: &[u8]) -> Result<Self, JournalError> {
+        let version = Self::version(journal_bytes)?;
+        if version != JOURNAL_VERSION {
+            return Err(JournalError::UnsupportedVersion(version));
+        }
+
+        Ok(Self::abi_decode(journal_bytes, true)?)
+    }
+}
+
+/// Human-readable version of the journal committed by the DA challenge guest.
+#[derive(Debug, Clone, Serialize)]
+pub struct JournalSummary {
+    pub version: u16,
+    pub commitment_id: U256,
+    pub commitment_digest: B256,
+    pub commitment_config_id: B256,
+    pub blobstream_address: Address,
+    pub blobstream_impl: BlobstreamImpl,
+    pub outcome: DaOutcome,
+    pub index_blob: crate::SpanSequence,
+    pub challenged_blob: crate::SpanSequence,
+    pub challenge: DaChallenge,
+    /// Kind of the proven fraud, `None` if no fraud is proven.
+    pub fraud_type: Option<DaFraudKind>,
+    /// Namespace the index blob was required to be posted to, `None` if not checked.
+    pub namespace: Option<Bytes>,
+    /// Other blobs of the index challenged by the same proof.
+    pub extra_challenged_blobs: Vec<crate::SpanSequence>,
+    /// Kind of the fraud proven for each extra challenged blob, `None` if no fraud is proven.
+    pub extra_fraud_types: Vec<Option<DaFraudKind>>,
+    /// Chain ID of the Celestia network of the challenged blobs.
+    pub celestia_chain_id: String,
+    /// Canonical hash of the index, `None` if the index was not read.
+    pub index_hash: Option<B256>,
+    /// Batch of the index, `None` if the index was not read or carries no metadata.
+    pub batch_metadata: Option<crate::BatchMetadata>,
+}
+
+impl JournalSummary {
+    /// Decodes the ABI-encoded journal of a DA challenge receipt.
+    pub fn decode(journal_bytes: &[u8]) -> Result<Self, JournalError> {
+        let journal = Journal::decode(journal_bytes)?;
+        let unknown = |field, value| JournalError::UnknownIdentifier { field, value };
+        let fraud_type = |fraud_type| match fraud_type {
+            0 => Ok(None),
+            fraud_type => DaFraudKind::from_u8(fraud_type)
+                .map(Some)
+                .ok_or(unknown("fraud type", fraud_type)),
+        };
+
+        Ok(Self {
+            version: journal.version,
+            commitment_id: journal.commitment.id,
+            commitment_digest: journal.commitment.digest,
+            commitment_config_id: journal.commitment.configID,
+            blobstream_address: journal.blobstreamAddress,
+            blobstream_impl: BlobstreamImpl::from_u8(journal.blobstreamImpl)
+                .ok_or(unknown("Blobstream implementation", journal.blobstreamImpl))?,
+            outcome: DaOutcome::from_u8(journal.outcome)
+                .ok_or(unknown("outcome", journal.outcome))?,
+            index_blob: journal.indexBlob.into(),
+            challenged_blob: journal.challengedBlob.clone().into(),
+            challenge: DaChallenge::from_u8(journal.challengeType, journal.challengedBlob.into())
+                .ok_or(unknown("challenge type", journal.challengeType))?,
+            fraud_type: fraud_type(journal.fraudType)?,
+            namespace: (!journal.namespace.is_empty()).then_some(journal.namespace),
+            extra_challenged_blobs: journal
+                .extraChallengedBlobs
+                .into_iter()
+                .map(Into::into)
+                .collect(),
+            extra_fraud_types: journal
+                .extraFraudTypes
+                .into_iter()
+                .map(fraud_type)
+                .collect::<Result<_, _>>()?,
+            celestia_chain_id: journal.celestiaChainId,
+            index_hash: (!journal.indexHash.is_zero()).then_some(journal.indexHash),
+            batch_metadata: journal.batchMetadata.into(),
+        })
+    }
+}
+
+/// Human-readable version of the journal committed by the aggregator guest.
+#[derive(Debug, Clone, Serialize)]
+pub struct AggregatedJournalSummary {
+    pub version: u16,
+    /// Image ID of the DA challenge guest program that proved the aggregated journals.
+    pub challenge_image_id: B256,
+    pub commitment_id: U256,
+    pub commitment_digest: B256,
+    pub commitment_config_id: B256,
+    pub blobstream_address: Address,
+    pub blobstream_impl: BlobstreamImpl,
+    pub index_blob: crate::SpanSequence,
+    /// Namespace the index blob was required to be posted to, `None` if not checked.
+    pub namespace: Option<Bytes>,
+    pub challenged_blobs: Vec<crate::SpanSequence>,
+    /// Kind of the fraud proven for each challenged blob.
+    pub fraud_types: Vec<DaFraudKind>,
+    /// Challenge of each challenged blob.
+    pub challenges: Vec<DaChallenge>,
+    pub celestia_chain_id: String,
+    /// Canonical hash of the index, `None` if none of the aggregated journals read the index.
+    pub index_hash: Option<B256>,
+    /// Batch of the index, `None` if none of the aggregated journals read the index or it
+    /// carries no metadata.
+    pub batch_metadata: Option<crate::BatchMetadata>,
+}
+
+impl AggregatedJournalSummary {
+    /// Decodes the ABI-encoded journal of an aggregated receipt.
+    pub fn decode(journal_bytes: &[u8]) -> Result<Self, JournalError> {
+        let journal = AggregatedJournal::decode(journal_bytes)?;
+        let unknown = |field, value| JournalError::UnknownIdentifier { field, value };
+        let challenged_blobs: Vec<crate::SpanSequence> = journal
+            .challengedBlobs
+            .into_iter()
+            .map(Into::into)
+            .collect();
+
+        Ok(Self {
+            version: journal.version,
+            challenge_image_id: journal.challengeImageId,
+            commitment_id: journal.commitment.id,
+            commitment_digest: journal.commitment.digest,
+            commitment_config_id: journal.commitment.configID,
+            blobstream_address: journal.blobstreamAddress,
+            blobstream_impl: BlobstreamImpl::from_u8(journal.blobstreamImpl)
+                .ok_or(unknown("Blobstream implementation", journal.blobstreamImpl))?,
+            index_blob: journal.indexBlob.into(),
+            namespace: (!journal.namespace.is_empty()).then_some(journal.namespace),
+            fraud_types: journal
+                .fraudTypes
+                .into_iter()
+                .map(|fraud_type| {
+                    DaFraudKind::from_u8(fraud_type).ok_or(unknown("fraud type", fraud_type))
+                })
+                .collect::<Result<_, _>>()?,
+            challenges: journal
+                .challengeTypes
+                .into_iter()
+                .zip(&challenged_blobs)
+                .map(|(challenge_type, &blob)| {
+                    DaChallenge::from_u8(challenge_type, blob)
+                        .ok_or(unknown("challenge type", challenge_type))
+                })
+                .collect::<Result<_, _>>()?,
+            challenged_blobs,
+            celestia_chain_id: journal.celestiaChainId,
+            index_hash: (!journal.indexHash.is_zero()).then_some(journal.indexHash),
+            batch_metadata: journal.batchMetadata.into(),
+        })
+    }
+}
+
+impl From<crate::SpanSequence> for SpanSequence {
+    fn from(span_sequence: crate::SpanSequence) -> Self {
+        Self {
+            height: span_sequence.height,
+            start: span_sequence.start,
+            size: span_sequence.size,
+        }
+    }
+}
+
+impl From<SpanSequence> for crate::SpanSequence {
+    fn from(span_sequence: SpanSequence) -> Self {
+        Self {
+            height: span_sequence.height,
+            start: span_sequence.start,
+            size: span_sequence.size,
+        }
+    }
+}
+
+impl From<Option<crate::BatchMetadata>> for BatchMetadata {
+    fn from(metadata: Option<crate::BatchMetadata>) -> Self {
+        let metadata = metadata.unwrap_or_default();
+        Self {
+            rollupId: metadata.rollup_id,
+            batchNumber: metadata.batch_number,
+            timestamp: metadata.timestamp,
+        }
+    }
+}
+
+impl From<BatchMetadata> for Option<crate::BatchMetadata> {
+    fn from(metadata: BatchMetadata) -> Self {
+        let metadata = crate::BatchMetadata {
+            rollup_id: metadata.rollupId,
+            batch_number: metadata.batchNumber,
+            timestamp: metadata.timestamp,
+        };
+        (metadata != crate::BatchMetadata::default()).then_some(metadata)
+    }
+}
+
+impl From<crate::SpanSequenceV2> for SpanSequenceV2 {
+    fn from(span_sequence: crate::SpanSequenceV2) -> Self {
+        Self {
+            height: span_sequence.height,
+            start: span_sequence.start,
+            size: span_sequence.size,
+        }
+    }
+}
+
+impl From<SpanSequenceV2> for crate::SpanSequenceV2 {
+    fn from(span_sequence: SpanSequenceV2) -> Self {
+        Self {
+            height: span_sequence.height,
+            start: span_sequence.start,
+            size: span_sequence.size,
+        }
+    }
+}
+
+/// What a DA challenge proof proves.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DaOutcome {
+    /// The challenged blob is unavailable or unreadable.
+    FraudProven,
+    /// The challenged blob is available. Only proven if the host requests proofs of
+    /// availability, the guest program panics otherwise.
+    BlobAvailable,
+}
+
+impl DaOutcome {
+    /// Returns the identifier of the outcome committed in the journal.
+    pub fn as_u8(&self) -> u8 {
+        match self {
+            DaOutcome::FraudProven => 0,
+            DaOutcome::BlobAvailable => 1,
+        }
+    }
+
+    /// Parses an outcome identifier committed in the journal.
+    pub fn from_u8(value: u8) -> Option<Self> {
+        match value {
+            0 => Some(DaOutcome::FraudProven),
+            1 => Some(DaOutcome::BlobAvailable),
+            _ => None,
+        }
+    }
+}
+
+/// Options of the guest program, written by the host after the `BlobstreamInfo`.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+pub struct GuestOptions {
+    /// Commit `DaOutcome::BlobAvailable` instead of panicking when the challenged blob is
+    /// available.
+    pub prove_availability: bool,
+}
+
+/// Input of the aggregator guest program. The receipts of the journals are passed as
+/// assumptions.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AggregationInput {
+    /// Image ID of the DA challenge guest program.
+    pub challenge_image_id: [u32; 8],
+    /// ABI-encoded journals of the DA challenge proofs to aggregate.
+    pub journals: Vec<Vec<u8>>,
+}
+
+impl AggregatedJournal {
+    /// Merges the journals of DA challenge proofs. All of them must prove DA fraud for distinct
+    /// blobs of the same index, against the same Blobstream contract and Steel commitment, so
+    /// that verifier contracts only check these once. The extra challenged blobs of the journals
+    /// are listed after their challenged blob.
+    pub fn aggregate(
+        challenge_image_id: B256,
+        journals: &[Journal],
+    ) -> Result<Self, AggregationError> {
+        let first = journals.first().ok_or(AggregationError::NoJournals)?;
+        let mut challenged_blobs = BTreeSet::new();
+        let mut index_hash = B256::ZERO;
+        let mut batch_metadata = BatchMetadata::from(None::<crate::BatchMetadata>);
+
+        for (i, journal) in journals.iter().enumerate() {
+            if journal.version != JOURNAL_VERSION {
+                return Err(JournalError::UnsupportedVersion(journal.version).into());
+            }
+            if DaOutcome::from_u8(journal.outcome) != Some(DaOutcome::FraudProven) {
+                return Err(AggregationError::NotFraud(i));
+            }
+
+            let mismatch = |field| AggregationError::Mismatch { index: i, field };
+            if journal.commitment.abi_encode() != first.commitment.abi_encode() {
+                return Err(mismatch("commitment"));
+            }
+            if journal.blobstreamAddress != first.blobstreamAddress
+                || journal.blobstreamImpl != first.blobstreamImpl
+            {
+                return Err(mismatch("Blobstream contract"));
+            }
+            if crate::SpanSequence::from(journal.indexBlob.clone())
+                != crate::SpanSequence::from(first.indexBlob.clone())
+            {
+                return Err(mismatch("index blob"));
+            }
+            if journal.namespace != first.namespace {
+                return Err(mismatch("namespace"));
+            }
+            if journal.celestiaChainId != first.celestiaChainId {
+                return Err(mismatch("Celestia chain ID"));
+            }
+            // Journals that did not read the index do not commit to its hash
+            if !journal.indexHash.is_zero() {
+                if !index_hash.is_zero() && journal.indexHash != index_hash {
+                    return Err(mismatch("index hash"));
+                }
+                index_hash = journal.indexHash;
+                // Same index, same metadata
+                batch_metadata = journal.batchMetadata.clone();
+            }
+
+            for blob in [&journal.challengedBlob]
+                .into_iter()
+                .chain(&journal.extraChallengedBlobs)
+            {
+                let challenged_blob = crate::SpanSequence::from(blob.clone());
+                if !challenged_blobs.insert(challenged_blob) {
+                    return Err(AggregationError::DuplicateChallengedBlob(challenged_blob));
+                }
+            }
+        }
+
+        Ok(Self {
+            version: AGGREGATED_JOURNAL_VERSION,
+            challengeImageId: challenge_image_id,
+            commitment: first.commitment.clone(),
+            blobstreamAddress: first.blobstreamAddress,
+            blobstreamImpl: first.blobstreamImpl,
+            indexBlob: first.indexBlob.clone(),
+            namespace: first.namespace.clone(),
+            challengedBlobs: journals
+                .iter()
+                .flat_map(|journal| {
+                    [journal.challengedBlob.clone()]
+                        .into_iter()
+                        .chain(journal.extraChallengedBlobs.iter().cloned())
+                })
+                .collect(),
+            fraudTypes: journals
+                .iter()
+                .flat_map(|journal| {
+                    [journal.fraudType]
+                        .into_iter()
+                        .chain(journal.extraFraudTypes.iter().copied())
+                })
+                .collect(),
+            celestiaChainId: first.celestiaChainId.clone(),
+            challengeTypes: journals
+                .iter()
+                .flat_map(|journal| {
+                    [journal.challengeType].into_iter().chain(
+                        journal.extraChallengedBlobs.iter().map(|blob| {
+                            DaChallenge::BlobInIndexIsUnavailable(blob.clone().into()).as_u8()
+                        }),
+                    )
+                })
+                .collect(),
+            indexHash: index_hash,
+            batchMetadata: batch_metadata,
+        })
+    }
+
+    /// ABI-encodes the aggregated journal, see `Journal::encode()`.
+    pub fn encode(&self) -> Vec<u8> {
+        self.abi_encode()
+    }
+
+    /// Decodes an ABI-encoded aggregated journal, rejecting unsupported layout versions.
+    pub fn decode(journal_bytes: &[u8]) -> Result<Self, JournalError> {
+        let version = Journal::version(journal_bytes)?;
+        if version != AGGREGATED_JOURNAL_VERSION {
+            return Err(JournalError::UnsupportedVersion(version));
+        }
+
+        Ok(Self::abi_decode(journal_bytes, true)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec;
+
+    const CHALLENGE_IMAGE_ID: B256 = B256::repeat_byte(5);
+
+    fn span_sequence(height: u64, start: u32, size: u32) -> SpanSequence {
+        crate::SpanSequence {
+            height,
+            start,
+            size,
+        }
+        .into()
+    }
+
+    /// Journal proving that blob `start` of block 11 of the index is unavailable.
+    fn fraud_journal(start: u32) -> Journal {
+        Journal {
+            version: JOURNAL_VERSION,
+            commitment: Commitment {
+                id: U256::from(7),
+                digest: B256::repeat_byte(1),
+                configID: B256::repeat_byte(2),
+            },
+            blobstreamAddress: Address::repeat_byte(3),
+            blobstreamImpl: BlobstreamImpl::Sp1.as_u8(),
+            outcome: DaOutcome::FraudProven.as_u8(),
+            indexBlob: span_sequence(10, 0, 4),
+            challengedBlob: span_sequence(11, start, 1),
+            fraudType: DaFraudKind::ShareIndexOutOfBounds.as_u8(),
+            namespace: vec![0; 29].into(),
+            extraChallengedBlobs: vec![],
+            extraFraudTypes: vec![],
+            celestiaChainId: "mocha-4".into(),
+            challengeType: DaChallenge::BlobInIndexIsUnavailable(crate::SpanSequence {
+                height: 11,
+                start,
+                size: 1,
+            })
+            .as_u8(),
+            indexHash: B256::repeat_byte(4),
+            batchMetadata: BatchMetadata::from(None::<crate::BatchMetadata>),
+        }
+    }
+
+    /// Aggregates a valid journal with a copy of it altered by `alter`.
+    fn aggregate_altered(alter: impl FnOnce(&mut Journal)) -> Result<(), AggregationError> {
+        let mut journal = fraud_journal(1);
+        alter(&mut journal);
+        AggregatedJournal::aggregate(CHALLENGE_IMAGE_ID, &[fraud_journal(0), journal]).map(|_| ())
+    }
+
+    fn assert_mismatch(result: Result<(), AggregationError>, expected_field: &str) {
+        match result {
+            Err(AggregationError::Mismatch { index: 1, field }) => {
+                assert_eq!(field, expected_field)
+            }
+            other => panic!("expected a {expected_field} mismatch, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn aggregates_frauds_of_the_same_index() {
+        let aggregated =
+            AggregatedJournal::aggregate(CHALLENGE_IMAGE_ID, &[fraud_journal(0), fraud_journal(1)])
+                .unwrap();
+        assert_eq!(aggregated.challengedBlobs.len(), 2);
+        assert_eq!(aggregated.indexHash, B256::repeat_byte(4));
+    }
+
+    #[test]
+    fn aggregate_rejects_no_journals() {
+        assert!(matches!(
+            AggregatedJournal::aggregate(CHALLENGE_IMAGE_ID, &[]),
+            Err(AggregationError::NoJournals)
+        ));
+    }
+
+    #[test]
+    fn aggregate_rejects_proofs_of_availability() {
+        assert!(matches!(
+            aggregate_altered(|journal| journal.outcome = DaOutcome::BlobAvailable.as_u8()),
+            Err(AggregationError::NotFraud(1))
+        ));
+    }
+
+    #[test]
+    fn aggregate_rejects_other_commitments() {
+        assert_mismatch(
+            aggregate_altered(|journal| journal.commitment.id = U256::from(8)),
+            "commitment",
+        );
+        assert_mismatch(
+            aggregate_altered(|journal| journal.commitment.configID = B256::repeat_byte(9)),
+            "commitment",
+        );
+    }
+
+    #[test]
+    fn aggregate_rejects_other_blobstream_contracts() {
+        assert_mismatch(
+            aggregate_altered(|journal| journal.blobstreamAddress = Address::repeat_byte(9)),
+            "Blobstream contract",
+        );
+        assert_mismatch(
+            aggregate_altered(|journal| journal.blobstreamImpl = BlobstreamImpl::DaOracle.as_u8()),
+            "Blobstream contract",
+        );
+    }
+
+    #[test]
+    fn aggregate_rejects_other_indexes() {
+        assert_mismatch(
+            aggregate_altered(|journal| journal.indexBlob = span_sequence(10, 4, 4)),
+            "index blob",
+        );
+    }
+
+    #[test]
+    fn aggregate_rejects_other_namespaces() {
+        assert_mismatch(
+            aggregate_altered(|journal| journal.namespace = vec![1; 29].into()),
+            "namespace",
+        );
+    }
+
+    #[test]
+    fn aggregate_rejects_other_celestia_chains() {
+        assert_mismatch(
+            aggregate_altered(|journal| journal.celestiaChainId = "celestia".into()),
+            "Celestia chain ID",
+        );
+    }
+
+    #[test]
+    fn aggregate_rejects_other_index_hashes() {
+        assert_mismatch(
+            aggregate_altered(|journal| journal.indexHash = B256::repeat_byte(9)),
+            "index hash",
+        );
+        // Journals that did not read the index match any index hash
+        aggregate_altered(|journal| journal.indexHash = B256::ZERO).unwrap();
+    }
+
+    #[test]
+    fn aggregate_rejects_blobs_challenged_twice() {
+        let duplicate = crate::SpanSequence {
+            height: 11,
+            start: 0,
+            size: 1,
+        };
+        assert!(matches!(
+            aggregate_altered(|journal| journal.challengedBlob = span_sequence(11, 0, 1)),
+            Err(AggregationError::DuplicateChallengedBlob(blob)) if blob == duplicate
+        ));
+        // Also across the extra challenged blobs
+        assert!(matches!(
+            aggregate_altered(|journal| {
+                journal.extraChallengedBlobs = vec![span_sequence(11, 0, 1)];
+                journal.extraFraudTypes = vec![DaFraudKind::ShareIndexOutOfBounds.as_u8()];
+            }),
+            Err(AggregationError::DuplicateChallengedBlob(blob)) if blob == duplicate
+        ));
     }
 }
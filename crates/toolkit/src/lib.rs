@@ -1,16 +1,60 @@
+//! Types and checks shared by the DA challenge guest program and its host.
+//!
+//! Without default features, only the core types build, under `no_std` + `alloc`: span
+//! sequences, indexes in the canonical encoding, journals and their errors. Celestia shares and
+//! proofs and Steel require `std`, everything the guest program needs. Host-only helpers, ex: the
+//! debug JSON dumps of the guest data, require the default `host` feature, which the guest does
+//! not enable. The legacy bincode index encodings require the `bincode` feature, enabled by
+//! `std`.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
+
+#[cfg(feature = "std")]
 pub mod blobstream;
+#[cfg(feature = "std")]
+pub mod chain;
+#[cfg(feature = "std")]
+pub mod challenge;
 pub mod constants;
 pub mod errors;
 pub mod journal;
 
-use alloy_primitives::Address;
-use celestia_types::consts::appconsts::SHARE_SIZE;
-use celestia_types::nmt::NamespacedHash;
+#[cfg(feature = "bincode")]
+use alloc::boxed::Box;
+use alloc::collections::{BTreeMap, BTreeSet};
+use alloc::string::String;
+#[cfg(feature = "bincode")]
+use alloc::string::ToString;
+use alloc::vec;
+use alloc::vec::Vec;
+#[cfg(feature = "std")]
+use alloy_primitives::U256;
+use alloy_primitives::{keccak256, Address, B256};
+#[cfg(feature = "std")]
+use alloy_sol_types::SolValue;
+#[cfg(feature = "std")]
+use blobstream::DataRootTuple;
+#[cfg(feature = "std")]
+use celestia_types::consts::appconsts::{SEQUENCE_LEN_BYTES, SHARE_INFO_BYTES, SHARE_SIZE};
+#[cfg(feature = "std")]
+use celestia_types::nmt::{Namespace, NamespacedHash, NS_SIZE};
+#[cfg(feature = "std")]
 use celestia_types::{AppVersion, Blob, MerkleProof, Share, ShareProof};
-use errors::DaFraud;
+use constants::{MAX_INDEX_ENTRIES, NAMESPACE_SIZE};
+use core::fmt::{Display, Formatter};
+#[cfg(feature = "std")]
+use core::ops::Range;
+use core::str::FromStr;
+#[cfg(feature = "std")]
+use errors::{
+    compute_square_width_from_row_proof, BlobProofDataError, DaFraud, DaGuestError, InputError,
+    ShareProofRangeError,
+};
+use errors::{IndexError, SquareIndexError};
 use serde::{Deserialize, Serialize};
-use std::collections::BTreeMap;
-use std::str::FromStr;
+use sha2::{Digest, Sha256};
 
 /// Commits to a Celestia blob by its position in the Original Data Square (ODS).
 /// Note that the start index refers to the ODS, but the Celestia API returns the EDS index
@@ -27,6 +71,7 @@ pub struct SpanSequence {
 
 impl SpanSequence {
     /// Returns the index of the first share after this blob / sequence of spans in the ODS.
+    #[cfg(feature = "std")]
     pub fn end_index_ods(&self) -> Result<u32, DaFraud> {
         if self.size == 0 {
             return Err(DaFraud::EmptySpanSequence(*self));
@@ -36,6 +81,45 @@ impl SpanSequence {
             .checked_add(self.size)
             .ok_or(DaFraud::SpanSequenceOverflow(*self))
     }
+
+    /// Returns the indexes of the shares of the blob in the ODS, see `end_index_ods()`.
+    #[cfg(feature = "std")]
+    pub fn ods_range(&self) -> Result<Range<u32>, DaFraud> {
+        Ok(self.start..self.end_index_ods()?)
+    }
+
+    /// Returns whether the share at `share_index` in the ODS of the block at `height` is one of
+    /// the shares of the blob.
+    pub fn contains_share(&self, height: u64, share_index: u32) -> bool {
+        height == self.height && (self.start as u64..self.end()).contains(&(share_index as u64))
+    }
+
+    /// Returns whether the blob shares at least one share with `other`. Empty span sequences
+    /// overlap nothing.
+    pub fn overlaps(&self, other: &SpanSequence) -> bool {
+        self.intersect(other).is_some()
+    }
+
+    /// Returns the shares that the blob shares with `other`, if any. Span sequences that overflow
+    /// the share indexes are clamped to the largest share index.
+    pub fn intersect(&self, other: &SpanSequence) -> Option<SpanSequence> {
+        if self.height != other.height {
+            return None;
+        }
+
+        let start = self.start.max(other.start);
+        let end = self.end().min(other.end()).min(u32::MAX as u64) as u32;
+        (start < end).then_some(SpanSequence {
+            height: self.height,
+            start,
+            size: end - start,
+        })
+    }
+
+    /// End of the blob in the ODS, which does not overflow unlike `end_index_ods()`.
+    fn end(&self) -> u64 {
+        self.start as u64 + self.size as u64
+    }
 }
 
 impl FromStr for SpanSequence {
@@ -59,28 +143,858 @@ impl FromStr for SpanSequence {
     }
 }
 
+impl Display for SpanSequence {
+    /// Formats the span sequence as `height:start:size`, the format accepted by `from_str()`.
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{}:{}:{}", self.height, self.start, self.size)
+    }
+}
+
+/// `SpanSequence` with 64-bit share indexes and sizes, for data squares too large for 32-bit
+/// indexes. Indexes, the guest program and the journal still use `SpanSequence`: span sequences
+/// that fit in 32 bits convert losslessly both ways, see `TryFrom<SpanSequenceV2>`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub struct SpanSequenceV2 {
+    /// Block height.
+    pub height: u64,
+    /// Index of the first share of the blob in the ODS.
+    pub start: u64,
+    /// Number of shares that make up the blob, ignoring parity shares.
+    pub size: u64,
+}
+
+impl SpanSequenceV2 {
+    /// Returns the index of the first share after the blob in the ODS, `None` if the blob is
+    /// empty or overflows the share indexes.
+    pub fn end_index_ods(&self) -> Option<u64> {
+        if self.size == 0 {
+            return None;
+        }
+
+        self.start.checked_add(self.size)
+    }
+}
+
+impl From<SpanSequence> for SpanSequenceV2 {
+    fn from(span_sequence: SpanSequence) -> Self {
+        Self {
+            height: span_sequence.height,
+            start: span_sequence.start.into(),
+            size: span_sequence.size.into(),
+        }
+    }
+}
+
+impl TryFrom<SpanSequenceV2> for SpanSequence {
+    type Error = IndexError;
+
+    fn try_from(span_sequence: SpanSequenceV2) -> Result<Self, Self::Error> {
+        let out_of_range = |_| IndexError::SpanSequenceOutOfRange(span_sequence);
+        Ok(Self {
+            height: span_sequence.height,
+            start: span_sequence.start.try_into().map_err(out_of_range)?,
+            size: span_sequence.size.try_into().map_err(out_of_range)?,
+        })
+    }
+}
+
+impl FromStr for SpanSequenceV2 {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let parts: Vec<&str> = s.split(':').collect();
+        if parts.len() != 3 {
+            return Err("Expected format: height:start:size".into());
+        }
+
+        let height = parts[0].parse::<u64>().map_err(|_| "Invalid height")?;
+        let start = parts[1].parse::<u64>().map_err(|_| "Invalid start")?;
+        let size = parts[2].parse::<u64>().map_err(|_| "Invalid size")?;
+
+        Ok(SpanSequenceV2 {
+            height,
+            start,
+            size,
+        })
+    }
+}
+
+impl Display for SpanSequenceV2 {
+    /// Formats the span sequence as `height:start:size`, like `SpanSequence`.
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{}:{}:{}", self.height, self.start, self.size)
+    }
+}
+
 /// The blob index is a structure that points to other blobs.
 /// Its purpose is to commit to multiple blobs with a single blob, enabling to push only one
 /// commitment on-chain instead of many.
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct BlobIndex {
     pub blobs: Vec<SpanSequence>,
+    /// SHA-256 hash of the data of each blob, in the order of `blobs`. Empty if the index does
+    /// not commit to the data of its blobs.
+    #[serde(default)]
+    pub data_hashes: Vec<[u8; 32]>,
+    /// Positions in `blobs` of the entries that are themselves indexes (sub-indexes), whose
+    /// blobs are listed in the sub-index instead of this index.
+    #[serde(default)]
+    pub sub_indexes: BTreeSet<u32>,
+    /// Raw namespace each blob is posted to, in the order of `blobs`. Empty if the blobs live in
+    /// the namespace of the index blob. Namespaces of sub-index entries are those of the
+    /// sub-index blobs.
+    #[serde(default)]
+    pub namespaces: Vec<[u8; NAMESPACE_SIZE]>,
+    /// Celestia share commitment of each blob (`Blob::commitment`), in the order of `blobs`.
+    /// Empty if the index does not carry them.
+    #[serde(default)]
+    pub commitments: Vec<[u8; 32]>,
+    /// Batch the index was posted for, if the sequencer sets it.
+    #[serde(default)]
+    pub metadata: Option<BatchMetadata>,
+}
+
+/// Describes the rollup batch an index was posted for, so that proofs can be correlated to the
+/// batch by verifier contracts. Committed in the journal when the guest program reads the index.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BatchMetadata {
+    /// Identifier of the rollup, chosen by the rollup.
+    pub rollup_id: B256,
+    /// Number of the batch in the rollup.
+    pub batch_number: u64,
+    /// Time the batch was produced, in seconds since the Unix epoch.
+    pub timestamp: u64,
+}
+
+/// Size of the batch metadata in the canonical index encoding: rollup ID, batch number and
+/// timestamp.
+const CANONICAL_METADATA_SIZE: usize = 32 + 8 + 8;
+
+/// Builds a `BlobIndex`, checking the invariants expected from well-formed indexes: entries are
+/// non-empty, sorted by height and start, do not overlap and fit in the share indexes, and there
+/// are at most `max_entries` of them. The guest program does not rely on these invariants, an
+/// index breaking them only wastes the rollup's own data.
+#[derive(Debug, Clone)]
+pub struct BlobIndexBuilder {
+    entries: Vec<BlobIndexBuilderEntry>,
+    max_entries: usize,
+    metadata: Option<BatchMetadata>,
+    /// First field set before any entry was appended, reported by `build()`.
+    field_without_entry: Option<&'static str>,
+}
+
+#[derive(Debug, Clone)]
+struct BlobIndexBuilderEntry {
+    blob: SpanSequence,
+    data_hash: Option<[u8; 32]>,
+    sub_index: bool,
+    namespace: Option<[u8; NAMESPACE_SIZE]>,
+    commitment: Option<[u8; 32]>,
+}
+
+impl Default for BlobIndexBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl BlobIndexBuilder {
+    /// Creates an empty builder, accepting up to `MAX_INDEX_ENTRIES` entries.
+    pub fn new() -> Self {
+        Self {
+            entries: vec![],
+            max_entries: MAX_INDEX_ENTRIES,
+            metadata: None,
+            field_without_entry: None,
+        }
+    }
+
+    /// Returns the number of entries appended so far.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns whether no entry was appended yet.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Sets the maximum number of entries of the index.
+    pub fn with_max_entries(mut self, max_entries: usize) -> Self {
+        self.max_entries = max_entries;
+        self
+    }
+
+    /// Sets the batch the index is posted for.
+    pub fn with_metadata(mut self, metadata: BatchMetadata) -> Self {
+        self.metadata = Some(metadata);
+        self
+    }
+
+    /// Appends an entry to the index. Data hashes must be set for all the entries or none.
+    pub fn with_entry(
+        mut self,
+        blob: SpanSequence,
+        data_hash: Option<[u8; 32]>,
+        sub_index: bool,
+    ) -> Self {
+        self.entries.push(BlobIndexBuilderEntry {
+            blob,
+            data_hash,
+            sub_index,
+            namespace: None,
+            commitment: None,
+        });
+        self
+    }
+
+    /// Sets the namespace of the last appended entry. Namespaces must be set for all the entries
+    /// or none. Without any entry yet, `build()` fails with `IndexError::NoEntry`.
+    pub fn with_namespace(mut self, namespace: [u8; NAMESPACE_SIZE]) -> Self {
+        match self.entries.last_mut() {
+            Some(entry) => entry.namespace = Some(namespace),
+            None => self.record_field_without_entry("namespace"),
+        }
+        self
+    }
+
+    /// Sets the share commitment of the last appended entry, see `BlobIndex::commitments`.
+    /// Commitments must be set for all the entries or none. Without any entry yet, `build()`
+    /// fails with `IndexError::NoEntry`.
+    pub fn with_commitment(mut self, commitment: [u8; 32]) -> Self {
+        match self.entries.last_mut() {
+            Some(entry) => entry.commitment = Some(commitment),
+            None => self.record_field_without_entry("share commitment"),
+        }
+        self
+    }
+
+    fn record_field_without_entry(&mut self, field: &'static str) {
+        self.field_without_entry.get_or_insert(field);
+    }
+
+    /// Appends a blob to the index.
+    pub fn with_blob(self, blob: SpanSequence) -> Self {
+        self.with_entry(blob, None, false)
+    }
+
+    /// Appends a blob to the index, with the hash of its data, see `blob_data_hash()`.
+    pub fn with_blob_and_data_hash(self, blob: SpanSequence, data_hash: [u8; 32]) -> Self {
+        self.with_entry(blob, Some(data_hash), false)
+    }
+
+    /// Appends a sub-index to the index.
+    pub fn with_sub_index(self, sub_index: SpanSequence) -> Self {
+        self.with_entry(sub_index, None, true)
+    }
+
+    /// Checks the entries and builds the index, see `BlobIndex::validate()`.
+    pub fn build(self) -> Result<BlobIndex, IndexError> {
+        if let Some(field) = self.field_without_entry {
+            return Err(IndexError::NoEntry(field));
+        }
+        let first = self.entries.first();
+        let has_data_hashes = first.is_some_and(|entry| entry.data_hash.is_some());
+        let has_namespaces = first.is_some_and(|entry| entry.namespace.is_some());
+        let has_commitments = first.is_some_and(|entry| entry.commitment.is_some());
+        let mut index = BlobIndex::new(Vec::with_capacity(self.entries.len()));
+        index.metadata = self.metadata;
+        for (position, entry) in self.entries.into_iter().enumerate() {
+            match entry.data_hash {
+                Some(data_hash) if has_data_hashes => index.data_hashes.push(data_hash),
+                None if !has_data_hashes => {}
+                _ => return Err(IndexError::MissingDataHash(position)),
+            }
+            match entry.namespace {
+                Some(namespace) if has_namespaces => index.namespaces.push(namespace),
+                None if !has_namespaces => {}
+                _ => return Err(IndexError::MissingNamespace(position)),
+            }
+            match entry.commitment {
+                Some(commitment) if has_commitments => index.commitments.push(commitment),
+                None if !has_commitments => {}
+                _ => return Err(IndexError::MissingCommitment(position)),
+            }
+            if entry.sub_index {
+                index.sub_indexes.insert(position as u32);
+            }
+            index.blobs.push(entry.blob);
+        }
+
+        index.validate_with_max_entries(self.max_entries)?;
+        Ok(index)
+    }
+}
+
+/// Prefix of index encodings that carry more than the span sequences. Legacy indexes start with
+/// the number of span sequences as a little-endian `u64`, which can never be `u64::MAX`.
+pub const INDEX_V2_MARKER: [u8; 8] = [0xff; 8];
+
+/// Prefix of index encodings with sub-indexes, see `INDEX_V2_MARKER`.
+pub const INDEX_V3_MARKER: [u8; 8] = [0xfe, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff];
+
+/// Prefix of the canonical index encoding, see `BlobIndex::encode_canonical()`.
+pub const INDEX_CANONICAL_MARKER: [u8; 8] = [0xfd, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff];
+
+/// Size of an entry of the canonical index encoding without data hash nor namespace: height,
+/// start, size and sub-index flag.
+const CANONICAL_ENTRY_SIZE: usize = 8 + 4 + 4 + 1;
+
+/// Flag of canonical indexes whose entries carry data hashes.
+const CANONICAL_FLAG_DATA_HASHES: u8 = 1;
+
+/// Flag of canonical indexes whose entries carry namespaces.
+const CANONICAL_FLAG_NAMESPACES: u8 = 2;
+
+/// Flag of canonical indexes whose entries carry share commitments.
+const CANONICAL_FLAG_COMMITMENTS: u8 = 4;
+
+/// Flag of canonical indexes carrying batch metadata.
+const CANONICAL_FLAG_METADATA: u8 = 8;
+
+/// Size of an entry of the canonical index encoding with the given flags.
+fn canonical_entry_size(flags: u8) -> usize {
+    let mut entry_size = CANONICAL_ENTRY_SIZE;
+    if flags & CANONICAL_FLAG_DATA_HASHES != 0 {
+        entry_size += 32;
+    }
+    if flags & CANONICAL_FLAG_NAMESPACES != 0 {
+        entry_size += NAMESPACE_SIZE;
+    }
+    if flags & CANONICAL_FLAG_COMMITMENTS != 0 {
+        entry_size += 32;
+    }
+    entry_size
+}
+
+/// Encoding of indexes with data hashes, after `INDEX_V2_MARKER`.
+#[cfg(feature = "bincode")]
+#[derive(Serialize, Deserialize)]
+struct BlobIndexV2 {
+    entries: Vec<(SpanSequence, [u8; 32])>,
+}
+
+/// Encoding of indexes with sub-indexes, after `INDEX_V3_MARKER`. Data hashes are set for all
+/// the entries or for none.
+#[cfg(feature = "bincode")]
+#[derive(Serialize, Deserialize)]
+struct BlobIndexV3 {
+    entries: Vec<BlobIndexEntryV3>,
+}
+
+#[cfg(feature = "bincode")]
+#[derive(Serialize, Deserialize)]
+struct BlobIndexEntryV3 {
+    blob: SpanSequence,
+    data_hash: Option<[u8; 32]>,
+    sub_index: bool,
 }
 
 impl BlobIndex {
     pub fn new(blobs: Vec<SpanSequence>) -> Self {
-        Self { blobs }
+        Self {
+            blobs,
+            data_hashes: vec![],
+            sub_indexes: BTreeSet::new(),
+            namespaces: vec![],
+            commitments: vec![],
+            metadata: None,
+        }
+    }
+
+    /// Creates an index that commits to the data of its blobs, see `blob_data_hash()`.
+    pub fn with_data_hashes(entries: Vec<(SpanSequence, [u8; 32])>) -> Self {
+        let (blobs, data_hashes) = entries.into_iter().unzip();
+        Self {
+            blobs,
+            data_hashes,
+            sub_indexes: BTreeSet::new(),
+            namespaces: vec![],
+            commitments: vec![],
+            metadata: None,
+        }
+    }
+
+    /// Flags the entries at the specified positions as sub-indexes.
+    pub fn with_sub_indexes(mut self, positions: impl IntoIterator<Item = u32>) -> Self {
+        self.sub_indexes.extend(positions);
+        self
+    }
+
+    /// Sets the namespace of each blob, in the order of the entries.
+    pub fn with_namespaces(mut self, namespaces: Vec<[u8; NAMESPACE_SIZE]>) -> Self {
+        self.namespaces = namespaces;
+        self
+    }
+
+    /// Returns the namespace of the blob at position `i` of the index, if the index sets one.
+    pub fn namespace(&self, i: usize) -> Option<&[u8; NAMESPACE_SIZE]> {
+        self.namespaces.get(i)
+    }
+
+    /// Sets the share commitment of each blob, in the order of the entries.
+    pub fn with_commitments(mut self, commitments: Vec<[u8; 32]>) -> Self {
+        self.commitments = commitments;
+        self
+    }
+
+    /// Sets the batch the index is posted for.
+    pub fn with_metadata(mut self, metadata: BatchMetadata) -> Self {
+        self.metadata = Some(metadata);
+        self
+    }
+
+    /// Returns the share commitment of the blob at position `i` of the index, if any.
+    pub fn commitment(&self, i: usize) -> Option<&[u8; 32]> {
+        self.commitments.get(i)
+    }
+
+    /// Returns the data hash of the blob at position `i` of the index, if any.
+    pub fn data_hash(&self, i: usize) -> Option<&[u8; 32]> {
+        self.data_hashes.get(i)
+    }
+
+    /// Returns whether the entry at position `i` of the index is a sub-index.
+    pub fn is_sub_index(&self, i: usize) -> bool {
+        self.sub_indexes.contains(&(i as u32))
+    }
+
+    /// Returns the span sequences of the sub-indexes of the index.
+    pub fn sub_indexes(&self) -> impl Iterator<Item = SpanSequence> + '_ {
+        self.sub_indexes
+            .iter()
+            .filter_map(|position| self.blobs.get(*position as usize).copied())
+    }
+
+    /// Returns whether `sub_index` is listed in the index as a sub-index.
+    pub fn contains_sub_index(&self, sub_index: &SpanSequence) -> bool {
+        self.sub_indexes().any(|blob| blob == *sub_index)
+    }
+
+    /// Checks that the index is well-formed, see `BlobIndexBuilder`. The guest program does not
+    /// call this: it must handle whatever the rollup posted.
+    pub fn validate(&self) -> Result<(), IndexError> {
+        self.validate_with_max_entries(MAX_INDEX_ENTRIES)
+    }
+
+    /// Same as `validate()`, with at most `max_entries` entries.
+    pub fn validate_with_max_entries(&self, max_entries: usize) -> Result<(), IndexError> {
+        if self.blobs.len() > max_entries {
+            return Err(IndexError::TooManyEntries {
+                entries: self.blobs.len(),
+                max_entries,
+            });
+        }
+        self.check_entry_fields()?;
+
+        for (position, &blob) in self.blobs.iter().enumerate() {
+            if blob.size == 0 {
+                return Err(IndexError::EmptyEntry { position, blob });
+            }
+            if blob.start.checked_add(blob.size).is_none() {
+                return Err(IndexError::EntryOverflow { position, blob });
+            }
+            let Some(&previous) = self.blobs[..position].last() else {
+                continue;
+            };
+            if (blob.height, blob.start) < (previous.height, previous.start) {
+                return Err(IndexError::UnsortedEntry {
+                    position,
+                    blob,
+                    previous,
+                });
+            }
+            if blob.overlaps(&previous) {
+                return Err(IndexError::OverlappingEntries {
+                    position,
+                    blob,
+                    previous,
+                });
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Checks that the data hashes, namespaces and share commitments are set for all the entries
+    /// or none, and that the sub-indexes are entries of the index. Encoding relies on it.
+    fn check_entry_fields(&self) -> Result<(), IndexError> {
+        if !self.data_hashes.is_empty() && self.data_hashes.len() != self.blobs.len() {
+            return Err(IndexError::DataHashCountMismatch {
+                entries: self.blobs.len(),
+                data_hashes: self.data_hashes.len(),
+            });
+        }
+        if !self.namespaces.is_empty() && self.namespaces.len() != self.blobs.len() {
+            return Err(IndexError::NamespaceCountMismatch {
+                entries: self.blobs.len(),
+                namespaces: self.namespaces.len(),
+            });
+        }
+        if !self.commitments.is_empty() && self.commitments.len() != self.blobs.len() {
+            return Err(IndexError::CommitmentCountMismatch {
+                entries: self.blobs.len(),
+                commitments: self.commitments.len(),
+            });
+        }
+        if let Some(&position) = self
+            .sub_indexes
+            .iter()
+            .find(|position| **position as usize >= self.blobs.len())
+        {
+            return Err(IndexError::SubIndexOutOfRange(position));
+        }
+
+        Ok(())
+    }
+
+    /// Returns the entries of the index grouped by Celestia height, in the order of the index
+    /// within each height. Sub-indexes are included, not the blobs they list.
+    pub fn entries_by_height(&self) -> BTreeMap<u64, Vec<SpanSequence>> {
+        let mut entries_by_height: BTreeMap<u64, Vec<SpanSequence>> = BTreeMap::new();
+        for blob in &self.blobs {
+            entries_by_height
+                .entry(blob.height)
+                .or_default()
+                .push(*blob);
+        }
+        entries_by_height
+    }
+
+    /// Returns the number of shares of all the entries of the index, sub-indexes included.
+    /// Shares listed more than once are counted each time, see `overlapping_entries()`.
+    pub fn total_shares(&self) -> u64 {
+        self.blobs.iter().map(|blob| blob.size as u64).sum()
+    }
+
+    /// Returns the pairs of positions of the entries that share at least one share, in
+    /// increasing order. Duplicate entries are reported as overlapping. The index does not need
+    /// to be sorted.
+    pub fn overlapping_entries(&self) -> Vec<(usize, usize)> {
+        let mut positions: Vec<usize> = (0..self.blobs.len())
+            .filter(|i| self.blobs[*i].size > 0)
+            .collect();
+        positions.sort_by_key(|i| (self.blobs[*i].height, self.blobs[*i].start));
+
+        let mut overlaps = vec![];
+        // Entries of the current height that may overlap the next ones
+        let mut open: Vec<usize> = vec![];
+        for position in positions {
+            let blob = &self.blobs[position];
+            open.retain(|i| self.blobs[*i].overlaps(blob));
+            overlaps.extend(open.iter().map(|&i| (i.min(position), i.max(position))));
+            open.push(position);
+        }
+        overlaps.sort();
+        overlaps
+    }
+
+    /// Serializes the index as posted on Celestia. Indexes without data hashes keep the legacy
+    /// encoding. Indexes with namespaces, commitments or metadata have no bincode encoding and
+    /// use the canonical one.
+    ///
+    /// Fails if the data hashes, namespaces or commitments are not set for all the entries or
+    /// none, or if a sub-index is not an entry of the index.
+    #[cfg(feature = "bincode")]
+    pub fn encode(&self) -> Result<Vec<u8>, IndexError> {
+        if !self.namespaces.is_empty() || !self.commitments.is_empty() || self.metadata.is_some() {
+            return self.encode_canonical();
+        }
+        self.check_entry_fields()?;
+        if !self.sub_indexes.is_empty() {
+            return self.encode_v3();
+        }
+        if self.data_hashes.is_empty() {
+            return Ok(bincode::serialize(&self.blobs)?);
+        }
+
+        let index = BlobIndexV2 {
+            entries: self
+                .blobs
+                .iter()
+                .copied()
+                .zip(self.data_hashes.iter().copied())
+                .collect(),
+        };
+        let mut encoded = INDEX_V2_MARKER.to_vec();
+        bincode::serialize_into(&mut encoded, &index)?;
+
+        Ok(encoded)
+    }
+
+    #[cfg(feature = "bincode")]
+    fn encode_v3(&self) -> Result<Vec<u8>, IndexError> {
+        let index = BlobIndexV3 {
+            entries: self
+                .blobs
+                .iter()
+                .enumerate()
+                .map(|(i, blob)| BlobIndexEntryV3 {
+                    blob: *blob,
+                    data_hash: self.data_hash(i).copied(),
+                    sub_index: self.is_sub_index(i),
+                })
+                .collect(),
+        };
+        let mut encoded = INDEX_V3_MARKER.to_vec();
+        bincode::serialize_into(&mut encoded, &index)?;
+
+        Ok(encoded)
+    }
+
+    /// Serializes the index in the canonical encoding: a fixed layout without any Rust-specific
+    /// framing, so that sequencers in any language can post indexes. After
+    /// `INDEX_CANONICAL_MARKER`:
+    /// - one flags byte: bit 0 set if the entries carry data hashes, bit 1 if they carry
+    ///   namespaces, bit 2 if they carry share commitments, bit 3 if the index carries batch
+    ///   metadata;
+    /// - the batch metadata if flagged: the rollup ID (32 bytes), then the batch number and
+    ///   timestamp (`u64`) in little-endian;
+    /// - the entries, without length prefix: the height (`u64`), start and size (`u32`) of the
+    ///   blob in little-endian, one byte set to `1` for sub-indexes and `0` otherwise, then the
+    ///   32-byte data hash of the blob, its 29-byte namespace and its 32-byte share commitment
+    ///   if flagged.
+    ///
+    /// Each index has exactly one canonical encoding: other flag values and trailing bytes are
+    /// rejected by `decode()`. Fails like `encode()`.
+    pub fn encode_canonical(&self) -> Result<Vec<u8>, IndexError> {
+        self.check_entry_fields()?;
+        let mut flags = 0;
+        if !self.data_hashes.is_empty() {
+            flags |= CANONICAL_FLAG_DATA_HASHES;
+        }
+        if !self.namespaces.is_empty() {
+            flags |= CANONICAL_FLAG_NAMESPACES;
+        }
+        if !self.commitments.is_empty() {
+            flags |= CANONICAL_FLAG_COMMITMENTS;
+        }
+        if self.metadata.is_some() {
+            flags |= CANONICAL_FLAG_METADATA;
+        }
+        let entry_size = canonical_entry_size(flags);
+
+        let mut encoded = Vec::with_capacity(
+            INDEX_CANONICAL_MARKER.len()
+                + 1
+                + CANONICAL_METADATA_SIZE
+                + self.blobs.len() * entry_size,
+        );
+        encoded.extend(INDEX_CANONICAL_MARKER);
+        encoded.push(flags);
+        if let Some(metadata) = &self.metadata {
+            encoded.extend(metadata.rollup_id.as_slice());
+            encoded.extend(metadata.batch_number.to_le_bytes());
+            encoded.extend(metadata.timestamp.to_le_bytes());
+        }
+        for (i, blob) in self.blobs.iter().enumerate() {
+            encoded.extend(blob.height.to_le_bytes());
+            encoded.extend(blob.start.to_le_bytes());
+            encoded.extend(blob.size.to_le_bytes());
+            encoded.push(self.is_sub_index(i) as u8);
+            if let Some(data_hash) = self.data_hash(i) {
+                encoded.extend(data_hash);
+            }
+            if let Some(namespace) = self.namespace(i) {
+                encoded.extend(namespace);
+            }
+            if let Some(commitment) = self.commitment(i) {
+                encoded.extend(commitment);
+            }
+        }
+
+        Ok(encoded)
+    }
+
+    /// Returns the Keccak-256 hash of the canonical encoding of the index, see
+    /// `encode_canonical()`. It identifies the index whatever encoding it was posted in, and is
+    /// committed in the journal as `indexHash`, so that contracts can match a proof to an index
+    /// announced on-chain. Fails like `encode()`.
+    pub fn canonical_hash(&self) -> Result<B256, IndexError> {
+        Ok(keccak256(self.encode_canonical()?))
+    }
+
+    /// Deserializes an index posted on Celestia, in any encoding.
+    #[cfg(feature = "bincode")]
+    pub fn decode(data: &[u8]) -> Result<Self, bincode::Error> {
+        if data.starts_with(&INDEX_CANONICAL_MARKER) {
+            return Self::decode_canonical(data)
+                .map_err(|e| Box::new(bincode::ErrorKind::Custom(e.to_string())));
+        }
+        if let Some(data) = data.strip_prefix(&INDEX_V3_MARKER) {
+            return Self::decode_v3(data);
+        }
+        match data.strip_prefix(&INDEX_V2_MARKER) {
+            Some(data) => {
+                let index: BlobIndexV2 = bincode::deserialize(data)?;
+                Ok(Self::with_data_hashes(index.entries))
+            }
+            None => Ok(Self::new(bincode::deserialize(data)?)),
+        }
+    }
+
+    /// Deserializes an index in the canonical encoding, `INDEX_CANONICAL_MARKER` included, see
+    /// `encode_canonical()`. Unlike `decode()`, available without the `bincode` feature.
+    pub fn decode_canonical(data: &[u8]) -> Result<Self, IndexError> {
+        let invalid = IndexError::InvalidCanonicalEncoding;
+
+        let data = data
+            .strip_prefix(&INDEX_CANONICAL_MARKER)
+            .ok_or(invalid("missing canonical index marker"))?;
+        let (&flags, mut entries) = data
+            .split_first()
+            .ok_or(invalid("missing canonical index flags"))?;
+        let known_flags = CANONICAL_FLAG_DATA_HASHES
+            | CANONICAL_FLAG_NAMESPACES
+            | CANONICAL_FLAG_COMMITMENTS
+            | CANONICAL_FLAG_METADATA;
+        if flags & !known_flags != 0 {
+            return Err(invalid("invalid canonical index flags"));
+        }
+        let mut metadata = None;
+        if flags & CANONICAL_FLAG_METADATA != 0 {
+            if entries.len() < CANONICAL_METADATA_SIZE {
+                return Err(invalid("truncated canonical index metadata"));
+            }
+            let (raw_metadata, rest) = entries.split_at(CANONICAL_METADATA_SIZE);
+            metadata = Some(BatchMetadata {
+                rollup_id: B256::from_slice(&raw_metadata[..32]),
+                batch_number: u64::from_le_bytes(raw_metadata[32..40].try_into().expect("8 bytes")),
+                timestamp: u64::from_le_bytes(raw_metadata[40..].try_into().expect("8 bytes")),
+            });
+            entries = rest;
+        }
+        let entry_size = canonical_entry_size(flags);
+        if entries.len() % entry_size != 0 {
+            return Err(invalid("trailing bytes after the canonical index entries"));
+        }
+
+        let mut index = Self::new(vec![]);
+        index.metadata = metadata;
+        for (i, entry) in entries.chunks_exact(entry_size).enumerate() {
+            let (blob, rest) = entry.split_at(CANONICAL_ENTRY_SIZE - 1);
+            index.blobs.push(SpanSequence {
+                height: u64::from_le_bytes(blob[..8].try_into().expect("8 bytes")),
+                start: u32::from_le_bytes(blob[8..12].try_into().expect("4 bytes")),
+                size: u32::from_le_bytes(blob[12..].try_into().expect("4 bytes")),
+            });
+            match rest[0] {
+                0 => {}
+                1 => {
+                    index.sub_indexes.insert(i as u32);
+                }
+                _ => return Err(invalid("invalid canonical index sub-index flag")),
+            }
+            let mut rest = &rest[1..];
+            if flags & CANONICAL_FLAG_DATA_HASHES != 0 {
+                let (data_hash, tail) = rest.split_at(32);
+                index
+                    .data_hashes
+                    .push(data_hash.try_into().expect("32 bytes"));
+                rest = tail;
+            }
+            if flags & CANONICAL_FLAG_NAMESPACES != 0 {
+                let (namespace, tail) = rest.split_at(NAMESPACE_SIZE);
+                index
+                    .namespaces
+                    .push(namespace.try_into().expect("namespace bytes"));
+                rest = tail;
+            }
+            if flags & CANONICAL_FLAG_COMMITMENTS != 0 {
+                index.commitments.push(rest.try_into().expect("32 bytes"));
+            }
+        }
+
+        Ok(index)
+    }
+
+    #[cfg(feature = "bincode")]
+    fn decode_v3(data: &[u8]) -> Result<Self, bincode::Error> {
+        let index: BlobIndexV3 = bincode::deserialize(data)?;
+
+        let data_hashes: Vec<_> = index
+            .entries
+            .iter()
+            .filter_map(|entry| entry.data_hash)
+            .collect();
+        if !data_hashes.is_empty() && data_hashes.len() != index.entries.len() {
+            return Err(Box::new(bincode::ErrorKind::Custom(
+                "data hashes must be set for all the entries or none".into(),
+            )));
+        }
+
+        Ok(Self {
+            blobs: index.entries.iter().map(|entry| entry.blob).collect(),
+            data_hashes,
+            namespaces: vec![],
+            commitments: vec![],
+            metadata: None,
+            sub_indexes: index
+                .entries
+                .iter()
+                .enumerate()
+                .filter(|(_, entry)| entry.sub_index)
+                .map(|(i, _)| i as u32)
+                .collect(),
+        })
+    }
+
+    /// Deserializes an index from the raw shares of its blob, reading its data in place. The
+    /// shares must form a single sequence, see `challenge::verify_share_layout()`.
+    #[cfg(feature = "std")]
+    pub fn decode_from_shares<'a, I>(raw_shares: I) -> Result<Self, bincode::Error>
+    where
+        I: IntoIterator<Item = &'a [u8; SHARE_SIZE]>,
+    {
+        let mut data = vec![];
+        for chunk in sequence_data(raw_shares) {
+            data.extend_from_slice(chunk);
+        }
+
+        Self::decode(&data)
+    }
+
+    /// Checks a blob retrieved from Celestia against the entry at position `i` of the index:
+    /// its data against the data hash and the blob against the share commitment, for the
+    /// commitments the index carries. Auditors can verify the blobs of a batch from the index
+    /// alone this way.
+    #[cfg(feature = "host")]
+    pub fn check_blob(&self, i: usize, blob: &Blob) -> Result<(), DaFraud> {
+        let span_sequence = self.blobs[i];
+        if let Some(expected) = self.data_hash(i) {
+            let actual = blob_data_hash(&blob.data);
+            if actual != *expected {
+                return Err(DaFraud::BlobDataMismatch {
+                    blob: span_sequence,
+                    expected: B256::from(*expected),
+                    actual: B256::from(actual),
+                });
+            }
+        }
+        if let Some(expected) = self.commitment(i) {
+            if blob.commitment.hash() != expected {
+                return Err(DaFraud::BlobCommitmentMismatch {
+                    blob: span_sequence,
+                    expected: B256::from(*expected),
+                    actual: B256::from(*blob.commitment.hash()),
+                });
+            }
+        }
+
+        Ok(())
     }
 
+    #[cfg(feature = "std")]
     pub fn reconstruct<'a, I>(shares: I, app_version: AppVersion) -> Result<Self, DaFraud>
     where
         I: IntoIterator<Item = &'a Share>,
     {
         let index_blob = Blob::reconstruct(shares, app_version)?;
-        let blob_index: BlobIndex = bincode::deserialize(&index_blob.data)?;
+        let blob_index = BlobIndex::decode(&index_blob.data)?;
 
         Ok(blob_index)
     }
+    #[cfg(feature = "std")]
     pub fn reconstruct_from_raw<'a, I>(
         raw_shares: I,
         app_version: AppVersion,
@@ -88,77 +1002,612 @@ impl BlobIndex {
     where
         I: IntoIterator<Item = &'a [u8; SHARE_SIZE]>,
     {
-        // TODO: implement a reconstruct_from_raw method for Blob in lumina, this is a temporary
-        //       workaround.
-        let shares: Vec<_> = raw_shares
-            .into_iter()
-            .map(|raw_share| Share::from_raw(raw_share).expect("invalid share size"))
-            .collect();
-
-        let index_blob = Blob::reconstruct(&shares, app_version)?;
-        let blob_index: BlobIndex = bincode::deserialize(&index_blob.data)?;
+        let index_blob = reconstruct_blob_from_raw(raw_shares, app_version)?;
+        let blob_index = BlobIndex::decode(&index_blob.data)?;
 
         Ok(blob_index)
     }
 }
 
+/// Reconstructs a blob from its raw shares.
+#[cfg(feature = "std")]
+pub fn reconstruct_blob_from_raw<'a, I>(
+    raw_shares: I,
+    app_version: AppVersion,
+) -> Result<Blob, celestia_types::Error>
+where
+    I: IntoIterator<Item = &'a [u8; SHARE_SIZE]>,
+{
+    // TODO: implement a reconstruct_from_raw method for Blob in lumina, this is a temporary
+    //       workaround.
+    let shares = raw_shares
+        .into_iter()
+        .map(|raw_share| Share::from_raw(raw_share))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Blob::reconstruct(&shares, app_version)
+}
+
+/// Returns the hash of the data of a blob, as committed in indexes with data hashes.
+pub fn blob_data_hash(data: &[u8]) -> [u8; 32] {
+    Sha256::digest(data).into()
+}
+
+/// Returns the hash of the data of a blob from its raw shares, without reconstructing the blob.
+/// The shares must form a single sequence, see `challenge::verify_share_layout()`.
+#[cfg(feature = "std")]
+pub fn blob_data_hash_from_shares<'a, I>(raw_shares: I) -> [u8; 32]
+where
+    I: IntoIterator<Item = &'a [u8; SHARE_SIZE]>,
+{
+    let mut hasher = Sha256::new();
+    for chunk in sequence_data(raw_shares) {
+        hasher.update(chunk);
+    }
+
+    hasher.finalize().into()
+}
+
+/// Size of the signer of a blob, written after the sequence length in the first share of share
+/// version 1 blobs.
+#[cfg(feature = "std")]
+pub(crate) const SIGNER_SIZE: usize = 20;
+
+/// Iterator over the data of a sequence of raw shares, share by share, see `sequence_data()`.
+#[cfg(feature = "std")]
+pub struct SequenceData<I> {
+    raw_shares: I,
+    /// Bytes of the sequence left to read, `None` before the first share.
+    remaining: Option<usize>,
+}
+
+/// Returns the data held by a sequence of raw shares, as slices of the shares. Padding after the
+/// declared sequence length is skipped. Unlike `Blob::reconstruct()`, the shares are neither
+/// validated nor copied: the layout of the sequence must be checked beforehand.
+#[cfg(feature = "std")]
+pub fn sequence_data<'a, I>(raw_shares: I) -> SequenceData<I::IntoIter>
+where
+    I: IntoIterator<Item = &'a [u8; SHARE_SIZE]>,
+{
+    SequenceData {
+        raw_shares: raw_shares.into_iter(),
+        remaining: None,
+    }
+}
+
+#[cfg(feature = "std")]
+impl<'a, I> Iterator for SequenceData<I>
+where
+    I: Iterator<Item = &'a [u8; SHARE_SIZE]>,
+{
+    type Item = &'a [u8];
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining == Some(0) {
+            return None;
+        }
+        let raw_share = self.raw_shares.next()?;
+
+        let content = match self.remaining {
+            None => {
+                let length_offset = NS_SIZE + SHARE_INFO_BYTES;
+                let length_bytes = &raw_share[length_offset..length_offset + SEQUENCE_LEN_BYTES];
+                let sequence_length = u32::from_be_bytes(
+                    length_bytes.try_into().expect("sequence length is 4 bytes"),
+                );
+                self.remaining = Some(sequence_length as usize);
+
+                let share_version = raw_share[NS_SIZE] >> 1;
+                let signer_size = if share_version == 1 { SIGNER_SIZE } else { 0 };
+                &raw_share[length_offset + SEQUENCE_LEN_BYTES + signer_size..]
+            }
+            Some(_) => &raw_share[NS_SIZE + SHARE_INFO_BYTES..],
+        };
+
+        let remaining = self.remaining.as_mut().expect("set by the first share");
+        let chunk = &content[..content.len().min(*remaining)];
+        *remaining -= chunk.len();
+
+        Some(chunk)
+    }
+}
+
+#[cfg(feature = "std")]
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct BlobstreamAttestation {
     pub data_root: [u8; 32],
     pub height: u64,
-    pub nonce: u64,
+    pub nonce: U256,
+    /// Data commitment of `nonce`, from its `DataCommitmentStored` event. The guest does not
+    /// trust it: the Blobstream contract verifies the attestation against its own commitment.
+    pub data_commitment: B256,
     pub proof: MerkleProof,
+    /// First Celestia block covered by the data commitment of `nonce`.
+    pub start_block: u64,
+    /// End of the Celestia block range covered by the data commitment, exclusive.
+    pub end_block: u64,
 }
 
+#[cfg(feature = "std")]
+impl BlobstreamAttestation {
+    /// Checks that the block range of the data commitment covers the attested height, and
+    /// matches the position of the data root tuple in the inclusion proof.
+    pub fn verify_block_range(&self) -> Result<(), InputError> {
+        if !(self.start_block..self.end_block).contains(&self.height) {
+            return Err(InputError::AttestationHeightOutOfRange {
+                height: self.height,
+                start_block: self.start_block,
+                end_block: self.end_block,
+            });
+        }
+
+        if self.proof.index as u64 != self.height - self.start_block
+            || self.proof.total as u64 != self.end_block - self.start_block
+        {
+            return Err(InputError::AttestationProofRangeMismatch {
+                height: self.height,
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Checks that the inclusion proof leads from the data root tuple of the block to
+    /// `data_commitment`.
+    pub fn verify_data_commitment(&self) -> Result<(), InputError> {
+        let data_root_tuple = DataRootTuple {
+            height: U256::from(self.height),
+            dataRoot: B256::from(self.data_root),
+        };
+
+        self.proof
+            .verify(data_root_tuple.abi_encode(), self.data_commitment.0)
+            .map_err(|_| InputError::AttestationDataCommitmentMismatch {
+                height: self.height,
+                nonce: self.nonce,
+            })
+    }
+
+    /// Checks the attestation against the `DataCommitmentStored` event of its nonce: same nonce,
+    /// block range and data commitment, and an inclusion proof leading to the data commitment.
+    /// Lets the host catch a Celestia node and a Blobstream contract that disagree without
+    /// querying Ethereum again.
+    pub fn verify_against_event(
+        &self,
+        proof_nonce: U256,
+        start_block: u64,
+        end_block: u64,
+        data_commitment: B256,
+    ) -> Result<(), InputError> {
+        let mismatch = |field| InputError::AttestationEventMismatch {
+            height: self.height,
+            field,
+        };
+        if self.nonce != proof_nonce {
+            return Err(mismatch("nonce"));
+        }
+        if (self.start_block, self.end_block) != (start_block, end_block) {
+            return Err(mismatch("block range"));
+        }
+        if self.data_commitment != data_commitment {
+            return Err(mismatch("data commitment"));
+        }
+
+        self.verify_block_range()?;
+        self.verify_data_commitment()
+    }
+
+    /// See `DaChallengeGuestData::to_debug_json()`.
+    #[cfg(feature = "host")]
+    pub fn to_debug_json(&self) -> serde_json::Value {
+        serde_json::json!({
+            "height": self.height,
+            "data_root": B256::from(self.data_root),
+            "nonce": self.nonce,
+            "data_commitment": self.data_commitment,
+            "start_block": self.start_block,
+            "end_block": self.end_block,
+            "proof": merkle_proof_debug_json(&self.proof),
+        })
+    }
+}
+
+#[cfg(feature = "std")]
 #[derive(Debug, Serialize, Deserialize)]
 pub struct BlobstreamAttestationAndRowProof {
     pub blobstream_attestation: BlobstreamAttestation,
     pub row_proof: MerkleProof,
     pub row_root_node: NamespacedHash,
+    /// Proof of the last root of the data square, the root of its last column, in the data root.
+    pub last_root_proof: MerkleProof,
+    pub last_root_node: NamespacedHash,
+    /// Celestia app version of the block, read from its header. The blobs of the block are
+    /// reconstructed with the rules of this version.
+    pub app_version: u64,
+}
+
+#[cfg(feature = "std")]
+impl BlobstreamAttestationAndRowProof {
+    /// Returns the width of the ODS of the block, see `square_width()`.
+    pub fn ods_width(&self) -> Result<u32, DaGuestError> {
+        Ok(self.square_width()?.ods)
+    }
+
+    /// Returns the widths of the data square of the block.
+    ///
+    /// The proof of row 0 alone does not bind the number of roots of the data square: the path
+    /// of the first leaf is the same for all tree sizes up to the next power of two. The path of
+    /// the last leaf depends on the exact size of the tree, so both proofs must agree on it.
+    /// The proofs themselves must be verified against the data root beforehand.
+    pub fn square_width(&self) -> Result<SquareWidth, DaGuestError> {
+        if self.row_proof.index != 0
+            || self.last_root_proof.total != self.row_proof.total
+            || self.last_root_proof.index + 1 != self.last_root_proof.total
+        {
+            return Err(InputError::InconsistentRowProofs {
+                height: self.blobstream_attestation.height,
+            }
+            .into());
+        }
+
+        compute_square_width_from_row_proof(&self.row_proof)
+    }
+
+    /// See `DaChallengeGuestData::to_debug_json()`.
+    #[cfg(feature = "host")]
+    pub fn to_debug_json(&self) -> serde_json::Value {
+        serde_json::json!({
+            "height": self.blobstream_attestation.height,
+            "app_version": self.app_version,
+            "ods_width": self.ods_width().ok(),
+            "blobstream_attestation": self.blobstream_attestation.to_debug_json(),
+            "row_proof": merkle_proof_debug_json(&self.row_proof),
+            "row_root": B256::from(self.row_root_node.hash()),
+            "last_root_proof": merkle_proof_debug_json(&self.last_root_proof),
+            "last_root": B256::from(self.last_root_node.hash()),
+        })
+    }
 }
 
+#[cfg(feature = "std")]
 #[derive(Debug, Serialize, Deserialize)]
 pub struct BlobProofData {
+    /// Share proofs of the blob, keyed by the ODS index of their first share. Each proof covers
+    /// consecutive shares of one or more rows, see `challenge::verify_share_proofs()`.
     pub share_proofs: BTreeMap<u32, ShareProof>,
+    /// Celestia app version of the block of the shares, must match the one of its block proof.
     pub app_version: u64,
 }
 
-/// Returns the start index of the share proof in the ODS.
-pub fn share_proof_start_index_ods(share_proof: &ShareProof) -> u32 {
-    // Row proofs cover rows + columns of the EDS, so we need to divide by 2 to isolate rows,
-    // then by 2 again to ignore parity shares.
-    let row_size = share_proof.row_proof.proofs()[0].total as u32 / 4;
-    let row_index = share_proof.row_proof.proofs()[0].index as u32;
-    let col_index = share_proof.share_proofs[0].start_idx();
+/// Returns the range of ODS indexes of the shares of a share proof, in a square of width
+/// `ods_width`. The proof has a row proof and a namespace proof per row, over consecutive rows:
+/// all the rows but the first start at column 0 and all the rows but the last end at the end of
+/// the ODS row. Parity shares are not part of the ODS, their indexes would alias shares of the
+/// next rows.
+///
+/// Only the layout of the proof is checked, the proof itself must be verified separately.
+#[cfg(feature = "std")]
+pub fn share_proof_ods_range(
+    share_proof: &ShareProof,
+    ods_width: u32,
+) -> Result<Range<u32>, ShareProofRangeError> {
+    let row_proofs = share_proof.row_proof.proofs();
+    let namespace_proofs = &share_proof.share_proofs;
+    if row_proofs.is_empty() || row_proofs.len() != namespace_proofs.len() {
+        return Err(ShareProofRangeError::RowCountMismatch {
+            row_proofs: row_proofs.len(),
+            namespace_proofs: namespace_proofs.len(),
+        });
+    }
+
+    let last_row = row_proofs.len() - 1;
+    let mut previous_row_index = None;
+    for (row, (row_proof, namespace_proof)) in row_proofs.iter().zip(namespace_proofs).enumerate() {
+        let (start, end) = (namespace_proof.start_idx(), namespace_proof.end_idx());
+        if row_proof.index >= ods_width as usize || start >= end || end > ods_width {
+            return Err(ShareProofRangeError::OutsideOds {
+                row_index: row_proof.index,
+                start,
+                end,
+            });
+        }
+
+        let row_index = row_proof.index as u32;
+        if let Some(previous) = previous_row_index.filter(|&previous| row_index != previous + 1) {
+            return Err(ShareProofRangeError::NonConsecutiveRows {
+                row_index,
+                previous,
+            });
+        }
+        if (row > 0 && start != 0) || (row < last_row && end != ods_width) {
+            return Err(ShareProofRangeError::PartialRow {
+                row_index,
+                start,
+                end,
+            });
+        }
+        previous_row_index = Some(row_index);
+    }
 
-    row_index * row_size + col_index
+    let start = row_proofs[0].index as u32 * ods_width + namespace_proofs[0].start_idx();
+    let end = row_proofs[last_row].index as u32 * ods_width + namespace_proofs[last_row].end_idx();
+    let n_shares = share_proof.shares().len();
+    if n_shares != (end - start) as usize {
+        return Err(ShareProofRangeError::ShareCountMismatch {
+            expected: (end - start) as usize,
+            actual: n_shares,
+        });
+    }
+
+    Ok(start..end)
 }
 
+#[cfg(feature = "std")]
 impl BlobProofData {
+    /// Checks that the share proofs are keyed by the ODS index of their first share, cover
+    /// ranges of the ODS without overlapping and agree on the size of the data square, so that
+    /// malformed proofs fail on the host instead of in the guest. The proofs are not verified
+    /// against a data root, see `challenge::verify_share_proofs()`.
+    pub fn new(
+        share_proofs: BTreeMap<u32, ShareProof>,
+        app_version: u64,
+    ) -> Result<Self, BlobProofDataError> {
+        let (&first_index, first_proof) = share_proofs
+            .iter()
+            .next()
+            .ok_or(BlobProofDataError::NoShareProofs)?;
+        let root_count = first_proof
+            .row_proof
+            .proofs()
+            .first()
+            .map_or(0, |row_proof| row_proof.total);
+        let square_width = errors::square_width_from_root_count(root_count).map_err(|_| {
+            BlobProofDataError::InvalidSquareSize {
+                share_index: first_index,
+                total: root_count,
+            }
+        })?;
+
+        let mut previous_end = None;
+        for (&share_index, share_proof) in &share_proofs {
+            if let Some(row_proof) = share_proof
+                .row_proof
+                .proofs()
+                .iter()
+                .find(|row_proof| row_proof.total != root_count)
+            {
+                return Err(BlobProofDataError::SquareSizeMismatch {
+                    share_index,
+                    total: row_proof.total,
+                    expected: root_count,
+                });
+            }
+
+            let proven = share_proof_ods_range(share_proof, square_width.ods)
+                .map_err(|error| BlobProofDataError::InvalidRange { share_index, error })?;
+            if proven.start != share_index {
+                return Err(BlobProofDataError::KeyMismatch {
+                    share_index,
+                    proven,
+                });
+            }
+            if let Some(previous_end) = previous_end.filter(|&end| share_index < end) {
+                return Err(BlobProofDataError::OverlappingProofs {
+                    share_index,
+                    previous_end,
+                });
+            }
+            previous_end = Some(proven.end);
+        }
+
+        Ok(Self {
+            share_proofs,
+            app_version,
+        })
+    }
+
     pub fn shares(&self) -> impl Iterator<Item = &[u8; SHARE_SIZE]> {
         self.share_proofs
             .values()
             .flat_map(|share_proof| share_proof.shares())
     }
+
+    /// See `DaChallengeGuestData::to_debug_json()`. Each share proof is described by the ODS
+    /// index of its first share, its rows and the columns it covers in each row.
+    #[cfg(feature = "host")]
+    pub fn to_debug_json(&self) -> serde_json::Value {
+        serde_json::json!({
+            "app_version": self.app_version,
+            "share_count": self.shares().count(),
+            "share_proofs": self
+                .share_proofs
+                .iter()
+                .map(|(start_index_ods, share_proof)| {
+                    let row_proofs = share_proof.row_proof.proofs();
+                    serde_json::json!({
+                        "start_index_ods": start_index_ods,
+                        "share_count": share_proof.shares().len(),
+                        "rows": row_proofs
+                            .iter()
+                            .map(|row_proof| row_proof.index)
+                            .collect::<Vec<_>>(),
+                        "row_proof_total": row_proofs.first().map(|row_proof| row_proof.total),
+                        "columns": share_proof
+                            .share_proofs
+                            .iter()
+                            .map(|proof| [proof.start_idx(), proof.end_idx()])
+                            .collect::<Vec<_>>(),
+                    })
+                })
+                .collect::<Vec<_>>(),
+        })
+    }
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum BlobstreamImpl {
     Sp1,
     R0,
+    /// Any contract implementing `IDAOracle`, ex: a rollup oracle wrapping Blobstream. Such
+    /// contracts do not expose their latest height, so block heights cannot be proven too high.
+    DaOracle,
+}
+
+impl BlobstreamImpl {
+    /// Returns the identifier of the implementation committed in the journal.
+    pub fn as_u8(&self) -> u8 {
+        match self {
+            BlobstreamImpl::Sp1 => 0,
+            BlobstreamImpl::R0 => 1,
+            BlobstreamImpl::DaOracle => 2,
+        }
+    }
+
+    /// Parses an implementation identifier committed in the journal.
+    pub fn from_u8(value: u8) -> Option<Self> {
+        match value {
+            0 => Some(BlobstreamImpl::Sp1),
+            1 => Some(BlobstreamImpl::R0),
+            2 => Some(BlobstreamImpl::DaOracle),
+            _ => None,
+        }
+    }
 }
 
+impl FromStr for BlobstreamImpl {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "sp1" => Ok(BlobstreamImpl::Sp1),
+            "r0" | "blobstream0" => Ok(BlobstreamImpl::R0),
+            "da-oracle" => Ok(BlobstreamImpl::DaOracle),
+            _ => Err("Expected one of: sp1, r0, da-oracle".into()),
+        }
+    }
+}
+
+/// A sub-index on the path from the index blob to the challenged blob, with its share proofs.
+#[cfg(feature = "std")]
 #[derive(Debug, Serialize, Deserialize)]
+pub struct SubIndexProofData {
+    pub sub_index: SpanSequence,
+    pub proof_data: BlobProofData,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct BlobstreamInfo {
     pub address: Address,
     pub implementation: BlobstreamImpl,
 }
 
+/// What a DA challenge claims.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum DaChallenge {
+    /// The index blob is unavailable.
+    IndexIsUnavailable,
+    /// The blob of the index is unavailable. Also covers unreadable indexes, since any blob can
+    /// be challenged against them.
+    BlobInIndexIsUnavailable(SpanSequence),
+    /// The data of the blob of the index does not match the data hash of its index entry.
+    BlobDataMismatch(SpanSequence),
+}
+
+impl DaChallenge {
+    /// Returns the challenge of the availability of `challenged_blob`: the index itself if it is
+    /// the index blob, a blob of the index otherwise.
+    pub fn availability(index_blob: SpanSequence, challenged_blob: SpanSequence) -> Self {
+        if challenged_blob == index_blob {
+            DaChallenge::IndexIsUnavailable
+        } else {
+            DaChallenge::BlobInIndexIsUnavailable(challenged_blob)
+        }
+    }
+
+    /// Returns the blob under challenge, given the index blob.
+    pub fn challenged_blob(&self, index_blob: SpanSequence) -> SpanSequence {
+        match self {
+            DaChallenge::IndexIsUnavailable => index_blob,
+            DaChallenge::BlobInIndexIsUnavailable(blob) | DaChallenge::BlobDataMismatch(blob) => {
+                *blob
+            }
+        }
+    }
+
+    /// Returns the identifier of the kind of challenge committed in the journal.
+    pub fn as_u8(&self) -> u8 {
+        match self {
+            DaChallenge::IndexIsUnavailable => 0,
+            DaChallenge::BlobInIndexIsUnavailable(_) => 1,
+            DaChallenge::BlobDataMismatch(_) => 2,
+        }
+    }
+
+    /// Parses the kind of challenge committed in the journal, along with its challenged blob.
+    pub fn from_u8(value: u8, challenged_blob: SpanSequence) -> Option<Self> {
+        match value {
+            0 => Some(DaChallenge::IndexIsUnavailable),
+            1 => Some(DaChallenge::BlobInIndexIsUnavailable(challenged_blob)),
+            2 => Some(DaChallenge::BlobDataMismatch(challenged_blob)),
+            _ => None,
+        }
+    }
+}
+
+impl FromStr for DaChallenge {
+    type Err = String;
+
+    /// Parses `index-unavailable`, `blob-unavailable:<span sequence>` or
+    /// `blob-data-mismatch:<span sequence>`, ex: `blob-unavailable:6671289:6:4`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        const EXPECTED: &str = "Expected one of: index-unavailable, \
+                                blob-unavailable:height:start:size, \
+                                blob-data-mismatch:height:start:size";
+
+        match s.split_once(':') {
+            None if s == "index-unavailable" => Ok(DaChallenge::IndexIsUnavailable),
+            Some(("blob-unavailable", blob)) => {
+                Ok(DaChallenge::BlobInIndexIsUnavailable(blob.parse()?))
+            }
+            Some(("blob-data-mismatch", blob)) => Ok(DaChallenge::BlobDataMismatch(blob.parse()?)),
+            _ => Err(EXPECTED.into()),
+        }
+    }
+}
+
+impl Display for DaChallenge {
+    /// Formats the challenge as accepted by `from_str()`.
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        match self {
+            DaChallenge::IndexIsUnavailable => write!(f, "index-unavailable"),
+            DaChallenge::BlobInIndexIsUnavailable(blob) => write!(f, "blob-unavailable:{blob}"),
+            DaChallenge::BlobDataMismatch(blob) => write!(f, "blob-data-mismatch:{blob}"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
 #[derive(Debug, Serialize, Deserialize)]
 pub struct DaChallengeGuestData {
+    /// Chain ID of the Celestia network the heights of the span sequences refer to, ex:
+    /// `celestia` or `mocha-4`. Checked against the Blobstream contract and committed in the
+    /// journal, see `constants::check_celestia_chain_id()`.
+    pub celestia_chain_id: String,
     pub index_blob: SpanSequence,
     pub challenged_blob: SpanSequence,
+    /// Other blobs of the same index challenged in the same execution, so that the Blobstream
+    /// attestations and the index are only verified once. Their availability is challenged, all
+    /// of them must be DA fraud along with the challenged blob.
+    pub extra_challenged_blobs: Vec<SpanSequence>,
     pub index_blob_proof_data: Option<BlobProofData>,
+    /// Share proofs of the challenged blob, for `DaChallenge::BlobDataMismatch` challenges.
+    pub challenged_blob_proof_data: Option<BlobProofData>,
+    /// Namespace the rollup posts its blobs to. If set, shares of the index blob or of the
+    /// challenged blob in another namespace are DA fraud.
+    pub namespace: Option<Namespace>,
+    /// Sub-indexes leading from the index blob to the index listing the challenged blob,
+    /// outermost first. Empty if the challenged blob is listed in the index blob itself.
+    pub sub_indexes: Vec<SubIndexProofData>,
     pub block_proofs: BTreeMap<u64, BlobstreamAttestationAndRowProof>,
     /// The attestation for the first Celestia block range covered by the Blobstream
     /// contract. This field is used to determine the lower bound of Celestia block heights
@@ -166,7 +1615,121 @@ pub struct DaChallengeGuestData {
     pub first_blobstream_attestation: BlobstreamAttestation,
 }
 
+#[cfg(feature = "std")]
 impl DaChallengeGuestData {
+    /// Creates guest data from externally supplied attestations and proofs, without any block
+    /// proof or index blob data. Use this to build challenges from another source than
+    /// a Celestia node, ex: archival snapshots or air-gapped machines.
+    pub fn new(
+        celestia_chain_id: String,
+        index_blob: SpanSequence,
+        challenged_blob: SpanSequence,
+        first_blobstream_attestation: BlobstreamAttestation,
+    ) -> Self {
+        Self {
+            celestia_chain_id,
+            index_blob,
+            challenged_blob,
+            extra_challenged_blobs: vec![],
+            index_blob_proof_data: None,
+            challenged_blob_proof_data: None,
+            namespace: None,
+            sub_indexes: vec![],
+            block_proofs: BTreeMap::new(),
+            first_blobstream_attestation,
+        }
+    }
+
+    /// Sets the share proofs of the index blob.
+    pub fn with_index_blob_proof_data(mut self, index_blob_proof_data: BlobProofData) -> Self {
+        self.index_blob_proof_data = Some(index_blob_proof_data);
+        self
+    }
+
+    /// Sets the share proofs of the challenged blob, turning the challenge into a
+    /// `DaChallenge::BlobDataMismatch` challenge.
+    pub fn with_challenged_blob_proof_data(
+        mut self,
+        challenged_blob_proof_data: BlobProofData,
+    ) -> Self {
+        self.challenged_blob_proof_data = Some(challenged_blob_proof_data);
+        self
+    }
+
+    /// Sets the other blobs of the index to challenge in the same execution, see
+    /// `extra_challenged_blobs`.
+    pub fn with_extra_challenged_blobs(
+        mut self,
+        extra_challenged_blobs: Vec<SpanSequence>,
+    ) -> Self {
+        self.extra_challenged_blobs = extra_challenged_blobs;
+        self
+    }
+
+    /// Returns all the challenged blobs, the challenged blob first.
+    pub fn challenged_blobs(&self) -> impl Iterator<Item = SpanSequence> + '_ {
+        [self.challenged_blob]
+            .into_iter()
+            .chain(self.extra_challenged_blobs.iter().copied())
+    }
+
+    /// Sets the namespace the index blob must be posted to.
+    pub fn with_namespace(mut self, namespace: Namespace) -> Self {
+        self.namespace = Some(namespace);
+        self
+    }
+
+    /// Appends a sub-index to the path leading to the challenged blob.
+    pub fn with_sub_index(mut self, sub_index: SpanSequence, proof_data: BlobProofData) -> Self {
+        self.sub_indexes.push(SubIndexProofData {
+            sub_index,
+            proof_data,
+        });
+        self
+    }
+
+    /// Returns the kind of challenge the guest data is for.
+    pub fn challenge(&self) -> DaChallenge {
+        if self.challenged_blob == self.index_blob {
+            DaChallenge::IndexIsUnavailable
+        } else if self.challenged_blob_proof_data.is_some() {
+            DaChallenge::BlobDataMismatch(self.challenged_blob)
+        } else {
+            DaChallenge::BlobInIndexIsUnavailable(self.challenged_blob)
+        }
+    }
+
+    /// Adds the Blobstream attestation and row proof of a Celestia block. The block height is
+    /// taken from the attestation.
+    pub fn with_block_proof(mut self, block_proof: BlobstreamAttestationAndRowProof) -> Self {
+        self.block_proofs
+            .insert(block_proof.blobstream_attestation.height, block_proof);
+        self
+    }
+
+    /// Encodes the guest data as read by the guest program. Postcard is used over bincode as
+    /// it is more compact and cheaper to decode in the guest, mostly for share proofs.
+    pub fn encode(&self) -> Result<Vec<u8>, postcard::Error> {
+        postcard::to_allocvec(self)
+    }
+
+    /// Decodes guest data encoded with `encode()`.
+    pub fn decode(bytes: &[u8]) -> Result<Self, postcard::Error> {
+        postcard::from_bytes(bytes)
+    }
+
+    /// Returns the heights of the Celestia blocks on the path of the challenge: the blocks of the
+    /// index blob, of the sub-indexes and of the challenged blobs. Block proofs of other blocks
+    /// are rejected, see `challenge::check_block_proofs()`.
+    pub fn challenge_block_heights(&self) -> BTreeSet<u64> {
+        [self.index_blob]
+            .into_iter()
+            .chain(self.sub_indexes.iter().map(|sub_index| sub_index.sub_index))
+            .chain(self.challenged_blobs())
+            .map(|span_sequence| span_sequence.height)
+            .collect()
+    }
+
     pub fn blobstream_attestations(&self) -> impl Iterator<Item = &BlobstreamAttestation> {
         [&self.first_blobstream_attestation].into_iter().chain(
             self.block_proofs
@@ -174,16 +1737,504 @@ impl DaChallengeGuestData {
                 .map(|block_proof| &block_proof.blobstream_attestation),
         )
     }
+
+    /// Returns a human-readable representation of the guest data, for debugging rejected
+    /// challenges: the challenge, the Blobstream attestations, the row proofs of each block and
+    /// the position of the share proofs. Proofs are summarized by their position and length,
+    /// hashes are hex-encoded. Not meant to be parsed back.
+    #[cfg(feature = "host")]
+    pub fn to_debug_json(&self) -> serde_json::Value {
+        serde_json::json!({
+            "celestia_chain_id": self.celestia_chain_id,
+            "challenge": self.challenge(),
+            "index_blob": self.index_blob.to_string(),
+            "challenged_blob": self.challenged_blob.to_string(),
+            "extra_challenged_blobs": self
+                .extra_challenged_blobs
+                .iter()
+                .map(SpanSequence::to_string)
+                .collect::<Vec<_>>(),
+            "namespace": self
+                .namespace
+                .map(|namespace| alloy_primitives::hex::encode_prefixed(namespace.as_bytes())),
+            "first_blobstream_attestation": self.first_blobstream_attestation.to_debug_json(),
+            "block_proofs": self
+                .block_proofs
+                .values()
+                .map(BlobstreamAttestationAndRowProof::to_debug_json)
+                .collect::<Vec<_>>(),
+            "index_blob_proof_data": self
+                .index_blob_proof_data
+                .as_ref()
+                .map(BlobProofData::to_debug_json),
+            "sub_indexes": self
+                .sub_indexes
+                .iter()
+                .map(|sub_index| serde_json::json!({
+                    "sub_index": sub_index.sub_index.to_string(),
+                    "proof_data": sub_index.proof_data.to_debug_json(),
+                }))
+                .collect::<Vec<_>>(),
+            "challenged_blob_proof_data": self
+                .challenged_blob_proof_data
+                .as_ref()
+                .map(BlobProofData::to_debug_json),
+        })
+    }
+}
+
+/// Summarizes a Merkle proof by its leaf position and path length, see
+/// `DaChallengeGuestData::to_debug_json()`.
+#[cfg(feature = "host")]
+fn merkle_proof_debug_json(proof: &MerkleProof) -> serde_json::Value {
+    serde_json::json!({
+        "index": proof.index,
+        "total": proof.total,
+        "path_length": proof.aunts.len(),
+    })
+}
+
+/// Widths of the original (ODS) and extended (EDS) data squares of a block, in shares.
+///
+/// Share indexes are row-major in both squares. The ODS is the top-left quarter of the EDS, the
+/// other quarters hold parity shares.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SquareWidth {
+    pub ods: u32,
+    pub eds: u32,
 }
 
-/// Converts an EDS index to an ODS index. Only works for data shares, parity share indexes
-/// will not be converted properly.
-pub fn eds_index_to_ods(eds_index: u32, eds_width: u32) -> u32 {
-    let ods_width = eds_width / 2;
+impl SquareWidth {
+    pub fn from_ods(ods_width: u32) -> Self {
+        Self {
+            ods: ods_width,
+            eds: 2 * ods_width,
+        }
+    }
+
+    /// Returns the widths of a square from the width of its EDS, such as
+    /// `DataAvailabilityHeader::square_width()`.
+    pub fn from_eds(eds_width: u32) -> Self {
+        Self::from_ods(eds_width / 2)
+    }
+
+    /// Converts the index of a share in the ODS to its index in the EDS.
+    pub fn ods_to_eds(&self, ods_index: u32) -> Result<u32, SquareIndexError> {
+        let out_of_square = SquareIndexError::OutOfSquare {
+            index: ods_index,
+            width: self.ods,
+        };
+        if ods_index as u64 >= self.ods as u64 * self.ods as u64 {
+            return Err(out_of_square);
+        }
+
+        let (row, column) = (ods_index / self.ods, ods_index % self.ods);
+        row.checked_mul(self.eds)
+            .and_then(|row_start| row_start.checked_add(column))
+            .ok_or(out_of_square)
+    }
+
+    /// Converts the index of a share in the EDS to its index in the ODS. Parity shares, out of
+    /// the ODS quarter of the EDS, have no ODS index.
+    pub fn eds_to_ods(&self, eds_index: u32) -> Result<u32, SquareIndexError> {
+        if eds_index as u64 >= self.eds as u64 * self.eds as u64 {
+            return Err(SquareIndexError::OutOfSquare {
+                index: eds_index,
+                width: self.eds,
+            });
+        }
+
+        let (row, column) = (eds_index / self.eds, eds_index % self.eds);
+        if row >= self.ods || column >= self.ods {
+            return Err(SquareIndexError::ParityShare { eds_index });
+        }
+
+        Ok(row * self.ods + column)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::string::ToString;
+    #[cfg(feature = "std")]
+    use errors::DaFraudKind;
+
+    fn span_sequence(height: u64, start: u32, size: u32) -> SpanSequence {
+        SpanSequence {
+            height,
+            start,
+            size,
+        }
+    }
+
+    #[test]
+    fn test_span_sequence_ods_range() {
+        assert_eq!(span_sequence(1, 4, 3).ods_range().unwrap(), 4..7);
+        assert!(span_sequence(1, 4, 0).ods_range().is_err());
+        assert!(span_sequence(1, u32::MAX, 1).ods_range().is_err());
+    }
+
+    #[test]
+    fn test_span_sequence_contains_share() {
+        let blob = span_sequence(1, 4, 3);
+        assert!(blob.contains_share(1, 4));
+        assert!(blob.contains_share(1, 6));
+        assert!(!blob.contains_share(1, 3));
+        assert!(!blob.contains_share(1, 7));
+        assert!(!blob.contains_share(2, 5));
+        assert!(!span_sequence(1, 4, 0).contains_share(1, 4));
+        assert!(span_sequence(1, u32::MAX, 2).contains_share(1, u32::MAX));
+    }
+
+    #[test]
+    fn test_span_sequence_intersect() {
+        let blob = span_sequence(1, 4, 3);
+        assert_eq!(
+            blob.intersect(&span_sequence(1, 6, 10)),
+            Some(span_sequence(1, 6, 1))
+        );
+        assert_eq!(
+            blob.intersect(&span_sequence(1, 0, 10)),
+            Some(span_sequence(1, 4, 3))
+        );
+        assert_eq!(blob.intersect(&blob), Some(blob));
+        assert_eq!(blob.intersect(&span_sequence(1, 7, 1)), None);
+        assert_eq!(blob.intersect(&span_sequence(2, 4, 3)), None);
+        assert_eq!(blob.intersect(&span_sequence(1, 5, 0)), None);
+        assert_eq!(
+            span_sequence(1, u32::MAX - 1, 4).intersect(&span_sequence(1, 0, u32::MAX)),
+            Some(span_sequence(1, u32::MAX - 1, 1))
+        );
+    }
+
+    #[test]
+    fn test_span_sequence_v2_conversion() {
+        let blob = span_sequence(1, u32::MAX - 1, 1);
+        let blob_v2 = SpanSequenceV2::from(blob);
+        assert_eq!(blob_v2.to_string(), blob.to_string());
+        assert_eq!(SpanSequence::try_from(blob_v2).unwrap(), blob);
+
+        let wide = SpanSequenceV2 {
+            height: 1,
+            start: u32::MAX as u64 + 1,
+            size: 1,
+        };
+        assert_eq!(wide.end_index_ods(), Some(u32::MAX as u64 + 2));
+        assert!(SpanSequence::try_from(wide).is_err());
+    }
+
+    #[test]
+    fn test_span_sequence_overlaps() {
+        let blob = span_sequence(1, 4, 3);
+        assert!(blob.overlaps(&span_sequence(1, 6, 1)));
+        assert!(span_sequence(1, 6, 1).overlaps(&blob));
+        assert!(!blob.overlaps(&span_sequence(1, 7, 1)));
+        assert!(!blob.overlaps(&span_sequence(1, 0, 4)));
+        assert!(!blob.overlaps(&span_sequence(2, 4, 3)));
+    }
+
+    #[cfg(feature = "host")]
+    #[test]
+    fn fraud_kind_is_serialized_with_the_fraud() {
+        let blob = span_sequence(10, 4, 2);
+        let frauds = [
+            DaFraud::EmptySpanSequence(blob),
+            DaFraud::ShareIndexOutOfBounds {
+                share_index: 20,
+                ods_size: 16,
+            },
+            DaFraud::SquareTooSmallForBlob { blob, ods_width: 1 },
+        ];
+
+        for fraud in frauds {
+            let serialized = serde_json::to_value(&fraud).unwrap();
+            let kind: DaFraudKind = serde_json::from_value(serialized["kind"].clone()).unwrap();
+            assert_eq!(kind, fraud.kind());
+        }
+
+        let input_error = serde_json::to_value(InputError::MissingBlockProof(10)).unwrap();
+        assert_eq!(
+            input_error,
+            serde_json::json!({"kind": "missing_block_proof", "details": 10})
+        );
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn square_width_from_root_count_accepts_legal_squares_only() {
+        use errors::square_width_from_root_count;
+
+        for root_count in 0..=4 * constants::MAX_ODS_WIDTH as usize + 16 {
+            let result = square_width_from_root_count(root_count);
+            let ods_width = root_count / 4;
+            if root_count % 4 == 0
+                && ods_width.is_power_of_two()
+                && ods_width <= constants::MAX_ODS_WIDTH as usize
+            {
+                let square_width = result.unwrap();
+                assert_eq!(square_width.ods as usize, ods_width);
+                assert_eq!(square_width.eds, 2 * square_width.ods);
+            } else {
+                assert!(result.is_err(), "{root_count} roots accepted");
+            }
+        }
+
+        assert!(matches!(
+            square_width_from_root_count(0),
+            Err(InputError::InvalidNumberOfLeavesInProof)
+        ));
+        assert!(matches!(
+            square_width_from_root_count(4 * 3),
+            Err(InputError::InvalidSquareWidth(3))
+        ));
+        assert!(matches!(
+            square_width_from_root_count(4 * 1024),
+            Err(InputError::InvalidSquareWidth(1024))
+        ));
+        assert!(matches!(
+            square_width_from_root_count(usize::MAX - 3),
+            Err(InputError::InvalidSquareWidth(_))
+        ));
+    }
+
+    #[test]
+    fn ods_and_eds_indexes_round_trip() {
+        for ods_width in (0..=6).map(|exponent| 1 << exponent) {
+            let width = SquareWidth::from_ods(ods_width);
+            assert_eq!(SquareWidth::from_eds(width.eds), width);
+
+            let mut eds_indexes = BTreeSet::new();
+            for ods_index in 0..ods_width * ods_width {
+                let eds_index = width.ods_to_eds(ods_index).unwrap();
+                assert!(eds_indexes.insert(eds_index));
+                assert_eq!(width.eds_to_ods(eds_index), Ok(ods_index));
+            }
+            assert_eq!(
+                width.ods_to_eds(ods_width * ods_width),
+                Err(SquareIndexError::OutOfSquare {
+                    index: ods_width * ods_width,
+                    width: ods_width,
+                })
+            );
+
+            for eds_index in 0..width.eds * width.eds {
+                if !eds_indexes.contains(&eds_index) {
+                    assert_eq!(
+                        width.eds_to_ods(eds_index),
+                        Err(SquareIndexError::ParityShare { eds_index })
+                    );
+                }
+            }
+            assert!(matches!(
+                width.eds_to_ods(width.eds * width.eds),
+                Err(SquareIndexError::OutOfSquare { .. })
+            ));
+        }
+    }
+
+    #[test]
+    fn ods_rows_are_the_first_half_of_eds_rows() {
+        let width = SquareWidth::from_ods(4);
+        assert_eq!(width.ods_to_eds(0), Ok(0));
+        assert_eq!(width.ods_to_eds(3), Ok(3));
+        assert_eq!(width.ods_to_eds(4), Ok(8));
+        assert_eq!(width.ods_to_eds(15), Ok(27));
+        assert_eq!(
+            width.eds_to_ods(4),
+            Err(SquareIndexError::ParityShare { eds_index: 4 })
+        );
+        assert_eq!(
+            width.eds_to_ods(32),
+            Err(SquareIndexError::ParityShare { eds_index: 32 })
+        );
+        assert!(matches!(
+            SquareWidth::from_ods(u32::MAX / 2).ods_to_eds(u32::MAX - 1),
+            Err(SquareIndexError::OutOfSquare { .. })
+        ));
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn blob_proof_data_needs_share_proofs() {
+        assert!(matches!(
+            BlobProofData::new(BTreeMap::new(), 1),
+            Err(BlobProofDataError::NoShareProofs)
+        ));
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn journals_round_trip_through_their_abi_encoding() {
+        use journal::{
+            AggregatedJournal, AggregatedJournalSummary, DaOutcome, Journal, JournalSummary,
+            AGGREGATED_JOURNAL_VERSION, JOURNAL_VERSION,
+        };
+
+        let metadata = BatchMetadata {
+            rollup_id: B256::repeat_byte(6),
+            batch_number: 42,
+            timestamp: 1_700_000_000,
+        };
+        let journal = Journal {
+            version: JOURNAL_VERSION,
+            commitment: risc0_steel::Commitment {
+                id: U256::from(7),
+                digest: B256::repeat_byte(1),
+                configID: B256::repeat_byte(2),
+            },
+            blobstreamAddress: Address::repeat_byte(3),
+            blobstreamImpl: BlobstreamImpl::Sp1.as_u8(),
+            outcome: DaOutcome::FraudProven.as_u8(),
+            indexBlob: span_sequence(10, 0, 4).into(),
+            challengedBlob: span_sequence(11, 8, 2).into(),
+            fraudType: DaFraudKind::ShareIndexOutOfBounds.as_u8(),
+            namespace: vec![0; 29].into(),
+            extraChallengedBlobs: vec![span_sequence(12, 0, 1).into()],
+            extraFraudTypes: vec![0],
+            celestiaChainId: "mocha-4".to_string(),
+            challengeType: DaChallenge::BlobInIndexIsUnavailable(span_sequence(11, 8, 2)).as_u8(),
+            indexHash: B256::repeat_byte(4),
+            batchMetadata: Some(metadata).into(),
+        };
+        let encoded = journal.encode();
+        assert_eq!(Journal::version(&encoded).unwrap(), JOURNAL_VERSION);
+        assert_eq!(Journal::decode(&encoded).unwrap().encode(), encoded);
+        let summary = JournalSummary::decode(&encoded).unwrap();
+        assert_eq!(summary.challenged_blob, span_sequence(11, 8, 2));
+        assert_eq!(summary.celestia_chain_id, "mocha-4");
+        assert_eq!(summary.batch_metadata, Some(metadata));
+        assert!(AggregatedJournal::decode(&encoded).is_err());
+
+        let aggregated = AggregatedJournal::aggregate(B256::repeat_byte(5), &[journal]).unwrap();
+        let encoded = aggregated.encode();
+        assert_eq!(
+            Journal::version(&encoded).unwrap(),
+            AGGREGATED_JOURNAL_VERSION
+        );
+        assert_eq!(
+            AggregatedJournal::decode(&encoded).unwrap().encode(),
+            encoded
+        );
+        let summary = AggregatedJournalSummary::decode(&encoded).unwrap();
+        assert_eq!(summary.batch_metadata, Some(metadata));
+    }
+
+    #[test]
+    fn namespaces_round_trip_through_the_canonical_encoding() {
+        let namespaces = [[1u8; NAMESPACE_SIZE], [2u8; NAMESPACE_SIZE]];
+        let index = BlobIndexBuilder::new()
+            .with_blob_and_data_hash(span_sequence(10, 0, 4), [3; 32])
+            .with_namespace(namespaces[0])
+            .with_blob_and_data_hash(span_sequence(10, 4, 2), [4; 32])
+            .with_namespace(namespaces[1])
+            .build()
+            .unwrap();
+        assert_eq!(index.namespace(1), Some(&namespaces[1]));
+
+        let encoded = index.encode_canonical().unwrap();
+        assert_eq!(encoded[INDEX_CANONICAL_MARKER.len()], 3);
+        assert_eq!(
+            encoded.len(),
+            INDEX_CANONICAL_MARKER.len() + 1 + 2 * (CANONICAL_ENTRY_SIZE + 32 + NAMESPACE_SIZE)
+        );
+        assert_eq!(BlobIndex::decode_canonical(&encoded).unwrap(), index);
+
+        // Namespaces are appended to the entries, indexes without them keep their encoding
+        let mut without_namespaces = index.clone();
+        without_namespaces.namespaces.clear();
+        assert_eq!(
+            without_namespaces.encode_canonical().unwrap()[INDEX_CANONICAL_MARKER.len()],
+            1
+        );
+
+        let missing = BlobIndexBuilder::new()
+            .with_blob(span_sequence(10, 0, 4))
+            .with_namespace(namespaces[0])
+            .with_blob(span_sequence(10, 4, 2))
+            .build();
+        assert!(matches!(missing, Err(IndexError::MissingNamespace(1))));
+
+        // Setting a field before any entry is reported by `build()`, even if entries follow
+        let no_entry = BlobIndexBuilder::new()
+            .with_namespace(namespaces[0])
+            .with_commitment([5; 32])
+            .with_blob(span_sequence(10, 0, 4))
+            .build();
+        assert!(matches!(no_entry, Err(IndexError::NoEntry("namespace"))));
+    }
+
+    #[test]
+    fn encoding_rejects_inconsistent_entries() {
+        let mut index = BlobIndex::new(vec![span_sequence(10, 0, 4), span_sequence(10, 4, 2)]);
+        index.namespaces.push([1; NAMESPACE_SIZE]);
+        assert!(matches!(
+            index.encode_canonical(),
+            Err(IndexError::NamespaceCountMismatch {
+                entries: 2,
+                namespaces: 1
+            })
+        ));
+        assert!(index.canonical_hash().is_err());
+
+        let mut index = BlobIndex::new(vec![span_sequence(10, 0, 4)]);
+        index.sub_indexes.insert(1);
+        assert!(matches!(
+            index.encode_canonical(),
+            Err(IndexError::SubIndexOutOfRange(1))
+        ));
+        #[cfg(feature = "bincode")]
+        assert!(matches!(
+            index.encode(),
+            Err(IndexError::SubIndexOutOfRange(1))
+        ));
+    }
+
+    #[cfg(feature = "host")]
+    #[test]
+    fn blobs_are_checked_against_their_share_commitment() {
+        let namespace = Namespace::new_v0(&[1, 2, 3]).unwrap();
+        let blob = Blob::new(namespace, vec![7; 600], AppVersion::V3).unwrap();
+        let other = Blob::new(namespace, vec![8; 600], AppVersion::V3).unwrap();
+        let index = BlobIndexBuilder::new()
+            .with_blob(span_sequence(10, 0, 2))
+            .with_commitment(*blob.commitment.hash())
+            .build()
+            .unwrap();
+
+        let encoded = index.encode_canonical().unwrap();
+        assert_eq!(encoded[INDEX_CANONICAL_MARKER.len()], 4);
+        assert_eq!(BlobIndex::decode_canonical(&encoded).unwrap(), index);
+
+        index.check_blob(0, &blob).unwrap();
+        assert!(matches!(
+            index.check_blob(0, &other),
+            Err(DaFraud::BlobCommitmentMismatch { .. })
+        ));
+    }
+
+    #[test]
+    fn batch_metadata_precedes_the_canonical_entries() {
+        let metadata = BatchMetadata {
+            rollup_id: B256::repeat_byte(1),
+            batch_number: 7,
+            timestamp: 1_700_000_000,
+        };
+        let index = BlobIndexBuilder::new()
+            .with_metadata(metadata)
+            .with_blob(span_sequence(10, 0, 4))
+            .build()
+            .unwrap();
 
-    if eds_index < ods_width {
-        eds_index
-    } else {
-        eds_index / 2
+        let encoded = index.encode_canonical().unwrap();
+        let header = INDEX_CANONICAL_MARKER.len() + 1;
+        assert_eq!(encoded[header - 1], 8);
+        assert_eq!(&encoded[header..header + 32], metadata.rollup_id.as_slice());
+        assert_eq!(
+            encoded.len(),
+            header + CANONICAL_METADATA_SIZE + CANONICAL_ENTRY_SIZE
+        );
+        assert_eq!(BlobIndex::decode_canonical(&encoded).unwrap(), index);
+        assert!(BlobIndex::decode_canonical(&encoded[..header + 40]).is_err());
     }
 }